@@ -0,0 +1,204 @@
+use crate::message::Message;
+use crate::{BuildScriptOutput, BuildTimings, ChecksumManifest, Diagnostic, DiagnosticLevel, DiagnosticsFilter, DuplicatePackage, FutureIncompatReport, SizeSnapshot, StepReport};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A single artifact produced by cargo, as reported by a `compiler-artifact` JSON message.
+///
+/// # Fields
+///
+/// * `package_id` - The cargo package ID of the package the artifact belongs to.
+/// * `target_name` - The name of the target (binary, library, example, ...) that was compiled.
+/// * `kind` - The target kinds, e.g. `["bin"]`, `["lib"]` or `["cdylib", "rlib"]`.
+/// * `filenames` - Paths of every file produced for the target.
+/// * `executable` - Path of the produced executable, if the target is executable.
+/// * `fresh` - `true` if the artifact was up to date and not rebuilt.
+/// * `manifest_path` - Path of the `Cargo.toml` of the package the artifact belongs to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Artifact {
+    pub package_id: String,
+    pub target_name: String,
+    pub kind: Vec<String>,
+    pub filenames: Vec<PathBuf>,
+    pub executable: Option<PathBuf>,
+    pub fresh: bool,
+    pub manifest_path: PathBuf,
+}
+
+impl Artifact {
+    /// Returns the name of the package the artifact belongs to
+    pub fn package_name(&self) -> &str {
+        split_package_id(&self.package_id).0
+    }
+
+    /// Returns the version of the package the artifact belongs to
+    pub fn package_version(&self) -> &str {
+        split_package_id(&self.package_id).1
+    }
+
+    /// Returns the paths of the executable, shared libraries and static libraries of the
+    /// artifact, excluding build scripts and the files only rustc uses (`.rlib`, `.rmeta`, ...)
+    pub fn binaries(&self) -> Vec<PathBuf> {
+        if self.kind.iter().any(|kind| kind == "custom-build") {
+            return Vec::new();
+        }
+        let libraries = self.filenames.iter().filter(|path| {
+            let extension = path.extension().and_then(|extension| extension.to_str()).unwrap_or_default();
+            self.kind.iter().any(|kind| match kind.as_str() {
+                "cdylib" | "dylib" => crate::pipeline::is_shared_library(path),
+                "staticlib" => extension == "a" || extension == "lib",
+                _ => false,
+            })
+        });
+        self.executable.iter().chain(libraries).cloned().collect()
+    }
+}
+
+/// Private function that splits the cargo package ID `package_id` into the name and version of
+/// the package. IDs are either `serde 1.0.0 (registry+https://...)`, or the newer
+/// `registry+https://...#serde@1.0.0` and `path+file:///path/to/app#0.1.0`, which omits the name
+/// when it is the directory name.
+pub(crate) fn split_package_id(package_id: &str) -> (&str, &str) {
+    if let Some((name, rest)) = package_id.split_once(' ') {
+        return (name, rest.split(' ').next().unwrap_or_default());
+    }
+    let (url, fragment) = package_id.rsplit_once('#').unwrap_or((package_id, ""));
+    match fragment.split_once('@') {
+        Some((name, version)) => (name, version),
+        None => {
+            let path = url.split('?').next().unwrap_or(url).trim_end_matches('/');
+            (path.rsplit('/').next().unwrap_or(path), fragment)
+        }
+    }
+}
+
+/// The output of a successful build.
+///
+/// # Fields
+///
+/// * `artifacts` - Every artifact reported by cargo, including those of dependencies.
+/// * `diagnostics` - Every compiler diagnostic (warnings, errors, ...) emitted during the build.
+/// * `build_scripts` - What the build scripts that were run or are up to date did, see [`BuildScriptOutput`].
+/// * `timings` - The compile time of every unit, if timings are enabled with [`Builder::set_timings`](crate::Builder::set_timings).
+/// * `pipeline` - What the steps of the [`ArtifactPipeline`](crate::ArtifactPipeline) did, if one is set with
+///   [`Builder::set_artifact_pipeline`](crate::Builder::set_artifact_pipeline).
+/// * `headers` - The headers generated by cbindgen, if enabled with [`Builder::set_cbindgen`](crate::Builder::set_cbindgen).
+/// * `checksums` - The checksums of the produced binaries, if enabled with [`Builder::set_checksums`](crate::Builder::set_checksums).
+/// * `duplicates` - The packages resolved at several versions and not allowed, if checked with
+///   [`Builder::set_duplicate_policy`](crate::Builder::set_duplicate_policy).
+/// * `installed` - The copies of the produced binaries, if installed with [`Builder::set_install_dir`](crate::Builder::set_install_dir).
+/// * `sizes` - The sizes of the produced binaries, if tracked with [`Builder::set_size_tracking`](crate::Builder::set_size_tracking).
+/// * `future_incompat` - The packages that will be rejected by a future version of Rust, if reported with
+///   [`Builder::set_future_incompat_report`](crate::Builder::set_future_incompat_report).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct BuildOutput {
+    pub artifacts: Vec<Artifact>,
+    pub diagnostics: Vec<Diagnostic>,
+    pub build_scripts: Vec<BuildScriptOutput>,
+    pub timings: Option<BuildTimings>,
+    pub pipeline: Vec<StepReport>,
+    pub headers: Vec<PathBuf>,
+    pub checksums: Option<ChecksumManifest>,
+    pub duplicates: Vec<DuplicatePackage>,
+    pub installed: Vec<PathBuf>,
+    pub sizes: Option<SizeSnapshot>,
+    pub future_incompat: Option<FutureIncompatReport>,
+}
+
+/// Private function that checks whether `code` is a compiler error code (`E` followed by digits)
+/// rather than a lint name
+fn is_error_code(code: &str) -> bool {
+    code.strip_prefix('E').is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl BuildOutput {
+    /// Returns the paths of every produced executable
+    pub fn executables(&self) -> impl Iterator<Item = &Path> {
+        self.artifacts.iter().filter_map(|a| a.executable.as_deref())
+    }
+
+    /// Returns the paths of every produced executable, shared library and static library,
+    /// excluding build scripts
+    pub fn binaries(&self) -> Vec<PathBuf> {
+        self.artifacts.iter().flat_map(Artifact::binaries).collect()
+    }
+
+    /// Returns the executable of the binary named `target_name`, if it was built
+    pub fn executable(&self, target_name: &str) -> Option<&Path> {
+        self.executables_by_target().remove(target_name)
+    }
+
+    /// Returns the executable of every binary built, by target name. Examples, tests and
+    /// benchmarks are not included.
+    pub fn executables_by_target(&self) -> BTreeMap<&str, &Path> {
+        self.artifacts
+            .iter()
+            .filter(|artifact| artifact.kind.iter().any(|kind| kind == "bin"))
+            .filter_map(|artifact| Some((artifact.target_name.as_str(), artifact.executable.as_deref()?)))
+            .collect()
+    }
+
+    /// Returns the artifacts of the target named `target_name`
+    pub fn artifacts_for(&self, target_name: &str) -> impl Iterator<Item = &Artifact> {
+        self.artifacts.iter().filter(move |a| a.target_name == target_name)
+    }
+
+    /// Returns the diagnostics with the given level
+    pub fn diagnostics_with_level(&self, level: DiagnosticLevel) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter().filter(move |d| d.level == level)
+    }
+
+    /// Returns the diagnostics with the given error or lint code (e.g. `unused_variables`)
+    pub fn diagnostics_with_code<'a>(&'a self, code: &'a str) -> impl Iterator<Item = &'a Diagnostic> {
+        self.diagnostics.iter().filter(move |d| d.code.as_deref() == Some(code))
+    }
+
+    /// Returns the output of the build script of the package named `package`, if it has one
+    pub fn build_script(&self, package: &str) -> Option<&BuildScriptOutput> {
+        self.build_scripts.iter().find(|script| script.package_name() == package)
+    }
+
+    /// Returns the diagnostics passing `filter`
+    pub fn filter_diagnostics<'a>(&'a self, filter: &'a DiagnosticsFilter) -> impl Iterator<Item = &'a Diagnostic> {
+        self.diagnostics.iter().filter(move |d| filter.matches(d))
+    }
+
+    /// Returns the number of warnings emitted during the build
+    pub fn warning_count(&self) -> usize {
+        self.diagnostics_with_level(DiagnosticLevel::Warning).count()
+    }
+
+    /// Returns the number of errors emitted during the build
+    pub fn error_count(&self) -> usize {
+        self.diagnostics_with_level(DiagnosticLevel::Error).count()
+    }
+
+    /// Returns the number of warnings that were turned into errors, e.g. by
+    /// [`Builder::set_deny_warnings`](crate::Builder::set_deny_warnings).
+    ///
+    /// These are errors reported for a lint (such as `unused_variables`) rather than for an error
+    /// code (such as `E0308`).
+    pub fn denied_warning_count(&self) -> usize {
+        self.diagnostics_with_level(DiagnosticLevel::Error)
+            .filter(|d| d.code.as_deref().is_some_and(|code| !is_error_code(code)))
+            .count()
+    }
+
+    /// Private function that collects the artifacts and diagnostics reported in cargo's JSON stdout
+    pub(crate) fn parse(stdout: &str) -> BuildOutput {
+        let mut output = BuildOutput::default();
+        for message in stdout.lines().filter_map(Message::parse) {
+            match message {
+                Message::CompilerArtifact(artifact) => output.artifacts.push(artifact.into()),
+                Message::CompilerMessage(diagnostic) => output.diagnostics.push(diagnostic.into()),
+                Message::BuildScriptExecuted(script) => {
+                    let mut script = BuildScriptOutput::from(script);
+                    script.read_instructions();
+                    output.build_scripts.push(script);
+                }
+                Message::Other => {}
+            }
+        }
+        output
+    }
+}
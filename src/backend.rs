@@ -0,0 +1,334 @@
+use crate::Rustup;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::{env, fmt};
+
+/// Environment variables that describe the host and are not forwarded into containers.
+const HOST_ENVS: [&str; 5] = ["PATH", "HOME", "CARGO_HOME", "RUSTUP_HOME", "RUSTUP_TOOLCHAIN"];
+
+/// The tool that executes cargo subcommands for a [`Builder`](crate::Builder).
+///
+/// A backend only decides which program runs a subcommand (e.g. `cargo build` or
+/// `cross build`); every flag and environment variable derived from the
+/// [`ProjectSettings`](crate::ProjectSettings) is added by the `Builder` afterwards, so the same
+/// settings work with any backend.
+pub trait BuildBackend: fmt::Debug + Send + Sync {
+    /// Creates the command that runs `subcommand` (e.g. `build`, `check`, `test`).
+    ///
+    /// # Arguments
+    ///
+    /// * `subcommand` - The cargo subcommand to run.
+    /// * `toolchain` - The rustup toolchain configured on the builder, if any.
+    fn command(&self, subcommand: &str, toolchain: Option<&str>) -> Command;
+
+    /// Adapts the command once the `Builder` added every flag and environment variable, right
+    /// before it is run or rendered. The default implementation returns it unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The complete command, as created by [`BuildBackend::command`].
+    fn finish(&self, command: Command) -> Command {
+        command
+    }
+}
+
+/// The default backend, which runs subcommands with `cargo` directly.
+///
+/// If a toolchain is set, cargo is invoked as `rustup run <toolchain> cargo` instead.
+///
+/// # Fields
+///
+/// * `cargo_path` - Path to the `cargo` binary.
+#[derive(Debug, Clone)]
+pub struct CargoBackend {
+    pub cargo_path: PathBuf,
+}
+
+impl BuildBackend for CargoBackend {
+    fn command(&self, subcommand: &str, toolchain: Option<&str>) -> Command {
+        let mut command = match toolchain {
+            Some(toolchain) => {
+                let mut command = Rustup::new().command();
+                command.arg("run").arg(toolchain).arg("cargo");
+                command
+            }
+            None => Command::new(&self.cargo_path),
+        };
+        command.arg(subcommand);
+        command
+    }
+}
+
+/// A backend that runs subcommands with [`cross`](https://github.com/cross-rs/cross), producing
+/// container-based cross builds, e.g. `cross build --target aarch64-unknown-linux-musl`.
+///
+/// If a toolchain is set, it is passed as `cross +<toolchain>`.
+///
+/// # Fields
+///
+/// * `cross_path` - Path to the `cross` binary. Defaults to `cross`, resolved through `PATH`.
+#[derive(Debug, Clone)]
+pub struct CrossBackend {
+    pub cross_path: PathBuf,
+}
+
+impl Default for CrossBackend {
+    fn default() -> Self {
+        CrossBackend { cross_path: PathBuf::from("cross") }
+    }
+}
+
+impl CrossBackend {
+    /// Creates a new `CrossBackend` that resolves `cross` through `PATH`
+    pub fn new() -> Self {
+        CrossBackend::default()
+    }
+}
+
+impl BuildBackend for CrossBackend {
+    fn command(&self, subcommand: &str, toolchain: Option<&str>) -> Command {
+        let mut command = Command::new(&self.cross_path);
+        if let Some(toolchain) = toolchain {
+            command.arg(format!("+{}", toolchain));
+        }
+        command.arg(subcommand);
+        command
+    }
+}
+
+/// A backend that runs subcommands with [`cargo-zigbuild`](https://github.com/rust-cross/cargo-zigbuild),
+/// which links with `zig` to produce portable Linux binaries without docker.
+///
+/// Builds run as `cargo-zigbuild zigbuild`, other subcommands as `cargo-zigbuild <subcommand>`.
+/// Compilation targets may carry a glibc version suffix, e.g. `x86_64-unknown-linux-gnu.2.17`.
+/// If a toolchain is set, it is passed through the `RUSTUP_TOOLCHAIN` environment variable.
+///
+/// # Fields
+///
+/// * `zigbuild_path` - Path to the `cargo-zigbuild` binary. Defaults to `cargo-zigbuild`,
+///   resolved through `PATH`.
+#[derive(Debug, Clone)]
+pub struct ZigbuildBackend {
+    pub zigbuild_path: PathBuf,
+}
+
+impl Default for ZigbuildBackend {
+    fn default() -> Self {
+        ZigbuildBackend { zigbuild_path: PathBuf::from("cargo-zigbuild") }
+    }
+}
+
+impl ZigbuildBackend {
+    /// Creates a new `ZigbuildBackend` that resolves `cargo-zigbuild` through `PATH`
+    pub fn new() -> Self {
+        ZigbuildBackend::default()
+    }
+
+    /// Returns a `ZigbuildBackend` if `cargo-zigbuild` is installed, or `None` otherwise
+    pub fn detect() -> Option<Self> {
+        let backend = ZigbuildBackend::new();
+        backend.is_available().then_some(backend)
+    }
+
+    /// Checks whether the `cargo-zigbuild` binary can be run
+    pub fn is_available(&self) -> bool {
+        Command::new(&self.zigbuild_path)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+}
+
+impl BuildBackend for ZigbuildBackend {
+    fn command(&self, subcommand: &str, toolchain: Option<&str>) -> Command {
+        let mut command = Command::new(&self.zigbuild_path);
+        command.arg(if subcommand == "build" { "zigbuild" } else { subcommand });
+        if let Some(toolchain) = toolchain {
+            command.env("RUSTUP_TOOLCHAIN", toolchain);
+        }
+        command
+    }
+}
+
+/// A backend that runs subcommands inside a container image with `docker` or `podman`, so builds
+/// do not depend on the toolchain installed on the host.
+///
+/// The working directory of the build, the target directory and every additional mount are
+/// bind-mounted at the same path inside the container, so paths reported by cargo (e.g. artifact
+/// paths) are valid on the host and artifacts end up in the host's target directory. Building a
+/// workspace member requires mounting the workspace root with [`ContainerBackend::mount`].
+///
+/// Environment variables set for the build (rustc flags, profile overrides, [`Builder::env`](crate::Builder::env), ...)
+/// are forwarded with `-e KEY`, except those describing the host such as `PATH` and `HOME`.
+/// Their values are set on the environment of the engine process rather than passed as
+/// arguments, so tokens do not appear in the process list. If a toolchain is set, it is passed
+/// as `cargo +<toolchain>`, which requires rustup in the image.
+///
+/// # Fields
+///
+/// * `engine` - Path to the container engine, e.g. `docker` or `podman`, resolved through `PATH`.
+/// * `image` - The image to run, e.g. `rust:1.80`.
+/// * `cache_volume` - Optional named volume (or host directory) mounted as cargo's registry
+///   cache, so dependencies are only downloaded once. Defaults to `cargo-wrap-registry`.
+/// * `cargo_home` - The `CARGO_HOME` of the image. Defaults to `/usr/local/cargo`, as in the
+///   official `rust` images.
+/// * `mounts` - Additional host directories mounted at the same path.
+/// * `user` - Optional user the build runs as (`--user X`), e.g. `1000:1000`. Defaults to the
+///   image's user, which means files created by docker builds belong to `root`.
+/// * `run_args` - Additional arguments for `<engine> run`, e.g. `--network=none`.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{Builder, ContainerBackend, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let settings = ProjectSettings::new("/path/to/project").release(true);
+///     let builder = Builder::new(settings)?.backend(ContainerBackend::podman("docker.io/library/rust:1.80"));
+///     let command = builder.render_command()?.to_string();
+///     assert!(command.starts_with("podman run --rm -v /path/to/project:/path/to/project"));
+///     assert!(command.contains("docker.io/library/rust:1.80 cargo build"));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContainerBackend {
+    pub engine: PathBuf,
+    pub image: String,
+    pub cache_volume: Option<String>,
+    pub cargo_home: String,
+    pub mounts: Vec<PathBuf>,
+    pub user: Option<String>,
+    pub run_args: Vec<String>,
+}
+
+impl ContainerBackend {
+    /// Creates a new `ContainerBackend` that runs `image` with `engine`
+    pub fn new(engine: impl Into<PathBuf>, image: impl Into<String>) -> Self {
+        ContainerBackend {
+            engine: engine.into(),
+            image: image.into(),
+            cache_volume: Some("cargo-wrap-registry".to_string()),
+            cargo_home: "/usr/local/cargo".to_string(),
+            mounts: Vec::new(),
+            user: None,
+            run_args: Vec::new(),
+        }
+    }
+
+    /// Creates a new `ContainerBackend` that runs `image` with `docker`
+    pub fn docker(image: impl Into<String>) -> Self {
+        ContainerBackend::new("docker", image)
+    }
+
+    /// Creates a new `ContainerBackend` that runs `image` with `podman`
+    pub fn podman(image: impl Into<String>) -> Self {
+        ContainerBackend::new("podman", image)
+    }
+
+    /// Sets the volume mounted as cargo's registry cache, `None` disabling the cache
+    pub fn cache_volume(mut self, cache_volume: Option<String>) -> Self {
+        self.cache_volume = cache_volume;
+        self
+    }
+
+    /// Mounts an additional host directory at the same path, e.g. the root of a workspace
+    pub fn mount(mut self, path: impl Into<PathBuf>) -> Self {
+        self.mounts.push(path.into());
+        self
+    }
+
+    /// Sets the user the build runs as (`--user X`)
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Runs the build as the current user, so the created files belong to them
+    #[cfg(unix)]
+    pub fn host_user(self) -> Self {
+        // SAFETY: `getuid` and `getgid` cannot fail and have no side effects
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        self.user(format!("{}:{}", uid, gid))
+    }
+
+    /// Adds an argument for `<engine> run`
+    pub fn run_arg(mut self, arg: impl Into<String>) -> Self {
+        self.run_args.push(arg.into());
+        self
+    }
+}
+
+impl BuildBackend for ContainerBackend {
+    fn command(&self, subcommand: &str, toolchain: Option<&str>) -> Command {
+        let mut command = Command::new("cargo");
+        if let Some(toolchain) = toolchain {
+            command.arg(format!("+{}", toolchain));
+        }
+        command.arg(subcommand);
+        command
+    }
+
+    fn finish(&self, command: Command) -> Command {
+        let mut args: Vec<OsString> = command.get_args().map(OsStr::to_os_string).collect();
+        let host_dir = env::current_dir().unwrap_or_default();
+        let cargo_dir = command.get_current_dir().map(|dir| host_dir.join(dir));
+        // The manifest is mounted at the same path, so it is passed as an absolute path that
+        // does not depend on the working directory inside the container
+        let manifest = args.iter().position(|arg| arg == "--manifest-path").and_then(|index| {
+            let manifest = cargo_dir.as_deref().unwrap_or(&host_dir).join(args.get(index + 1)?);
+            args[index + 1] = manifest.clone().into_os_string();
+            Some(manifest)
+        });
+        let work_dir = match (cargo_dir, &manifest) {
+            (Some(dir), _) => dir,
+            (None, Some(manifest)) => manifest.parent().map(Path::to_path_buf).unwrap_or_else(|| host_dir.clone()),
+            (None, None) => host_dir.clone(),
+        };
+        let target_dir = command
+            .get_envs()
+            .find(|(key, _)| *key == "CARGO_TARGET_DIR")
+            .and_then(|(_, value)| value)
+            .map(|dir| work_dir.join(dir));
+
+        let mut container = Command::new(&self.engine);
+        container.arg("run").arg("--rm");
+        let mut mounted: Vec<&Path> = Vec::new();
+        for dir in [Some(&work_dir), target_dir.as_ref()].into_iter().flatten().chain(&self.mounts) {
+            if !mounted.iter().any(|parent| dir.starts_with(parent)) {
+                let mut volume = dir.clone().into_os_string();
+                volume.push(":");
+                volume.push(dir);
+                container.arg("-v").arg(volume);
+                mounted.push(dir);
+            }
+        }
+        if let Some(cache_volume) = &self.cache_volume {
+            container.arg("-v").arg(format!("{}:{}/registry", cache_volume, self.cargo_home));
+        }
+        container.arg("-w").arg(&work_dir);
+        if let Some(user) = &self.user {
+            container.arg("--user").arg(user);
+        }
+        // Only the names are passed as arguments, the engine reads the values from its own
+        // environment so secrets do not show up in the process list
+        for (key, value) in command.get_envs() {
+            if let (Some(value), false) = (value, HOST_ENVS.iter().any(|host| key == *host)) {
+                container.arg("-e").arg(key).env(key, value);
+            }
+        }
+        container.args(&self.run_args).arg(&self.image).arg(command.get_program()).args(args);
+        container
+    }
+}
+
+/// Private function that strips a glibc version suffix from a target triple, e.g.
+/// `x86_64-unknown-linux-gnu.2.17` becomes `x86_64-unknown-linux-gnu`
+pub(crate) fn base_target_triple(triple: &str) -> &str {
+    match triple.split_once('.') {
+        Some((base, version)) if !version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.') => base,
+        _ => triple,
+    }
+}
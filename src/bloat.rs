@@ -0,0 +1,196 @@
+use crate::Result;
+use object::{Object, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name symbols are attributed to when their crate cannot be told, e.g. C functions.
+const UNKNOWN_CRATE: &str = "[unknown]";
+
+/// The size of a function in a binary, see [`BloatReport`].
+///
+/// # Fields
+///
+/// * `name` - The demangled name of the function, without its hash.
+/// * `crate_name` - The crate the function belongs to, e.g. `std` or `serde_json`, or `[unknown]`
+///   for symbols that are not Rust paths.
+/// * `size` - The size of the machine code of the function, in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSize {
+    pub name: String,
+    pub crate_name: String,
+    pub size: u64,
+}
+
+/// The size of the code of a crate in a binary, see [`BloatReport`].
+///
+/// # Fields
+///
+/// * `name` - The name of the crate.
+/// * `size` - The size of the functions of the crate, in bytes.
+/// * `symbols` - The number of functions of the crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrateSize {
+    pub name: String,
+    pub size: u64,
+    pub symbols: usize,
+}
+
+/// The size of the code of a binary per function and per crate, read from its symbol table, to
+/// find out which dependencies bloat it.
+///
+/// Functions are attributed to the crate of their path, e.g. `serde_json` for
+/// `serde_json::de::from_str` and `alloc` for `<alloc::string::String as core::fmt::Debug>::fmt`.
+/// Generic functions are attributed to the crate defining them, not the crate instantiating them.
+/// The symbol table must be present: binaries built with `strip = true` cannot be analyzed.
+///
+/// # Fields
+///
+/// * `path` - The analyzed binary.
+/// * `file_size` - The size of the file, in bytes.
+/// * `text_size` - The size of its code sections (`.text`), in bytes.
+/// * `symbols` - Every function, largest first.
+/// * `crates` - Every crate, largest first.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::BloatReport;
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let report = BloatReport::analyze("target/release/app")?;
+///     for krate in report.crates.iter().take(10) {
+///         println!("{:>10} {}", krate.size, krate.name);
+///     }
+///     for symbol in report.top_symbols(10) {
+///         println!("{:>10} {}", symbol.size, symbol.name);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloatReport {
+    pub path: PathBuf,
+    pub file_size: u64,
+    pub text_size: u64,
+    pub symbols: Vec<SymbolSize>,
+    pub crates: Vec<CrateSize>,
+}
+
+impl BloatReport {
+    /// Reads the symbol table of the ELF, Mach-O or PE binary at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the file cannot be
+    /// read, is not a supported object file, or has no symbol table.
+    pub fn analyze(path: impl AsRef<Path>) -> Result<BloatReport> {
+        let path = path.as_ref();
+        let data = fs::read(path)?;
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let file = object::File::parse(&*data).map_err(|e| invalid(format!("cannot parse {}: {}", path.display(), e)))?;
+
+        let text_sections: BTreeMap<usize, (u64, u64)> = file
+            .sections()
+            .filter(|section| section.kind() == SectionKind::Text)
+            .map(|section| (section.index().0, (section.address(), section.size())))
+            .collect();
+        let text_size = text_sections.values().map(|(_, size)| size).sum();
+
+        let mut functions: Vec<(usize, u64, u64, &str)> = file
+            .symbols()
+            .filter(|symbol| symbol.kind() == SymbolKind::Text && symbol.is_definition())
+            .filter_map(|symbol| {
+                let section = symbol.section_index().map(|section| section.0).filter(|section| text_sections.contains_key(section))?;
+                let name = symbol.name().ok().filter(|name| !name.is_empty())?;
+                Some((section, symbol.address(), symbol.size(), name))
+            })
+            .collect();
+        if functions.is_empty() {
+            return Err(invalid(format!("{} has no symbol table, build it without stripping", path.display())).into());
+        }
+        // Aliases share an address, keep the first name with the largest size
+        functions.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)).then(b.2.cmp(&a.2)));
+        functions.dedup_by(|next, kept| (next.0, next.1) == (kept.0, kept.1));
+        // Mach-O symbols have no size, they extend to the next symbol or the end of the section
+        for i in 0..functions.len() {
+            if functions[i].2 == 0 {
+                let (section, address) = (functions[i].0, functions[i].1);
+                let end = match functions.get(i + 1).filter(|next| next.0 == section) {
+                    Some(next) => next.1,
+                    None => text_sections[&section].0 + text_sections[&section].1,
+                };
+                functions[i].2 = end.saturating_sub(address);
+            }
+        }
+
+        let mut symbols: Vec<SymbolSize> = functions
+            .into_iter()
+            .map(|(_, _, size, name)| {
+                let name = match rustc_demangle::try_demangle(name) {
+                    Ok(demangled) => format!("{:#}", demangled),
+                    Err(_) => name.to_string(),
+                };
+                SymbolSize { crate_name: crate_of(&name).to_string(), name, size }
+            })
+            .collect();
+        symbols.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        let mut crates: BTreeMap<&str, CrateSize> = BTreeMap::new();
+        for symbol in &symbols {
+            let entry = crates.entry(&symbol.crate_name).or_insert_with(|| CrateSize { name: symbol.crate_name.clone(), size: 0, symbols: 0 });
+            entry.size += symbol.size;
+            entry.symbols += 1;
+        }
+        let mut crates: Vec<CrateSize> = crates.into_values().collect();
+        crates.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        Ok(BloatReport { path: path.to_path_buf(), file_size: data.len() as u64, text_size, symbols, crates })
+    }
+
+    /// Returns the `count` largest functions
+    pub fn top_symbols(&self, count: usize) -> &[SymbolSize] {
+        &self.symbols[..count.min(self.symbols.len())]
+    }
+
+    /// Returns the size of the crate named `name`, if it has code in the binary
+    pub fn get_crate(&self, name: &str) -> Option<&CrateSize> {
+        self.crates.iter().find(|krate| krate.name == name)
+    }
+
+    /// Returns the functions of the crate named `name`, largest first
+    pub fn symbols_of<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a SymbolSize> {
+        self.symbols.iter().filter(move |symbol| symbol.crate_name == name)
+    }
+}
+
+impl fmt::Display for BloatReport {
+    /// Formats the crates as a table of their share of the code, their size and their name, e.g.
+    /// `  41.2%   1268128  std`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for krate in &self.crates {
+            let percent = if self.text_size > 0 { krate.size as f64 * 100.0 / self.text_size as f64 } else { 0.0 };
+            writeln!(f, "{:>6.1}% {:>9}  {}", percent, krate.size, krate.name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Private function that returns the crate of the demangled function `name`: the first segment
+/// of its path, e.g. `core` for `core::fmt::write` or `<core::str::Utf8Error as core::fmt::Debug>::fmt`.
+/// Trait implementations for types without a path, such as tuples, belong to the crate of the trait.
+fn crate_of(name: &str) -> &str {
+    first_segment(name)
+        .or_else(|| name.starts_with('<').then(|| name.split_once(" as ")).flatten().and_then(|(_, source)| first_segment(source)))
+        .unwrap_or(UNKNOWN_CRATE)
+}
+
+/// Private function that returns the first segment of the path `path` starts with, skipping
+/// references, pointers and opening brackets, if it is an identifier
+fn first_segment(path: &str) -> Option<&str> {
+    let path = path.trim_start_matches(['<', '&', '*', '[', '(']);
+    let path = ["mut ", "const ", "dyn "].iter().fold(path, |path, prefix| path.strip_prefix(prefix).unwrap_or(path));
+    let (krate, _) = path.split_once("::")?;
+    (!krate.is_empty() && krate.chars().all(|c| c.is_alphanumeric() || c == '_')).then_some(krate)
+}
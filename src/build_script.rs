@@ -0,0 +1,92 @@
+use crate::artifact::split_package_id;
+use std::fs;
+use std::path::PathBuf;
+
+/// What a build script did, from the `build-script-executed` message cargo emitted for it and
+/// the instructions it printed (`cargo:KEY=VALUE` or `cargo::KEY=VALUE`).
+///
+/// Cargo keeps the printed instructions in an `output` file next to the script's `OUT_DIR`,
+/// which is also read for scripts that did not rerun.
+///
+/// # Fields
+///
+/// * `package_id` - The cargo package ID of the package the build script belongs to.
+/// * `out_dir` - The `OUT_DIR` of the build script.
+/// * `linked_libs` - The native libraries linked (`rustc-link-lib`), e.g. `static=z` or `ssl`.
+/// * `linked_paths` - The library search paths added (`rustc-link-search`), e.g. `native=/usr/lib`.
+/// * `cfgs` - The `--cfg` flags set (`rustc-cfg`).
+/// * `env` - The environment variables set for the compilation of the package (`rustc-env`).
+/// * `warnings` - The warnings emitted (`warning`), shown by cargo for local packages only.
+/// * `errors` - The errors emitted (`error`), which fail the build.
+/// * `rerun_if_changed` - The files and directories the script reruns for when they change.
+/// * `rerun_if_env_changed` - The environment variables the script reruns for when they change.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     for script in &builder.build()?.build_scripts {
+///         println!("{} links {:?}", script.package_name(), script.linked_libs);
+///         for warning in &script.warnings {
+///             println!("  warning: {}", warning);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildScriptOutput {
+    pub package_id: String,
+    pub out_dir: PathBuf,
+    pub linked_libs: Vec<String>,
+    pub linked_paths: Vec<String>,
+    pub cfgs: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+    pub rerun_if_changed: Vec<PathBuf>,
+    pub rerun_if_env_changed: Vec<String>,
+}
+
+impl BuildScriptOutput {
+    /// Returns the name of the package the build script belongs to
+    pub fn package_name(&self) -> &str {
+        split_package_id(&self.package_id).0
+    }
+
+    /// Returns the value of the environment variable `key` set by the build script, if any
+    pub fn env_var(&self, key: &str) -> Option<&str> {
+        self.env.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str())
+    }
+
+    /// Private function that reads the instructions the build script printed from the `output`
+    /// file next to its `OUT_DIR`. Scripts whose output was removed report none.
+    pub(crate) fn read_instructions(&mut self) {
+        let Some(output) = self.out_dir.parent().map(|directory| directory.join("output")) else {
+            return;
+        };
+        if let Ok(output) = fs::read(output) {
+            self.parse_instructions(&String::from_utf8_lossy(&output));
+        }
+    }
+
+    /// Private function that collects the warnings, errors and rerun conditions of the printed
+    /// instructions `output`. The other instructions are reported by cargo's message.
+    fn parse_instructions(&mut self, output: &str) {
+        for line in output.lines() {
+            let instruction = line.strip_prefix("cargo::").or_else(|| line.strip_prefix("cargo:"));
+            let Some((key, value)) = instruction.and_then(|instruction| instruction.split_once('=')) else {
+                continue;
+            };
+            match key {
+                "warning" => self.warnings.push(value.to_string()),
+                "error" => self.errors.push(value.to_string()),
+                "rerun-if-changed" => self.rerun_if_changed.push(PathBuf::from(value)),
+                "rerun-if-env-changed" => self.rerun_if_env_changed.push(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+}
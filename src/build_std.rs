@@ -0,0 +1,58 @@
+/// The standard library crates rebuilt from source with `-Z build-std`, see
+/// [`Builder::set_build_std`](crate::Builder::set_build_std).
+///
+/// Rebuilding the standard library is needed for targets without a prebuilt one (e.g. custom
+/// target specifications), or to compile it with the project's profile and features. It needs
+/// the nightly toolchain and its `rust-src` component, see
+/// [`Builder::ensure_rust_src`](crate::Builder::ensure_rust_src).
+///
+/// # Fields
+///
+/// * `crates` - The crates to rebuild, e.g. `core` and `alloc`. Empty means cargo's default (`std`).
+/// * `features` - The features of the standard library to enable (`-Z build-std-features`), e.g. `panic_immediate_abort`.
+///
+/// # Example
+/// ```
+/// use cargo_wrap::BuildStd;
+///
+/// let build_std = BuildStd::new(["core", "alloc"]).feature("compiler-builtins-mem");
+/// assert_eq!(build_std.args(), ["-Zbuild-std=core,alloc", "-Zbuild-std-features=compiler-builtins-mem"]);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BuildStd {
+    pub crates: Vec<String>,
+    pub features: Vec<String>,
+}
+
+impl BuildStd {
+    /// Creates a `BuildStd` rebuilding the given crates, e.g. `["core", "alloc"]`. No crates
+    /// rebuilds cargo's default set.
+    pub fn new<I, S>(crates: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        BuildStd { crates: crates.into_iter().map(Into::into).collect(), features: Vec::new() }
+    }
+
+    /// Enables a feature of the standard library (`-Z build-std-features`)
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.features.push(feature.into());
+        self
+    }
+
+    /// Returns the flags passed to cargo
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec![if self.crates.is_empty() {
+            "-Zbuild-std".to_string()
+        } else {
+            format!("-Zbuild-std={}", self.crates.join(","))
+        }];
+        if !self.features.is_empty() {
+            args.push(format!("-Zbuild-std-features={}", self.features.join(",")));
+        }
+        args
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{BuildOutput, RenderedCommand, Result, ToolchainInfo};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Name of the directory in the target directory holding the build cache.
+pub(crate) const CACHE_DIR: &str = "cargo-wrap-cache";
+
+/// Directories skipped when hashing a source tree that is not in a git repository.
+const SKIPPED_DIRS: &[&str] = &[".git", "target"];
+
+/// A file produced by a cached build, with the size and modification time it had when the
+/// build finished.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct CachedFile {
+    path: PathBuf,
+    size: u64,
+    modified: Duration,
+}
+
+impl CachedFile {
+    /// Private function that reads the size and modification time of the file at `path`
+    fn stat(path: &Path) -> Option<CachedFile> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(CachedFile { path: path.to_path_buf(), size: metadata.len(), modified })
+    }
+}
+
+/// A successful build recorded in the build cache, see
+/// [`Builder::set_build_cache`](crate::Builder::set_build_cache).
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    files: Vec<CachedFile>,
+}
+
+impl CacheEntry {
+    /// Records the output of a successful build, along with the state of the files it produced
+    pub(crate) fn new(stdout: String, stderr: String, output: &BuildOutput) -> CacheEntry {
+        let paths = output.artifacts.iter().flat_map(|artifact| artifact.filenames.iter().chain(&artifact.executable));
+        let files = paths.filter_map(|path| CachedFile::stat(path)).collect();
+        CacheEntry { stdout, stderr, files }
+    }
+
+    /// Reads the entry at `path`, returning `None` if it does not exist or cannot be parsed
+    pub(crate) fn load(path: &Path) -> Option<CacheEntry> {
+        serde_json::from_slice(&fs::read(path).ok()?).ok()
+    }
+
+    /// Writes the entry to `path`, creating its directory if needed
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(path, serde_json::to_vec(self).map_err(std::io::Error::from)?)?;
+        Ok(())
+    }
+
+    /// Returns `true` if every file produced by the build is still there, unchanged since. Other
+    /// builds sharing the target directory may have replaced them.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.files.iter().all(|file| CachedFile::stat(&file.path).as_ref() == Some(file))
+    }
+}
+
+/// Computes the fingerprint of a build: the SHA-256 of the toolchain, the cargo invocation, the
+/// lockfile and the files of the source tree rooted at `root`, as a hex string.
+///
+/// The files are those git considers part of the tree (tracked, or untracked and not ignored) if
+/// `root` is in a git repository, otherwise every file outside of `.git` and `target`
+/// directories. Files under `target_dir` are skipped either way.
+pub(crate) fn fingerprint(
+    toolchain: &ToolchainInfo,
+    command: &RenderedCommand,
+    lockfile: Option<&Path>,
+    root: &Path,
+    target_dir: &Path,
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let toolchain = [
+        Some(toolchain.release.as_str()),
+        toolchain.commit_hash.as_deref(),
+        Some(toolchain.host.as_str()),
+        toolchain.llvm_version.as_deref(),
+    ];
+    for part in toolchain {
+        hasher.update(part.unwrap_or_default());
+        hasher.update([0]);
+    }
+    hasher.update(command.to_string());
+    hasher.update([0]);
+    // Hashed on its own since libraries often keep it out of git
+    if let Some(lockfile) = lockfile {
+        hasher.update(fs::read(lockfile)?);
+    }
+    hasher.update([0]);
+
+    let target_dir = std::path::absolute(target_dir)?;
+    let mut files = source_files(root);
+    files.retain(|file| !root.join(file).starts_with(&target_dir));
+    files.sort();
+    for file in files {
+        let Ok(contents) = fs::read(root.join(&file)) else { continue };
+        hasher.update(file.to_string_lossy().as_bytes());
+        hasher.update([0]);
+        hasher.update((contents.len() as u64).to_le_bytes());
+        hasher.update(&contents);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Private function that lists the files of the source tree rooted at `root`, relative to it
+fn source_files(root: &Path) -> Vec<PathBuf> {
+    let output = Command::new("git")
+        .args(["ls-files", "-z", "--cached", "--others", "--exclude-standard"])
+        .current_dir(root)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|file| !file.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        _ => {
+            let mut files = Vec::new();
+            walk(root, Path::new(""), &mut files);
+            files
+        }
+    }
+}
+
+/// Private function that collects the files under `root/relative` recursively
+fn walk(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(root.join(relative)) else { return };
+    for entry in entries.flatten() {
+        let path = relative.join(entry.file_name());
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() && !SKIPPED_DIRS.iter().any(|skipped| entry.file_name() == *skipped) => {
+                walk(root, &path, files)
+            }
+            Ok(kind) if kind.is_file() => files.push(path),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crate::pipeline::{resolve_tool, run_tool};
+use crate::{Artifact, BuildOutput, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{fs, io};
+use toml::Value;
+
+/// The language of the headers generated by [`Cbindgen`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HeaderLanguage {
+    /// A C header (`--lang c`).
+    #[default]
+    C,
+    /// A C++ header (`--lang c++`).
+    Cxx,
+    /// A Cython declaration file (`--lang cython`).
+    Cython,
+}
+
+impl HeaderLanguage {
+    /// Private function that returns the value passed to `--lang`
+    fn as_arg(self) -> &'static str {
+        match self {
+            HeaderLanguage::C => "c",
+            HeaderLanguage::Cxx => "c++",
+            HeaderLanguage::Cython => "cython",
+        }
+    }
+
+    /// Private function that returns the extension of the generated file
+    fn extension(self) -> &'static str {
+        match self {
+            HeaderLanguage::C => "h",
+            HeaderLanguage::Cxx => "hpp",
+            HeaderLanguage::Cython => "pxd",
+        }
+    }
+}
+
+/// How [`cbindgen`](https://github.com/mozilla/cbindgen) generates the header of the `cdylib`
+/// and `staticlib` libraries of a build, see [`Builder::set_cbindgen`](crate::Builder::set_cbindgen).
+///
+/// `cbindgen` is run as a subprocess in the directory of the library's crate, so a
+/// `cbindgen.toml` in that directory is picked up unless `config` is set.
+///
+/// # Fields
+///
+/// * `language` - The language of the header, overriding the language of the configuration file.
+/// * `include_guard` - Optional include guard of the header, e.g. `MY_LIB_H`.
+/// * `output` - Optional path of the header, relative to the crate directory. Defaults to
+///   `<library name>.h` (`.hpp` for C++, `.pxd` for Cython) next to the library.
+/// * `config` - Optional `cbindgen` configuration file, relative to the crate directory.
+/// * `tool` - Optional path or name of the `cbindgen` binary. Defaults to `cbindgen`, searched in `PATH`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Cbindgen {
+    pub language: HeaderLanguage,
+    pub include_guard: Option<String>,
+    pub output: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub tool: Option<PathBuf>,
+}
+
+impl Cbindgen {
+    /// Private function that generates the header of every `cdylib` and `staticlib` of `output`.
+    ///
+    /// Headers of libraries that were up to date are only generated if they are missing.
+    /// Configuration files with an include guard are written to `scratch_dir`.
+    pub(crate) fn generate(&self, output: &BuildOutput, scratch_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut headers = Vec::new();
+        for artifact in &output.artifacts {
+            if !artifact.kind.iter().any(|kind| kind == "cdylib" || kind == "staticlib") {
+                continue;
+            }
+            let Some(crate_dir) = artifact.manifest_path.parent() else { continue };
+            let header = self.header_path(artifact, crate_dir);
+            if !artifact.fresh || !header.is_file() {
+                self.run(artifact, crate_dir, &header, scratch_dir)?;
+            }
+            if !headers.contains(&header) {
+                headers.push(header);
+            }
+        }
+        Ok(headers)
+    }
+
+    /// Private function that returns where the header of `artifact` is written
+    fn header_path(&self, artifact: &Artifact, crate_dir: &Path) -> PathBuf {
+        if let Some(output) = &self.output {
+            return crate_dir.join(output);
+        }
+        let directory = artifact.filenames.first().and_then(|library| library.parent()).unwrap_or(crate_dir);
+        directory.join(format!("{}.{}", artifact.target_name, self.language.extension()))
+    }
+
+    /// Private function that runs `cbindgen` for the crate in `crate_dir`
+    fn run(&self, artifact: &Artifact, crate_dir: &Path, header: &Path, scratch_dir: &Path) -> Result<()> {
+        let mut command = Command::new(resolve_tool(self.tool.as_deref(), "cbindgen")?);
+        command.current_dir(crate_dir).arg("--lang").arg(self.language.as_arg());
+        let config = match &self.config {
+            Some(config) => Some(crate_dir.join(config)),
+            None => Some(crate_dir.join("cbindgen.toml")).filter(|config| config.is_file()),
+        };
+        if let Some(include_guard) = &self.include_guard {
+            // cbindgen has no flag for the include guard, so it is added to the configuration
+            let mut table = match &config {
+                Some(config) => toml::from_str(&fs::read_to_string(config)?).map_err(io::Error::other)?,
+                None => toml::Table::new(),
+            };
+            table.insert("include_guard".to_string(), Value::String(include_guard.clone()));
+            fs::create_dir_all(scratch_dir)?;
+            let config = scratch_dir.join(format!("cbindgen-{}.toml", artifact.target_name));
+            fs::write(&config, toml::to_string(&table).map_err(io::Error::other)?)?;
+            command.arg("--config").arg(config);
+        } else if let Some(config) = config {
+            command.arg("--config").arg(config);
+        }
+        if let Some(parent) = header.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        command.arg("--output").arg(header).arg("--quiet").arg(crate_dir);
+        run_tool("cbindgen", &mut command)?;
+        Ok(())
+    }
+}
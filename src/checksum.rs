@@ -0,0 +1,183 @@
+use crate::{BuildOutput, Result};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// The name of the manifest written next to the artifacts by [`Builder::set_checksums`](crate::Builder::set_checksums).
+pub(crate) const MANIFEST_FILE_NAME: &str = "checksums.json";
+
+/// The checksums of a single artifact, see [`ChecksumManifest`].
+///
+/// # Fields
+///
+/// * `name` - The file name of the artifact.
+/// * `path` - The path of the artifact.
+/// * `size` - The size of the artifact, in bytes.
+/// * `sha256` - The hex-encoded SHA-256 hash of the artifact.
+/// * `blake3` - The hex-encoded BLAKE3 hash of the artifact, if the `blake3` feature is enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactChecksum {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub sha256: String,
+    pub blake3: Option<String>,
+}
+
+/// The checksums of the binaries and libraries produced by a build, for integrity verification
+/// in release pipelines.
+///
+/// The manifest covers every executable, shared library and static library of the build,
+/// including those that were up to date. It is written as JSON by [`ChecksumManifest::write`]:
+///
+/// ```json
+/// {
+///   "target": "x86_64-unknown-linux-gnu",
+///   "profile": "release",
+///   "artifacts": [
+///     { "name": "app", "size": 4435400, "sha256": "9f86d0...", "target": "x86_64-unknown-linux-gnu", "profile": "release" }
+///   ]
+/// }
+/// ```
+///
+/// # Fields
+///
+/// * `target` - The compilation target triple, `None` meaning the host.
+/// * `profile` - The name of the profile the artifacts were built with.
+/// * `artifacts` - The checksums of every artifact.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ChecksumManifest, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+///     let output = builder.build()?;
+///     let manifest = ChecksumManifest::compute(&output, None, "release")?;
+///     for artifact in &manifest.artifacts {
+///         println!("{}  {}", artifact.sha256, artifact.name);
+///     }
+///     manifest.write("dist/checksums.json")?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumManifest {
+    pub target: Option<String>,
+    pub profile: String,
+    pub artifacts: Vec<ArtifactChecksum>,
+}
+
+impl ArtifactChecksum {
+    /// Hashes the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the file cannot be read.
+    pub fn compute(path: impl AsRef<Path>) -> Result<ArtifactChecksum> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        let mut sha256 = Sha256::new();
+        #[cfg(feature = "blake3")]
+        let mut blake3 = blake3::Hasher::new();
+        let mut buffer = vec![0; 64 * 1024];
+        let mut size = 0;
+        loop {
+            let read = match file.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            sha256.update(&buffer[..read]);
+            #[cfg(feature = "blake3")]
+            blake3.update(&buffer[..read]);
+            size += read as u64;
+        }
+        #[cfg(feature = "blake3")]
+        let blake3 = Some(blake3.finalize().to_hex().to_string());
+        #[cfg(not(feature = "blake3"))]
+        let blake3 = None;
+        Ok(ArtifactChecksum {
+            name: path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+            path: path.to_path_buf(),
+            size,
+            sha256: sha256.finalize().iter().map(|byte| format!("{:02x}", byte)).collect(),
+            blake3,
+        })
+    }
+}
+
+impl ChecksumManifest {
+    /// Hashes the executables, shared libraries and static libraries of `output`, see [`BuildOutput::binaries`].
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output of the build.
+    /// * `target` - The compilation target triple of the build, `None` meaning the host.
+    /// * `profile` - The name of the profile of the build.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if an artifact cannot be read.
+    pub fn compute(output: &BuildOutput, target: Option<&str>, profile: &str) -> Result<ChecksumManifest> {
+        let artifacts = output.binaries().iter().map(ArtifactChecksum::compute).collect::<Result<_>>()?;
+        Ok(ChecksumManifest { target: target.map(String::from), profile: profile.to_string(), artifacts })
+    }
+
+    /// Returns the checksums of the artifact named `name`
+    pub fn get(&self, name: &str) -> Option<&ArtifactChecksum> {
+        self.artifacts.iter().find(|artifact| artifact.name == name)
+    }
+
+    /// Returns the deepest directory containing every artifact, where
+    /// [`Builder::set_checksums`](crate::Builder::set_checksums) writes the manifest
+    pub fn directory(&self) -> Option<PathBuf> {
+        let mut artifacts = self.artifacts.iter().filter_map(|artifact| artifact.path.parent());
+        let mut directory = artifacts.next()?.to_path_buf();
+        for parent in artifacts {
+            while !parent.starts_with(&directory) {
+                directory = directory.parent()?.to_path_buf();
+            }
+        }
+        Some(directory)
+    }
+
+    /// Returns the manifest as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let artifacts: Vec<serde_json::Value> = self
+            .artifacts
+            .iter()
+            .map(|artifact| {
+                let mut entry = serde_json::json!({
+                    "name": artifact.name,
+                    "size": artifact.size,
+                    "sha256": artifact.sha256,
+                    "target": self.target,
+                    "profile": self.profile,
+                });
+                if let Some(blake3) = &artifact.blake3 {
+                    entry["blake3"] = blake3.as_str().into();
+                }
+                entry
+            })
+            .collect();
+        let manifest = serde_json::json!({ "target": self.target, "profile": self.profile, "artifacts": artifacts });
+        serde_json::to_string_pretty(&manifest).unwrap_or_default()
+    }
+
+    /// Writes the manifest as JSON to `path`, creating its parent directories.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the file cannot be written.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+}
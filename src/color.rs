@@ -0,0 +1,60 @@
+use std::borrow::Cow;
+
+/// Whether cargo colors its output (`--color X`).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorChoice {
+    /// Cargo decides based on whether its output is a terminal. No flag is passed.
+    #[default]
+    Auto,
+    /// Output is always colored (`--color always`).
+    Always,
+    /// Output is never colored (`--color never`).
+    Never,
+}
+
+impl ColorChoice {
+    /// Private function that returns the value passed to `--color`, or `None` for cargo's default
+    pub(crate) fn as_arg(self) -> Option<&'static str> {
+        match self {
+            ColorChoice::Auto => None,
+            ColorChoice::Always => Some("always"),
+            ColorChoice::Never => Some("never"),
+        }
+    }
+}
+
+/// Private function that removes ANSI escape sequences (e.g. colors and cursor movements) from `output`
+pub(crate) fn strip_ansi(output: &[u8]) -> Cow<'_, [u8]> {
+    if !output.contains(&0x1b) {
+        return Cow::Borrowed(output);
+    }
+    let mut stripped = Vec::with_capacity(output.len());
+    let mut bytes = output.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        if byte != 0x1b {
+            stripped.push(byte);
+            continue;
+        }
+        match bytes.next() {
+            // CSI sequences end with a byte in `@`..=`~`
+            Some(b'[') => {
+                for byte in bytes.by_ref() {
+                    if (0x40..=0x7e).contains(&byte) {
+                        break;
+                    }
+                }
+            }
+            // OSC sequences end with BEL or `ESC \`
+            Some(b']') => {
+                while let Some(byte) = bytes.next() {
+                    if byte == 0x07 || (byte == 0x1b && bytes.next_if_eq(&b'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Cow::Owned(stripped)
+}
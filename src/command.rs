@@ -0,0 +1,61 @@
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A fully rendered `cargo` invocation, as it would be executed.
+///
+/// # Fields
+///
+/// * `program` - The program that is executed (the `cargo` binary).
+/// * `args` - Every argument passed to the program.
+/// * `current_dir` - The working directory of the process, if it is changed.
+/// * `envs` - Environment overrides. A value of `None` means the variable is removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedCommand {
+    pub program: PathBuf,
+    pub args: Vec<OsString>,
+    pub current_dir: Option<PathBuf>,
+    pub envs: Vec<(OsString, Option<OsString>)>,
+}
+
+impl From<&Command> for RenderedCommand {
+    fn from(command: &Command) -> Self {
+        RenderedCommand {
+            program: PathBuf::from(command.get_program()),
+            args: command.get_args().map(OsStr::to_os_string).collect(),
+            current_dir: command.get_current_dir().map(PathBuf::from),
+            envs: command.get_envs().map(|(k, v)| (k.to_os_string(), v.map(OsStr::to_os_string))).collect(),
+        }
+    }
+}
+
+/// Renders the command the way it could be typed into a POSIX shell, e.g.
+/// `CARGO_TARGET_DIR=out cargo build --release`. Removed variables and the working
+/// directory are not included.
+impl fmt::Display for RenderedCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, value) in &self.envs {
+            if let Some(value) = value {
+                write!(f, "{}={} ", key.to_string_lossy(), quote(value))?;
+            }
+        }
+        write!(f, "{}", quote(self.program.as_os_str()))?;
+        for arg in &self.args {
+            write!(f, " {}", quote(arg))?;
+        }
+        Ok(())
+    }
+}
+
+/// Private function that single-quotes a shell word if it contains special characters
+fn quote(word: &OsStr) -> String {
+    let word = word.to_string_lossy();
+    let is_plain = !word.is_empty()
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if is_plain {
+        word.into_owned()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
@@ -0,0 +1,319 @@
+use crate::{BuildStd, BuildTarget, CargoWrapError, Cbindgen, ColorChoice, CrateType, DuplicatePolicy, Prerequisites, ProfileOverrides, ProjectSettings, Result, RetryPolicy, RunStdio, RustFlagsPolicy, Sanitizer, Verbosity};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A plain, serializable snapshot of a [`Builder`](crate::Builder)'s configuration.
+///
+/// With the `serde` feature enabled, configurations can be stored (e.g. as JSON or TOML),
+/// diffed and replayed later with [`Builder::from_config`](crate::Builder::from_config).
+/// Custom [`BuildBackend`](crate::BuildBackend)s are not part of the configuration.
+///
+/// # Fields
+///
+/// * `cargo_path` - Optional path to the `cargo` binary. If `None`, it is looked up when the
+///   `Builder` is created.
+/// * `project_settings` - The settings of the project to build.
+/// * `jobs` - Number of parallel jobs (`--jobs N`), `0` meaning cargo's default.
+/// * `log_path` - Optional log file to store output.
+/// * `verbosity` - How much cargo reports (`--quiet`, `--verbose` or `-vv`).
+/// * `rustc_flags` - Flags passed to `rustc`.
+/// * `rustflags_policy` - How `rustc_flags` are combined with the `rustc` flags of the environment.
+/// * `deny_warnings` - If `true`, warnings are turned into errors (`-D warnings`).
+/// * `timings` - If `true`, cargo writes a timing report (`--timings`) that is parsed into the build output.
+/// * `keep_going` - If `true`, cargo builds as many crates as possible after one fails (`--keep-going`).
+/// * `future_incompat_report` - If `true`, cargo's future-incompatibility report (`--future-incompat-report`) is parsed into the build output.
+/// * `checksums` - If `true`, the produced binaries are hashed and a `checksums.json` manifest is written next to them.
+/// * `build_cache` - If `true`, builds whose inputs match an earlier successful build are skipped.
+/// * `isolated_target_dir` - If `true`, every configuration (target, features, profile) builds into its own target directory.
+/// * `install_dir` - Optional directory the binaries of every successful build are copied into.
+/// * `install_template` - Optional name the binaries are installed under, e.g. `{name}-{version}-{target}{ext}`.
+/// * `size_history` - Optional history file the artifact sizes of every successful build are recorded in.
+/// * `incremental` - Optional override of incremental compilation (`CARGO_INCREMENTAL`), `None` meaning the profile's setting.
+/// * `rustc_wrapper` - Optional program `rustc` is invoked through (`RUSTC_WRAPPER`), e.g. `sccache`.
+/// * `run_stdio` - Standard stream handling for programs started with `cargo run`.
+/// * `timeout` - Optional time limit after which a running build is killed.
+/// * `retry_policy` - Optional policy retrying builds that failed because of the network.
+/// * `duplicate_policy` - Optional policy checking the dependency graph for packages resolved at several versions before builds.
+/// * `prerequisites` - Optional system tools and libraries probed before builds.
+/// * `toolchain` - Optional rustup toolchain to build with.
+/// * `sanitizer` - Optional runtime sanitizer the project is instrumented with.
+/// * `build_std` - Optional standard library crates rebuilt from source.
+/// * `profile` - Optional custom profile to build with (`--profile X`).
+/// * `profile_overrides` - Settings of the active profile overridden for the build.
+/// * `crate_type` - Optional crate type the library is compiled as, overriding the manifest.
+/// * `cbindgen` - Optional settings of the headers generated for `cdylib` and `staticlib` libraries.
+/// * `envs` - Environment variables set for the cargo process.
+/// * `hermetic` - If `true`, the cargo process only inherits an allowlist of environment variables.
+/// * `allowed_envs` - Additional environment variables inherited by hermetic builds.
+/// * `color` - Whether cargo colors its output (`--color X`).
+/// * `strip_log_ansi` - If `true`, ANSI escape sequences are removed from the output written to the log file.
+/// * `log_timestamps` - If `true`, every line written to the log file is prefixed with the time and the stream it was read from.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BuilderConfig {
+    pub cargo_path: Option<PathBuf>,
+    pub project_settings: ProjectSettings,
+    pub jobs: usize,
+    pub log_path: Option<PathBuf>,
+    pub verbosity: Verbosity,
+    pub rustc_flags: Vec<String>,
+    pub rustflags_policy: RustFlagsPolicy,
+    pub deny_warnings: bool,
+    pub timings: bool,
+    pub keep_going: bool,
+    pub future_incompat_report: bool,
+    pub checksums: bool,
+    pub build_cache: bool,
+    pub isolated_target_dir: bool,
+    pub install_dir: Option<PathBuf>,
+    pub install_template: Option<String>,
+    pub size_history: Option<PathBuf>,
+    pub incremental: Option<bool>,
+    pub rustc_wrapper: Option<PathBuf>,
+    pub run_stdio: RunStdio,
+    pub timeout: Option<Duration>,
+    pub retry_policy: Option<RetryPolicy>,
+    pub duplicate_policy: Option<DuplicatePolicy>,
+    pub prerequisites: Option<Prerequisites>,
+    pub toolchain: Option<String>,
+    pub sanitizer: Option<Sanitizer>,
+    pub build_std: Option<BuildStd>,
+    pub profile: Option<String>,
+    pub profile_overrides: ProfileOverrides,
+    pub crate_type: Option<CrateType>,
+    pub cbindgen: Option<Cbindgen>,
+    pub envs: BTreeMap<String, String>,
+    pub hermetic: bool,
+    pub allowed_envs: Vec<String>,
+    pub color: ColorChoice,
+    pub strip_log_ansi: bool,
+    pub log_timestamps: bool,
+}
+
+/// The declarative build recipe read by [`BuilderConfig::from_file`] and
+/// [`ProjectSettings::from_file`].
+///
+/// Relative paths are resolved against the directory containing the file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BuildRecipe {
+    project_path: Option<PathBuf>,
+    manifest_path: Option<PathBuf>,
+    target: Option<String>,
+    bin: Option<String>,
+    bins: Vec<String>,
+    all_bins: bool,
+    lib: bool,
+    examples: Vec<String>,
+    tests: Vec<String>,
+    benches: Vec<String>,
+    all_targets: bool,
+    features: Vec<String>,
+    no_default_features: bool,
+    all_features: bool,
+    release: bool,
+    output_path: Option<PathBuf>,
+    workspace: bool,
+    packages: Vec<String>,
+    exclude: Vec<String>,
+    offline: bool,
+    locked: bool,
+    frozen: bool,
+    rustflags: Vec<String>,
+    deny_warnings: bool,
+    timings: bool,
+    keep_going: bool,
+    future_incompat_report: bool,
+    checksums: bool,
+    build_cache: bool,
+    isolated_target_dir: bool,
+    install_dir: Option<PathBuf>,
+    install_template: Option<String>,
+    size_history: Option<PathBuf>,
+    incremental: Option<bool>,
+    rustc_wrapper: Option<PathBuf>,
+    jobs: usize,
+    toolchain: Option<String>,
+    profile: Option<String>,
+    hermetic: bool,
+    allow_env: Vec<String>,
+    env: BTreeMap<String, String>,
+}
+
+impl BuilderConfig {
+    /// Reads a build configuration from a declarative TOML file.
+    ///
+    /// The file may contain the following keys, all of which are optional:
+    ///
+    /// ```toml
+    /// project_path = "."                     # defaults to the directory containing the file
+    /// manifest_path = "tools/cli/Cargo.toml" # build with --manifest-path instead of project_path
+    /// target = "x86_64-unknown-linux-musl"   # compilation target triple (--target)
+    /// bin = "my-cli"                         # binary (or library, with lib = true) to build
+    /// bins = ["daemon", "helper"]            # also built: --bin X
+    /// all_bins = false                       # --bins
+    /// lib = false
+    /// examples = ["demo"]                    # also built: --example X, --test X, --bench X
+    /// tests = []
+    /// benches = []
+    /// all_targets = false                    # --all-targets
+    /// features = ["tls", "metrics"]
+    /// no_default_features = true
+    /// all_features = false
+    /// release = true
+    /// profile = "release-lto"                # custom profile (--profile), overrides release
+    /// output_path = "out"                    # CARGO_TARGET_DIR
+    /// workspace = false
+    /// packages = []
+    /// exclude = []
+    /// offline = false                        # --offline
+    /// locked = true                          # --locked
+    /// frozen = false                         # --frozen
+    /// rustflags = ["-C", "target-cpu=native"]
+    /// deny_warnings = true                   # -D warnings
+    /// timings = true                         # --timings, per-crate compile durations
+    /// keep_going = true                      # --keep-going, build past failing crates
+    /// future_incompat_report = true          # --future-incompat-report, dependencies that will break
+    /// checksums = true                       # checksums.json next to the binaries
+    /// build_cache = true                     # skip builds whose inputs did not change
+    /// isolated_target_dir = true             # target/config-<hash> per target, features and profile
+    /// install_dir = "dist"                   # copy the binaries into a flat directory
+    /// install_template = "{name}-{version}-{target}{ext}"
+    /// size_history = "sizes.json"            # record binary sizes after every build
+    /// incremental = false                    # CARGO_INCREMENTAL=0
+    /// rustc_wrapper = "sccache"              # RUSTC_WRAPPER
+    /// jobs = 4
+    /// toolchain = "nightly-2024-05-01"
+    /// hermetic = true                        # only inherit PATH, HOME, CARGO_HOME, RUSTUP_HOME
+    /// allow_env = ["SSH_AUTH_SOCK"]          # ...and these variables
+    ///
+    /// [env]                                  # environment variables for the cargo process
+    /// OPENSSL_DIR = "/opt/openssl"
+    /// ```
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the configuration file, e.g. `wrapbuild.toml`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BuilderConfig)` - The configuration described by the file.
+    /// * `Err(CargoWrapError)` - If the file cannot be read or parsed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The file cannot be read ([`CargoWrapError::Io`]).
+    /// - The file is not valid TOML or contains unknown keys ([`CargoWrapError::ConfigParse`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, BuilderConfig};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::from_config(BuilderConfig::from_file("wrapbuild.toml")?)?;
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<BuilderConfig> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)?;
+        let recipe: BuildRecipe = toml::from_str(&content).map_err(|source| CargoWrapError::ConfigParse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let base = path.parent().unwrap_or(Path::new(""));
+
+        let project_settings = match recipe.manifest_path {
+            Some(manifest_path) => ProjectSettings::from_manifest_path(base.join(manifest_path)),
+            None => ProjectSettings::new(base.join(recipe.project_path.unwrap_or_default())),
+        };
+        let mut project_settings = project_settings
+            .lib(recipe.lib)
+            .no_default_features(recipe.no_default_features)
+            .all_features(recipe.all_features)
+            .release(recipe.release)
+            .workspace(recipe.workspace)
+            .packages(recipe.packages)
+            .exclude(recipe.exclude)
+            .offline(recipe.offline)
+            .locked(recipe.locked)
+            .frozen(recipe.frozen);
+        if let Some(triple) = recipe.target {
+            project_settings = project_settings.compilation_target(triple);
+        }
+        if let Some(target) = recipe.bin {
+            project_settings = project_settings.target(target);
+        }
+        let targets = recipe.bins.into_iter().map(BuildTarget::Bin)
+            .chain(recipe.examples.into_iter().map(BuildTarget::Example))
+            .chain(recipe.tests.into_iter().map(BuildTarget::Test))
+            .chain(recipe.benches.into_iter().map(BuildTarget::Bench));
+        for target in targets {
+            project_settings = project_settings.build_target(target);
+        }
+        if recipe.all_bins {
+            project_settings = project_settings.build_target(BuildTarget::Bins);
+        }
+        if recipe.all_targets {
+            project_settings = project_settings.build_target(BuildTarget::AllTargets);
+        }
+        if !recipe.features.is_empty() {
+            project_settings = project_settings.features(recipe.features);
+        }
+        if let Some(output_path) = recipe.output_path {
+            project_settings = project_settings.output_path(base.join(output_path));
+        }
+
+        Ok(BuilderConfig {
+            project_settings,
+            jobs: recipe.jobs,
+            rustc_flags: recipe.rustflags,
+            deny_warnings: recipe.deny_warnings,
+            timings: recipe.timings,
+            keep_going: recipe.keep_going,
+            future_incompat_report: recipe.future_incompat_report,
+            checksums: recipe.checksums,
+            build_cache: recipe.build_cache,
+            isolated_target_dir: recipe.isolated_target_dir,
+            install_dir: recipe.install_dir.map(|install_dir| base.join(install_dir)),
+            install_template: recipe.install_template,
+            size_history: recipe.size_history.map(|size_history| base.join(size_history)),
+            incremental: recipe.incremental,
+            rustc_wrapper: recipe.rustc_wrapper,
+            toolchain: recipe.toolchain,
+            profile: recipe.profile,
+            envs: recipe.env,
+            hermetic: recipe.hermetic,
+            allowed_envs: recipe.allow_env,
+            ..Default::default()
+        })
+    }
+}
+
+impl ProjectSettings {
+    /// Reads project settings from a declarative TOML file.
+    ///
+    /// See [`BuilderConfig::from_file`] for the file format. The builder-level keys it handles,
+    /// such as `rustflags` or `env`, are accepted but ignored; use [`BuilderConfig::from_file`]
+    /// to keep them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::ProjectSettings;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::from_file("wrapbuild.toml")?;
+    ///     println!("Building {}", settings.get_project_path().display());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<ProjectSettings> {
+        Ok(BuilderConfig::from_file(path)?.project_settings)
+    }
+}
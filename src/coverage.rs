@@ -0,0 +1,319 @@
+use crate::pipeline::{resolve_llvm_tool, run_stdout, run_tool};
+use crate::{BuildOutput, Builder, CargoWrapError, Result, TestOptions, TestSummary};
+use serde::Deserialize;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Source files excluded from coverage reports unless [`Coverage::include_dependencies`] is set:
+/// registry and git dependencies, and the standard library.
+const DEPENDENCY_PATHS: &[&str] = &[r"[/\\]\.cargo[/\\](registry|git)[/\\]", r"[/\\]rustlib[/\\]", r"^/rustc/"];
+
+/// The format of the report written by [`Coverage::run`], besides the summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CoverageFormat {
+    /// An LCOV tracefile (`lcov.info`), as read by most coverage services and editors.
+    Lcov,
+    /// A browsable HTML report (`html/index.html`).
+    Html,
+}
+
+/// How many items of one kind were covered, see [`CoverageSummary`].
+///
+/// # Fields
+///
+/// * `count` - The number of instrumented items.
+/// * `covered` - The number of items executed at least once.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct CoverageCount {
+    pub count: usize,
+    pub covered: usize,
+}
+
+impl CoverageCount {
+    /// Returns the covered share of the items, from `0.0` to `100.0`; `100.0` if there are none
+    pub fn percent(&self) -> f64 {
+        if self.count == 0 {
+            return 100.0;
+        }
+        self.covered as f64 * 100.0 / self.count as f64
+    }
+}
+
+/// The coverage totals over every source file of a report.
+///
+/// # Fields
+///
+/// * `lines` - Lines of code with instrumented code.
+/// * `functions` - Functions, including closures and monomorphizations of generic functions.
+/// * `regions` - Code regions, the branches and blocks of the functions.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct CoverageSummary {
+    pub lines: CoverageCount,
+    pub functions: CoverageCount,
+    pub regions: CoverageCount,
+}
+
+/// Private mirror of the output of `llvm-cov export -summary-only`
+#[derive(Deserialize)]
+struct CoverageExport {
+    data: Vec<ExportData>,
+}
+
+/// Private mirror of an entry of [`CoverageExport`]
+#[derive(Deserialize)]
+struct ExportData {
+    totals: CoverageSummary,
+}
+
+impl CoverageSummary {
+    /// Parses the totals written by `llvm-cov export -format=text -summary-only`.
+    ///
+    /// Returns `None` if `json` is not such an export.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::CoverageSummary;
+    ///
+    /// let json = r#"{"type": "llvm.coverage.json.export", "version": "2.0.1", "data": [{
+    ///     "files": [],
+    ///     "totals": {
+    ///         "lines": {"count": 200, "covered": 150, "percent": 75},
+    ///         "functions": {"count": 20, "covered": 19, "percent": 95},
+    ///         "regions": {"count": 80, "covered": 60, "notcovered": 20, "percent": 75}
+    ///     }
+    /// }]}"#;
+    /// let summary = CoverageSummary::parse(json).unwrap();
+    /// assert_eq!(summary.lines.covered, 150);
+    /// assert_eq!(summary.functions.percent(), 95.0);
+    /// ```
+    pub fn parse(json: &str) -> Option<CoverageSummary> {
+        let export: CoverageExport = serde_json::from_str(json).ok()?;
+        export.data.into_iter().next().map(|data| data.totals)
+    }
+}
+
+/// The output of [`Coverage::run`].
+///
+/// # Fields
+///
+/// * `tests` - The results of the instrumented test run.
+/// * `summary` - The line, function and region coverage of the project's sources.
+/// * `profdata` - The merged profile (`coverage.profdata`), for running `llvm-cov` with other options.
+/// * `objects` - The instrumented binaries the profile applies to.
+/// * `report` - The report written in the requested [`CoverageFormat`], if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageOutput {
+    pub tests: TestSummary,
+    pub summary: CoverageSummary,
+    pub profdata: PathBuf,
+    pub objects: Vec<PathBuf>,
+    pub report: Option<PathBuf>,
+}
+
+/// Measures the code coverage of a project's tests with LLVM source-based coverage.
+///
+/// The tests are built with `-C instrument-coverage` into a separate target directory, so the
+/// regular build cache is kept, and run with `cargo test`, every process writing its profile to
+/// the output directory through `LLVM_PROFILE_FILE`. The profiles are merged with
+/// `llvm-profdata`, and `llvm-cov` computes the summary and writes the report.
+///
+/// The LLVM tools must match the LLVM version of rustc; they are taken from the `llvm-tools`
+/// rustup component (`rustup component add llvm-tools`) when it is installed, otherwise from
+/// `PATH`. Documentation tests are not instrumented.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, Coverage, CoverageFormat, ProjectSettings, TestOptions};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     let output = Coverage::new(builder).format(CoverageFormat::Lcov).run(&TestOptions::default())?;
+///     println!("{:.1}% of lines covered, report at {:?}", output.summary.lines.percent(), output.report);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Coverage {
+    builder: Builder,
+    format: Option<CoverageFormat>,
+    out_dir: Option<PathBuf>,
+    ignore_filename_regex: Vec<String>,
+    include_dependencies: bool,
+    llvm_tools_dir: Option<PathBuf>,
+}
+
+impl Coverage {
+    /// Creates a new `Coverage` for the project of `builder`
+    pub fn new(builder: Builder) -> Self {
+        Coverage {
+            builder,
+            format: None,
+            out_dir: None,
+            ignore_filename_regex: Vec::new(),
+            include_dependencies: false,
+            llvm_tools_dir: None,
+        }
+    }
+
+    /// Writes a report in `format` to the output directory
+    pub fn format(mut self, format: CoverageFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Sets the directory the profiles, reports and instrumented build are written to. Defaults
+    /// to `coverage/` in the target directory.
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Excludes the source files whose paths match the regular expression `regex` from the
+    /// summary and report (`-ignore-filename-regex`), e.g. `tests/` or `generated\.rs$`
+    pub fn ignore_filename_regex(mut self, regex: impl Into<String>) -> Self {
+        self.ignore_filename_regex.push(regex.into());
+        self
+    }
+
+    /// Sets whether the sources of registry and git dependencies and of the standard library are
+    /// included in the summary and report. Defaults to `false`.
+    pub fn include_dependencies(mut self, include_dependencies: bool) -> Self {
+        self.include_dependencies = include_dependencies;
+        self
+    }
+
+    /// Sets the directory `llvm-profdata` and `llvm-cov` are taken from
+    pub fn llvm_tools_dir(mut self, llvm_tools_dir: impl Into<PathBuf>) -> Self {
+        self.llvm_tools_dir = Some(llvm_tools_dir.into());
+        self
+    }
+
+    /// Returns the builder providing the project settings
+    pub fn get_builder(&self) -> &Builder {
+        &self.builder
+    }
+
+    /// Returns the directory the profiles, reports and instrumented build are written to
+    pub fn get_out_dir(&self) -> PathBuf {
+        self.out_dir.clone().unwrap_or_else(|| self.builder.target_dir().join("coverage"))
+    }
+
+    /// Runs the project's tests with coverage instrumentation, and computes the coverage.
+    ///
+    /// Profiles of earlier runs in the output directory are removed first. Failing tests do not
+    /// stop the coverage from being computed; they are reported in [`CoverageOutput::tests`].
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A `TestOptions` instance with filters and harness settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CoverageOutput)` - The coverage summary, and the paths of the profile and report.
+    /// * `Err(CargoWrapError)` - If the tests could not be compiled or the LLVM tools fail.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The tests cannot be compiled ([`CargoWrapError::CompilationFailed`]).
+    /// - No profile was written, e.g. because `options.no_run` is set ([`CargoWrapError::InvalidSettings`]).
+    /// - `llvm-profdata` or `llvm-cov` cannot be found ([`CargoWrapError::ToolNotFound`]).
+    /// - `llvm-profdata` or `llvm-cov` fails, e.g. because its LLVM version does not match
+    ///   rustc's ([`CargoWrapError::ToolFailed`]).
+    pub fn run(&self, options: &TestOptions) -> Result<CoverageOutput> {
+        let out_dir = std::path::absolute(self.get_out_dir())?;
+        let profraw_dir = out_dir.join("profraw");
+        if profraw_dir.exists() {
+            fs::remove_dir_all(&profraw_dir)?;
+        }
+        fs::create_dir_all(&profraw_dir)?;
+
+        let mut config = self.builder.config();
+        config.project_settings.output_path = Some(out_dir.join("target"));
+        config.rustc_flags.push("-Cinstrument-coverage".to_string());
+        // `%p` and `%m` keep the profiles of concurrent processes and different binaries apart
+        config.envs.insert("LLVM_PROFILE_FILE".to_string(), profraw_dir.join("%p-%m.profraw").to_string_lossy().into_owned());
+        let mut builder = Builder::from_config(config)?;
+        builder.backend = self.builder.backend.clone();
+        builder.sinks = self.builder.sinks.clone();
+
+        let tests = builder.test(options)?;
+        let objects = Coverage::objects(&builder)?;
+        let profraws: Vec<PathBuf> = fs::read_dir(&profraw_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension() == Some(OsStr::new("profraw")))
+            .collect();
+        if profraws.is_empty() {
+            return Err(CargoWrapError::InvalidSettings("no coverage profile was written, no instrumented test ran".to_string()));
+        }
+
+        let profdata = out_dir.join("coverage.profdata");
+        let mut merge = Command::new(resolve_llvm_tool(&self.builder, self.llvm_tools_dir.as_deref(), "llvm-profdata")?);
+        merge.args(["merge", "-sparse"]).args(&profraws).arg("-o").arg(&profdata);
+        run_tool("llvm-profdata", &mut merge)?;
+
+        let llvm_cov = resolve_llvm_tool(&self.builder, self.llvm_tools_dir.as_deref(), "llvm-cov")?;
+        let mut export = self.llvm_cov(&llvm_cov, "export", &profdata, &objects);
+        export.args(["-format=text", "-summary-only"]);
+        let summary = CoverageSummary::parse(&run_stdout("llvm-cov", &mut export)?)
+            .ok_or_else(|| io::Error::other("unrecognized output of `llvm-cov export`"))?;
+
+        let report = match self.format {
+            Some(CoverageFormat::Lcov) => {
+                let mut export = self.llvm_cov(&llvm_cov, "export", &profdata, &objects);
+                export.arg("-format=lcov");
+                let path = out_dir.join("lcov.info");
+                fs::write(&path, run_stdout("llvm-cov", &mut export)?)?;
+                Some(path)
+            }
+            Some(CoverageFormat::Html) => {
+                let html_dir = out_dir.join("html");
+                let mut show = self.llvm_cov(&llvm_cov, "show", &profdata, &objects);
+                show.arg("-format=html").arg(format!("-output-dir={}", html_dir.display())).arg("-show-line-counts-or-regions");
+                run_tool("llvm-cov", &mut show)?;
+                Some(html_dir.join("index.html"))
+            }
+            None => None,
+        };
+        Ok(CoverageOutput { tests, summary, profdata, objects, report })
+    }
+
+    /// Private function that lists the instrumented test binaries and executables of `builder`,
+    /// from the JSON messages of `cargo test --no-run`
+    fn objects(builder: &Builder) -> Result<Vec<PathBuf>> {
+        let mut command = builder.test_command(&TestOptions { no_run: true, ..Default::default() })?;
+        command.arg("--message-format=json");
+        let output = builder.execute(command)?;
+        Builder::check_status(output.status, || String::from_utf8_lossy(&output.stderr).into_owned())?;
+        let build = BuildOutput::parse(&String::from_utf8_lossy(&output.stdout));
+        let mut objects = Vec::new();
+        for artifact in build.artifacts.iter().filter(|artifact| !artifact.kind.iter().any(|kind| kind == "custom-build")) {
+            if let Some(executable) = artifact.executable.as_ref().filter(|executable| !objects.contains(*executable)) {
+                objects.push(executable.clone());
+            }
+        }
+        Ok(objects)
+    }
+
+    /// Private function that assembles an `llvm-cov <subcommand>` invocation reading `profdata`
+    /// and `objects`, with the filename filters
+    fn llvm_cov(&self, llvm_cov: &Path, subcommand: &str, profdata: &Path, objects: &[PathBuf]) -> Command {
+        let mut command = Command::new(llvm_cov);
+        command.arg(subcommand).arg(format!("-instr-profile={}", profdata.display()));
+        for (index, object) in objects.iter().enumerate() {
+            if index > 0 {
+                command.arg("-object");
+            }
+            command.arg(object);
+        }
+        let defaults = DEPENDENCY_PATHS.iter().filter(|_| !self.include_dependencies).map(|regex| regex.to_string());
+        for regex in defaults.chain(self.ignore_filename_regex.iter().cloned()) {
+            command.arg(format!("-ignore-filename-regex={}", regex));
+        }
+        command
+    }
+}
@@ -0,0 +1,37 @@
+use std::fmt;
+
+/// The kind of crate a library is compiled as, see [`Builder::set_crate_type`](crate::Builder::set_crate_type).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum CrateType {
+    /// A Rust library (`rlib`).
+    Rlib,
+    /// A Rust dynamic library (`dylib`).
+    Dylib,
+    /// A dynamic library with a C ABI, e.g. for plugins or FFI consumers (`cdylib`).
+    Cdylib,
+    /// A static library with a C ABI (`staticlib`).
+    Staticlib,
+    /// A procedural macro library (`proc-macro`).
+    ProcMacro,
+}
+
+impl CrateType {
+    /// Returns the name of the crate type as understood by `rustc` and cargo, e.g. `cdylib`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CrateType::Rlib => "rlib",
+            CrateType::Dylib => "dylib",
+            CrateType::Cdylib => "cdylib",
+            CrateType::Staticlib => "staticlib",
+            CrateType::ProcMacro => "proc-macro",
+        }
+    }
+}
+
+impl fmt::Display for CrateType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,297 @@
+use crate::glob::path_matches;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// The severity of a compiler diagnostic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum DiagnosticLevel {
+    #[serde(rename = "error")]
+    Error,
+    #[serde(rename = "warning")]
+    Warning,
+    #[serde(rename = "note")]
+    Note,
+    #[serde(rename = "help")]
+    Help,
+    #[serde(rename = "failure-note")]
+    FailureNote,
+    #[serde(rename = "error: internal compiler error")]
+    InternalCompilerError,
+    /// A level this version of `cargo_wrap` does not know about.
+    #[serde(other)]
+    Unknown,
+}
+
+/// A source location a diagnostic refers to.
+///
+/// # Fields
+///
+/// * `file_name` - Path of the file, relative to the package root for local sources.
+/// * `line_start` / `line_end` - 1-based line range of the span.
+/// * `column_start` / `column_end` - 1-based column range of the span.
+/// * `is_primary` - `true` if this is the main location of the diagnostic.
+/// * `label` - Optional label rustc attached to the span.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DiagnosticSpan {
+    pub file_name: PathBuf,
+    pub line_start: usize,
+    pub line_end: usize,
+    pub column_start: usize,
+    pub column_end: usize,
+    pub is_primary: bool,
+    pub label: Option<String>,
+}
+
+/// A compiler diagnostic (error, warning, lint, ...) emitted during a build.
+///
+/// # Fields
+///
+/// * `package_id` - The cargo package ID of the package being compiled.
+/// * `level` - The severity of the diagnostic.
+/// * `message` - The primary message.
+/// * `code` - The error or lint code, e.g. `E0308` or `unused_variables`.
+/// * `spans` - Source locations the diagnostic refers to.
+/// * `children` - Attached notes, help messages and suggestions.
+/// * `rendered` - The diagnostic as rustc would have printed it, including ANSI colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub package_id: String,
+    pub level: DiagnosticLevel,
+    pub message: String,
+    pub code: Option<String>,
+    pub spans: Vec<DiagnosticSpan>,
+    pub children: Vec<Diagnostic>,
+    pub rendered: Option<String>,
+}
+
+impl Diagnostic {
+    /// Returns the primary span of the diagnostic, if any
+    pub fn primary_span(&self) -> Option<&DiagnosticSpan> {
+        self.spans.iter().find(|s| s.is_primary)
+    }
+
+    /// Converts the diagnostic into the shape editors annotate source files with, located at
+    /// its primary span.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - Directory the relative paths reported by rustc are resolved against, the
+    ///   workspace root (see [`Builder::metadata`](crate::Builder::metadata)). Absolute paths,
+    ///   e.g. of registry dependencies, are kept as they are.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(EditorDiagnostic)` - The location, severity and message of the diagnostic.
+    /// * `None` - If the diagnostic has no primary span, e.g. `aborting due to previous error`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{Diagnostic, DiagnosticLevel, DiagnosticSpan, EditorSeverity};
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let diagnostic = Diagnostic {
+    ///     package_id: "app 0.1.0 (path+file:///work/app)".to_string(),
+    ///     level: DiagnosticLevel::Warning,
+    ///     message: "unused variable: `x`".to_string(),
+    ///     code: Some("unused_variables".to_string()),
+    ///     spans: vec![DiagnosticSpan {
+    ///         file_name: PathBuf::from("src/main.rs"),
+    ///         line_start: 2,
+    ///         line_end: 2,
+    ///         column_start: 9,
+    ///         column_end: 10,
+    ///         is_primary: true,
+    ///         label: None,
+    ///     }],
+    ///     children: Vec::new(),
+    ///     rendered: None,
+    /// };
+    /// let editor = diagnostic.to_editor(Path::new("/work/app")).unwrap();
+    /// assert_eq!(editor.file, Path::new("/work/app/src/main.rs"));
+    /// assert_eq!((editor.line, editor.column), (2, 9));
+    /// assert_eq!(editor.severity, EditorSeverity::Warning);
+    /// ```
+    pub fn to_editor(&self, root: &Path) -> Option<EditorDiagnostic> {
+        let span = self.primary_span()?;
+        Some(EditorDiagnostic {
+            file: root.join(&span.file_name),
+            line: span.line_start,
+            column: span.column_start,
+            end_line: span.line_end,
+            end_column: span.column_end,
+            severity: EditorSeverity::from(self.level),
+            message: self.message.clone(),
+            code: self.code.clone(),
+        })
+    }
+}
+
+/// Selects compiler diagnostics by level, error or lint code, and file.
+///
+/// Every criterion that is set must match: a diagnostic passes if its level is one of the
+/// levels, its code one of the codes and not one of the excluded codes, and the file of its
+/// primary span matches one of the path patterns. Criteria left empty match every diagnostic.
+///
+/// Path patterns are matched against the paths reported by rustc, relative to the workspace
+/// root, using `/` as the separator. `*` matches any characters except `/`, `**` matches any
+/// characters and `?` matches a single character. A pattern containing no `/` (other than a
+/// trailing one) matches any path component, e.g. `*.rs`; other patterns match the path or one
+/// of its parent directories, e.g. `src/generated/` or `crates/*/src/**/*.rs`.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, DiagnosticLevel, DiagnosticsFilter, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     let output = builder.build()?;
+///     let filter = DiagnosticsFilter::new()
+///         .level(DiagnosticLevel::Warning)
+///         .exclude_code("dead_code")
+///         .path("src/**");
+///     let root = builder.metadata()?.workspace_root;
+///     for diagnostic in output.filter_diagnostics(&filter).filter_map(|d| d.to_editor(&root)) {
+///         println!("{}:{}:{}: {}", diagnostic.file.display(), diagnostic.line, diagnostic.column, diagnostic.message);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiagnosticsFilter {
+    levels: Vec<DiagnosticLevel>,
+    codes: Vec<String>,
+    excluded_codes: Vec<String>,
+    paths: Vec<String>,
+}
+
+impl DiagnosticsFilter {
+    /// Creates a filter matching every diagnostic
+    pub fn new() -> Self {
+        DiagnosticsFilter::default()
+    }
+
+    /// Keeps the diagnostics of the level `level`, in addition to the other levels set
+    pub fn level(mut self, level: DiagnosticLevel) -> Self {
+        self.levels.push(level);
+        self
+    }
+
+    /// Keeps the diagnostics with the error or lint code `code`, e.g. `E0308` or `unused_variables`
+    pub fn code(mut self, code: impl Into<String>) -> Self {
+        self.codes.push(code.into());
+        self
+    }
+
+    /// Drops the diagnostics with the error or lint code `code`
+    pub fn exclude_code(mut self, code: impl Into<String>) -> Self {
+        self.excluded_codes.push(code.into());
+        self
+    }
+
+    /// Keeps the diagnostics whose primary span is in a file matching the glob `pattern`
+    pub fn path(mut self, pattern: impl Into<String>) -> Self {
+        self.paths.push(pattern.into());
+        self
+    }
+
+    /// Checks whether `diagnostic` passes the filter.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{Diagnostic, DiagnosticLevel, DiagnosticSpan, DiagnosticsFilter};
+    /// use std::path::PathBuf;
+    ///
+    /// let span = DiagnosticSpan {
+    ///     file_name: PathBuf::from("src/generated/bindings.rs"),
+    ///     line_start: 1,
+    ///     line_end: 1,
+    ///     column_start: 1,
+    ///     column_end: 4,
+    ///     is_primary: true,
+    ///     label: None,
+    /// };
+    /// let diagnostic = Diagnostic {
+    ///     package_id: "app 0.1.0 (path+file:///work/app)".to_string(),
+    ///     level: DiagnosticLevel::Warning,
+    ///     message: "type `foo` should have an upper camel case name".to_string(),
+    ///     code: Some("non_camel_case_types".to_string()),
+    ///     spans: vec![span],
+    ///     children: Vec::new(),
+    ///     rendered: None,
+    /// };
+    /// assert!(DiagnosticsFilter::new().level(DiagnosticLevel::Warning).path("src/**/*.rs").matches(&diagnostic));
+    /// assert!(!DiagnosticsFilter::new().level(DiagnosticLevel::Error).matches(&diagnostic));
+    /// assert!(!DiagnosticsFilter::new().exclude_code("non_camel_case_types").matches(&diagnostic));
+    /// assert!(!DiagnosticsFilter::new().path("src/lib.rs").matches(&diagnostic));
+    /// assert!(DiagnosticsFilter::new().path("generated/").matches(&diagnostic));
+    /// ```
+    pub fn matches(&self, diagnostic: &Diagnostic) -> bool {
+        let code = diagnostic.code.as_deref();
+        (self.levels.is_empty() || self.levels.contains(&diagnostic.level))
+            && (self.codes.is_empty() || code.is_some_and(|code| self.codes.iter().any(|c| c == code)))
+            && !code.is_some_and(|code| self.excluded_codes.iter().any(|c| c == code))
+            && (self.paths.is_empty()
+                || diagnostic
+                    .primary_span()
+                    .is_some_and(|span| self.paths.iter().any(|pattern| path_matches(pattern, &span.file_name))))
+    }
+}
+
+/// The severity of an [`EditorDiagnostic`], as editors and the Language Server Protocol know it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EditorSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl EditorSeverity {
+    /// Returns the value of the severity in the Language Server Protocol (`DiagnosticSeverity`),
+    /// from `1` for errors to `4` for hints
+    pub fn lsp_value(self) -> u8 {
+        match self {
+            EditorSeverity::Error => 1,
+            EditorSeverity::Warning => 2,
+            EditorSeverity::Information => 3,
+            EditorSeverity::Hint => 4,
+        }
+    }
+}
+
+impl From<DiagnosticLevel> for EditorSeverity {
+    fn from(level: DiagnosticLevel) -> Self {
+        match level {
+            DiagnosticLevel::Error | DiagnosticLevel::InternalCompilerError => EditorSeverity::Error,
+            DiagnosticLevel::Warning => EditorSeverity::Warning,
+            DiagnosticLevel::Help => EditorSeverity::Hint,
+            DiagnosticLevel::Note | DiagnosticLevel::FailureNote | DiagnosticLevel::Unknown => EditorSeverity::Information,
+        }
+    }
+}
+
+/// A compiler diagnostic in the shape editors annotate source files with, see
+/// [`Diagnostic::to_editor`].
+///
+/// # Fields
+///
+/// * `file` - Path of the file the diagnostic is in.
+/// * `line` / `column` - 1-based start of the primary span. The Language Server Protocol counts
+///   from 0.
+/// * `end_line` / `end_column` - 1-based end of the primary span, exclusive of the column.
+/// * `severity` - The severity of the diagnostic.
+/// * `message` - The primary message.
+/// * `code` - The error or lint code, e.g. `E0308` or `unused_variables`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EditorDiagnostic {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+    pub severity: EditorSeverity,
+    pub message: String,
+    pub code: Option<String>,
+}
@@ -0,0 +1,78 @@
+use crate::{CargoWrapError, DependencyGraph, DuplicatePackage, Result};
+
+/// How packages resolved at several versions are handled by builds, see
+/// [`Builder::set_duplicate_policy`](crate::Builder::set_duplicate_policy).
+///
+/// Duplicated packages increase compile times and binary sizes, and every copy has to be
+/// audited separately. Packages in `allow` are never reported, e.g. because the ecosystem has
+/// not settled on one major version yet.
+///
+/// # Fields
+///
+/// * `deny` - If `true`, builds fail with [`CargoWrapError::DuplicateDependencies`] when a
+///   package that is not allowed is duplicated. Otherwise duplicates are only reported in
+///   [`BuildOutput::duplicates`](crate::BuildOutput::duplicates).
+/// * `allow` - Names of the packages that may be resolved at several versions.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{DuplicatePackage, DuplicatePolicy};
+///
+/// let policy = DuplicatePolicy::deny().allow("windows-sys");
+/// let duplicates = vec![
+///     DuplicatePackage { name: "windows-sys".to_string(), versions: vec!["0.52.0".to_string(), "0.59.0".to_string()] },
+///     DuplicatePackage { name: "bitflags".to_string(), versions: vec!["1.3.2".to_string(), "2.6.0".to_string()] },
+/// ];
+/// let reported = policy.filter(duplicates);
+/// assert_eq!(reported.len(), 1);
+/// assert_eq!(reported[0].name, "bitflags");
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DuplicatePolicy {
+    pub deny: bool,
+    pub allow: Vec<String>,
+}
+
+impl DuplicatePolicy {
+    /// Creates a new `DuplicatePolicy` that reports duplicated packages without failing builds
+    pub fn warn() -> Self {
+        DuplicatePolicy::default()
+    }
+
+    /// Creates a new `DuplicatePolicy` that fails builds on duplicated packages
+    pub fn deny() -> Self {
+        DuplicatePolicy { deny: true, ..Default::default() }
+    }
+
+    /// Allows the package named `name` to be resolved at several versions
+    pub fn allow(mut self, name: impl Into<String>) -> Self {
+        self.allow.push(name.into());
+        self
+    }
+
+    /// Returns `true` if the package named `name` may be resolved at several versions
+    pub fn is_allowed(&self, name: &str) -> bool {
+        self.allow.iter().any(|allowed| allowed == name)
+    }
+
+    /// Returns the duplicates among `duplicates` that are not allowed
+    pub fn filter(&self, duplicates: Vec<DuplicatePackage>) -> Vec<DuplicatePackage> {
+        duplicates.into_iter().filter(|duplicate| !self.is_allowed(&duplicate.name)).collect()
+    }
+
+    /// Checks the duplicated packages of `graph` against the policy.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<DuplicatePackage>)` - The duplicates that are not allowed, if the policy does not deny them.
+    /// * `Err(CargoWrapError::DuplicateDependencies)` - If the policy denies duplicates and some are not allowed.
+    pub fn check(&self, graph: &DependencyGraph) -> Result<Vec<DuplicatePackage>> {
+        let duplicates = self.filter(graph.duplicates());
+        if self.deny && !duplicates.is_empty() {
+            return Err(CargoWrapError::DuplicateDependencies(duplicates));
+        }
+        Ok(duplicates)
+    }
+}
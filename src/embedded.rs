@@ -0,0 +1,232 @@
+use crate::pipeline::{is_elf, resolve_binutil, run_tool};
+use crate::{ArtifactStep, Result, StepContext, StepOutcome};
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The format of a flashable image written by [`ObjCopy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum ImageFormat {
+    /// A raw memory image (`objcopy -O binary`), written to `<artifact>.bin`.
+    Bin,
+    /// An Intel HEX file (`objcopy -O ihex`), written to `<artifact>.hex`.
+    Hex,
+}
+
+impl ImageFormat {
+    /// Returns the name of the format as understood by `objcopy -O X`, e.g. `ihex`
+    pub fn objcopy_name(self) -> &'static str {
+        match self {
+            ImageFormat::Bin => "binary",
+            ImageFormat::Hex => "ihex",
+        }
+    }
+
+    /// Returns the extension of the images in this format, e.g. `hex`
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Bin => "bin",
+            ImageFormat::Hex => "hex",
+        }
+    }
+}
+
+impl fmt::Display for ImageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+/// A pipeline step that converts ELF binaries into flashable images with `objcopy`,
+/// `llvm-objcopy` or `rust-objcopy` (from cargo-binutils).
+///
+/// The image is written next to the artifact, with the extension of the [`ImageFormat`] appended
+/// (`firmware` becomes `firmware.bin`), and listed in [`StepOutcome::produced`]. Tools are
+/// resolved like for [`Strip`](crate::Strip). Artifacts that are not ELF files are left
+/// untouched, with a warning in the [`StepOutcome`].
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{ImageFormat, ObjCopy};
+/// use std::path::Path;
+///
+/// let objcopy = ObjCopy::new(ImageFormat::Hex);
+/// assert_eq!(ObjCopy::image_path(Path::new("target/firmware"), objcopy.get_format()), Path::new("target/firmware.hex"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ObjCopy {
+    format: ImageFormat,
+    tool: Option<PathBuf>,
+}
+
+impl ObjCopy {
+    /// Creates a new `ObjCopy` step writing images in `format` with the first tool found
+    pub fn new(format: ImageFormat) -> Self {
+        ObjCopy { format, tool: None }
+    }
+
+    /// Sets the `objcopy` tool to use
+    pub fn tool(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    /// Returns the format of the images
+    pub fn get_format(&self) -> ImageFormat {
+        self.format
+    }
+
+    /// Returns the `objcopy` tool to use, if set
+    pub fn get_tool(&self) -> Option<&Path> {
+        self.tool.as_deref()
+    }
+
+    /// Returns the path the image of `artifact` in `format` is written to
+    pub fn image_path(artifact: &Path, format: ImageFormat) -> PathBuf {
+        let mut path = OsString::from(artifact.as_os_str());
+        path.push(".");
+        path.push(format.extension());
+        PathBuf::from(path)
+    }
+}
+
+impl ArtifactStep for ObjCopy {
+    fn name(&self) -> &str {
+        "objcopy"
+    }
+
+    fn process(&self, path: &Path, context: &StepContext) -> Result<StepOutcome> {
+        if !is_elf(path)? {
+            let warning = format!("{} is not an ELF file, no {} image is written", path.display(), self.format);
+            return Ok(StepOutcome { warnings: vec![warning], ..Default::default() });
+        }
+        let tool = resolve_binutil(self.tool.as_deref(), "objcopy", context.target.as_deref())?;
+        let name = tool.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let image = ObjCopy::image_path(path, self.format);
+        let output = run_tool(&name, Command::new(&tool).arg("-O").arg(self.format.objcopy_name()).arg(path).arg(&image))?;
+        Ok(StepOutcome { output, warnings: Vec::new(), produced: vec![image] })
+    }
+}
+
+/// The settings of a firmware build for a bare-metal target, applied with
+/// [`Builder::set_embedded_preset`](crate::Builder::set_embedded_preset).
+///
+/// # Fields
+///
+/// * `target` - The target triple, e.g. `thumbv7em-none-eabihf`.
+/// * `linker_scripts` - The linker scripts passed to the linker (`-C link-arg=-T<script>`), e.g. `link.x` from `cortex-m-rt`.
+/// * `no_default_features` - If `true`, disables default features (`--no-default-features`), which often pull in `std`.
+/// * `images` - The flashable images written next to every binary, see [`ObjCopy`].
+/// * `objcopy` - Optional `objcopy` tool writing the images. Defaults to the first one found, see [`ObjCopy`].
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{EmbeddedPreset, ImageFormat};
+///
+/// let preset = EmbeddedPreset::cortex_m4f().image(ImageFormat::Hex).linker_script("defmt.x");
+/// assert_eq!(preset.target, "thumbv7em-none-eabihf");
+/// assert_eq!(preset.rustc_flags(), ["-Clink-arg=-Tlink.x", "-Clink-arg=-Tdefmt.x"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmbeddedPreset {
+    pub target: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub linker_scripts: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub no_default_features: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub images: Vec<ImageFormat>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub objcopy: Option<PathBuf>,
+}
+
+impl EmbeddedPreset {
+    /// Creates a new `EmbeddedPreset` for `target`, linking with `link.x` and writing no image
+    pub fn new(target: impl Into<String>) -> Self {
+        EmbeddedPreset {
+            target: target.into(),
+            linker_scripts: vec!["link.x".to_string()],
+            no_default_features: false,
+            images: Vec::new(),
+            objcopy: None,
+        }
+    }
+
+    /// Creates a preset for Cortex-M0 and M0+ cores (`thumbv6m-none-eabi`)
+    pub fn cortex_m0() -> Self {
+        EmbeddedPreset::new("thumbv6m-none-eabi")
+    }
+
+    /// Creates a preset for Cortex-M3 cores (`thumbv7m-none-eabi`)
+    pub fn cortex_m3() -> Self {
+        EmbeddedPreset::new("thumbv7m-none-eabi")
+    }
+
+    /// Creates a preset for Cortex-M4 and M7 cores without an FPU (`thumbv7em-none-eabi`)
+    pub fn cortex_m4() -> Self {
+        EmbeddedPreset::new("thumbv7em-none-eabi")
+    }
+
+    /// Creates a preset for Cortex-M4F and M7F cores (`thumbv7em-none-eabihf`)
+    pub fn cortex_m4f() -> Self {
+        EmbeddedPreset::new("thumbv7em-none-eabihf")
+    }
+
+    /// Creates a preset for Cortex-M33F cores (`thumbv8m.main-none-eabihf`)
+    pub fn cortex_m33f() -> Self {
+        EmbeddedPreset::new("thumbv8m.main-none-eabihf")
+    }
+
+    /// Creates a preset for RV32IMC cores (`riscv32imc-unknown-none-elf`), e.g. the ESP32-C3
+    pub fn riscv32imc() -> Self {
+        EmbeddedPreset::new("riscv32imc-unknown-none-elf")
+    }
+
+    /// Creates a preset for RV32IMAC cores (`riscv32imac-unknown-none-elf`)
+    pub fn riscv32imac() -> Self {
+        EmbeddedPreset::new("riscv32imac-unknown-none-elf")
+    }
+
+    /// Adds a linker script, after `link.x`
+    pub fn linker_script(mut self, script: impl Into<String>) -> Self {
+        self.linker_scripts.push(script.into());
+        self
+    }
+
+    /// Sets whether default features are disabled
+    pub fn no_default_features(mut self, no_default_features: bool) -> Self {
+        self.no_default_features = no_default_features;
+        self
+    }
+
+    /// Writes an image in `format` next to every binary
+    pub fn image(mut self, format: ImageFormat) -> Self {
+        if !self.images.contains(&format) {
+            self.images.push(format);
+        }
+        self
+    }
+
+    /// Sets the `objcopy` tool writing the images
+    pub fn objcopy(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.objcopy = Some(tool.into());
+        self
+    }
+
+    /// Returns the flags passed to `rustc` for the linker scripts
+    pub fn rustc_flags(&self) -> Vec<String> {
+        self.linker_scripts.iter().map(|script| format!("-Clink-arg=-T{}", script)).collect()
+    }
+
+    /// Returns the pipeline steps writing the images
+    pub fn steps(&self) -> Vec<ObjCopy> {
+        self.images
+            .iter()
+            .map(|&format| ObjCopy { format, tool: self.objcopy.clone() })
+            .collect()
+    }
+}
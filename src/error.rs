@@ -0,0 +1,145 @@
+use crate::{Diagnostic, DuplicatePackage, MissingPrerequisite, ValidationError};
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+/// Convenience alias for results returned by `cargo_wrap`.
+pub type Result<T> = std::result::Result<T, CargoWrapError>;
+
+/// The error type returned by every fallible `cargo_wrap` API.
+///
+/// Each variant describes a distinct failure mode so callers can react to, for example, a
+/// missing `cargo` binary differently from a compilation error.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CargoWrapError {
+    /// The `cargo` binary could not be located, neither through the `CARGO` environment variable
+    /// nor in `PATH` or `~/.cargo/bin`.
+    CargoNotFound,
+    /// An external tool (e.g. `sccache`) could not be located.
+    ToolNotFound(String),
+    /// `cargo` ran but exited unsuccessfully.
+    ///
+    /// The output is converted to strings, invalid UTF-8 sequences replaced with `�`; the raw
+    /// bytes are kept in the log file and in [`BuildReport`](crate::BuildReport)s.
+    ///
+    /// * `status` - The exit status of the `cargo` process.
+    /// * `stdout` - Everything the process wrote to stdout, e.g. the JSON messages of a build.
+    /// * `stderr` - Everything the process wrote to stderr, or only its end for streamed builds
+    ///   (see [`Builder::build_streaming`](crate::Builder::build_streaming)).
+    /// * `diagnostics` - The error diagnostics of a failed build, empty for other subcommands.
+    CompilationFailed { status: ExitStatus, stdout: String, stderr: String, diagnostics: Vec<Diagnostic> },
+    /// An external tool (e.g. `rustup`) exited unsuccessfully.
+    ///
+    /// * `tool` - The name of the tool.
+    /// * `status` - The exit status of the tool.
+    /// * `stderr` - Everything the tool wrote to stderr.
+    ToolFailed { tool: String, status: ExitStatus, stderr: String },
+    /// `cargo` did not finish within the configured timeout and was killed.
+    ///
+    /// * `timeout` - The timeout that was exceeded.
+    /// * `stdout` - Everything the process wrote to stdout before it was killed.
+    /// * `stderr` - Everything the process wrote to stderr before it was killed.
+    TimedOut { timeout: Duration, stdout: String, stderr: String },
+    /// The compilation target is not installed for the toolchain.
+    ///
+    /// * `target` - The missing target triple.
+    /// * `toolchain` - The toolchain the target is missing from, `None` meaning the active toolchain.
+    MissingTarget { target: String, toolchain: Option<String> },
+    /// A rustup component, such as `rust-src`, is not installed for the toolchain.
+    ///
+    /// * `component` - The missing component.
+    /// * `toolchain` - The toolchain the component is missing from, `None` meaning the active toolchain.
+    MissingComponent { component: String, toolchain: Option<String> },
+    /// The project does not compile with the toolchain of its minimum supported Rust version.
+    ///
+    /// * `rust_version` - The minimum supported Rust version (`package.rust-version`).
+    /// * `stderr` - Everything cargo wrote to stderr.
+    MsrvFailed { rust_version: String, stderr: String },
+    /// A manifest (`Cargo.toml`) could not be parsed.
+    ManifestParse { path: PathBuf, source: toml::de::Error },
+    /// A lockfile (`Cargo.lock`) could not be parsed.
+    LockfileParse { path: PathBuf, source: toml::de::Error },
+    /// A `cargo_wrap` configuration file could not be parsed.
+    ConfigParse { path: PathBuf, source: toml::de::Error },
+    /// The provided settings are inconsistent or cannot be used.
+    InvalidSettings(String),
+    /// Packages were resolved at several versions while the
+    /// [`DuplicatePolicy`](crate::DuplicatePolicy) denies it.
+    DuplicateDependencies(Vec<DuplicatePackage>),
+    /// System prerequisites of the build are missing, see
+    /// [`Builder::set_prerequisites`](crate::Builder::set_prerequisites).
+    MissingPrerequisites(Vec<MissingPrerequisite>),
+    /// The settings do not match the project, see
+    /// [`ProjectSettings::validate`](crate::ProjectSettings::validate).
+    ValidationFailed(Vec<ValidationError>),
+    /// An underlying I/O operation failed.
+    Io(io::Error),
+}
+
+impl fmt::Display for CargoWrapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CargoWrapError::CargoNotFound => write!(f, "cargo binary not found"),
+            CargoWrapError::ToolNotFound(tool) => write!(f, "{} binary not found", tool),
+            CargoWrapError::CompilationFailed { status, .. } => write!(f, "Failed to compile project: {}", status),
+            CargoWrapError::ToolFailed { tool, status, .. } => write!(f, "{} failed: {}", tool, status),
+            CargoWrapError::TimedOut { timeout, .. } => write!(f, "Build timed out after {:?}", timeout),
+            CargoWrapError::MissingTarget { target, toolchain } => match toolchain {
+                Some(toolchain) => write!(f, "Target {} is not installed for toolchain {}", target, toolchain),
+                None => write!(f, "Target {} is not installed", target),
+            },
+            CargoWrapError::MissingComponent { component, toolchain } => match toolchain {
+                Some(toolchain) => write!(f, "Component {} is not installed for toolchain {}", component, toolchain),
+                None => write!(f, "Component {} is not installed", component),
+            },
+            CargoWrapError::MsrvFailed { rust_version, .. } => {
+                write!(f, "Project does not compile with its minimum supported Rust version {}", rust_version)
+            }
+            CargoWrapError::ManifestParse { path, source } => {
+                write!(f, "Failed to parse manifest {}: {}", path.display(), source)
+            }
+            CargoWrapError::LockfileParse { path, source } => {
+                write!(f, "Failed to parse lockfile {}: {}", path.display(), source)
+            }
+            CargoWrapError::ConfigParse { path, source } => {
+                write!(f, "Failed to parse configuration {}: {}", path.display(), source)
+            }
+            CargoWrapError::InvalidSettings(reason) => write!(f, "Invalid settings: {}", reason),
+            CargoWrapError::DuplicateDependencies(duplicates) => {
+                let duplicates: Vec<String> =
+                    duplicates.iter().map(|duplicate| format!("{} ({})", duplicate.name, duplicate.versions.join(", "))).collect();
+                write!(f, "Duplicate dependencies: {}", duplicates.join(", "))
+            }
+            CargoWrapError::MissingPrerequisites(missing) => {
+                let missing: Vec<String> = missing.iter().map(MissingPrerequisite::to_string).collect();
+                write!(f, "Missing build prerequisites: {}", missing.join("; "))
+            }
+            CargoWrapError::ValidationFailed(errors) => {
+                let errors: Vec<String> = errors.iter().map(ValidationError::to_string).collect();
+                write!(f, "Invalid project settings: {}", errors.join("; "))
+            }
+            CargoWrapError::Io(e) => write!(f, "I/O error: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CargoWrapError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CargoWrapError::ManifestParse { source, .. }
+            | CargoWrapError::LockfileParse { source, .. }
+            | CargoWrapError::ConfigParse { source, .. } => Some(source),
+            CargoWrapError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CargoWrapError {
+    fn from(e: io::Error) -> Self {
+        CargoWrapError::Io(e)
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The features declared by a package, as read from its `Cargo.toml`.
+///
+/// Besides the entries of the `[features]` table, the map includes the implicit features cargo
+/// creates for optional dependencies that are never referenced with the `dep:` syntax.
+///
+/// # Fields
+///
+/// * `features` - Every feature mapped to the list of values it enables.
+/// * `implicit` - Names of features that were created implicitly for optional dependencies.
+/// * `optional_dependencies` - Names of every optional dependency.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct FeatureMap {
+    features: BTreeMap<String, Vec<String>>,
+    implicit: BTreeSet<String>,
+    optional_dependencies: BTreeSet<String>,
+}
+
+/// Everything a selection of features activates, as computed by [`FeatureMap::feature_closure`].
+///
+/// # Fields
+///
+/// * `features` - Every feature of the package that ends up enabled, including the selection.
+/// * `dependencies` - Optional dependencies that end up enabled.
+/// * `dependency_features` - Features enabled on dependencies, as `dependency/feature`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct FeatureClosure {
+    pub features: BTreeSet<String>,
+    pub dependencies: BTreeSet<String>,
+    pub dependency_features: BTreeSet<String>,
+}
+
+impl FeatureMap {
+    /// Private function that builds the feature map from the `[features]` table and the names of
+    /// the optional dependencies of a manifest, see [`Manifest::get_features`](crate::Manifest::get_features)
+    pub(crate) fn from_parts(features: BTreeMap<String, Vec<String>>, optional_dependencies: impl IntoIterator<Item = String>) -> FeatureMap {
+        let mut map = FeatureMap { features, optional_dependencies: optional_dependencies.into_iter().collect(), ..Default::default() };
+
+        // Cargo only creates an implicit feature if the dependency is never referenced as `dep:name`
+        let explicit: BTreeSet<&str> = map
+            .features
+            .values()
+            .flatten()
+            .filter_map(|value| value.strip_prefix("dep:"))
+            .collect();
+        map.implicit = map
+            .optional_dependencies
+            .iter()
+            .filter(|dependency| !explicit.contains(dependency.as_str()) && !map.features.contains_key(*dependency))
+            .cloned()
+            .collect();
+        for dependency in &map.implicit {
+            map.features.insert(dependency.clone(), vec![format!("dep:{}", dependency)]);
+        }
+        map
+    }
+
+    /// Returns the names of every feature, including implicit ones
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.features.keys().map(String::as_str)
+    }
+
+    /// Returns `true` if the package declares (or implicitly has) the feature
+    pub fn contains(&self, feature: &str) -> bool {
+        self.features.contains_key(feature)
+    }
+
+    /// Returns the values a feature enables, or `None` if the feature doesn't exist
+    pub fn dependencies_of(&self, feature: &str) -> Option<&[String]> {
+        self.features.get(feature).map(Vec::as_slice)
+    }
+
+    /// Returns `true` if the feature was created implicitly for an optional dependency
+    pub fn is_implicit(&self, feature: &str) -> bool {
+        self.implicit.contains(feature)
+    }
+
+    /// Returns the names of every optional dependency
+    pub fn optional_dependencies(&self) -> impl Iterator<Item = &str> {
+        self.optional_dependencies.iter().map(String::as_str)
+    }
+
+    /// Returns the features enabled by default (the `default` feature), if declared
+    pub fn default_features(&self) -> &[String] {
+        self.dependencies_of("default").unwrap_or_default()
+    }
+
+    /// Returns the number of features
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    /// Returns `true` if the package has no features
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    /// Computes everything that enabling `feature` activates.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::ProjectSettings;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let features = ProjectSettings::new("/path/to/project").get_features()?;
+    ///     let closure = features.feature_closure("tls");
+    ///     println!("tls enables {:?} and pulls in {:?}", closure.features, closure.dependencies);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn feature_closure(&self, feature: &str) -> FeatureClosure {
+        self.selection_closure([feature])
+    }
+
+    /// Computes everything that enabling every feature in `selection` activates.
+    ///
+    /// Values are resolved the way cargo resolves them: `name` enables another feature,
+    /// `dep:name` enables an optional dependency, `name/feature` enables a dependency and one
+    /// of its features, and `name?/feature` enables a dependency feature only if the dependency
+    /// is enabled by something else.
+    pub fn selection_closure<'a>(&self, selection: impl IntoIterator<Item = &'a str>) -> FeatureClosure {
+        let mut closure = FeatureClosure::default();
+        let mut weak = Vec::new();
+        let mut pending: Vec<String> = selection.into_iter().map(String::from).collect();
+        while let Some(value) = pending.pop() {
+            if let Some(dependency) = value.strip_prefix("dep:") {
+                closure.dependencies.insert(dependency.to_string());
+            } else if let Some((dependency, feature)) = value.split_once('/') {
+                match dependency.strip_suffix('?') {
+                    Some(dependency) => weak.push((dependency.to_string(), feature.to_string())),
+                    None => {
+                        if self.optional_dependencies.contains(dependency) {
+                            closure.dependencies.insert(dependency.to_string());
+                            if self.is_implicit(dependency) {
+                                pending.push(dependency.to_string());
+                            }
+                        }
+                        closure.dependency_features.insert(value.clone());
+                    }
+                }
+            } else if let Some(values) = self.features.get(&value)
+                && closure.features.insert(value)
+            {
+                pending.extend(values.iter().cloned());
+            }
+        }
+        for (dependency, feature) in weak {
+            if closure.dependencies.contains(&dependency) || !self.optional_dependencies.contains(&dependency) {
+                closure.dependency_features.insert(format!("{}/{}", dependency, feature));
+            }
+        }
+        closure
+    }
+}
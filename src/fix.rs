@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+/// Options controlling a `cargo fix` run, see [`Builder::fix`](crate::Builder::fix).
+///
+/// # Fields
+///
+/// * `edition` - If `true`, the code is migrated to the next edition (`--edition`).
+/// * `edition_idioms` - If `true`, the idioms of the current edition are applied (`--edition-idioms`).
+/// * `broken_code` - If `true`, fixes are kept even if the code no longer compiles afterwards (`--broken-code`).
+/// * `allow_dirty` - If `true`, files with uncommitted changes may be fixed (`--allow-dirty`).
+/// * `allow_staged` - If `true`, files with staged changes may be fixed (`--allow-staged`).
+/// * `allow_no_vcs` - If `true`, projects outside of version control may be fixed (`--allow-no-vcs`).
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FixOptions {
+    pub edition: bool,
+    pub edition_idioms: bool,
+    pub broken_code: bool,
+    pub allow_dirty: bool,
+    pub allow_staged: bool,
+    pub allow_no_vcs: bool,
+}
+
+/// A file modified by `cargo fix`.
+///
+/// # Fields
+///
+/// * `path` - The path of the file, as reported by cargo: relative to the workspace root for
+///   workspace members.
+/// * `fixes` - The number of fixes applied to the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedFile {
+    pub path: PathBuf,
+    pub fixes: usize,
+}
+
+/// The output of [`Builder::fix`](crate::Builder::fix).
+///
+/// # Fields
+///
+/// * `files` - Every file cargo modified, in the order they were reported.
+/// * `stderr` - Everything cargo wrote to stderr, including the warnings it could not fix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixOutput {
+    pub files: Vec<FixedFile>,
+    pub stderr: String,
+}
+
+/// Private function that collects the files cargo reports as fixed on stderr
+pub(crate) fn parse_fixed(stderr: &str) -> Vec<FixedFile> {
+    let mut files: Vec<FixedFile> = Vec::new();
+    for line in stderr.lines() {
+        // e.g. "       Fixed src/lib.rs (2 fixes)"
+        let Some(rest) = line.trim_start().strip_prefix("Fixed ") else { continue };
+        let Some((path, count)) = rest.rsplit_once(" (") else { continue };
+        let fixes = count.split_whitespace().next().and_then(|count| count.parse().ok()).unwrap_or(1);
+        // A file shared by several targets is reported once per target
+        match files.iter_mut().find(|file| file.path.as_os_str() == path) {
+            Some(file) => file.fixes += fixes,
+            None => files.push(FixedFile { path: PathBuf::from(path), fixes }),
+        }
+    }
+    files
+}
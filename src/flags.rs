@@ -0,0 +1,39 @@
+/// How the flags configured on a [`Builder`](crate::Builder) are combined with the `rustc` flags
+/// already present in the environment (`CARGO_ENCODED_RUSTFLAGS` or `RUSTFLAGS`).
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RustFlagsPolicy {
+    /// The configured flags are passed after the flags from the environment.
+    #[default]
+    Append,
+    /// The configured flags are passed before the flags from the environment.
+    Prepend,
+    /// The flags from the environment are ignored.
+    Replace,
+}
+
+/// Private function that reads the `rustc` flags of the environment through `var`, preferring
+/// `CARGO_ENCODED_RUSTFLAGS` over `RUSTFLAGS` like cargo does
+fn environment_rustflags(var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+    if let Some(encoded) = var("CARGO_ENCODED_RUSTFLAGS") {
+        return encoded.split('\x1f').filter(|flag| !flag.is_empty()).map(String::from).collect();
+    }
+    var("RUSTFLAGS")
+        .map(|flags| flags.split_whitespace().map(String::from).collect())
+        .unwrap_or_default()
+}
+
+/// Private function that combines `flags` with the environment's flags according to `policy` and
+/// encodes them for `CARGO_ENCODED_RUSTFLAGS` (separated by `0x1f`)
+pub(crate) fn encode_rustflags(
+    policy: RustFlagsPolicy,
+    flags: &[String],
+    var: impl Fn(&str) -> Option<String>,
+) -> String {
+    let merged = match policy {
+        RustFlagsPolicy::Append => [environment_rustflags(var), flags.to_vec()].concat(),
+        RustFlagsPolicy::Prepend => [flags.to_vec(), environment_rustflags(var)].concat(),
+        RustFlagsPolicy::Replace => flags.to_vec(),
+    };
+    merged.join("\x1f")
+}
@@ -0,0 +1,154 @@
+use crate::Result;
+use crate::color::strip_ansi;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Name of the file in the target directory cargo stores its future-incompatibility reports in.
+const REPORT_FILE: &str = ".future-incompat-report.json";
+
+/// The command cargo suggests to show the report of a build, followed by its ID.
+const REPORT_ID: &str = "cargo report future-incompatibilities --id ";
+
+/// A package that uses code a future version of Rust will reject, see [`FutureIncompatReport`].
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `version` - The version of the package.
+/// * `warnings` - The message of every future-incompatibility warning, e.g.
+///   `defaults for generic parameters are not allowed here`.
+/// * `issues` - The URLs of the Rust issues tracking the changes, deduplicated.
+/// * `rendered` - The warnings as cargo prints them, without ANSI colors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FutureIncompatPackage {
+    pub name: String,
+    pub version: String,
+    pub warnings: Vec<String>,
+    pub issues: Vec<String>,
+    pub rendered: String,
+}
+
+/// A future-incompatibility report: the packages of a build, usually dependencies, that
+/// trigger lints which will become hard errors in a future version of Rust.
+///
+/// Cargo records a report for every build with such warnings and keeps the latest ones in the
+/// target directory, see [`Builder::set_future_incompat_report`](crate::Builder::set_future_incompat_report)
+/// and [`Builder::future_incompat_reports`](crate::Builder::future_incompat_reports).
+///
+/// # Fields
+///
+/// * `id` - The ID of the report, as used by `cargo report future-incompatibilities --id N`.
+/// * `suggestion` - Cargo's advice, e.g. newer versions of the packages to update to. Empty if
+///   it has none.
+/// * `packages` - Every package with warnings, sorted by name.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::FutureIncompatReport;
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     for report in FutureIncompatReport::read_all("target")? {
+///         for package in &report.packages {
+///             println!("report {}: {} {} ({})", report.id, package.name, package.version, package.issues.join(", "));
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FutureIncompatReport {
+    pub id: u64,
+    pub suggestion: String,
+    pub packages: Vec<FutureIncompatPackage>,
+}
+
+#[derive(Deserialize)]
+struct RawReports {
+    #[serde(default)]
+    reports: Vec<RawReport>,
+}
+
+#[derive(Deserialize)]
+struct RawReport {
+    id: u64,
+    #[serde(default)]
+    suggestion_message: String,
+    #[serde(default)]
+    per_package: BTreeMap<String, String>,
+}
+
+impl From<RawReport> for FutureIncompatReport {
+    fn from(report: RawReport) -> Self {
+        let packages = report.per_package.into_iter().map(|(package, rendered)| FutureIncompatPackage::parse(&package, &rendered)).collect();
+        FutureIncompatReport { id: report.id, suggestion: report.suggestion_message.trim().to_string(), packages }
+    }
+}
+
+impl FutureIncompatPackage {
+    /// Private function that reads the entry of the package `package` (`name@version`) with the
+    /// rendered warnings `rendered`
+    fn parse(package: &str, rendered: &str) -> FutureIncompatPackage {
+        let (name, version) = package.rsplit_once('@').unwrap_or((package, ""));
+        let rendered = String::from_utf8_lossy(&strip_ansi(rendered.as_bytes())).into_owned();
+        // Every line of the diagnostics is quoted with `> `
+        let lines: Vec<&str> = rendered.lines().map(|line| line.strip_prefix("> ").or(line.strip_prefix('>')).unwrap_or(line)).collect();
+        let warnings = lines.iter().filter_map(|line| line.strip_prefix("warning: ")).map(String::from).collect();
+        let mut issues: Vec<String> = lines
+            .iter()
+            .filter(|line| line.contains("see issue #"))
+            .filter_map(|line| Some(line.split_once('<')?.1.split_once('>')?.0.to_string()))
+            .collect();
+        issues.dedup();
+        FutureIncompatPackage { name: name.to_string(), version: version.to_string(), warnings, issues, rendered: lines.join("\n") }
+    }
+}
+
+impl FutureIncompatReport {
+    /// Reads the reports cargo keeps in the target directory `target_dir`, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<FutureIncompatReport>)` - The stored reports, empty if no build had
+    ///   future-incompatibility warnings.
+    /// * `Err(CargoWrapError)` - If the reports cannot be read or parsed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the file
+    /// cannot be read or is not in the format cargo writes.
+    pub fn read_all(target_dir: impl AsRef<Path>) -> Result<Vec<FutureIncompatReport>> {
+        let path = target_dir.as_ref().join(REPORT_FILE);
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let reports: RawReports = serde_json::from_slice(&contents)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+        Ok(reports.reports.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns the entry of the package named `name`, if it has warnings
+    pub fn get_package(&self, name: &str) -> Option<&FutureIncompatPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    /// Private function that returns when the reports in the target directory `target_dir` were
+    /// last written, if there are any
+    pub(crate) fn modified(target_dir: &Path) -> Option<SystemTime> {
+        fs::metadata(target_dir.join(REPORT_FILE)).and_then(|metadata| metadata.modified()).ok()
+    }
+
+    /// Private function that finds the ID of the report of a build in cargo's stderr
+    pub(crate) fn report_id(stderr: &str) -> Option<u64> {
+        let stderr = strip_ansi(stderr.as_bytes());
+        String::from_utf8_lossy(&stderr)
+            .lines()
+            .find_map(|line| line.split_once(REPORT_ID))
+            .and_then(|(_, id)| id.trim_end_matches(|c: char| !c.is_ascii_digit()).parse().ok())
+    }
+}
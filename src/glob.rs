@@ -0,0 +1,40 @@
+use std::path::{Component, Path};
+
+/// Private function that checks whether the relative path `relative` matches the glob `pattern`.
+///
+/// Paths use `/` as the separator. `*` matches any characters except `/`, `**` matches any
+/// characters and `?` matches a single character. A pattern containing no `/` (other than a
+/// trailing one) matches any path component, e.g. `*.swp`; other patterns match the path or one
+/// of its parent directories, e.g. `src/generated/`.
+pub(crate) fn path_matches(pattern: &str, relative: &Path) -> bool {
+    let components: Vec<&str> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+    let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+    if pattern.contains('/') {
+        (1..=components.len()).any(|end| glob_match(pattern.as_bytes(), components[..end].join("/").as_bytes()))
+    } else {
+        components.iter().any(|component| glob_match(pattern.as_bytes(), component.as_bytes()))
+    }
+}
+
+/// Private function that matches `text` against the glob `pattern`
+pub(crate) fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            (0..=text.len()).any(|i| (i == 0 || text[i - 1] == b'/') && glob_match(rest, &text[i..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => {
+            let segment = text.iter().position(|&byte| byte == b'/').unwrap_or(text.len());
+            (0..=segment).any(|i| glob_match(rest, &text[i..]))
+        }
+        [b'?', rest @ ..] => matches!(text, [byte, tail @ ..] if *byte != b'/' && glob_match(rest, tail)),
+        [byte, rest @ ..] => matches!(text, [first, tail @ ..] if first == byte && glob_match(rest, tail)),
+    }
+}
@@ -0,0 +1,220 @@
+use crate::toolchain::compare_versions;
+use crate::{Metadata, MetadataPackage};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// A package resolved at several versions, see [`DependencyGraph::duplicates`].
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `versions` - Every resolved version of the package, in ascending order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicatePackage {
+    pub name: String,
+    pub versions: Vec<String>,
+}
+
+/// The resolved dependency graph of a project, built from its [`Metadata`], with the queries of
+/// `cargo tree`.
+///
+/// Packages are identified by their cargo package ID, see [`MetadataPackage::id`]. Every kind
+/// of dependency (normal, build and dev) is an edge of the graph.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     let graph = builder.dependency_graph()?;
+///     for path in graph.why_depends_on("openssl-sys") {
+///         let names: Vec<String> = path.iter().map(|package| format!("{} v{}", package.name, package.version)).collect();
+///         println!("{}", names.join(" -> "));
+///     }
+///     for duplicate in graph.duplicates() {
+///         println!("{}: {}", duplicate.name, duplicate.versions.join(", "));
+///     }
+///     print!("{}", graph.render_tree());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    metadata: Metadata,
+    /// Index of every package in `metadata.packages`, by package ID
+    index: BTreeMap<String, usize>,
+    /// Package IDs of the dependencies of every resolved package, by package ID
+    edges: BTreeMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    /// Creates the graph of the resolve graph of `metadata`. The graph is empty if `metadata`
+    /// was read without resolving dependencies.
+    pub fn new(metadata: Metadata) -> Self {
+        let index = metadata.packages.iter().enumerate().map(|(index, package)| (package.id.clone(), index)).collect();
+        let edges = metadata
+            .resolve
+            .iter()
+            .flat_map(|resolve| &resolve.nodes)
+            .map(|node| {
+                let mut dependencies: Vec<String> = node.deps.iter().map(|dependency| dependency.pkg.clone()).collect();
+                dependencies.dedup();
+                (node.id.clone(), dependencies)
+            })
+            .collect();
+        DependencyGraph { metadata, index, edges }
+    }
+
+    /// Returns the metadata the graph was built from
+    pub fn get_metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Returns the package with the package ID `id`
+    pub fn package(&self, id: &str) -> Option<&MetadataPackage> {
+        self.index.get(id).map(|&index| &self.metadata.packages[index])
+    }
+
+    /// Returns the resolved packages that `id` directly depends on
+    pub fn dependencies_of(&self, id: &str) -> Vec<&MetadataPackage> {
+        self.edges.get(id).into_iter().flatten().filter_map(|dependency| self.package(dependency)).collect()
+    }
+
+    /// Returns the resolved packages that directly depend on `id`
+    pub fn dependents_of(&self, id: &str) -> Vec<&MetadataPackage> {
+        self.edges
+            .iter()
+            .filter(|(_, dependencies)| dependencies.iter().any(|dependency| dependency == id))
+            .filter_map(|(dependent, _)| self.package(dependent))
+            .collect()
+    }
+
+    /// Returns the packages the workspace members directly depend on, excluding other members,
+    /// ordered by name and version
+    pub fn direct_dependencies(&self) -> Vec<&MetadataPackage> {
+        let members = &self.metadata.workspace_members;
+        let ids: BTreeSet<&String> = members
+            .iter()
+            .filter_map(|member| self.edges.get(member))
+            .flatten()
+            .filter(|dependency| !members.contains(dependency))
+            .collect();
+        let mut packages: Vec<&MetadataPackage> = ids.into_iter().filter_map(|id| self.package(id)).collect();
+        packages.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        packages
+    }
+
+    /// Returns the packages resolved at more than one version, ordered by name
+    pub fn duplicates(&self) -> Vec<DuplicatePackage> {
+        let mut versions: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        for package in self.edges.keys().filter_map(|id| self.package(id)) {
+            versions.entry(&package.name).or_default().insert(&package.version);
+        }
+        versions
+            .into_iter()
+            .filter(|(_, versions)| versions.len() > 1)
+            .map(|(name, versions)| {
+                let mut versions: Vec<String> = versions.into_iter().map(String::from).collect();
+                versions.sort_by(|a, b| compare_versions(a, b));
+                DuplicatePackage { name: name.to_string(), versions }
+            })
+            .collect()
+    }
+
+    /// Explains why the package named `name` is in the graph.
+    ///
+    /// # Returns
+    ///
+    /// For every workspace member and every resolved version of `name` it (transitively) depends
+    /// on, the shortest chain of packages from the member to that version, both included. Empty
+    /// if no member depends on `name`.
+    pub fn why_depends_on(&self, name: &str) -> Vec<Vec<&MetadataPackage>> {
+        let mut paths = Vec::new();
+        for member in &self.metadata.workspace_members {
+            // Breadth-first, so the first path found to every package is a shortest one
+            let mut parents: BTreeMap<&str, &str> = BTreeMap::new();
+            let mut queue = VecDeque::from([member.as_str()]);
+            let mut seen = BTreeSet::from([member.as_str()]);
+            while let Some(id) = queue.pop_front() {
+                if self.package(id).is_some_and(|package| package.name == name) {
+                    let mut path = vec![id];
+                    while let Some(parent) = parents.get(path[path.len() - 1]) {
+                        path.push(parent);
+                    }
+                    paths.push(path.into_iter().rev().filter_map(|id| self.package(id)).collect());
+                    continue;
+                }
+                for dependency in self.edges.get(id).into_iter().flatten() {
+                    if seen.insert(dependency) {
+                        parents.insert(dependency, id);
+                        queue.push_back(dependency);
+                    }
+                }
+            }
+        }
+        paths
+    }
+
+    /// Renders the dependencies of every workspace member as a tree, like `cargo tree`.
+    ///
+    /// Packages whose dependencies were already listed are marked with `(*)` and not expanded again.
+    pub fn render_tree(&self) -> String {
+        let mut output = String::new();
+        let mut expanded = BTreeSet::new();
+        for member in &self.metadata.workspace_members {
+            self.render(member, "", None, &mut expanded, &mut output, &|id| self.dependencies_of(id));
+        }
+        output
+    }
+
+    /// Renders the packages depending on the package named `name` as an inverted tree, like
+    /// `cargo tree --invert`, one tree per resolved version.
+    pub fn render_inverted(&self, name: &str) -> String {
+        let mut output = String::new();
+        let mut expanded = BTreeSet::new();
+        let mut roots: Vec<&MetadataPackage> = self.edges.keys().filter_map(|id| self.package(id)).filter(|package| package.name == name).collect();
+        roots.sort_by(|a, b| compare_versions(&a.version, &b.version));
+        for root in roots {
+            self.render(&root.id, "", None, &mut expanded, &mut output, &|id| self.dependents_of(id));
+        }
+        output
+    }
+
+    /// Private function that renders `id` and, unless already expanded, its children
+    fn render<'a>(
+        &'a self,
+        id: &'a str,
+        prefix: &str,
+        last: Option<bool>,
+        expanded: &mut BTreeSet<&'a str>,
+        output: &mut String,
+        next: &dyn Fn(&str) -> Vec<&'a MetadataPackage>,
+    ) {
+        let Some(package) = self.package(id) else { return };
+        let mut children = next(id);
+        children.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+        let connector = match last {
+            Some(true) => "└── ",
+            Some(false) => "├── ",
+            None => "",
+        };
+        let repeated = !children.is_empty() && !expanded.insert(id);
+        output.push_str(&format!("{}{}{} v{}", prefix, connector, package.name, package.version));
+        if package.source.is_none() {
+            output.push_str(&format!(" ({})", package.manifest_path.parent().unwrap_or(&package.manifest_path).display()));
+        }
+        output.push_str(if repeated { " (*)\n" } else { "\n" });
+        if repeated {
+            return;
+        }
+        let prefix = match last {
+            Some(true) => format!("{}    ", prefix),
+            Some(false) => format!("{}│   ", prefix),
+            None => prefix.to_string(),
+        };
+        let count = children.len();
+        for (index, child) in children.into_iter().enumerate() {
+            self.render(&child.id, &prefix, Some(index + 1 == count), expanded, output, next);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::sink::{CapturedOutput, OutputLogWriter};
+use crate::{BuildOutput, Builder, Result};
+use std::io;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Interval at which [`BuildHandle::wait_timeout`] polls the running build.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A handle to a build started with [`Builder::spawn`].
+///
+/// The build's stdout and stderr are drained on background threads so it never blocks on a
+/// full pipe, and written to the log file line by line as they are read. Dropping the handle
+/// does not stop the build; use [`BuildHandle::cancel`] for that.
+#[derive(Debug)]
+pub struct BuildHandle {
+    child: Child,
+    output: Option<CapturedOutput>,
+}
+
+impl BuildHandle {
+    /// Private function that wraps a spawned `cargo build` process with piped stdout/stderr,
+    /// whose output is written to `log_writer`
+    pub(crate) fn new(mut child: Child, log_writer: OutputLogWriter) -> BuildHandle {
+        let output = Some(log_writer.capture(&mut child));
+        BuildHandle { child, output }
+    }
+
+    /// Returns the OS process ID of the `cargo` process
+    pub fn id(&self) -> u32 {
+        self.child.id()
+    }
+
+    /// Checks whether the build has finished without blocking.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(ExitStatus))` - If the build has finished.
+    /// * `Ok(None)` - If the build is still running.
+    /// * `Err(CargoWrapError)` - If the process status could not be queried.
+    pub fn try_wait(&mut self) -> Result<Option<ExitStatus>> {
+        Ok(self.child.try_wait()?)
+    }
+
+    /// Waits up to `timeout` for the build to finish.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(ExitStatus))` - If the build finished within the timeout.
+    /// * `Ok(None)` - If the build is still running after the timeout.
+    /// * `Err(CargoWrapError)` - If the process status could not be queried.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> Result<Option<ExitStatus>> {
+        Ok(wait_until(&mut self.child, Instant::now() + timeout)?)
+    }
+
+    /// Waits for the build to finish and returns its artifacts and diagnostics.
+    ///
+    /// Unlike [`Builder::build`], the build is not post-processed: the returned [`BuildOutput`]
+    /// has no timings or future-incompatibility report, header generation, the artifact pipeline,
+    /// checksums, installation and size history are skipped, and the after-build hooks are not run.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::CompilationFailed`](crate::CargoWrapError::CompilationFailed)
+    /// if the build fails, or [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the process
+    /// or its output cannot be read.
+    pub fn wait(mut self) -> Result<BuildOutput> {
+        let output = self.collect()?;
+        Builder::finish_build(&output)
+    }
+
+    /// Cancels the build, killing `cargo` and (on Unix) every process in its process group.
+    ///
+    /// Output produced before the build was cancelled is still written to the log file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the processes could not be killed
+    /// or the log file could not be written.
+    pub fn cancel(mut self) -> Result<()> {
+        self.kill()?;
+        self.collect()?;
+        Ok(())
+    }
+
+    /// Private function that kills the build and every process it started
+    pub(crate) fn kill(&mut self) -> Result<()> {
+        Ok(kill_process_group(&mut self.child)?)
+    }
+
+    /// Private function that waits for the process and its output readers
+    pub(crate) fn collect(&mut self) -> Result<Output> {
+        let status = self.child.wait()?;
+        let (stdout, stderr) = match self.output.take() {
+            Some(output) => output.join()?,
+            None => (Vec::new(), Vec::new()),
+        };
+        Ok(Output { status, stdout, stderr })
+    }
+}
+
+/// Private function that spawns `command` as the leader of a new process group (on Unix), with
+/// stdin closed and stdout/stderr piped, so it can later be killed with [`kill_process_group`]
+pub(crate) fn spawn_in_group(command: &mut Command) -> io::Result<Child> {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()
+}
+
+/// Private function that kills a child spawned with [`spawn_in_group`] along with its process group
+#[cfg(unix)]
+pub(crate) fn kill_process_group(child: &mut Child) -> io::Result<()> {
+    if child.try_wait()?.is_some() {
+        return Ok(());
+    }
+    // The child was spawned as the leader of its own process group
+    let pgid = child.id() as libc::pid_t;
+    if unsafe { libc::kill(-pgid, libc::SIGKILL) } != 0 {
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::ESRCH) {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// Private function that kills a child spawned with [`spawn_in_group`]
+#[cfg(not(unix))]
+pub(crate) fn kill_process_group(child: &mut Child) -> io::Result<()> {
+    if child.try_wait()?.is_some() {
+        return Ok(());
+    }
+    child.kill()
+}
+
+/// Private function that waits for `child` to exit, giving up at `deadline`
+pub(crate) fn wait_until(child: &mut Child, deadline: Instant) -> io::Result<Option<ExitStatus>> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
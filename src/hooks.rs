@@ -0,0 +1,57 @@
+use crate::BuildReport;
+use std::fmt;
+use std::process::Command;
+use std::sync::{Mutex, PoisonError};
+
+/// A callback run with the `cargo build` command before it is started.
+pub(crate) type BeforeBuildHook = Box<dyn FnMut(&mut Command) + Send>;
+
+/// A callback run with the report of a finished build.
+pub(crate) type AfterBuildHook = Box<dyn FnMut(&BuildReport) + Send>;
+
+/// Private collection of the hooks attached to a builder
+#[derive(Default)]
+pub(crate) struct BuildHooks {
+    before: Mutex<Vec<BeforeBuildHook>>,
+    after: Mutex<Vec<AfterBuildHook>>,
+}
+
+impl BuildHooks {
+    /// Private function that attaches a hook run before builds
+    pub(crate) fn push_before(&self, hook: BeforeBuildHook) {
+        self.before.lock().unwrap_or_else(PoisonError::into_inner).push(hook);
+    }
+
+    /// Private function that attaches a hook run after builds
+    pub(crate) fn push_after(&self, hook: AfterBuildHook) {
+        self.after.lock().unwrap_or_else(PoisonError::into_inner).push(hook);
+    }
+
+    /// Private function that runs every hook attached with [`BuildHooks::push_before`], in order
+    pub(crate) fn run_before(&self, command: &mut Command) {
+        for hook in self.before.lock().unwrap_or_else(PoisonError::into_inner).iter_mut() {
+            hook(command);
+        }
+    }
+
+    /// Private function that runs every hook attached with [`BuildHooks::push_after`], in order
+    pub(crate) fn run_after(&self, report: &BuildReport) {
+        for hook in self.after.lock().unwrap_or_else(PoisonError::into_inner).iter_mut() {
+            hook(report);
+        }
+    }
+
+    /// Private function that returns the number of hooks run before and after builds
+    fn len(&self) -> (usize, usize) {
+        let before = self.before.lock().unwrap_or_else(PoisonError::into_inner).len();
+        let after = self.after.lock().unwrap_or_else(PoisonError::into_inner).len();
+        (before, after)
+    }
+}
+
+impl fmt::Debug for BuildHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (before, after) = self.len();
+        write!(f, "BuildHooks {{ before: {}, after: {} }}", before, after)
+    }
+}
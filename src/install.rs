@@ -0,0 +1,71 @@
+use crate::{BuildOutput, CargoWrapError, Result};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The template artifacts are installed under when none is set: their file name, unchanged.
+pub(crate) const DEFAULT_TEMPLATE: &str = "{file}";
+
+/// What the placeholders of an install template are replaced with for one file.
+///
+/// # Fields
+///
+/// * `target` - The compilation target triple, or the host triple.
+/// * `profile` - The name of the profile the artifacts were built with.
+pub(crate) struct InstallContext<'a> {
+    pub(crate) target: &'a str,
+    pub(crate) profile: &'a str,
+}
+
+/// Copies the binaries and libraries of `output` (see [`BuildOutput::binaries`]) into `dir`,
+/// named after `template`, see [`Builder::set_install_template`](crate::Builder::set_install_template).
+///
+/// # Returns
+///
+/// * `Ok(Vec<PathBuf>)` - The installed files.
+/// * `Err(CargoWrapError)` - If two files would be installed under the same name, or a file
+///   cannot be copied.
+pub(crate) fn install(output: &BuildOutput, dir: &Path, template: &str, context: &InstallContext) -> Result<Vec<PathBuf>> {
+    let mut destinations: BTreeMap<PathBuf, &Path> = BTreeMap::new();
+    let mut files = Vec::new();
+    for artifact in &output.artifacts {
+        for file in artifact.binaries() {
+            let extension = file.extension().map(|extension| format!(".{}", extension.to_string_lossy())).unwrap_or_default();
+            let name = template
+                .replace("{file}", &file.file_name().unwrap_or_default().to_string_lossy())
+                .replace("{name}", &artifact.target_name)
+                .replace("{package}", artifact.package_name())
+                .replace("{version}", artifact.package_version())
+                .replace("{target}", context.target)
+                .replace("{profile}", context.profile)
+                .replace("{ext}", &extension);
+            files.push((file, dir.join(name)));
+        }
+    }
+    for (file, destination) in &files {
+        if let Some(other) = destinations.insert(destination.clone(), file)
+            && other != file
+        {
+            return Err(CargoWrapError::InvalidSettings(format!(
+                "{} and {} would both be installed as {}",
+                other.display(),
+                file.display(),
+                destination.display()
+            )));
+        }
+    }
+
+    fs::create_dir_all(dir)?;
+    let mut installed = Vec::new();
+    for (file, destination) in files {
+        if installed.contains(&destination) {
+            continue;
+        }
+        // Renamed into place, so that a running copy of an executable can be replaced
+        let partial = destination.with_file_name(format!(".{}.partial", destination.file_name().unwrap_or_default().to_string_lossy()));
+        fs::copy(&file, &partial)?;
+        fs::rename(&partial, &destination)?;
+        installed.push(destination);
+    }
+    Ok(installed)
+}
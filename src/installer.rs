@@ -0,0 +1,212 @@
+use crate::pipeline::run_stdout;
+use crate::{Builder, CargoWrapError, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A package installed with `cargo install`, as listed by [`Installer::installed`].
+///
+/// # Fields
+///
+/// * `name` - The name of the package, e.g. `cargo-nextest`.
+/// * `version` - The installed version, without the leading `v`.
+/// * `binaries` - The names of the binaries the package installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+    pub binaries: Vec<String>,
+}
+
+/// The outcome of [`Installer::install`].
+///
+/// # Fields
+///
+/// * `package` - The package as installed.
+/// * `already_installed` - `true` if a matching version was already installed and `cargo install` was not run.
+/// * `stderr` - Everything `cargo install` wrote to stderr, empty if it was not run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallOutcome {
+    pub package: InstalledPackage,
+    pub already_installed: bool,
+    pub stderr: String,
+}
+
+/// Provisions cargo subcommands and other tools with `cargo install`, e.g. `cargo-nextest` for
+/// [`Nextest`](crate::Nextest) or `cargo-zigbuild` for the [`ZigbuildBackend`](crate::ZigbuildBackend).
+///
+/// Packages are installed into the root set with [`Installer::root`] (`--root`), or cargo's
+/// default (`$CARGO_HOME`). Before installing, the packages of the root are listed with
+/// `cargo install --list`, so tools already installed at a matching version are not rebuilt.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::Installer;
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let installer = Installer::new()?.root("/opt/tools");
+///     let outcome = installer.install("cargo-nextest", Some("0.9.72"), true)?;
+///     if !outcome.already_installed {
+///         println!("Installed {} {}", outcome.package.name, outcome.package.version);
+///     }
+///     println!("{:?}", installer.bin_dir());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Installer {
+    cargo_path: PathBuf,
+    root: Option<PathBuf>,
+    registry: Option<String>,
+}
+
+impl Installer {
+    /// Creates a new `Installer` with the `cargo` binary found like [`Builder::new`] does.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::CargoNotFound`] if `cargo` cannot be found.
+    pub fn new() -> Result<Self> {
+        Ok(Installer::with_cargo_path(Builder::find_cargo_path()?))
+    }
+
+    /// Creates a new `Installer` using the given `cargo` binary
+    pub fn with_cargo_path(cargo_path: impl Into<PathBuf>) -> Self {
+        Installer { cargo_path: cargo_path.into(), root: None, registry: None }
+    }
+
+    /// Sets the directory packages are installed into (`--root X`); binaries go to its `bin` directory
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Sets the registry packages are installed from (`--registry X`), as named in the cargo
+    /// configuration. Defaults to crates.io.
+    pub fn registry(mut self, registry: impl Into<String>) -> Self {
+        self.registry = Some(registry.into());
+        self
+    }
+
+    /// Returns the path to the `cargo` binary
+    pub fn get_cargo_path(&self) -> &Path {
+        &self.cargo_path
+    }
+
+    /// Returns the directory packages are installed into, if set
+    pub fn get_root(&self) -> Option<&Path> {
+        self.root.as_deref()
+    }
+
+    /// Returns the registry packages are installed from, if set
+    pub fn get_registry(&self) -> Option<&str> {
+        self.registry.as_deref()
+    }
+
+    /// Returns the directory the binaries are installed into, if a root is set
+    pub fn bin_dir(&self) -> Option<PathBuf> {
+        self.root.as_ref().map(|root| root.join("bin"))
+    }
+
+    /// Private function that creates a `cargo install` command for the configured root
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.cargo_path);
+        command.arg("install");
+        if let Some(root) = &self.root {
+            command.arg("--root").arg(root);
+        }
+        command
+    }
+
+    /// Lists the packages installed in the root with `cargo install --list`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `cargo` cannot be run or fails ([`CargoWrapError::ToolFailed`]).
+    pub fn installed(&self) -> Result<Vec<InstalledPackage>> {
+        let stdout = run_stdout("cargo install", self.command().arg("--list"))?;
+        let mut packages: Vec<InstalledPackage> = Vec::new();
+        for line in stdout.lines().filter(|line| !line.trim().is_empty()) {
+            if line.starts_with(char::is_whitespace) {
+                if let Some(package) = packages.last_mut() {
+                    package.binaries.push(line.trim().to_string());
+                }
+                continue;
+            }
+            // e.g. `ripgrep v14.1.0 (/path/to/ripgrep):`
+            let mut parts = line.trim_end_matches(':').split(' ');
+            let (Some(name), Some(version)) = (parts.next(), parts.next()) else { continue };
+            packages.push(InstalledPackage {
+                name: name.to_string(),
+                version: version.trim_start_matches('v').to_string(),
+                binaries: Vec::new(),
+            });
+        }
+        Ok(packages)
+    }
+
+    /// Returns the installed version of the package `name`, if it is installed in the root.
+    ///
+    /// # Errors
+    ///
+    /// See [`Installer::installed`].
+    pub fn installed_version(&self, name: &str) -> Result<Option<String>> {
+        Ok(self.installed()?.into_iter().find(|package| package.name == name).map(|package| package.version))
+    }
+
+    /// Installs the package `name` with `cargo install`, unless a matching version is already
+    /// installed in the root.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The package to install, e.g. `cargo-nextest`.
+    /// * `version` - The version to install (`--version X`), `None` meaning the latest. An exact
+    ///   version (`0.9.72` or `=0.9.72`) must match the installed version, a partial one (`0.9`)
+    ///   matches every installed version starting with it. Without a version, any installed
+    ///   version is kept.
+    /// * `locked` - If `true`, the dependencies are built at the versions of the package's
+    ///   lockfile (`--locked`), as most tools recommend.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(InstallOutcome)` - The installed package, and whether it was already installed.
+    /// * `Err(CargoWrapError)` - If the package could not be installed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `cargo` cannot be run ([`CargoWrapError::Io`]).
+    /// - `cargo install` fails, e.g. because the package or version does not exist ([`CargoWrapError::ToolFailed`]).
+    pub fn install(&self, name: &str, version: Option<&str>, locked: bool) -> Result<InstallOutcome> {
+        let installed = self.installed()?.into_iter().find(|package| package.name == name);
+        if let Some(package) = installed.filter(|package| version.is_none_or(|version| version_matches(&package.version, version))) {
+            return Ok(InstallOutcome { package, already_installed: true, stderr: String::new() });
+        }
+        let mut command = self.command();
+        command.arg(name);
+        if let Some(version) = version {
+            command.arg("--version").arg(version);
+        }
+        if locked {
+            command.arg("--locked");
+        }
+        if let Some(registry) = &self.registry {
+            command.arg("--registry").arg(registry);
+        }
+        let output = command.output()?;
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        if !output.status.success() {
+            return Err(CargoWrapError::ToolFailed { tool: "cargo install".to_string(), status: output.status, stderr });
+        }
+        let package = self.installed()?.into_iter().find(|package| package.name == name).ok_or_else(|| {
+            CargoWrapError::InvalidSettings(format!("{} is not listed by cargo install --list after installing it", name))
+        })?;
+        Ok(InstallOutcome { package, already_installed: false, stderr })
+    }
+}
+
+/// Private function that checks whether the installed version `installed` satisfies the
+/// requested version `requested`, exactly or as a prefix of whole components
+fn version_matches(installed: &str, requested: &str) -> bool {
+    let requested = requested.trim().trim_start_matches('=');
+    installed == requested || installed.strip_prefix(requested).is_some_and(|rest| rest.starts_with('.'))
+}
@@ -1,10 +1,159 @@
-use std::{env, fs, io};
-use std::fs::OpenOptions;
-use std::io::{Error, ErrorKind, Write};
-use std::path::PathBuf;
-use std::process::Command;
-use toml::Value;
+mod artifact;
+mod backend;
+mod build_script;
+mod build_std;
+#[cfg(feature = "bloat")]
+mod bloat;
+mod cache;
+mod cbindgen;
+mod checksum;
+mod color;
+mod command;
+mod config;
+mod coverage;
+mod crate_type;
+mod diagnostics;
+mod duplicates;
+mod embedded;
+mod error;
+mod features;
+mod fix;
+mod flags;
+mod future_incompat;
+mod glob;
+mod graph;
+mod handle;
+mod hooks;
+mod install;
+mod installer;
+mod lockfile;
+mod manifest;
+mod matrix;
+mod message;
+mod metadata;
+mod nextest;
+mod orchestrator;
+mod pgo;
+mod pipeline;
+mod prerequisites;
+mod profile;
+mod progress;
+mod publish;
+mod remote_cache;
+mod report;
+mod reproducible;
+mod retry;
+mod run;
+mod rustfmt;
+mod sanitizer;
+mod sbom;
+mod scaffold;
+mod sign;
+mod sink;
+mod size;
+mod target;
+mod testing;
+mod timestamp;
+mod timings;
+mod toolchain;
+mod trace;
+#[cfg(feature = "unstable")]
+mod unit_graph;
+mod universal;
+mod update;
+mod validation;
+#[cfg(feature = "upx")]
+mod upx;
+mod vendor;
+mod verbosity;
+mod wasm;
+#[cfg(feature = "notify")]
+mod watch;
 
+pub use artifact::{Artifact, BuildOutput};
+pub use backend::{BuildBackend, CargoBackend, ContainerBackend, CrossBackend, ZigbuildBackend};
+pub use build_script::BuildScriptOutput;
+pub use build_std::BuildStd;
+#[cfg(feature = "bloat")]
+pub use bloat::{BloatReport, CrateSize, SymbolSize};
+pub use cbindgen::{Cbindgen, HeaderLanguage};
+pub use checksum::{ArtifactChecksum, ChecksumManifest};
+pub use color::ColorChoice;
+pub use command::RenderedCommand;
+pub use config::BuilderConfig;
+pub use coverage::{Coverage, CoverageCount, CoverageFormat, CoverageOutput, CoverageSummary};
+pub use crate_type::CrateType;
+pub use diagnostics::{Diagnostic, DiagnosticLevel, DiagnosticSpan, DiagnosticsFilter, EditorDiagnostic, EditorSeverity};
+pub use duplicates::DuplicatePolicy;
+pub use embedded::{EmbeddedPreset, ImageFormat, ObjCopy};
+pub use error::{CargoWrapError, Result};
+pub use features::{FeatureClosure, FeatureMap};
+pub use fix::{FixOptions, FixOutput, FixedFile};
+pub use flags::RustFlagsPolicy;
+pub use future_incompat::{FutureIncompatPackage, FutureIncompatReport};
+pub use graph::{DependencyGraph, DuplicatePackage};
+pub use handle::BuildHandle;
+pub use installer::{InstallOutcome, InstalledPackage, Installer};
+pub use lockfile::{LockedPackage, Lockfile, LockfileDiff, PackageUpdate};
+pub use manifest::{BinTarget, Bump, Dependency, DependencyKind, DependencySpec, LibTarget, Manifest, Package, Workspace};
+pub use matrix::{BuildMatrix, MatrixCell, MatrixResult};
+pub use metadata::{DependencyKindInfo, Metadata, MetadataDependency, MetadataPackage, MetadataTarget, NodeDependency, Resolve, ResolveNode};
+pub use nextest::Nextest;
+pub use orchestrator::{JobResult, JobStatus, Orchestrator, OrchestratorReport};
+pub use pgo::{PgoOutput, PgoPipeline};
+pub use pipeline::{ArtifactPipeline, ArtifactStep, StepContext, StepOutcome, SplitDebugInfo, StepReport, Strip};
+
+use cache::CacheEntry;
+use handle::{kill_process_group, spawn_in_group, wait_until};
+use hooks::BuildHooks;
+use install::InstallContext;
+use sink::{OutputLog, OutputSinks, OutputTail, Stream};
+pub use prerequisites::{MissingPrerequisite, Prerequisites, Requirement};
+pub use profile::ProfileOverrides;
+pub use progress::ProgressEvent;
+pub use publish::{PublishOutput, PublishedPackage, Publisher};
+pub use remote_cache::{CacheBackend, FilesystemCache, HttpCache};
+pub use report::BuildReport;
+pub use reproducible::ReproducibilityReport;
+pub use retry::RetryPolicy;
+pub use run::{RunOutput, RunStdio, StdioMode};
+pub use rustfmt::FmtOptions;
+pub use sanitizer::Sanitizer;
+pub use sbom::{Sbom, SbomComponent, SbomFormat};
+pub use scaffold::{ProjectKind, ProjectScaffold, ScaffoldOptions, Vcs};
+pub use size::{ArtifactSize, SectionSize, SizeComparison, SizeDelta, SizeHistory, SizeSnapshot};
+pub use sign::{Codesign, GpgSigner, Osslsigncode, Signer};
+pub use sink::{MemorySink, OutputSink};
+pub use target::BuildTarget;
+pub use testing::{TestCase, TestOptions, TestReport, TestStatus, TestSuite, TestSummary};
+pub use timings::{BuildTimings, UnitTiming};
+pub use toolchain::{ReleaseChannel, RustVersion, Rustup, ToolchainInfo};
+#[cfg(feature = "unstable")]
+pub use unit_graph::{Unit, UnitDependency, UnitGraph, UnitProfile, UnitTarget};
+pub use universal::UniversalOutput;
+pub use update::UpdateOptions;
+pub use validation::ValidationError;
+#[cfg(feature = "upx")]
+pub use upx::Upx;
+pub use vendor::{VendorOptions, VendorOutput};
+pub use verbosity::Verbosity;
+pub use wasm::{WasmBindgen, WasmBuild, WasmOpt, WasmOutput};
+#[cfg(feature = "notify")]
+pub use watch::Watcher;
+
+use std::{env, fs, io, thread};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::Arc;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Number of bytes at the end of stderr kept for the errors of streamed builds.
+const STDERR_TAIL_BYTES: usize = 64 * 1024;
+
+/// Environment variables that hermetic builds keep from the current process.
+const HERMETIC_ENVS: [&str; 4] = ["PATH", "HOME", "CARGO_HOME", "RUSTUP_HOME"];
 
 /// Holds configuration settings for a Rust project build.
 ///
@@ -17,12 +166,34 @@ use toml::Value;
 /// * `features` - Optional list of features to enable during the build.
 /// * `output_path` - Optional path to store compiled artifacts.
 /// * `release` - Whether to compile in release mode (`true`) or debug mode (`false`).
-/// * `is_lib` - If `true`, builds the project as a library (`--lib`), otherwise builds as a binary (`--bin`).
+/// * `is_lib` - If `true`, builds the project's library (`--lib`), otherwise the named target is built as a binary (`--bin`).
 /// * `no_default_features` - If `true`, disables default features (`--no-default-features`).
+/// * `all_features` - If `true`, enables every feature of the selected packages (`--all-features`).
 /// * `project_path` - The root directory of the Rust project.
 /// * `cargo_toml_path` - Path to the project's `Cargo.toml`.
 /// * `target` - Optional specific binary/library to build.
-#[derive(Default, Debug, Clone)]
+/// * `build_targets` - Additional targets to build, e.g. examples, tests or benches.
+/// * `workspace` - If `true`, builds every member of the workspace (`--workspace`).
+/// * `packages` - Packages to build (`-p X`).
+/// * `excluded_packages` - Packages to exclude from a workspace build (`--exclude X`).
+/// * `offline` - If `true`, cargo is not allowed to access the network (`--offline`).
+/// * `locked` - If `true`, the build fails if `Cargo.lock` needs to be updated (`--locked`).
+/// * `frozen` - If `true`, equivalent to both `--locked` and `--offline` (`--frozen`).
+/// * `use_manifest_path` - If `true`, cargo is pointed at `Cargo.toml` (`--manifest-path X`) instead
+///   of being run from the project root.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::ProjectSettings;
+///
+/// let settings = ProjectSettings::new("/path/to/project").release(true).feature("cli");
+/// assert_eq!(settings, ProjectSettings::new("/path/to/project").feature("cli").release(true));
+/// assert_ne!(settings, ProjectSettings::new("/path/to/project").feature("cli"));
+/// assert_eq!(settings.get_enabled_features(), ["cli"]);
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ProjectSettings {
     compilation_target: Option<String>,
     features: Option<Vec<String>>,
@@ -30,21 +201,30 @@ pub struct ProjectSettings {
     release: bool,
     is_lib: bool,
     no_default_features: bool,
+    all_features: bool,
     project_path: PathBuf,
     cargo_toml_path: PathBuf,
-    target: Option<String>
+    target: Option<String>,
+    build_targets: Vec<BuildTarget>,
+    workspace: bool,
+    packages: Vec<String>,
+    excluded_packages: Vec<String>,
+    offline: bool,
+    locked: bool,
+    frozen: bool,
+    use_manifest_path: bool
 }
 
 impl ProjectSettings {
 
     /// Creates a new `ProjectSettings` instance for managing build configurations.
     ///
+    /// Every other setting starts with its default value and can be configured by chaining the
+    /// builder methods below.
+    ///
     /// # Arguments
     ///
     /// * `project_path` - The root directory of the Rust project.
-    /// * `output_path` - Optional path where the build output should be stored.
-    /// * `target` - Optional target triple (e.g., "x86_64-unknown-linux-gnu").
-    /// * `is_lib` - If `true`, builds the project as a library (`--lib`). If `false`, builds as a binary (`--bin`).
     ///
     /// # Returns
     ///
@@ -53,54 +233,448 @@ impl ProjectSettings {
     /// # Example
     /// ```rust
     /// use cargo_wrap::ProjectSettings;
-    /// let settings = ProjectSettings::new("/path/to/project", None, None, false);
+    /// let settings = ProjectSettings::new("/path/to/project")
+    ///     .release(true)
+    ///     .feature("foo")
+    ///     .no_default_features(true);
+    /// assert!(settings.is_release());
     /// ```
-    pub fn new(project_path: impl Into<PathBuf>, output_path: Option<impl Into<PathBuf>>, target: Option<String>,
-               is_lib: bool) -> Self {
+    pub fn new(project_path: impl Into<PathBuf>) -> Self {
         let project_path = project_path.into();
         let cargo_toml = project_path.clone().join("Cargo.toml");
         Self {
             project_path,
-            release: false,
-            output_path: output_path.map(Into::into),
             cargo_toml_path: cargo_toml,
-            is_lib,
-            target,
             ..Default::default()
         }
     }
 
-    /// Retrieves a list of available features from `Cargo.toml`.
+    /// Creates a new `ProjectSettings` instance from the path to the project's `Cargo.toml`.
+    ///
+    /// Cargo is invoked with `--manifest-path` instead of being run from the project root, so
+    /// the working directory of the cargo process is left unchanged. This is useful for nested
+    /// packages in a monorepo, or when the working directory must stay fixed.
+    ///
+    /// # Arguments
+    ///
+    /// * `manifest_path` - Path to the package's `Cargo.toml`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::ProjectSettings;
+    /// let settings = ProjectSettings::from_manifest_path("/path/to/monorepo/tools/cli/Cargo.toml");
+    /// assert_eq!(settings.get_project_path(), std::path::Path::new("/path/to/monorepo/tools/cli"));
+    /// assert!(settings.is_manifest_path_build());
+    /// ```
+    pub fn from_manifest_path(manifest_path: impl Into<PathBuf>) -> Self {
+        ProjectSettings::default().manifest_path(manifest_path)
+    }
+
+    /// Creates a new `ProjectSettings` instance for the package of the `Cargo.toml` at
+    /// `manifest_path`, selecting what to build from its targets (see [`Manifest::get_targets`]):
+    ///
+    /// * The binary named by `package.default-run`, if set.
+    /// * Otherwise the only binary, or the binary named after the package if there are several.
+    /// * Otherwise the library, if the package has no binaries.
+    ///
+    /// If no target can be selected, e.g. for virtual manifests or packages with several binaries
+    /// none of which is the default, cargo's defaults apply. Cargo is run from the directory of
+    /// the manifest.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The file cannot be read ([`CargoWrapError::Io`]).
+    /// - The file is not valid TOML ([`CargoWrapError::ManifestParse`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::from_manifest("/path/to/project/Cargo.toml")?.release(true);
+    ///     println!("Building {:?} (lib: {})", settings.get_target(), settings.is_lib());
+    ///     Builder::new(settings)?.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_manifest(manifest_path: impl Into<PathBuf>) -> Result<Self> {
+        let manifest_path = manifest_path.into();
+        let manifest = Manifest::open(&manifest_path)?;
+        let project_path = manifest_path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let mut settings = ProjectSettings::new(project_path);
+        settings.cargo_toml_path = manifest_path;
+
+        let targets = manifest.get_targets();
+        let bins: Vec<&str> = targets
+            .iter()
+            .filter_map(|target| match target {
+                BuildTarget::Bin(name) => Some(name.as_str()),
+                _ => None,
+            })
+            .collect();
+        let default = manifest.get_default_run().or(manifest.get_name()).filter(|name| bins.contains(name));
+        match (default, bins.as_slice()) {
+            (Some(name), _) | (None, &[name]) => settings.target = Some(name.to_string()),
+            (None, []) => settings.is_lib = targets.contains(&BuildTarget::Lib),
+            (None, _) => {}
+        }
+        Ok(settings)
+    }
+
+    /// Sets the path to the project's `Cargo.toml`, passed as `--manifest-path X`, see
+    /// [`ProjectSettings::from_manifest_path`]
+    pub fn manifest_path(mut self, manifest_path: impl Into<PathBuf>) -> Self {
+        let manifest_path = manifest_path.into();
+        self.project_path = manifest_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.cargo_toml_path = manifest_path;
+        self.use_manifest_path = true;
+        self
+    }
+
+    /// Sets the compilation target triple (`--target X`), e.g. `x86_64-unknown-linux-gnu`
+    pub fn compilation_target(mut self, triple: impl Into<String>) -> Self {
+        self.compilation_target = Some(triple.into());
+        self
+    }
+
+    /// Enables a feature that's available in the project
+    pub fn feature(mut self, feature: impl Into<String>) -> Self {
+        self.add_feature(feature.into());
+        self
+    }
+
+    /// Enables several features that are available in the project
+    pub fn features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.features.get_or_insert_with(Vec::new).extend(features.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets the path where compiled artifacts are stored (`CARGO_TARGET_DIR`)
+    pub fn output_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output_path = Some(path.into());
+        self
+    }
+
+    /// Sets whether the project is built in release mode (`--release`)
+    pub fn release(mut self, release: bool) -> Self {
+        self.release = release;
+        self
+    }
+
+    /// Sets whether the library is built (`--lib`) instead of the named binary (`--bin`)
+    pub fn lib(mut self, is_lib: bool) -> Self {
+        self.is_lib = is_lib;
+        self
+    }
+
+    /// Sets whether default features are disabled (`--no-default-features`)
+    pub fn no_default_features(mut self, no_default_features: bool) -> Self {
+        self.no_default_features = no_default_features;
+        self
+    }
+
+    /// Sets whether every feature of the selected packages is enabled (`--all-features`)
+    pub fn all_features(mut self, all_features: bool) -> Self {
+        self.all_features = all_features;
+        self
+    }
+
+    /// Sets the root directory of the project, updating the `Cargo.toml` path accordingly
+    pub fn project_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.set_project_path(path.into());
+        self
+    }
+
+    /// Sets the specific binary/library to build
+    pub fn target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Adds a target to build, e.g. an example (`--example X`) or every target (`--all-targets`).
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{BuildTarget, ProjectSettings};
+    /// let settings = ProjectSettings::new("/path/to/project")
+    ///     .build_target(BuildTarget::Bins)
+    ///     .build_target(BuildTarget::Example("demo".to_string()));
+    /// assert_eq!(settings.get_build_targets().len(), 2);
+    /// ```
+    pub fn build_target(mut self, target: BuildTarget) -> Self {
+        self.build_targets.push(target);
+        self
+    }
+
+    /// Adds binaries to build in the same cargo invocation (`--bin X` for each), see
+    /// [`BuildOutput::executable`] to find each of them after the build.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").release(true).bins(["daemon", "cli", "helper"]);
+    ///     let output = Builder::new(settings)?.build()?;
+    ///     for (name, executable) in output.executables_by_target() {
+    ///         println!("{}: {}", name, executable.display());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn bins(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.build_targets.extend(names.into_iter().map(|name| BuildTarget::Bin(name.into())));
+        self
+    }
+
+    /// Sets whether every member of the workspace is built (`--workspace`)
+    pub fn workspace(mut self, workspace: bool) -> Self {
+        self.workspace = workspace;
+        self
+    }
+
+    /// Sets the packages to build, each passed as `-p X`
+    pub fn packages(mut self, packages: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.packages = packages.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Excludes packages from a workspace build, each passed as `--exclude X`
+    pub fn exclude(mut self, packages: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.excluded_packages.extend(packages.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets whether cargo is prevented from accessing the network (`--offline`)
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Sets whether the build fails if `Cargo.lock` is missing or out of date (`--locked`)
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Sets whether the build is both locked and offline (`--frozen`)
+    pub fn frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Returns the compilation target triple, if set
+    pub fn get_compilation_target(&self) -> Option<&str> {
+        self.compilation_target.as_deref()
+    }
+
+    /// Returns the features enabled for the build
+    pub fn get_enabled_features(&self) -> &[String] {
+        self.features.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the path where compiled artifacts are stored, if set
+    pub fn get_output_path(&self) -> Option<&Path> {
+        self.output_path.as_deref()
+    }
+
+    /// Returns `true` if the project is built in release mode
+    pub fn is_release(&self) -> bool {
+        self.release
+    }
+
+    /// Returns `true` if the library is built instead of the named binary
+    pub fn is_lib(&self) -> bool {
+        self.is_lib
+    }
+
+    /// Returns `true` if default features are disabled
+    pub fn is_no_default_features(&self) -> bool {
+        self.no_default_features
+    }
+
+    /// Returns `true` if every feature is enabled
+    pub fn is_all_features(&self) -> bool {
+        self.all_features
+    }
+
+    /// Returns the root directory of the project
+    pub fn get_project_path(&self) -> &Path {
+        &self.project_path
+    }
+
+    /// Returns the path to the project's `Cargo.toml`
+    pub fn get_cargo_toml_path(&self) -> &Path {
+        &self.cargo_toml_path
+    }
+
+    /// Returns the specific binary/library to build, if set
+    pub fn get_target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Returns the additional targets to build
+    pub fn get_build_targets(&self) -> &[BuildTarget] {
+        &self.build_targets
+    }
+
+    /// Returns `true` if every member of the workspace is built
+    pub fn is_workspace(&self) -> bool {
+        self.workspace
+    }
+
+    /// Returns the packages to build
+    pub fn get_packages(&self) -> &[String] {
+        &self.packages
+    }
+
+    /// Returns the packages excluded from a workspace build
+    pub fn get_excluded_packages(&self) -> &[String] {
+        &self.excluded_packages
+    }
+
+    /// Returns `true` if cargo is prevented from accessing the network
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Returns `true` if the build fails when `Cargo.lock` is missing or out of date
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Returns `true` if the build is both locked and offline
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Returns `true` if cargo is invoked with `--manifest-path` instead of from the project root
+    pub fn is_manifest_path_build(&self) -> bool {
+        self.use_manifest_path
+    }
+
+    /// Retrieves the available features from `Cargo.toml`, see [`Manifest::get_features`].
+    ///
+    /// The returned [`FeatureMap`] contains every feature with the values it enables, including
+    /// the implicit features of optional dependencies.
     ///
     /// # Returns
     ///
-    /// * `Ok(Vec<String>)` - A list of feature names if parsing succeeds.
-    /// * `Err(io::Error)` - If `Cargo.toml` is missing or cannot be parsed.
+    /// * `Ok(FeatureMap)` - The features of the package if parsing succeeds.
+    /// * `Err(CargoWrapError)` - If `Cargo.toml` is missing or cannot be parsed.
     ///
     /// # Errors
     ///
     /// This function will return an error if:
-    /// - `Cargo.toml` does not exist.
-    /// - The file cannot be read due to I/O issues.
-    /// - The `features` section in `Cargo.toml` is invalid.
+    /// - `Cargo.toml` does not exist ([`CargoWrapError::Io`]).
+    /// - The file cannot be read due to I/O issues ([`CargoWrapError::Io`]).
+    /// - `Cargo.toml` is not valid TOML ([`CargoWrapError::ManifestParse`]).
     ///
     /// # Example
     /// ```rust
     /// use cargo_wrap::ProjectSettings;
-    /// let settings = ProjectSettings::new("/path/to/project", None, None, false);
+    /// let settings = ProjectSettings::new("/path/to/project");
     /// match settings.get_features() {
-    ///     Ok(features) => println!("Available features: {:?}", features),
+    ///     Ok(features) => println!("Available features: {:?}", features.names().collect::<Vec<_>>()),
     ///     Err(e) => eprintln!("Error retrieving features: {}", e),
     /// }
     /// ```
-    pub fn get_features(&self) -> io::Result<Vec<String>> {
-        let cargo_content = fs::read_to_string(&self.cargo_toml_path)?;
-        let parsed_toml: Value = cargo_content.parse().map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
-        if let Some(features) = parsed_toml.get("features").and_then(|f| f.as_table()) {
-            Ok(features.keys().cloned().collect())
-        } else {
-            Ok(vec![])
-        }
+    pub fn get_features(&self) -> Result<FeatureMap> {
+        Ok(self.manifest()?.get_features())
+    }
+
+    /// Loads the project's `Cargo.toml` for editing, see [`Manifest`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The file cannot be read ([`CargoWrapError::Io`]).
+    /// - `Cargo.toml` is not valid TOML ([`CargoWrapError::ManifestParse`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Bump, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut manifest = ProjectSettings::new("/path/to/project").manifest()?;
+    ///     let version = manifest.bump_version(Bump::Minor)?;
+    ///     manifest.save()?;
+    ///     println!("released {}", version);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn manifest(&self) -> Result<Manifest> {
+        Manifest::open(&self.cargo_toml_path)
+    }
+
+    /// Checks the settings against the project before building, so that mistakes are reported
+    /// together and by kind instead of by cargo in the middle of a build.
+    ///
+    /// The checks are:
+    /// - The project directory and its `Cargo.toml` exist, and the manifest parses.
+    /// - The enabled features are declared by the package, and `dependency/feature` values name
+    ///   one of its dependencies.
+    /// - The compilation target is a target triple. Custom target specifications (`.json`) are
+    ///   not checked. Whether the target is installed depends on the toolchain and backend, see
+    ///   [`Builder::ensure_target_installed`].
+    /// - The library exists when it is built, or the named binary exists, declared in `[[bin]]`
+    ///   or discovered by cargo in `src/main.rs` and `src/bin`.
+    ///
+    /// Features and targets are not checked for workspace builds or builds of other packages.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::ValidationFailed`] with every problem found.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{CargoWrapError, ProjectSettings, ValidationError};
+    ///
+    /// let settings = ProjectSettings::new("/path/to/project").compilation_target("x86_64-linux-gnu-");
+    /// match settings.validate() {
+    ///     Err(CargoWrapError::ValidationFailed(errors)) => assert_eq!(
+    ///         errors,
+    ///         [
+    ///             ValidationError::ProjectPathMissing("/path/to/project".into()),
+    ///             ValidationError::InvalidTargetTriple("x86_64-linux-gnu-".to_string()),
+    ///         ]
+    ///     ),
+    ///     result => panic!("unexpected result: {:?}", result),
+    /// }
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let errors = validation::validate(self);
+        if errors.is_empty() { Ok(()) } else { Err(CargoWrapError::ValidationFailed(errors)) }
+    }
+
+    /// Lists every target of the project's package that can be built, declared in the manifest
+    /// or discovered by cargo, see [`Manifest::get_targets`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<BuildTarget>)` - The library, binaries, examples, tests and benchmarks, e.g.
+    ///   `[Lib, Bin("app"), Example("demo")]`. Each can be passed to [`ProjectSettings::build_target`].
+    /// * `Err(CargoWrapError)` - If `Cargo.toml` is missing or cannot be parsed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{BuildTarget, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project");
+    ///     for target in settings.list_targets()? {
+    ///         match target {
+    ///             BuildTarget::Lib => println!("lib"),
+    ///             BuildTarget::Bin(name) => println!("bin {}", name),
+    ///             other => println!("{:?}", other),
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn list_targets(&self) -> Result<Vec<BuildTarget>> {
+        Ok(self.manifest()?.get_targets())
     }
 
     /// Marks the project to be built as `release`
@@ -113,11 +687,26 @@ impl ProjectSettings {
         self.features.get_or_insert_with(Vec::new).push(feature)
     }
 
+    /// Enables every feature of the selected packages (`--all-features`)
+    pub fn set_all_features(&mut self) {
+        self.all_features = true;
+    }
+
+    /// Disables the default features of the selected packages (`--no-default-features`)
+    pub fn set_no_default_features(&mut self) {
+        self.no_default_features = true;
+    }
+
     /// Manually sets the build target
     pub fn set_target(&mut self, target: String) {
         self.target = Some(target)
     }
 
+    /// Adds a target to build, e.g. a test (`--test X`) or every benchmark (`--benches`)
+    pub fn add_build_target(&mut self, target: BuildTarget) {
+        self.build_targets.push(target)
+    }
+
     /// Manually set the build output path
     pub fn set_output_path(&mut self, path: PathBuf) {
         self.output_path = Some(path)
@@ -125,8 +714,69 @@ impl ProjectSettings {
 
     /// Manually set the project path
     pub fn set_project_path(&mut self, path: PathBuf) {
+        self.cargo_toml_path = path.join("Cargo.toml");
         self.project_path = path
     }
+
+    /// Marks the build to include every member of the workspace (`--workspace`)
+    pub fn set_workspace(&mut self) {
+        self.workspace = true;
+    }
+
+    /// Sets the packages to build, each passed as `-p X`
+    pub fn set_packages(&mut self, packages: Vec<String>) {
+        self.packages = packages
+    }
+
+    /// Excludes packages from a workspace build, each passed as `--exclude X`.
+    ///
+    /// Exclusions only take effect for workspace builds (see [`ProjectSettings::set_workspace`]
+    /// and [`Builder::build_workspace`]).
+    pub fn exclude_packages(&mut self, packages: Vec<String>) {
+        self.excluded_packages.extend(packages)
+    }
+
+    /// Prevents cargo from accessing the network (`--offline`), e.g. in air-gapped environments
+    pub fn set_offline(&mut self) {
+        self.offline = true;
+    }
+
+    /// Makes the build fail if `Cargo.lock` is missing or out of date (`--locked`)
+    pub fn set_locked(&mut self) {
+        self.locked = true;
+    }
+
+    /// Makes the build both locked and offline (`--frozen`)
+    pub fn set_frozen(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Retrieves the directories of every workspace member declared in the root `Cargo.toml`,
+    /// see [`Manifest::get_workspace_members`].
+    ///
+    /// Entries of `workspace.members` may use `*` and `?` within a path component, and are
+    /// expanded to every matching directory containing a `Cargo.toml`. Entries matching
+    /// `workspace.exclude` are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<PathBuf>)` - The member directories, or an empty list if the manifest has no
+    ///   `[workspace]` section.
+    /// * `Err(CargoWrapError)` - If `Cargo.toml` is missing or cannot be parsed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::ProjectSettings;
+    /// let settings = ProjectSettings::new("/path/to/workspace");
+    /// if let Ok(members) = settings.get_workspace_members() {
+    ///     for member in members {
+    ///         println!("Member: {}", member.display());
+    ///     }
+    /// }
+    /// ```
+    pub fn get_workspace_members(&self) -> Result<Vec<PathBuf>> {
+        self.manifest()?.get_workspace_members()
+    }
 }
 
 /// The main struct responsible for building a Rust project.
@@ -140,159 +790,4077 @@ impl ProjectSettings {
 /// * `project_settings` - The `ProjectSettings` instance containing build configurations.
 /// * `thread_count` - Optional number of jobs (`--jobs N`) to use during the build. Default value is 0.
 /// * `output_path` - Optional log file to store output.
-/// * `verbose_build` - If `true`, enables verbose output (`--verbose`).
-/// * `additional_flags` - Optional flags to pass to the `rustc` binary (via the `RUSTFLAGS` environment variable)
+/// * `verbosity` - How much cargo reports (`--quiet`, `--verbose` or `-vv`).
+/// * `additional_flags` - Optional flags to pass to the `rustc` binary (via the `CARGO_ENCODED_RUSTFLAGS` environment variable)
+/// * `rustflags_policy` - How `additional_flags` are combined with the `rustc` flags of the environment.
+/// * `deny_warnings` - If `true`, warnings are turned into errors (`-D warnings`).
+/// * `timings` - If `true`, cargo writes a timing report (`--timings`) that is parsed into the build output.
+/// * `keep_going` - If `true`, cargo builds as many crates as possible after one fails (`--keep-going`).
+/// * `future_incompat_report` - If `true`, cargo's future-incompatibility report (`--future-incompat-report`) is parsed into the build output.
+/// * `checksums` - If `true`, the produced binaries are hashed and a `checksums.json` manifest is written next to them.
+/// * `build_cache` - If `true`, builds whose inputs match an earlier successful build are skipped.
+/// * `isolated_target_dir` - If `true`, every configuration (target, features, profile) builds into its own target directory.
+/// * `install_dir` - Optional directory the binaries of every successful build are copied into.
+/// * `install_template` - Optional name the binaries are installed under, e.g. `{name}-{version}-{target}{ext}`.
+/// * `size_history` - Optional history file the artifact sizes of every successful build are recorded in.
+/// * `incremental` - Optional override of incremental compilation (`CARGO_INCREMENTAL`), `None` meaning the profile's setting.
+/// * `rustc_wrapper` - Optional program `rustc` is invoked through (`RUSTC_WRAPPER`), e.g. `sccache`.
+/// * `run_stdio` - Standard stream handling for programs started with `cargo run`.
+/// * `timeout` - Optional time limit after which a running build is killed.
+/// * `retry_policy` - Optional policy retrying builds that failed because of the network.
+/// * `duplicate_policy` - Optional policy checking the dependency graph for packages resolved at several versions before builds.
+/// * `prerequisites` - Optional system tools and libraries probed before builds.
+/// * `toolchain` - Optional rustup toolchain to build with (e.g. `nightly-2024-05-01`).
+/// * `sanitizer` - Optional runtime sanitizer the project is instrumented with (`-Z sanitizer=X`).
+/// * `build_std` - Optional standard library crates rebuilt from source (`-Z build-std`).
+/// * `backend` - Optional backend that runs cargo subcommands. Defaults to [`CargoBackend`].
+/// * `cache_backend` - Optional store target directories are restored from and saved to.
+/// * `artifact_pipeline` - Optional steps applied to the binaries of successful builds.
+/// * `profile` - Optional custom profile to build with (`--profile X`), overriding the release setting.
+/// * `profile_overrides` - Settings of the active profile overridden through `CARGO_PROFILE_*` variables.
+/// * `crate_type` - Optional crate type the library is compiled as (`cargo rustc --crate-type X`), overriding the manifest.
+/// * `cbindgen` - Optional settings of the headers generated for `cdylib` and `staticlib` libraries.
+/// * `envs` - Environment variables set for the cargo process.
+/// * `hermetic` - If `true`, the cargo process only inherits an allowlist of environment variables.
+/// * `allowed_envs` - Additional environment variables inherited by hermetic builds.
+/// * `sinks` - Additional destinations for the output, alongside the log file.
+/// * `hooks` - Callbacks run before and after builds.
+/// * `color` - Whether cargo colors its output (`--color X`).
+/// * `strip_log_ansi` - If `true`, ANSI escape sequences are removed from the output written to the log file and sinks.
+/// * `log_timestamps` - If `true`, every line written to the log file and sinks is prefixed with the time and the stream it was read from.
 #[derive(Default, Debug)]
 pub struct Builder {
     cargo_path: PathBuf,
     project_settings: ProjectSettings,
     thread_count: usize,
     log_path: Option<PathBuf>,
-    verbose_build: bool,
-    additional_flags: Vec<String>
+    verbosity: Verbosity,
+    additional_flags: Vec<String>,
+    rustflags_policy: RustFlagsPolicy,
+    deny_warnings: bool,
+    timings: bool,
+    keep_going: bool,
+    future_incompat_report: bool,
+    checksums: bool,
+    build_cache: bool,
+    isolated_target_dir: bool,
+    install_dir: Option<PathBuf>,
+    install_template: Option<String>,
+    size_history: Option<PathBuf>,
+    incremental: Option<bool>,
+    rustc_wrapper: Option<PathBuf>,
+    run_stdio: RunStdio,
+    timeout: Option<Duration>,
+    retry_policy: Option<RetryPolicy>,
+    duplicate_policy: Option<DuplicatePolicy>,
+    prerequisites: Option<Prerequisites>,
+    toolchain: Option<String>,
+    sanitizer: Option<Sanitizer>,
+    build_std: Option<BuildStd>,
+    backend: Option<Arc<dyn BuildBackend>>,
+    cache_backend: Option<Arc<dyn CacheBackend>>,
+    artifact_pipeline: Option<ArtifactPipeline>,
+    profile: Option<String>,
+    profile_overrides: ProfileOverrides,
+    crate_type: Option<CrateType>,
+    cbindgen: Option<Cbindgen>,
+    envs: BTreeMap<String, String>,
+    hermetic: bool,
+    allowed_envs: Vec<String>,
+    sinks: Arc<OutputSinks>,
+    hooks: BuildHooks,
+    color: ColorChoice,
+    strip_log_ansi: bool,
+    log_timestamps: bool,
 }
 
 impl Builder {
 
-    /// Private function to get the `cargo` binary path from the environment
-    fn get_cargo_path() -> io::Result<PathBuf> {
-        env::var_os("CARGO")
-            .map(PathBuf::from)
-            .ok_or_else(|| Error::new(ErrorKind::NotFound, "CARGO environment variable not found"))
+    /// Private function to get the `cargo` binary path from the environment.
+    ///
+    /// The `CARGO` environment variable is used if set (e.g. when running under cargo), otherwise
+    /// `cargo` is searched in `PATH`, then in `$CARGO_HOME/bin` and `~/.cargo/bin`.
+    fn find_cargo_path() -> Result<PathBuf> {
+        if let Some(cargo) = env::var_os("CARGO") {
+            return Ok(PathBuf::from(cargo));
+        }
+        Builder::find_binary("cargo").ok_or(CargoWrapError::CargoNotFound)
+    }
+
+    /// Private function that searches the binary `name` in `PATH`, then in `$CARGO_HOME/bin` and
+    /// `~/.cargo/bin`
+    fn find_binary(name: &str) -> Option<PathBuf> {
+        let file_name = format!("{}{}", name, env::consts::EXE_SUFFIX);
+        let path_dirs: Vec<PathBuf> = env::var_os("PATH").map(|path| env::split_paths(&path).collect()).unwrap_or_default();
+        let cargo_home = env::var_os("CARGO_HOME").map(PathBuf::from);
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(|home| PathBuf::from(home).join(".cargo"));
+        path_dirs
+            .into_iter()
+            .chain([cargo_home, home].into_iter().flatten().map(|dir| dir.join("bin")))
+            .map(|dir| dir.join(&file_name))
+            .find(|candidate| candidate.is_file())
     }
 
     /// Creates a new `Builder` instance for managing and executing cargo builds.
     ///
-    /// This function initializes the builder with the given project settings,
-    /// allowing for configuration of build parameters such as job count and log output.
+    /// This function initializes the builder with the given project settings. Build parameters
+    /// such as job count and log output can then be configured by chaining the builder methods below.
     ///
     /// # Arguments
     ///
     /// * `project_settings` - A `ProjectSettings` instance containing the configuration
     ///   for the Rust project to be built.
-    /// * `thread_count` - Optional number of parallel jobs (`--jobs N`) to use for building.
-    ///   If `0`, the default job count will be used.
-    /// * `log_path` - Optional path to a log file where build output will be stored.
     ///
     /// # Returns
     ///
     /// * `Ok(Builder)` - A new `Builder` instance ready to execute a build.
-    /// * `Err(io::Error)` - If the `cargo` binary is not found in the environment.
+    /// * `Err(CargoWrapError)` - If the `cargo` binary is not found in the environment.
     ///
     /// # Errors
     ///
-    /// This function will return an error if:
-    /// - The `CARGO` environment variable is not set, meaning `cargo` cannot be found.
-    /// - The provided `log_path` is invalid or cannot be written to.
+    /// This function will return [`CargoWrapError::CargoNotFound`] if the `CARGO` environment
+    /// variable is not set and `cargo` cannot be found in `PATH`, `$CARGO_HOME/bin` or
+    /// `~/.cargo/bin`. Use [`Builder::with_cargo_path`] to provide the binary explicitly.
     ///
     /// # Example
     /// ```rust
     /// use cargo_wrap::{Builder, ProjectSettings};
-    /// use std::io;
     ///
-    /// fn main() -> io::Result<()> {
-    ///     let settings = ProjectSettings::new("/path/to/project", None, None, false);
-    ///     let builder = Builder::new(settings, 4, Some("build.log"))?;
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project");
+    ///     let builder = Builder::new(settings)?.jobs(4).log_path("build.log");
+    ///     assert_eq!(builder.get_jobs(), 4);
     ///     Ok(())
     /// }
-    pub fn new(project_settings: ProjectSettings, thread_count: usize, log_path:
-    Option<impl Into<PathBuf>>) ->
-               io::Result<Builder> {
-        let cargo_path = Builder::get_cargo_path()?;
+    /// ```
+    pub fn new(project_settings: ProjectSettings) -> Result<Builder> {
+        let cargo_path = Builder::find_cargo_path()?;
         Ok(Self {
             cargo_path,
             project_settings,
-            thread_count,
-            log_path: log_path.map(Into::into),
             ..Default::default()
         })
     }
 
-    /// Tells the builder to use the `--verbose` flag when building
-    pub fn set_verbose(&mut self) {
-        self.verbose_build = true;
-    }
-
-    /// Adds a flag to the list of additional flags that will be passed to `rustc`
-    pub fn add_rustc_flag(&mut self, flag: String) {
-        self.additional_flags.push(flag);
+    /// Creates a new `Builder` instance that uses the given `cargo` binary, without looking it up
+    /// in the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `project_settings` - The configuration for the Rust project to be built.
+    /// * `cargo_path` - Path to the `cargo` binary.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    /// let builder = Builder::with_cargo_path(ProjectSettings::new("/path/to/project"), "/opt/rust/bin/cargo");
+    /// assert_eq!(builder.get_cargo_path(), std::path::Path::new("/opt/rust/bin/cargo"));
+    /// ```
+    pub fn with_cargo_path(project_settings: ProjectSettings, cargo_path: impl Into<PathBuf>) -> Builder {
+        Self {
+            cargo_path: cargo_path.into(),
+            project_settings,
+            ..Default::default()
+        }
     }
 
-    /// Executes the build process using `cargo build`.
+    /// Creates a new `Builder` from a [`BuilderConfig`].
     ///
-    /// This function spawns a `cargo build` process with the specified settings,
-    /// such as release/debug mode, enabled features, and output directories.
+    /// # Arguments
+    ///
+    /// * `config` - The configuration to restore.
     ///
     /// # Returns
     ///
-    /// * `Ok(())` - If the build succeeds.
-    /// * `Err(io::Error)` - If the build process fails.
+    /// * `Ok(Builder)` - A new `Builder` instance configured exactly like `config`.
+    /// * `Err(CargoWrapError)` - If `config` has no `cargo_path` and the `cargo` binary is not
+    ///   found in the environment.
     ///
-    /// # Errors
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{Builder, BuilderConfig, ProjectSettings};
     ///
-    /// This function will return an error if:
-    /// - The `cargo` binary is missing from the system.
-    /// - The build process fails (e.g., compilation errors).
-    /// - The log file cannot be written to (if logging is enabled).
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let config = BuilderConfig {
+    ///         project_settings: ProjectSettings::new("/path/to/project").release(true),
+    ///         jobs: 4,
+    ///         ..Default::default()
+    ///     };
+    ///     let builder = Builder::from_config(config)?;
+    ///     assert_eq!(builder.get_jobs(), 4);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_config(config: BuilderConfig) -> Result<Builder> {
+        let cargo_path = match config.cargo_path {
+            Some(cargo_path) => cargo_path,
+            None => Builder::find_cargo_path()?,
+        };
+        Ok(Self {
+            cargo_path,
+            project_settings: config.project_settings,
+            thread_count: config.jobs,
+            log_path: config.log_path,
+            verbosity: config.verbosity,
+            additional_flags: config.rustc_flags,
+            rustflags_policy: config.rustflags_policy,
+            deny_warnings: config.deny_warnings,
+            timings: config.timings,
+            keep_going: config.keep_going,
+            future_incompat_report: config.future_incompat_report,
+            checksums: config.checksums,
+            build_cache: config.build_cache,
+            isolated_target_dir: config.isolated_target_dir,
+            install_dir: config.install_dir,
+            install_template: config.install_template,
+            size_history: config.size_history,
+            incremental: config.incremental,
+            rustc_wrapper: config.rustc_wrapper,
+            run_stdio: config.run_stdio,
+            timeout: config.timeout,
+            retry_policy: config.retry_policy,
+            duplicate_policy: config.duplicate_policy,
+            prerequisites: config.prerequisites,
+            // Sanitizers and build-std need a nightly toolchain, see `Builder::set_sanitizer`
+            toolchain: config
+                .toolchain
+                .or_else(|| (config.sanitizer.is_some() || config.build_std.is_some()).then(|| "nightly".to_string())),
+            sanitizer: config.sanitizer,
+            build_std: config.build_std,
+            backend: None,
+            cache_backend: None,
+            artifact_pipeline: None,
+            profile: config.profile,
+            profile_overrides: config.profile_overrides,
+            crate_type: config.crate_type,
+            cbindgen: config.cbindgen,
+            envs: config.envs,
+            hermetic: config.hermetic,
+            allowed_envs: config.allowed_envs,
+            sinks: Arc::default(),
+            hooks: BuildHooks::default(),
+            color: config.color,
+            strip_log_ansi: config.strip_log_ansi,
+            log_timestamps: config.log_timestamps,
+        })
+    }
+
+    /// Returns a snapshot of the builder's configuration, which can be restored with
+    /// [`Builder::from_config`].
+    ///
+    /// Builders hold backends and callbacks, which cannot be compared; their configurations can.
     ///
     /// # Example
     /// ```rust
     /// use cargo_wrap::{Builder, ProjectSettings};
-    /// use std::io;
     ///
-    /// fn main() -> io::Result<()> {
-    ///     let settings = ProjectSettings::new("/path/to/project", None, None, false);
-    ///     let builder = Builder::new(settings, 4, Some("build.log"))?;
-    ///     builder.build()?;
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").release(true);
+    ///     let builder = Builder::new(settings.clone())?.jobs(4).cargo_path("/usr/bin/cargo");
+    ///     let expected = Builder::new(settings)?.cargo_path("/usr/bin/cargo").jobs(4);
+    ///     assert_eq!(builder.config(), expected.config());
+    ///     assert_ne!(builder.config(), expected.jobs(8).config());
     ///     Ok(())
     /// }
     /// ```
-    pub fn build(&self) -> io::Result<()> {
-        let mut command = Command::new(self.cargo_path.clone());
-        command.arg("build");
-        if self.verbose_build {
-            command.arg("--verbose");
+    pub fn config(&self) -> BuilderConfig {
+        BuilderConfig {
+            cargo_path: Some(self.cargo_path.clone()),
+            project_settings: self.project_settings.clone(),
+            jobs: self.thread_count,
+            log_path: self.log_path.clone(),
+            verbosity: self.verbosity,
+            rustc_flags: self.additional_flags.clone(),
+            rustflags_policy: self.rustflags_policy,
+            deny_warnings: self.deny_warnings,
+            timings: self.timings,
+            keep_going: self.keep_going,
+            future_incompat_report: self.future_incompat_report,
+            checksums: self.checksums,
+            build_cache: self.build_cache,
+            isolated_target_dir: self.isolated_target_dir,
+            install_dir: self.install_dir.clone(),
+            install_template: self.install_template.clone(),
+            size_history: self.size_history.clone(),
+            incremental: self.incremental,
+            rustc_wrapper: self.rustc_wrapper.clone(),
+            run_stdio: self.run_stdio,
+            timeout: self.timeout,
+            retry_policy: self.retry_policy.clone(),
+            duplicate_policy: self.duplicate_policy.clone(),
+            prerequisites: self.prerequisites.clone(),
+            toolchain: self.toolchain.clone(),
+            sanitizer: self.sanitizer,
+            build_std: self.build_std.clone(),
+            profile: self.profile.clone(),
+            profile_overrides: self.profile_overrides.clone(),
+            crate_type: self.crate_type,
+            cbindgen: self.cbindgen.clone(),
+            envs: self.envs.clone(),
+            hermetic: self.hermetic,
+            allowed_envs: self.allowed_envs.clone(),
+            color: self.color,
+            strip_log_ansi: self.strip_log_ansi,
+            log_timestamps: self.log_timestamps,
         }
-        if self.project_settings.release {
-            command.arg("--release");
-        }
-        if self.thread_count > 0 {
-            command.arg("--jobs").arg(self.thread_count.to_string());
+    }
 
-        }
-        if let Some(output_path) = &self.project_settings.output_path {
-            command.env("CARGO_TARGET_DIR", output_path);
-        }
-        if !self.additional_flags.is_empty() {
-            command.env("RUSTFLAGS", self.additional_flags.join(" "));
-        }
-        if let Some(ref target) = self.project_settings.compilation_target {
-            command.arg("--target").arg(target);
-        }
-        if let Some(features) = &self.project_settings.features {
-            features.iter().for_each(|f| {
-                command.arg("--features");
-                command.arg(f);
-            });
-        }
-        if self.project_settings.no_default_features {
-            command.arg("--no-default-features");
-        }
-        if let Some(target) = &self.project_settings.target {
-            command.arg(if self.project_settings.is_lib { "--lib" } else { "--bin" }).arg(target);
-        }
+    /// Sets the path to the `cargo` binary
+    pub fn cargo_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cargo_path = path.into();
+        self
+    }
 
-        let output = command.current_dir(&self.project_settings.project_path).output()?;
-        if let Some(output_log) = &self.log_path {
-            let mut output_file = OpenOptions::new().create(true).append(true).open(output_log)?;
-            output_file.write_all(&output.stdout)?;
-            output_file.write_all(&output.stderr)?;
-        }
-        if output.status.success() {
-            Ok(())
-        } else {
-            Err(Error::new(ErrorKind::Other, format!("Failed to compile project: {}", output.status)))
-        }
+    /// Replaces the project settings used for the build
+    pub fn project_settings(mut self, project_settings: ProjectSettings) -> Self {
+        self.project_settings = project_settings;
+        self
+    }
+
+    /// Sets the number of parallel jobs (`--jobs N`). If `0`, the default job count will be used.
+    pub fn jobs(mut self, thread_count: usize) -> Self {
+        self.thread_count = thread_count;
+        self
+    }
+
+    /// Sets the path to a log file where build output will be stored
+    pub fn log_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log_path = Some(path.into());
+        self
+    }
+
+    /// Attaches an additional destination for the build output, see [`Builder::add_sink`]
+    pub fn sink(mut self, sink: impl OutputSink + 'static) -> Self {
+        self.add_sink(sink);
+        self
+    }
+
+    /// Attaches a callback run with the `cargo build` command before it is started, see [`Builder::add_before_build_hook`]
+    pub fn on_before_build(mut self, hook: impl FnMut(&mut Command) + Send + 'static) -> Self {
+        self.add_before_build_hook(hook);
+        self
+    }
+
+    /// Attaches a callback run with the report of every finished build, see [`Builder::add_after_build_hook`]
+    pub fn on_after_build(mut self, hook: impl FnMut(&BuildReport) + Send + 'static) -> Self {
+        self.add_after_build_hook(hook);
+        self
+    }
+
+    /// Sets whether cargo colors its output (`--color X`), see [`Builder::set_color`]
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Sets whether ANSI escape sequences are removed from the output written to the log file and sinks
+    pub fn strip_log_ansi(mut self, strip_log_ansi: bool) -> Self {
+        self.strip_log_ansi = strip_log_ansi;
+        self
+    }
+
+    /// Sets whether every line written to the log file and sinks is prefixed with the time and
+    /// the stream it was read from, e.g. `[2024-05-01T12:00:00.250Z stderr]   Compiling app v0.1.0`.
+    ///
+    /// Lines of stdout and stderr are always written in the order they are read; timestamps make
+    /// that order visible and show how long each step took.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project");
+    ///     let builder = Builder::new(settings)?.log_path("build.log").log_timestamps(true);
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn log_timestamps(mut self, log_timestamps: bool) -> Self {
+        self.log_timestamps = log_timestamps;
+        self
+    }
+
+    /// Sets whether the `--verbose` flag is used when building
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.verbosity = if verbose { Verbosity::Verbose } else { Verbosity::Normal };
+        self
+    }
+
+    /// Sets how much cargo reports about the build, see [`Builder::set_verbosity`]
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Adds a flag that will be passed to `rustc`, as a single argument
+    pub fn rustc_flag(mut self, flag: impl Into<String>) -> Self {
+        self.additional_flags.push(flag.into());
+        self
+    }
+
+    /// Sets how the `rustc` flags are combined with those of the environment, see [`Builder::set_rustflags_policy`]
+    pub fn rustflags_policy(mut self, policy: RustFlagsPolicy) -> Self {
+        self.rustflags_policy = policy;
+        self
+    }
+
+    /// Sets whether warnings are turned into errors, see [`Builder::set_deny_warnings`]
+    pub fn deny_warnings(mut self, deny_warnings: bool) -> Self {
+        self.deny_warnings = deny_warnings;
+        self
+    }
+
+    /// Sets whether cargo writes a timing report that is parsed into the build output, see [`Builder::set_timings`]
+    pub fn timings(mut self, timings: bool) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Sets whether cargo keeps building after a crate fails, see [`Builder::set_keep_going`]
+    pub fn keep_going(mut self, keep_going: bool) -> Self {
+        self.keep_going = keep_going;
+        self
+    }
+
+    /// Sets whether cargo's future-incompatibility report is parsed into the build output, see
+    /// [`Builder::set_future_incompat_report`]
+    pub fn future_incompat_report(mut self, future_incompat_report: bool) -> Self {
+        self.future_incompat_report = future_incompat_report;
+        self
+    }
+
+    /// Sets whether the build is compiled incrementally, see [`Builder::set_incremental`]
+    pub fn incremental(mut self, incremental: bool) -> Self {
+        self.incremental = Some(incremental);
+        self
+    }
+
+    /// Sets whether the produced binaries are hashed into a checksum manifest, see [`Builder::set_checksums`]
+    pub fn checksums(mut self, checksums: bool) -> Self {
+        self.checksums = checksums;
+        self
+    }
+
+    /// Sets whether builds whose inputs match an earlier successful build are skipped, see
+    /// [`Builder::set_build_cache`]
+    pub fn build_cache(mut self, build_cache: bool) -> Self {
+        self.build_cache = build_cache;
+        self
+    }
+
+    /// Sets whether every configuration builds into its own target directory, see
+    /// [`Builder::set_isolated_target_dir`]
+    pub fn isolated_target_dir(mut self, isolated_target_dir: bool) -> Self {
+        self.isolated_target_dir = isolated_target_dir;
+        self
+    }
+
+    /// Copies the binaries of every successful build into `dir`, see [`Builder::set_install_dir`]
+    pub fn install_to(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.set_install_dir(dir);
+        self
+    }
+
+    /// Sets the name the binaries are installed under, see [`Builder::set_install_template`]
+    pub fn install_template(mut self, template: impl Into<String>) -> Self {
+        self.set_install_template(template);
+        self
+    }
+
+    /// Records the artifact sizes of every successful build in a history file, see
+    /// [`Builder::set_size_tracking`]
+    pub fn size_tracking(mut self, history_path: impl Into<PathBuf>) -> Self {
+        self.set_size_tracking(history_path);
+        self
+    }
+
+    /// Sets the program `rustc` is invoked through, see [`Builder::set_rustc_wrapper`]
+    pub fn rustc_wrapper(mut self, wrapper: impl Into<PathBuf>) -> Self {
+        self.rustc_wrapper = Some(wrapper.into());
+        self
+    }
+
+    /// Sets how stdin/stdout/stderr are handled for programs started with `cargo run`
+    pub fn run_stdio(mut self, run_stdio: RunStdio) -> Self {
+        self.run_stdio = run_stdio;
+        self
+    }
+
+    /// Sets a time limit after which a running build is killed, see [`Builder::set_timeout`]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets when failed builds are retried, see [`Builder::set_retry_policy`]
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Sets how packages resolved at several versions are handled, see [`Builder::set_duplicate_policy`]
+    pub fn duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = Some(policy);
+        self
+    }
+
+    /// Sets the system tools and libraries probed before builds, see [`Builder::set_prerequisites`]
+    pub fn prerequisites(mut self, prerequisites: Prerequisites) -> Self {
+        self.prerequisites = Some(prerequisites);
+        self
+    }
+
+    /// Sets the rustup toolchain to build with, see [`Builder::set_toolchain`]
+    pub fn toolchain(mut self, toolchain: impl Into<String>) -> Self {
+        self.toolchain = Some(toolchain.into());
+        self
+    }
+
+    /// Instruments the project with a runtime sanitizer, see [`Builder::set_sanitizer`]
+    pub fn sanitizer(mut self, sanitizer: Sanitizer) -> Self {
+        self.set_sanitizer(sanitizer);
+        self
+    }
+
+    /// Rebuilds standard library crates from source, see [`Builder::set_build_std`]
+    pub fn build_std(mut self, build_std: BuildStd) -> Self {
+        self.set_build_std(build_std);
+        self
+    }
+
+    /// Sets the backend that runs cargo subcommands, see [`Builder::set_backend`]
+    pub fn backend(mut self, backend: impl BuildBackend + 'static) -> Self {
+        self.backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// Sets the store target directories are restored from and saved to, see
+    /// [`Builder::set_cache_backend`]
+    pub fn cache_backend(mut self, cache_backend: impl CacheBackend + 'static) -> Self {
+        self.cache_backend = Some(Arc::new(cache_backend));
+        self
+    }
+
+    /// Sets the steps applied to the binaries of successful builds, see [`Builder::set_artifact_pipeline`]
+    pub fn artifact_pipeline(mut self, artifact_pipeline: ArtifactPipeline) -> Self {
+        self.artifact_pipeline = Some(artifact_pipeline);
+        self
+    }
+
+    /// Applies the settings of a firmware build, see [`Builder::set_embedded_preset`]
+    pub fn embedded_preset(mut self, preset: EmbeddedPreset) -> Self {
+        self.set_embedded_preset(preset);
+        self
+    }
+
+    /// Sets the custom profile to build with, see [`Builder::set_profile`]
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Overrides settings of the active profile, see [`Builder::set_profile_overrides`]
+    pub fn profile_overrides(mut self, overrides: ProfileOverrides) -> Self {
+        self.profile_overrides = overrides;
+        self
+    }
+
+    /// Sets the crate type the library is compiled as, see [`Builder::set_crate_type`]
+    pub fn crate_type(mut self, crate_type: CrateType) -> Self {
+        self.crate_type = Some(crate_type);
+        self
+    }
+
+    /// Generates C headers for the `cdylib` and `staticlib` libraries, see [`Builder::set_cbindgen`]
+    pub fn cbindgen(mut self, cbindgen: Cbindgen) -> Self {
+        self.cbindgen = Some(cbindgen);
+        self
+    }
+
+    /// Sets an environment variable for the cargo process, see [`Builder::set_env`]
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.set_env(key, value);
+        self
+    }
+
+    /// Sets several environment variables for the cargo process, see [`Builder::set_env`]
+    pub fn envs(mut self, envs: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>) -> Self {
+        self.envs.extend(envs.into_iter().map(|(key, value)| (key.into(), value.into())));
+        self
+    }
+
+    /// Sets whether the cargo process only inherits an allowlist of environment variables, see
+    /// [`Builder::set_hermetic`]
+    pub fn hermetic(mut self, hermetic: bool) -> Self {
+        self.hermetic = hermetic;
+        self
+    }
+
+    /// Configures the builder for reproducible builds, see [`Builder::set_reproducible`]
+    pub fn reproducible(mut self) -> Self {
+        self.set_reproducible();
+        self
+    }
+
+    /// Adds an environment variable that hermetic builds inherit from the current process
+    pub fn allow_env(mut self, key: impl Into<String>) -> Self {
+        self.allowed_envs.push(key.into());
+        self
+    }
+
+    /// Returns the path to the `cargo` binary
+    pub fn get_cargo_path(&self) -> &Path {
+        &self.cargo_path
+    }
+
+    /// Returns the project settings used for the build
+    pub fn get_project_settings(&self) -> &ProjectSettings {
+        &self.project_settings
+    }
+
+    /// Returns the number of parallel jobs, `0` meaning cargo's default
+    pub fn get_jobs(&self) -> usize {
+        self.thread_count
+    }
+
+    /// Returns the path of the log file, if set
+    pub fn get_log_path(&self) -> Option<&Path> {
+        self.log_path.as_deref()
+    }
+
+    /// Returns whether cargo colors its output
+    pub fn get_color(&self) -> ColorChoice {
+        self.color
+    }
+
+    /// Returns `true` if ANSI escape sequences are removed from the output written to the log file and sinks
+    pub fn is_strip_log_ansi(&self) -> bool {
+        self.strip_log_ansi
+    }
+
+    /// Returns `true` if every line written to the log file and sinks is prefixed with the time and stream
+    pub fn is_log_timestamps(&self) -> bool {
+        self.log_timestamps
+    }
+
+    /// Returns `true` if the `--verbose` flag is used when building
+    pub fn is_verbose(&self) -> bool {
+        self.verbosity >= Verbosity::Verbose
+    }
+
+    /// Returns how much cargo reports about the build
+    pub fn get_verbosity(&self) -> Verbosity {
+        self.verbosity
+    }
+
+    /// Returns the flags passed to `rustc`
+    pub fn get_rustc_flags(&self) -> &[String] {
+        &self.additional_flags
+    }
+
+    /// Returns how the `rustc` flags are combined with those of the environment
+    pub fn get_rustflags_policy(&self) -> RustFlagsPolicy {
+        self.rustflags_policy
+    }
+
+    /// Returns `true` if warnings are turned into errors
+    pub fn is_deny_warnings(&self) -> bool {
+        self.deny_warnings
+    }
+
+    /// Returns `true` if cargo writes a timing report that is parsed into the build output
+    pub fn is_timings(&self) -> bool {
+        self.timings
+    }
+
+    /// Returns `true` if cargo keeps building after a crate fails
+    pub fn is_keep_going(&self) -> bool {
+        self.keep_going
+    }
+
+    /// Returns `true` if cargo's future-incompatibility report is parsed into the build output
+    pub fn is_future_incompat_report(&self) -> bool {
+        self.future_incompat_report
+    }
+
+    /// Returns `true` if the produced binaries are hashed into a checksum manifest
+    pub fn is_checksums(&self) -> bool {
+        self.checksums
+    }
+
+    /// Returns `true` if builds whose inputs match an earlier successful build are skipped
+    pub fn is_build_cache(&self) -> bool {
+        self.build_cache
+    }
+
+    /// Returns `true` if every configuration builds into its own target directory
+    pub fn is_isolated_target_dir(&self) -> bool {
+        self.isolated_target_dir
+    }
+
+    /// Returns the directory the binaries of every successful build are copied into, if any
+    pub fn get_install_dir(&self) -> Option<&Path> {
+        self.install_dir.as_deref()
+    }
+
+    /// Returns the name the binaries are installed under
+    pub fn get_install_template(&self) -> &str {
+        self.install_template.as_deref().unwrap_or(install::DEFAULT_TEMPLATE)
+    }
+
+    /// Returns the history file the artifact sizes of successful builds are recorded in, if set
+    pub fn get_size_history(&self) -> Option<&Path> {
+        self.size_history.as_deref()
+    }
+
+    /// Returns whether the build is compiled incrementally, `None` meaning the profile's setting
+    pub fn get_incremental(&self) -> Option<bool> {
+        self.incremental
+    }
+
+    /// Returns the program `rustc` is invoked through, if set
+    pub fn get_rustc_wrapper(&self) -> Option<&Path> {
+        self.rustc_wrapper.as_deref()
+    }
+
+    /// Returns how the standard streams are handled for programs started with `cargo run`
+    pub fn get_run_stdio(&self) -> RunStdio {
+        self.run_stdio
+    }
+
+    /// Returns the time limit for builds, if set
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Returns the policy retrying failed builds, if set
+    pub fn get_retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
+    }
+
+    /// Returns the policy handling packages resolved at several versions, if set
+    pub fn get_duplicate_policy(&self) -> Option<&DuplicatePolicy> {
+        self.duplicate_policy.as_ref()
+    }
+
+    /// Returns the system tools and libraries probed before builds, if any
+    pub fn get_prerequisites(&self) -> Option<&Prerequisites> {
+        self.prerequisites.as_ref()
+    }
+
+    /// Returns the rustup toolchain to build with, if set
+    pub fn get_toolchain(&self) -> Option<&str> {
+        self.toolchain.as_deref()
+    }
+
+    /// Returns the runtime sanitizer the project is instrumented with, if set
+    pub fn get_sanitizer(&self) -> Option<Sanitizer> {
+        self.sanitizer
+    }
+
+    /// Returns the standard library crates rebuilt from source, if set
+    pub fn get_build_std(&self) -> Option<&BuildStd> {
+        self.build_std.as_ref()
+    }
+
+    /// Returns the custom backend, if set
+    pub fn get_backend(&self) -> Option<&dyn BuildBackend> {
+        self.backend.as_deref()
+    }
+
+    /// Returns the store target directories are restored from and saved to, if set
+    pub fn get_cache_backend(&self) -> Option<&dyn CacheBackend> {
+        self.cache_backend.as_deref()
+    }
+
+    /// Returns the steps applied to the binaries of successful builds, if set
+    pub fn get_artifact_pipeline(&self) -> Option<&ArtifactPipeline> {
+        self.artifact_pipeline.as_ref()
+    }
+
+    /// Returns the custom profile to build with, if set
+    pub fn get_profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Returns the overridden settings of the active profile
+    pub fn get_profile_overrides(&self) -> &ProfileOverrides {
+        &self.profile_overrides
+    }
+
+    /// Returns the crate type the library is compiled as, if overridden
+    pub fn get_crate_type(&self) -> Option<CrateType> {
+        self.crate_type
+    }
+
+    /// Returns the settings of the generated headers, if enabled
+    pub fn get_cbindgen(&self) -> Option<&Cbindgen> {
+        self.cbindgen.as_ref()
+    }
+
+    /// Returns the environment variables set for the cargo process
+    pub fn get_envs(&self) -> &BTreeMap<String, String> {
+        &self.envs
+    }
+
+    /// Returns `true` if the cargo process only inherits an allowlist of environment variables
+    pub fn is_hermetic(&self) -> bool {
+        self.hermetic
+    }
+
+    /// Returns the additional environment variables inherited by hermetic builds
+    pub fn get_allowed_envs(&self) -> &[String] {
+        &self.allowed_envs
+    }
+
+    /// Returns the name of the profile the build uses: the custom profile if set, otherwise
+    /// `release` or `dev`
+    pub fn get_active_profile(&self) -> &str {
+        match &self.profile {
+            Some(profile) => profile,
+            None if self.project_settings.release => "release",
+            None => "dev",
+        }
+    }
+
+    /// Attaches an additional destination for the build output.
+    ///
+    /// Sinks receive the same output as the log file (see [`Builder::log_path`]), and any number
+    /// of them can be attached, e.g. a file, a [`MemorySink`] and a network logger. Sinks are
+    /// shared with builds started by [`Builder::spawn`] and are not part of the [`BuilderConfig`].
+    pub fn add_sink(&mut self, sink: impl OutputSink + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Attaches a callback run with the `cargo build` command right before it is started.
+    ///
+    /// The hook can add arguments or environment variables to the command, or run a code
+    /// generation step before compilation. Hooks run in the order they were attached, for every
+    /// build started with [`Builder::build`], [`Builder::build_workspace`], [`Builder::build_report`],
+    /// [`Builder::build_streaming`], [`Builder::build_with_progress`] and [`Builder::spawn`]. They
+    /// see the command before the backend prepares it, so environment variables set by a hook are
+    /// also forwarded into containers. [`Builder::render_command`] and [`Builder::fingerprint`]
+    /// run them too, so they describe the command that is built, and hooks may run without a build.
+    ///
+    /// Hooks are not part of the [`BuilderConfig`], and must not start builds with the same builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Callback receiving the command about to be started.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    /// use std::process::Command;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.add_before_build_hook(|command| {
+    ///         let _ = Command::new("protoc").args(["--rust_out=src/generated", "api.proto"]).status();
+    ///         command.env("BUILD_ID", "1234");
+    ///     });
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn add_before_build_hook(&mut self, hook: impl FnMut(&mut Command) + Send + 'static) {
+        self.hooks.push_before(Box::new(hook));
+    }
+
+    /// Attaches a callback run with the report of every finished build, successful or not.
+    ///
+    /// Hooks run in the order they were attached, after every build started with
+    /// [`Builder::build`], [`Builder::build_workspace`] or [`Builder::build_report`], e.g. to upload
+    /// the produced artifacts. Builds that could not be run (see [`Builder::build_report`]) do not
+    /// run hooks.
+    ///
+    /// Hooks are not part of the [`BuilderConfig`], and must not start builds with the same builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - Callback receiving the [`BuildReport`] of the finished build.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.add_after_build_hook(|report| {
+    ///         if report.success() {
+    ///             for executable in report.output.executables() {
+    ///                 println!("uploading {}", executable.display());
+    ///             }
+    ///         }
+    ///     });
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn add_after_build_hook(&mut self, hook: impl FnMut(&BuildReport) + Send + 'static) {
+        self.hooks.push_after(Box::new(hook));
+    }
+
+    /// Sets whether cargo colors its output (`--color always` or `--color never`).
+    ///
+    /// With [`ColorChoice::Auto`] (the default) no flag is passed and cargo decides on its own;
+    /// since its output is captured, it is usually not colored. Combine [`ColorChoice::Always`]
+    /// with [`Builder::strip_log_ansi`] to show colored output while keeping the log file readable.
+    pub fn set_color(&mut self, color: ColorChoice) {
+        self.color = color;
+    }
+
+    /// Tells the builder to use the `--verbose` flag when building
+    pub fn set_verbose(&mut self) {
+        self.verbosity = Verbosity::Verbose;
+    }
+
+    /// Sets how much cargo reports about the build.
+    ///
+    /// [`Verbosity::Quiet`] (`--quiet`) suppresses cargo's own status messages and its progress
+    /// bar, so [`Builder::build_with_progress`] reports no progress. [`Verbosity::VeryVerbose`]
+    /// (`-vv`) additionally shows the output of build scripts.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, Verbosity};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_verbosity(Verbosity::Quiet);
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_verbosity(&mut self, verbosity: Verbosity) {
+        self.verbosity = verbosity;
+    }
+
+    /// Adds a flag to the list of additional flags that will be passed to `rustc`
+    pub fn add_rustc_flag(&mut self, flag: String) {
+        self.additional_flags.push(flag);
+    }
+
+    /// Sets how the configured `rustc` flags are combined with the flags already present in the
+    /// environment (`CARGO_ENCODED_RUSTFLAGS` or `RUSTFLAGS`).
+    ///
+    /// The flags are passed to cargo through `CARGO_ENCODED_RUSTFLAGS`, so each flag is a single
+    /// argument and may contain spaces (e.g. `-Clink-arg=-Wl,-rpath,/opt/my libs`). By default,
+    /// the configured flags are appended to those of the environment. Note that, like
+    /// `RUSTFLAGS`, this takes precedence over `build.rustflags` in cargo's configuration files.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, RustFlagsPolicy};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?.rustc_flag("-Ctarget-cpu=native");
+    ///     builder.set_rustflags_policy(RustFlagsPolicy::Replace);
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_rustflags_policy(&mut self, policy: RustFlagsPolicy) {
+        self.rustflags_policy = policy;
+    }
+
+    /// Turns every warning of the built packages into an error, by passing `-D warnings` to `rustc`
+    /// along with the other flags (see [`Builder::set_rustflags_policy`]).
+    ///
+    /// Dependencies from registries and git are not affected, since cargo caps their lints. The
+    /// denied warnings are reported as errors; see [`BuildOutput::denied_warning_count`] and
+    /// [`Builder::build_report`], which reports the diagnostics of failed builds.
+    pub fn set_deny_warnings(&mut self) {
+        self.deny_warnings = true;
+    }
+
+    /// Makes cargo record how long every unit takes to compile (`--timings`).
+    ///
+    /// Cargo writes an HTML report to `cargo-timings` in the target directory, which is parsed
+    /// into [`BuildOutput::timings`] by [`Builder::build`], [`Builder::build_workspace`],
+    /// [`Builder::build_report`] and [`Builder::build_with_progress`]. The report is located
+    /// through cargo's output, or in the target directory for quiet builds.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_timings();
+    ///     let output = builder.build()?;
+    ///     if let Some(timings) = output.timings {
+    ///         for (name, duration) in timings.crate_durations().iter().take(10) {
+    ///             println!("{:>8.2}s {}", duration.as_secs_f64(), name);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_timings(&mut self) {
+        self.timings = true;
+    }
+
+    /// Makes cargo build every crate it can after one fails (`--keep-going`), instead of stopping
+    /// at the first failure, to see everything that is broken in a workspace in one build.
+    ///
+    /// The packages that failed and those that built are reported by
+    /// [`BuildReport::failed_packages`] and [`BuildReport::succeeded_packages`]; see
+    /// [`Builder::build_report`], which reports failed builds.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/workspace").workspace(true))?;
+    ///     builder.set_keep_going();
+    ///     let report = builder.build_report()?;
+    ///     for package in report.failed_packages() {
+    ///         println!("FAILED {}", package);
+    ///     }
+    ///     for package in report.succeeded_packages() {
+    ///         println!("ok     {}", package);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_keep_going(&mut self) {
+        self.keep_going = true;
+    }
+
+    /// Makes cargo report the packages that use code a future version of Rust will reject
+    /// (`--future-incompat-report`), and parses the report into [`BuildOutput::future_incompat`].
+    ///
+    /// Such lints are silenced in dependencies like every other lint, so the report is the only
+    /// warning that a dependency will stop compiling. Builds without future-incompatibility
+    /// warnings have no report. Quiet builds (see [`Verbosity::Quiet`]) only have a report if it
+    /// differs from the last one cargo recorded. See [`Builder::future_incompat_reports`] for the
+    /// reports of earlier builds.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_future_incompat_report();
+    ///     let output = builder.build()?;
+    ///     for package in output.future_incompat.iter().flat_map(|report| &report.packages) {
+    ///         println!("{} {} will break: {}", package.name, package.version, package.warnings.join("; "));
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_future_incompat_report(&mut self) {
+        self.future_incompat_report = true;
+    }
+
+    /// Reads the future-incompatibility reports cargo keeps in the target directory, as listed by
+    /// `cargo report future-incompatibilities`, oldest first.
+    ///
+    /// Cargo records a report for every build with future-incompatibility warnings, whether or
+    /// not [`Builder::set_future_incompat_report`] is set, and keeps the latest ones.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The target directory cannot be located with `cargo metadata` (see [`Builder::metadata`]).
+    /// - The reports cannot be read or parsed ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     if let Some(report) = builder.future_incompat_reports()?.last() {
+    ///         for package in &report.packages {
+    ///             println!("{}\n{}", package.name, package.rendered);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn future_incompat_reports(&self) -> Result<Vec<FutureIncompatReport>> {
+        FutureIncompatReport::read_all(self.metadata()?.target_directory)
+    }
+
+    /// Hashes the binaries and libraries of every successful build and writes a `checksums.json`
+    /// manifest next to them.
+    ///
+    /// The manifest lists the name, size, SHA-256 hash (and BLAKE3 hash with the `blake3`
+    /// feature), target and profile of every executable, shared library and static library,
+    /// see [`ChecksumManifest`]. It is written to the deepest directory containing them all,
+    /// after the [`ArtifactPipeline`] ran, and returned in [`BuildOutput::checksums`] by
+    /// [`Builder::build`], [`Builder::build_workspace`], [`Builder::build_report`] and
+    /// [`Builder::build_with_progress`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     builder.set_checksums();
+    ///     let output = builder.build()?;
+    ///     if let Some(checksums) = output.checksums {
+    ///         for artifact in &checksums.artifacts {
+    ///             println!("{}  {}", artifact.sha256, artifact.name);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_checksums(&mut self) {
+        self.checksums = true;
+    }
+
+    /// Skips builds whose inputs match an earlier successful build, without invoking cargo.
+    ///
+    /// Before every build, the inputs are hashed into a fingerprint (see [`Builder::fingerprint`]).
+    /// If a build with the same fingerprint succeeded before and the files it produced are
+    /// unchanged, [`Builder::build`], [`Builder::build_workspace`] and [`Builder::build_report`]
+    /// return the artifacts and diagnostics of that build, with [`BuildReport::cached`] set.
+    /// Successful builds are recorded in `cargo-wrap-cache/` in the target directory.
+    ///
+    /// Cached outputs carry no timings, pipeline reports, headers, checksums, installed files or
+    /// sizes, since neither cargo nor the [`ArtifactPipeline`] ran. Build hooks run as usual.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     builder.set_build_cache();
+    ///     let report = builder.build_report()?;
+    ///     if report.cached {
+    ///         println!("Up to date, skipped cargo");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_build_cache(&mut self) {
+        self.build_cache = true;
+    }
+
+    /// Builds every configuration into its own target directory, so that alternating between
+    /// configurations does not make cargo rebuild the crates whose settings changed every time.
+    ///
+    /// The directory is `config-<hash>` in the usual target directory, the hash covering the
+    /// compilation target, the enabled features (including `--no-default-features` and
+    /// `--all-features`) and the active profile (see [`Builder::get_active_profile`]). It is
+    /// passed to cargo as `CARGO_TARGET_DIR`, and is where [`Builder::build`] and every other
+    /// command look for their outputs. Configurations differing only in other settings, such as
+    /// `rustc` flags, share a directory.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     for features in [vec![], vec!["tls"]] {
+    ///         let settings = ProjectSettings::new("/path/to/project").features(features);
+    ///         let mut builder = Builder::new(settings)?;
+    ///         builder.set_isolated_target_dir();
+    ///         // target/config-<hash>/debug/app, rebuilt only when the sources change
+    ///         println!("{:?}", builder.build()?.binaries());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_isolated_target_dir(&mut self) {
+        self.isolated_target_dir = true;
+    }
+
+    /// Copies the binaries and libraries of every successful build into the flat directory `dir`.
+    ///
+    /// Unlike [`ProjectSettings::output_path`], which moves the whole target directory, only the
+    /// final executables, shared libraries and static libraries are copied (see
+    /// [`BuildOutput::binaries`]), after the [`ArtifactPipeline`] ran and the checksums were
+    /// written. The directory is created if needed and existing files are replaced. The copies
+    /// are returned in [`BuildOutput::installed`] by [`Builder::build`],
+    /// [`Builder::build_workspace`], [`Builder::build_report`] and [`Builder::build_with_progress`].
+    ///
+    /// Files are installed under their own name unless a template is set with
+    /// [`Builder::set_install_template`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     builder.set_install_dir("dist");
+    ///     for file in builder.build()?.installed {
+    ///         println!("Installed {}", file.display());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_install_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.install_dir = Some(dir.into());
+    }
+
+    /// Sets the name the binaries are installed under by [`Builder::set_install_dir`].
+    ///
+    /// The following placeholders are replaced for every file:
+    ///
+    /// * `{file}` - The file name cargo produced, e.g. `libapp.so` or `app.exe`. The default template.
+    /// * `{name}` - The name of the target, e.g. `app`.
+    /// * `{package}` and `{version}` - The name and version of the package.
+    /// * `{target}` - The compilation target triple, or the host triple.
+    /// * `{profile}` - The active profile, see [`Builder::get_active_profile`].
+    /// * `{ext}` - The extension of the file including the dot, e.g. `.exe`, or nothing.
+    ///
+    /// Builds fail with [`CargoWrapError::InvalidSettings`] before copying anything if two files
+    /// would be installed under the same name, e.g. a `cdylib` and `staticlib` with `{name}` but
+    /// without `{ext}`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").compilation_target("x86_64-pc-windows-gnu");
+    ///     let mut builder = Builder::new(settings)?.install_to("dist");
+    ///     builder.set_install_template("{name}-{version}-{target}{ext}");
+    ///     // dist/app-0.1.0-x86_64-pc-windows-gnu.exe
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_install_template(&mut self, template: impl Into<String>) {
+        self.install_template = Some(template.into());
+    }
+
+    /// Records the sizes of the binaries and libraries of every successful build in a history
+    /// file, to track size as a metric and catch regressions with [`Builder::compare_with_previous`].
+    ///
+    /// The size of every executable, shared library and static library is measured after the
+    /// [`ArtifactPipeline`] ran, along with the sizes of its sections when `size`, `llvm-size` or
+    /// `objdump` is installed (see [`ArtifactSize`]). The measurements are appended to the history
+    /// (see [`SizeHistory`]) and returned in [`BuildOutput::sizes`] by [`Builder::build`],
+    /// [`Builder::build_workspace`], [`Builder::build_report`] and [`Builder::build_with_progress`].
+    ///
+    /// # Arguments
+    ///
+    /// * `history_path` - The JSON file the history is stored in, created by the first build.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     builder.set_size_tracking("sizes.json");
+    ///     let output = builder.build()?;
+    ///     if let Some(sizes) = output.sizes {
+    ///         println!("{} bytes in total", sizes.total());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_size_tracking(&mut self, history_path: impl Into<PathBuf>) {
+        self.size_history = Some(history_path.into());
+    }
+
+    /// Compares the artifact sizes of the last build with those of the build before it, as
+    /// recorded by [`Builder::set_size_tracking`] for the same compilation target and profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The growth, in percent of the previous size, beyond which an artifact is
+    ///   reported as a regression, e.g. `5.0`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(SizeComparison)` - The change of every artifact. If only one build was recorded, it
+    ///   is compared with nothing.
+    /// * `Err(CargoWrapError)` - If no size was recorded, or the history cannot be read.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Size tracking is not enabled, or no build for the target and profile was recorded
+    ///   ([`CargoWrapError::InvalidSettings`]).
+    /// - The history file cannot be read or parsed ([`CargoWrapError::Io`], [`CargoWrapError::InvalidSettings`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?.size_tracking("sizes.json");
+    ///     builder.build()?;
+    ///     let comparison = builder.compare_with_previous(5.0)?;
+    ///     print!("{}", comparison);
+    ///     if comparison.has_regressions() {
+    ///         std::process::exit(1);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn compare_with_previous(&self, threshold: f64) -> Result<SizeComparison> {
+        let history_path = self.size_history.as_ref().ok_or_else(|| CargoWrapError::InvalidSettings("size tracking is not enabled".to_string()))?;
+        let target = self.project_settings.compilation_target.as_deref();
+        SizeHistory::open(history_path)?
+            .compare_latest(target, self.get_active_profile(), threshold)
+            .ok_or_else(|| CargoWrapError::InvalidSettings(format!("no size is recorded in {}", history_path.display())))
+    }
+
+    /// Builds the project and attributes the size of the code of the binary to its functions and
+    /// crates, to find out which dependencies bloat it, see [`BloatReport`].
+    ///
+    /// The analyzed binary is the first executable or shared library of the build, so the
+    /// target should be selected with [`ProjectSettings::target`] if the project has several.
+    /// The symbol table is read directly from the binary, no external tool is needed.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BloatReport)` - The size of every function and crate, largest first.
+    /// * `Err(CargoWrapError)` - If the build fails or the binary cannot be analyzed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The build fails ([`CargoWrapError::CompilationFailed`]).
+    /// - The build produced no executable or shared library ([`CargoWrapError::InvalidSettings`]).
+    /// - The binary cannot be parsed or was stripped of its symbol table, e.g. by `strip = true`
+    ///   in the profile or a [`Strip`] step ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     let report = builder.bloat()?;
+    ///     print!("{}", report);
+    ///     if let Some(regex) = report.get_crate("regex") {
+    ///         println!("regex accounts for {} bytes", regex.size);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "bloat")]
+    pub fn bloat(&self) -> Result<BloatReport> {
+        let output = self.build()?;
+        let binary = output
+            .binaries()
+            .into_iter()
+            .find(|binary| !binary.extension().is_some_and(|extension| extension == "a" || extension == "lib"))
+            .ok_or_else(|| CargoWrapError::InvalidSettings("the build produced no executable or shared library".to_string()))?;
+        BloatReport::analyze(binary)
+    }
+
+    /// Turns incremental compilation on or off for the cargo process (`CARGO_INCREMENTAL=1/0`).
+    ///
+    /// This overrides the `incremental` setting of every profile, without touching the
+    /// environment of the current process. Release pipelines usually turn it off for
+    /// reproducible builds, while development loops benefit from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `incremental` - `true` to compile incrementally, `false` otherwise.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     builder.set_incremental(false);
+    ///     assert!(builder.render_command()?.to_string().contains("CARGO_INCREMENTAL=0"));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_incremental(&mut self, incremental: bool) {
+        self.incremental = Some(incremental);
+    }
+
+    /// Sets a program `rustc` is invoked through (`RUSTC_WRAPPER`), e.g. a compilation cache
+    /// such as `sccache`.
+    ///
+    /// Cargo runs the wrapper with the path of `rustc` as its first argument, followed by the
+    /// arguments for `rustc`. The wrapper is only set for the cargo process, so caches can be
+    /// enabled per build.
+    ///
+    /// # Arguments
+    ///
+    /// * `wrapper` - Path or name of the wrapper program.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_rustc_wrapper("/usr/local/bin/sccache");
+    ///     assert!(builder.render_command()?.to_string().contains("RUSTC_WRAPPER=/usr/local/bin/sccache"));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_rustc_wrapper(&mut self, wrapper: impl Into<PathBuf>) {
+        self.rustc_wrapper = Some(wrapper.into());
+    }
+
+    /// Compiles through `sccache`, located in `PATH`, `$CARGO_HOME/bin` or `~/.cargo/bin`.
+    ///
+    /// See [`Builder::set_rustc_wrapper`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::ToolNotFound`] if `sccache` cannot be found.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.use_sccache()?;
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn use_sccache(&mut self) -> Result<()> {
+        let sccache = Builder::find_binary("sccache").ok_or_else(|| CargoWrapError::ToolNotFound("sccache".to_string()))?;
+        self.set_rustc_wrapper(sccache);
+        Ok(())
+    }
+
+    /// Sets how stdin/stdout/stderr are handled for programs started with `cargo run`
+    pub fn set_run_stdio(&mut self, run_stdio: RunStdio) {
+        self.run_stdio = run_stdio;
+    }
+
+    /// Sets a time limit for builds.
+    ///
+    /// A build, check or test run that exceeds the limit is killed (along with every process it
+    /// started) and reported as [`CargoWrapError::TimedOut`] with the output captured so far.
+    /// The limit does not apply to [`Builder::run`] or to builds started with [`Builder::spawn`].
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = Some(timeout);
+    }
+
+    /// Retries builds that failed because of a transient error, such as a registry timeout.
+    ///
+    /// Builds, checks and test runs whose stderr matches one of the policy's patterns are run
+    /// again after the policy's backoff, until they succeed or the maximum number of attempts is
+    /// reached. Other failures are reported right away. The output of every attempt is logged.
+    /// Streamed builds ([`Builder::build_streaming`], [`Builder::build_with_progress`]) and
+    /// [`Builder::run`] are not retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The [`RetryPolicy`] to apply.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, RetryPolicy};
+    /// use std::time::Duration;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_retry_policy(RetryPolicy::new(5).backoff(Duration::from_secs(3)));
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = Some(policy);
+    }
+
+    /// Checks the resolved dependency graph for packages present at several versions before
+    /// every build.
+    ///
+    /// The graph is read with [`Builder::metadata`] before cargo compiles anything. Duplicates
+    /// that the policy does not allow are reported in [`BuildOutput::duplicates`], or fail the
+    /// build with [`CargoWrapError::DuplicateDependencies`] if the policy denies them. Streamed
+    /// builds ([`Builder::build_streaming`]) are not checked.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The [`DuplicatePolicy`] to apply.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, CargoWrapError, DuplicatePolicy, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_duplicate_policy(DuplicatePolicy::deny().allow("windows-sys"));
+    ///     match builder.build() {
+    ///         Err(CargoWrapError::DuplicateDependencies(duplicates)) => {
+    ///             for duplicate in duplicates {
+    ///                 eprintln!("{} is resolved at {}", duplicate.name, duplicate.versions.join(", "));
+    ///             }
+    ///         }
+    ///         result => {
+    ///             result?;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_duplicate_policy(&mut self, policy: DuplicatePolicy) {
+        self.duplicate_policy = Some(policy);
+    }
+
+    /// Probes the system tools and libraries the build needs before cargo runs, failing with a
+    /// list of everything that is missing instead of a build script error.
+    ///
+    /// The prerequisites are probed with the environment variables set with [`Builder::set_env`]
+    /// (e.g. `PKG_CONFIG_PATH`) by [`Builder::build`], [`Builder::build_workspace`],
+    /// [`Builder::build_report`] and [`Builder::build_with_progress`], and by
+    /// [`Builder::check_prerequisites`]. Cached builds ([`Builder::set_build_cache`]) are not probed.
+    ///
+    /// # Arguments
+    ///
+    /// * `prerequisites` - The [`Prerequisites`] of the build.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, CargoWrapError, Prerequisites, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_prerequisites(Prerequisites::new().binary_version("cmake", "3.20").pkg_config("libudev"));
+    ///     match builder.build() {
+    ///         Err(CargoWrapError::MissingPrerequisites(missing)) => {
+    ///             for prerequisite in missing {
+    ///                 eprintln!("missing: {}", prerequisite);
+    ///             }
+    ///         }
+    ///         result => {
+    ///             result?;
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_prerequisites(&mut self, prerequisites: Prerequisites) {
+        self.prerequisites = Some(prerequisites);
+    }
+
+    /// Probes the prerequisites set with [`Builder::set_prerequisites`], without building.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If no prerequisites are set or every prerequisite is available.
+    /// * `Err(CargoWrapError::MissingPrerequisites)` - Listing every missing prerequisite.
+    pub fn check_prerequisites(&self) -> Result<()> {
+        let Some(prerequisites) = &self.prerequisites else {
+            return Ok(());
+        };
+        let missing = prerequisites.missing(&self.envs);
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(CargoWrapError::MissingPrerequisites(missing))
+    }
+
+    /// Sets the rustup toolchain to build with (e.g. `nightly` or `nightly-2024-05-01`).
+    ///
+    /// When a toolchain is set, the default backend invokes cargo as `rustup run <toolchain> cargo`,
+    /// so `rustup` must be available in `PATH`. The configured cargo path is not used in that case, since it
+    /// usually points into a specific toolchain. Use [`Builder::is_toolchain_installed`] to check
+    /// whether the toolchain is available before building.
+    pub fn set_toolchain(&mut self, toolchain: impl Into<String>) {
+        self.toolchain = Some(toolchain.into());
+    }
+
+    /// Instruments the project with a runtime sanitizer (`-Z sanitizer=X`), for builds, runs and tests.
+    ///
+    /// Sanitizers are unstable, so the `nightly` toolchain is selected unless a toolchain is
+    /// already set. The instrumentation must not apply to build scripts and procedural macros,
+    /// so cargo always gets a `--target`: the compilation target if set, otherwise the host of
+    /// the toolchain, and commands fail if it cannot be detected. ThreadSanitizer and
+    /// MemorySanitizer also rebuild the standard library (`-Z build-std`), which needs the
+    /// `rust-src` component. MemorySanitizer reports false positives for C dependencies that are
+    /// not instrumented as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `sanitizer` - The [`Sanitizer`] to instrument the project with.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, Sanitizer, TestOptions};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_sanitizer(Sanitizer::Address);
+    ///     let summary = builder.test(&TestOptions::default())?;
+    ///     println!("{} passed, {} failed", summary.passed, summary.failed);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_sanitizer(&mut self, sanitizer: Sanitizer) {
+        self.sanitizer = Some(sanitizer);
+        if self.toolchain.is_none() {
+            self.toolchain = Some("nightly".to_string());
+        }
+    }
+
+    /// Rebuilds standard library crates from source (`-Z build-std`), e.g. `core` and `alloc`
+    /// for `no_std` targets without a prebuilt standard library.
+    ///
+    /// `-Z build-std` is unstable, so the `nightly` toolchain is selected unless a toolchain is
+    /// already set. The crates must not be rebuilt for build scripts and procedural macros, so
+    /// cargo always gets a `--target`: the compilation target if set, otherwise the host of the
+    /// toolchain. The toolchain needs the `rust-src` component: builds fail with
+    /// [`CargoWrapError::MissingComponent`] without it, use [`Builder::ensure_rust_src`] to
+    /// install it before building.
+    ///
+    /// # Arguments
+    ///
+    /// * `build_std` - The [`BuildStd`] crates and features to rebuild.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, BuildStd, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/firmware").compilation_target("thumbv7em-none-eabihf");
+    ///     let mut builder = Builder::new(settings)?;
+    ///     builder.set_build_std(BuildStd::new(["core", "alloc"]).feature("compiler-builtins-mem"));
+    ///     builder.ensure_rust_src(true)?;
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_build_std(&mut self, build_std: BuildStd) {
+        self.build_std = Some(build_std);
+        if self.toolchain.is_none() {
+            self.toolchain = Some("nightly".to_string());
+        }
+    }
+
+    /// Sets the profile to build with (`--profile X`), e.g. a custom `release-lto` profile
+    /// declared in the project's `Cargo.toml`.
+    ///
+    /// The profile takes precedence over [`ProjectSettings::release`]: when a profile is set,
+    /// `--release` is not passed. Artifacts of custom profiles are placed in a directory named
+    /// after the profile, e.g. `target/release-lto`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_profile("release-lto");
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_profile(&mut self, profile: impl Into<String>) {
+        self.profile = Some(profile.into());
+    }
+
+    /// Overrides settings of the active profile (see [`Builder::get_active_profile`]) for this
+    /// build only.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProfileOverrides, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     builder.set_profile_overrides(ProfileOverrides {
+    ///         lto: Some("thin".to_string()),
+    ///         codegen_units: Some(1),
+    ///         ..Default::default()
+    ///     });
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_profile_overrides(&mut self, overrides: ProfileOverrides) {
+        self.profile_overrides = overrides;
+    }
+
+    /// Compiles the library as `crate_type`, regardless of the `crate-type` of its manifest,
+    /// e.g. as a `cdylib` plugin when the manifest only declares an `rlib`.
+    ///
+    /// Builds then run `cargo rustc --crate-type X` instead of `cargo build`, which is stable and
+    /// only affects the library itself: dependencies keep their crate types and the manifest is
+    /// left untouched. The library is selected with `--lib` unless [`ProjectSettings::lib`] or an
+    /// example target is set. As `cargo rustc` builds a single package, the override cannot be
+    /// combined with [`Builder::build_workspace`] or several packages.
+    ///
+    /// # Arguments
+    ///
+    /// * `crate_type` - The [`CrateType`] to compile the library as.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, CrateType, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/plugin"))?;
+    ///     builder.set_crate_type(CrateType::Cdylib);
+    ///     let output = builder.build()?;
+    ///     for artifact in &output.artifacts {
+    ///         println!("{:?}: {:?}", artifact.kind, artifact.filenames);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_crate_type(&mut self, crate_type: CrateType) {
+        self.crate_type = Some(crate_type);
+    }
+
+    /// Generates a header with [`cbindgen`](https://github.com/mozilla/cbindgen) for every
+    /// `cdylib` and `staticlib` library of successful builds, so FFI consumers get the library and
+    /// its header from one call.
+    ///
+    /// `cbindgen` runs as a subprocess in the directory of the library's crate, before the
+    /// [`ArtifactPipeline`]. By default the header is written next to the library, e.g.
+    /// `target/release/my_lib.h`; its path is returned in [`BuildOutput::headers`]. Combine with
+    /// [`Builder::set_crate_type`] to build a C library from a crate whose manifest only declares an `rlib`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cbindgen` - The [`Cbindgen`] settings of the headers.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, Cbindgen, HeaderLanguage, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/ffi-crate").release(true))?;
+    ///     builder.set_cbindgen(Cbindgen {
+    ///         language: HeaderLanguage::C,
+    ///         include_guard: Some("MY_LIB_H".to_string()),
+    ///         output: Some("include/my_lib.h".into()),
+    ///         ..Default::default()
+    ///     });
+    ///     let output = builder.build()?;
+    ///     println!("{:?}", output.headers);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_cbindgen(&mut self, cbindgen: Cbindgen) {
+        self.cbindgen = Some(cbindgen);
+    }
+
+    /// Sets an environment variable for the cargo process (e.g. `PKG_CONFIG_PATH` or `OPENSSL_DIR`),
+    /// without modifying the environment of the current process.
+    ///
+    /// Variables derived from the settings, such as `CARGO_TARGET_DIR`, take precedence over
+    /// variables set here. `RUSTFLAGS` and `CARGO_ENCODED_RUSTFLAGS` set here are merged with the
+    /// configured `rustc` flags according to the [`RustFlagsPolicy`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_env("OPENSSL_DIR", "/opt/openssl");
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_env(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.envs.insert(key.into(), value.into());
+    }
+
+    /// Makes builds hermetic: the cargo process no longer inherits the environment of the current
+    /// process, except for `PATH`, `HOME`, `CARGO_HOME`, `RUSTUP_HOME` and the variables allowed
+    /// with [`Builder::allow_env`].
+    ///
+    /// Variables set with [`Builder::set_env`] and those derived from the settings are still
+    /// passed, and `RUSTFLAGS` from the current process is ignored unless it is allowed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?.allow_env("SSH_AUTH_SOCK");
+    ///     builder.set_hermetic();
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_hermetic(&mut self) {
+        self.hermetic = true;
+    }
+
+    /// Configures the builder so that building the same sources produces the same artifacts,
+    /// regardless of where and when they are built.
+    ///
+    /// The preset:
+    /// - Remaps the absolute paths embedded in the artifacts (`--remap-path-prefix`): the project
+    ///   directory to `.`, the target directory to `/target` and `CARGO_HOME`, where the sources
+    ///   of dependencies live, to `/cargo`.
+    /// - Sets `SOURCE_DATE_EPOCH`, read by build scripts embedding timestamps, to the value of
+    ///   the current process, or the time of the last git commit of the project, or `0`.
+    /// - Disables incremental compilation (`CARGO_INCREMENTAL=0`).
+    /// - Makes builds hermetic (see [`Builder::set_hermetic`]) and requires an up-to-date
+    ///   `Cargo.lock` (`--locked`).
+    ///
+    /// Use [`Builder::verify_reproducible`] to check that the project builds reproducibly.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     builder.set_reproducible();
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_reproducible(&mut self) {
+        let absolute = |path: PathBuf| std::path::absolute(&path).unwrap_or(path);
+        let cargo_home = self
+            .env_var("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(|home| PathBuf::from(home).join(".cargo")));
+        // When several prefixes match, rustc applies the last one, so the target directory comes after the project
+        let prefixes = [
+            (cargo_home, "/cargo"),
+            (Some(self.project_settings.project_path.clone()), "."),
+            (Some(self.target_dir()), "/target"),
+        ];
+        for (path, replacement) in prefixes {
+            if let Some(path) = path {
+                self.additional_flags.push(format!("--remap-path-prefix={}={}", absolute(path).display(), replacement));
+            }
+        }
+        let epoch = env::var("SOURCE_DATE_EPOCH").ok().or_else(|| {
+            let output = Command::new("git")
+                .args(["log", "-1", "--format=%ct"])
+                .current_dir(&self.project_settings.project_path)
+                .output()
+                .ok()?;
+            let epoch = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            (output.status.success() && !epoch.is_empty()).then_some(epoch)
+        });
+        self.envs.insert("SOURCE_DATE_EPOCH".to_string(), epoch.unwrap_or_else(|| "0".to_string()));
+        self.incremental = Some(false);
+        self.hermetic = true;
+        self.project_settings.locked = true;
+    }
+
+    /// Private function that looks up an environment variable as the cargo process sees it,
+    /// before the settings are applied
+    fn env_var(&self, key: &str) -> Option<String> {
+        if let Some(value) = self.envs.get(key) {
+            return Some(value.clone());
+        }
+        let inherited = !self.hermetic || HERMETIC_ENVS.contains(&key) || self.allowed_envs.iter().any(|k| k == key);
+        inherited.then(|| env::var(key).ok()).flatten()
+    }
+
+    /// Checks whether the configured toolchain is installed.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - If no toolchain is configured or the configured toolchain is installed.
+    /// * `Ok(false)` - If the configured toolchain is not installed.
+    /// * `Err(CargoWrapError)` - If `rustup` cannot be run.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.toolchain("nightly-2024-05-01");
+    ///     if builder.is_toolchain_installed()? {
+    ///         builder.build()?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn is_toolchain_installed(&self) -> Result<bool> {
+        match &self.toolchain {
+            Some(toolchain) => Rustup::new().is_installed(toolchain),
+            None => Ok(true),
+        }
+    }
+
+    /// Detects the versions of `cargo` and `rustc` the builder builds with: those of the
+    /// configured toolchain if set, otherwise those of the builder's `cargo` binary.
+    ///
+    /// # Errors
+    ///
+    /// See [`ToolchainInfo::detect`].
+    pub fn toolchain_info(&self) -> Result<ToolchainInfo> {
+        match &self.toolchain {
+            Some(toolchain) => ToolchainInfo::detect_toolchain(toolchain),
+            None => ToolchainInfo::detect_with(&self.cargo_path),
+        }
+    }
+
+    /// Returns the minimum supported Rust version of the package (`package.rust-version`).
+    ///
+    /// If the version is inherited from the workspace (`rust-version.workspace = true`), it is
+    /// resolved with [`Builder::metadata`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(String))` - The minimum supported Rust version, e.g. `1.70`.
+    /// * `Ok(None)` - If the manifest does not declare one, or is a virtual manifest.
+    /// * `Err(CargoWrapError)` - If the manifest cannot be read or the metadata cannot be resolved.
+    pub fn get_rust_version(&self) -> Result<Option<String>> {
+        let Some(package) = self.project_settings.manifest()?.get_package() else { return Ok(None) };
+        if package.inherited.iter().any(|key| key == "rust-version") {
+            return Ok(self.metadata()?.root_package().and_then(|package| package.rust_version.clone()));
+        }
+        Ok(package.rust_version)
+    }
+
+    /// Verifies that the project compiles with its minimum supported Rust version, by running
+    /// [`Builder::check`] with the toolchain of that exact version (e.g. `rustup run 1.70 cargo check`).
+    ///
+    /// Every other setting of the builder, including its backend, is kept. The toolchain must
+    /// be installed, e.g. with [`Rustup::install_toolchain`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The verified minimum supported Rust version.
+    /// * `Err(CargoWrapError)` - If the project does not compile with it, or it cannot be checked.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The package does not declare a `rust-version`, or its toolchain is not installed
+    ///   ([`CargoWrapError::InvalidSettings`]).
+    /// - The project does not compile with the toolchain ([`CargoWrapError::MsrvFailed`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, CargoWrapError, ProjectSettings, Rustup};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     if let Some(rust_version) = builder.get_rust_version()? {
+    ///         Rustup::new().install_toolchain(&rust_version)?;
+    ///     }
+    ///     match builder.verify_msrv() {
+    ///         Ok(rust_version) => println!("compiles with Rust {}", rust_version),
+    ///         Err(CargoWrapError::MsrvFailed { rust_version, stderr }) => eprintln!("broken on Rust {}:\n{}", rust_version, stderr),
+    ///         Err(e) => return Err(e),
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn verify_msrv(&self) -> Result<String> {
+        let rust_version = self
+            .get_rust_version()?
+            .ok_or_else(|| CargoWrapError::InvalidSettings("the package does not declare a rust-version".to_string()))?;
+        if !Rustup::new().is_installed(&rust_version)? {
+            return Err(CargoWrapError::InvalidSettings(format!("the {} toolchain is not installed", rust_version)));
+        }
+        let mut config = self.config();
+        config.toolchain = Some(rust_version.clone());
+        let mut builder = Builder::from_config(config)?;
+        builder.backend = self.backend.clone();
+        builder.sinks = self.sinks.clone();
+        match builder.check() {
+            Err(CargoWrapError::CompilationFailed { stderr, .. }) => Err(CargoWrapError::MsrvFailed { rust_version, stderr }),
+            result => result.map(|_| rust_version),
+        }
+    }
+
+    /// Builds the project twice from scratch and compares the checksums of the artifacts, to
+    /// check that the configured build is reproducible (see [`Builder::set_reproducible`]).
+    ///
+    /// The builds go to `reproducible/first` and `reproducible/second` in the target directory,
+    /// which are removed first. Both target directories are remapped to `/target` so that they do
+    /// not leak into the artifacts. The builder's [`ArtifactPipeline`] is not applied.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ReproducibilityReport)` - The checksums of the artifacts of both builds.
+    /// * `Err(CargoWrapError)` - If a build fails or the artifacts cannot be hashed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - A build fails ([`CargoWrapError::CompilationFailed`]).
+    /// - The target directories cannot be cleared or an artifact cannot be read ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?.reproducible();
+    ///     let report = builder.verify_reproducible()?;
+    ///     for name in report.mismatches() {
+    ///         eprintln!("{} is not reproducible", name);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn verify_reproducible(&self) -> Result<ReproducibilityReport> {
+        let base = std::path::absolute(self.target_dir().join("reproducible"))?;
+        let dirs = [base.join("first"), base.join("second")];
+        let build = |dir: &PathBuf| -> Result<ChecksumManifest> {
+            if dir.exists() {
+                fs::remove_dir_all(dir)?;
+            }
+            let mut config = self.config();
+            config.project_settings.output_path = Some(dir.clone());
+            config.rustc_flags.extend(dirs.iter().map(|dir| format!("--remap-path-prefix={}=/target", dir.display())));
+            let mut builder = Builder::from_config(config)?;
+            builder.backend = self.backend.clone();
+            builder.sinks = self.sinks.clone();
+            let output = builder.build()?;
+            ChecksumManifest::compute(&output, self.project_settings.compilation_target.as_deref(), self.get_active_profile())
+        };
+        Ok(ReproducibilityReport { first: build(&dirs[0])?, second: build(&dirs[1])? })
+    }
+
+    /// Makes sure the configured compilation target is installed for the active toolchain.
+    ///
+    /// This is an opt-in step meant to be called before building, so that a missing target
+    /// surfaces as a typed error instead of a confusing `rustc` failure. Custom target
+    /// specifications (paths to `.json` files) are not checked, and glibc version suffixes used by
+    /// [`ZigbuildBackend`] (e.g. `x86_64-unknown-linux-gnu.2.17`) are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `install` - If `true`, a missing target is installed with `rustup target add`.
+    ///   Otherwise it is reported as [`CargoWrapError::MissingTarget`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If no compilation target is set, or the target is (now) installed.
+    /// * `Err(CargoWrapError)` - If the target is missing or could not be installed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `rustup` cannot be run ([`CargoWrapError::Io`]).
+    /// - The target is missing and `install` is `false` ([`CargoWrapError::MissingTarget`]).
+    /// - `rustup target add` fails ([`CargoWrapError::ToolFailed`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").compilation_target("aarch64-unknown-linux-musl");
+    ///     let builder = Builder::new(settings)?;
+    ///     builder.ensure_target_installed(true)?;
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ensure_target_installed(&self, install: bool) -> Result<()> {
+        let Some(target) = &self.project_settings.compilation_target else {
+            return Ok(());
+        };
+        if target.ends_with(".json") {
+            return Ok(());
+        }
+        let target = backend::base_target_triple(target);
+        let rustup = Rustup::new();
+        let toolchain = self.toolchain.as_deref();
+        if rustup.installed_targets(toolchain)?.iter().any(|t| t == target) {
+            return Ok(());
+        }
+        if !install {
+            return Err(CargoWrapError::MissingTarget {
+                target: target.to_string(),
+                toolchain: self.toolchain.clone(),
+            });
+        }
+        rustup.add_target(target, toolchain)
+    }
+
+    /// Checks that the toolchain has the `rust-src` component needed to rebuild the standard
+    /// library, see [`Builder::set_build_std`]. Sanitizers that rebuild the standard library
+    /// ([`Sanitizer::requires_build_std`]) need it as well.
+    ///
+    /// Builds run this check without installing the component, so a missing `rust-src` is
+    /// reported before cargo starts.
+    ///
+    /// # Arguments
+    ///
+    /// * `install` - If `true`, a missing component is installed with `rustup component add`.
+    ///   Otherwise it is reported as [`CargoWrapError::MissingComponent`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the standard library is not rebuilt, or `rust-src` is (now) installed.
+    /// * `Err(CargoWrapError)` - If the component is missing or could not be installed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `rustup` cannot be run ([`CargoWrapError::Io`]).
+    /// - The component is missing and `install` is `false` ([`CargoWrapError::MissingComponent`]).
+    /// - `rustup component add` fails ([`CargoWrapError::ToolFailed`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, BuildStd, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.build_std(BuildStd::new(["core"]));
+    ///     builder.ensure_rust_src(false)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ensure_rust_src(&self, install: bool) -> Result<()> {
+        if self.build_std.is_none() && !self.sanitizer.is_some_and(Sanitizer::requires_build_std) {
+            return Ok(());
+        }
+        let rustup = Rustup::new();
+        let toolchain = self.toolchain.as_deref();
+        if rustup.installed_components(toolchain)?.iter().any(|c| c == "rust-src") {
+            return Ok(());
+        }
+        if !install {
+            return Err(CargoWrapError::MissingComponent {
+                component: "rust-src".to_string(),
+                toolchain: self.toolchain.clone(),
+            });
+        }
+        rustup.add_component("rust-src", toolchain)
+    }
+
+    /// Sets the backend that runs cargo subcommands.
+    ///
+    /// By default subcommands are run with `cargo` ([`CargoBackend`]). Other backends, such as
+    /// [`CrossBackend`], receive the same flags and environment derived from the settings.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, CrossBackend, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").compilation_target("aarch64-unknown-linux-musl");
+    ///     let mut builder = Builder::new(settings)?;
+    ///     builder.set_backend(CrossBackend::new());
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_backend(&mut self, backend: impl BuildBackend + 'static) {
+        self.backend = Some(Arc::new(backend));
+    }
+
+    /// Sets the store target directories are restored from and saved to, with
+    /// [`Builder::restore_target_dir`] and [`Builder::save_target_dir`].
+    ///
+    /// [`FilesystemCache`] keeps archives in a directory, [`HttpCache`] on an HTTP server or in
+    /// an S3 bucket. Other stores can be used by implementing [`CacheBackend`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, FilesystemCache, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.set_cache_backend(FilesystemCache::new("/mnt/ci-cache"));
+    ///     builder.restore_target_dir()?;
+    ///     builder.build()?;
+    ///     builder.save_target_dir()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_cache_backend(&mut self, cache_backend: impl CacheBackend + 'static) {
+        self.cache_backend = Some(Arc::new(cache_backend));
+    }
+
+    /// Sets the steps applied to the binaries and shared libraries of successful builds.
+    ///
+    /// The pipeline runs after [`Builder::build`], [`Builder::build_workspace`],
+    /// [`Builder::build_report`] and [`Builder::build_with_progress`], before the hooks attached
+    /// with [`Builder::add_after_build_hook`]. Artifacts are processed in place, with the target
+    /// and profile of the build, and what every step did is recorded in [`BuildOutput::pipeline`].
+    /// The pipeline is not part of the [`BuilderConfig`].
+    ///
+    /// # Arguments
+    ///
+    /// * `artifact_pipeline` - The steps to apply.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{ArtifactPipeline, Builder, ProjectSettings, Strip};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").compilation_target("aarch64-unknown-linux-gnu");
+    ///     let mut builder = Builder::new(settings)?;
+    ///     builder.set_artifact_pipeline(ArtifactPipeline::new().step(Strip::new()));
+    ///     builder.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_artifact_pipeline(&mut self, artifact_pipeline: ArtifactPipeline) {
+        self.artifact_pipeline = Some(artifact_pipeline);
+    }
+
+    /// Applies the settings of a firmware build for a bare-metal target, so a single
+    /// [`Builder::build`] goes from source to flashable images.
+    ///
+    /// The compilation target is replaced by the target of the preset, its linker scripts are
+    /// added to the `rustc` flags and default features are disabled if requested. For every
+    /// image format, an [`ObjCopy`] step is appended to the artifact pipeline (created if none is
+    /// set), so the images are listed in the [`StepOutcome::produced`] of [`BuildOutput::pipeline`].
+    /// The preset itself is not stored: later changes to these settings override it.
+    ///
+    /// # Arguments
+    ///
+    /// * `preset` - The [`EmbeddedPreset`] to apply.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, EmbeddedPreset, ImageFormat, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut builder = Builder::new(ProjectSettings::new("/path/to/firmware").release(true))?;
+    ///     builder.set_embedded_preset(EmbeddedPreset::cortex_m4f().image(ImageFormat::Bin).image(ImageFormat::Hex));
+    ///     for report in builder.build()?.pipeline {
+    ///         println!("{:?}", report.outcome.produced);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_embedded_preset(&mut self, preset: EmbeddedPreset) {
+        self.project_settings.compilation_target = Some(preset.target.clone());
+        self.additional_flags.extend(preset.rustc_flags());
+        if preset.no_default_features {
+            self.project_settings.no_default_features = true;
+        }
+        if !preset.images.is_empty() {
+            let pipeline = preset.steps().into_iter().fold(self.artifact_pipeline.take().unwrap_or_default(), ArtifactPipeline::step);
+            self.artifact_pipeline = Some(pipeline);
+        }
+    }
+
+    /// Private function that generates the headers and applies the artifact pipeline, if any, to
+    /// the output of a successful build, then writes its checksum manifest and installs its
+    /// binaries if enabled
+    fn process_artifacts(&self, output: &mut BuildOutput) -> Result<()> {
+        let context = StepContext {
+            target: self.project_settings.compilation_target.clone(),
+            profile: self.get_active_profile().to_string(),
+        };
+        if let Some(cbindgen) = &self.cbindgen {
+            output.headers = cbindgen.generate(output, &std::path::absolute(self.target_dir())?)?;
+        }
+        if let Some(pipeline) = &self.artifact_pipeline {
+            output.pipeline = pipeline.run(output, &context)?;
+        }
+        if self.checksums {
+            let manifest = ChecksumManifest::compute(output, context.target.as_deref(), &context.profile)?;
+            if let Some(directory) = manifest.directory() {
+                manifest.write(directory.join(checksum::MANIFEST_FILE_NAME))?;
+            }
+            output.checksums = Some(manifest);
+        }
+        if let Some(install_dir) = &self.install_dir {
+            let template = self.get_install_template();
+            // The host triple is only looked up when the template needs it
+            let host = match &context.target {
+                None if template.contains("{target}") => Some(self.toolchain_info()?.host),
+                _ => None,
+            };
+            let target = context.target.as_deref().or(host.as_deref()).unwrap_or_default();
+            let install_context = InstallContext { target, profile: &context.profile };
+            output.installed = install::install(output, install_dir, template, &install_context)?;
+        }
+        if let Some(history_path) = &self.size_history {
+            let snapshot = SizeSnapshot::measure(output, context.target.as_deref(), &context.profile)?;
+            let mut history = SizeHistory::open(history_path)?;
+            history.snapshots.push(snapshot.clone());
+            history.write(history_path)?;
+            output.sizes = Some(snapshot);
+        }
+        Ok(())
+    }
+
+    /// Private function that creates the command running `subcommand` through the configured backend
+    fn cargo(&self, subcommand: &str) -> Command {
+        let toolchain = self.toolchain.as_deref();
+        match &self.backend {
+            Some(backend) => backend.command(subcommand, toolchain),
+            None => CargoBackend { cargo_path: self.cargo_path.clone() }.command(subcommand, toolchain),
+        }
+    }
+
+    /// Private function that lets the configured backend adapt a complete command before it is run
+    fn finish(&self, command: Command) -> Command {
+        match &self.backend {
+            Some(backend) => backend.finish(command),
+            None => command,
+        }
+    }
+
+    /// Private function that applies the before-build hooks to a `cargo build` command, then lets
+    /// the backend adapt it, so the command fingerprinted and rendered is the one that is run
+    fn finish_build_command(&self, mut command: Command) -> Command {
+        self.hooks.run_before(&mut command);
+        self.finish(command)
+    }
+
+    /// Private function that creates the command running `subcommand` with the environment, verbosity
+    /// and color settings shared by every cargo subcommand
+    fn base_command(&self, subcommand: &str) -> Command {
+        let mut command = self.env_command(subcommand);
+        if let Some(verbosity) = self.verbosity.as_arg() {
+            command.arg(verbosity);
+        }
+        if let Some(color) = self.color.as_arg() {
+            command.arg("--color").arg(color);
+        }
+        command
+    }
+
+    /// Private function that creates the command running `subcommand` with the environment settings
+    /// only, for subcommands that do not accept the common flags
+    fn env_command(&self, subcommand: &str) -> Command {
+        let mut command = self.cargo(subcommand);
+        if self.hermetic {
+            // Keep the variables set by the backend, e.g. `RUSTUP_TOOLCHAIN`
+            let backend_envs: Vec<_> =
+                command.get_envs().map(|(k, v)| (k.to_os_string(), v.map(|v| v.to_os_string()))).collect();
+            command.env_clear();
+            let allowed = HERMETIC_ENVS.iter().copied().chain(self.allowed_envs.iter().map(String::as_str));
+            for key in allowed {
+                if let Some(value) = env::var_os(key) {
+                    command.env(key, value);
+                }
+            }
+            for (key, value) in backend_envs {
+                match value {
+                    Some(value) => command.env(key, value),
+                    None => command.env_remove(key),
+                };
+            }
+        }
+        command.envs(&self.envs);
+        command
+    }
+
+    /// Private function that assembles the `cargo <subcommand>` invocation from the current settings
+    fn command(&self, subcommand: &str) -> Result<Command> {
+        let mut command = self.base_command(subcommand);
+        self.add_build_args(&mut command, "--profile", "--jobs")?;
+        Ok(command)
+    }
+
+    /// Private function that adds the profile, job, environment, target, feature and package
+    /// settings of a build, passing the profile and job count with the given flags
+    fn add_build_args(&self, command: &mut Command, profile_flag: &str, jobs_flag: &str) -> Result<()> {
+        if let Some(profile) = &self.profile {
+            command.arg(profile_flag).arg(profile);
+        } else if self.project_settings.release {
+            command.arg("--release");
+        }
+        if self.thread_count > 0 {
+            command.arg(jobs_flag).arg(self.thread_count.to_string());
+        }
+        if let Some(target_dir) = self.target_dir_env() {
+            command.env("CARGO_TARGET_DIR", target_dir);
+        }
+        command.envs(self.profile_overrides.envs(self.get_active_profile()));
+        if let Some(incremental) = self.incremental {
+            command.env("CARGO_INCREMENTAL", if incremental { "1" } else { "0" });
+        }
+        if let Some(wrapper) = &self.rustc_wrapper {
+            command.env("RUSTC_WRAPPER", wrapper);
+        }
+        let mut rustc_flags = self.additional_flags.clone();
+        if self.deny_warnings {
+            rustc_flags.push("-Dwarnings".to_string());
+        }
+        if let Some(sanitizer) = self.sanitizer {
+            rustc_flags.push(format!("-Zsanitizer={}", sanitizer));
+        }
+        if !rustc_flags.is_empty() {
+            command
+                .env("CARGO_ENCODED_RUSTFLAGS", flags::encode_rustflags(self.rustflags_policy, &rustc_flags, |key| self.env_var(key)))
+                .env_remove("RUSTFLAGS");
+        }
+        if let Some(target) = self.build_target()? {
+            command.arg("--target").arg(target);
+        }
+        if let Some(build_std) = &self.build_std {
+            command.args(build_std.args());
+        } else if self.sanitizer.is_some_and(Sanitizer::requires_build_std) {
+            command.arg("-Zbuild-std");
+        }
+        self.add_feature_args(command);
+        if self.project_settings.is_lib {
+            command.arg("--lib");
+        } else if let Some(target) = &self.project_settings.target {
+            command.arg("--bin").arg(target);
+        }
+        for target in &self.project_settings.build_targets {
+            target.apply(command);
+        }
+        if self.project_settings.workspace {
+            command.arg("--workspace");
+            for package in &self.project_settings.excluded_packages {
+                command.arg("--exclude").arg(package);
+            }
+        }
+        for package in &self.project_settings.packages {
+            command.arg("-p").arg(package);
+        }
+        self.add_project_args(command);
+        Ok(())
+    }
+
+    /// Private function that returns the target passed to cargo: the compilation target if set,
+    /// otherwise the host of the toolchain if sanitizer flags or build-std must not apply to
+    /// build scripts and procedural macros
+    fn build_target(&self) -> Result<Option<String>> {
+        match &self.project_settings.compilation_target {
+            Some(target) => Ok(Some(target.clone())),
+            None if self.sanitizer.is_some() || self.build_std.is_some() => Ok(Some(self.toolchain_info()?.host)),
+            None => Ok(None),
+        }
+    }
+
+    /// Private function that adds the feature selection flags
+    fn add_feature_args(&self, command: &mut Command) {
+        if let Some(features) = self.project_settings.features.as_ref().filter(|f| !f.is_empty()) {
+            command.arg("--features").arg(features.join(","));
+        }
+        if self.project_settings.all_features {
+            command.arg("--all-features");
+        }
+        if self.project_settings.no_default_features {
+            command.arg("--no-default-features");
+        }
+    }
+
+    /// Private function that adds the network and lockfile flags, and points `command` at the project
+    fn add_project_args(&self, command: &mut Command) {
+        if self.project_settings.offline {
+            command.arg("--offline");
+        }
+        if self.project_settings.locked {
+            command.arg("--locked");
+        }
+        if self.project_settings.frozen {
+            command.arg("--frozen");
+        }
+        if self.project_settings.use_manifest_path {
+            command.arg("--manifest-path").arg(&self.project_settings.cargo_toml_path);
+        } else {
+            command.current_dir(&self.project_settings.project_path);
+        }
+    }
+
+    /// Private function that returns where the output of cargo processes is written
+    fn output_log(&self) -> OutputLog {
+        OutputLog {
+            path: self.log_path.clone(),
+            sinks: self.sinks.clone(),
+            strip_ansi: self.strip_log_ansi,
+            timestamps: self.log_timestamps,
+        }
+    }
+
+    /// Private function that runs `command` to completion and appends its output to the log file, if any
+    fn execute(&self, command: Command) -> Result<Output> {
+        self.execute_finished(&mut self.finish(command))
+    }
+
+    /// Private function that runs `command`, already prepared by the backend, see [`Builder::execute`]
+    fn execute_finished(&self, command: &mut Command) -> Result<Output> {
+        let mut attempt = 1;
+        loop {
+            let output = self.execute_once(command)?;
+            match &self.retry_policy {
+                Some(policy)
+                    if !output.status.success()
+                        && attempt < policy.max_attempts
+                        && policy.is_retryable(&String::from_utf8_lossy(&output.stderr)) =>
+                {
+                    let delay = policy.delay(attempt);
+                    trace::retry(attempt, delay);
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                _ => return Ok(output),
+            }
+        }
+    }
+
+    /// Private function that runs `command` once, see [`Builder::execute`]
+    fn execute_once(&self, command: &mut Command) -> Result<Output> {
+        let log_writer = self.output_log().writer()?;
+        let Some(timeout) = self.timeout else {
+            let child = command.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+            return BuildHandle::new(child, log_writer).collect();
+        };
+        let mut handle = BuildHandle::new(spawn_in_group(command)?, log_writer);
+        if handle.wait_timeout(timeout)?.is_some() {
+            return handle.collect();
+        }
+        handle.kill()?;
+        let output = handle.collect()?;
+        Err(CargoWrapError::TimedOut {
+            timeout,
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        })
+    }
+
+    /// Private function that maps the exit status of a finished build to a result
+    fn check_status(status: ExitStatus, stderr: impl FnOnce() -> String) -> Result<()> {
+        if status.success() {
+            Ok(())
+        } else {
+            Err(CargoWrapError::CompilationFailed { status, stdout: String::new(), stderr: stderr(), diagnostics: Vec::new() })
+        }
+    }
+
+    /// Private function that maps the exit status of a cargo subcommand that does not compile
+    /// the project, e.g. `cargo fmt`, to a result
+    fn check_tool_status(tool: &str, status: ExitStatus, stderr: impl FnOnce() -> String) -> Result<()> {
+        if status.success() {
+            Ok(())
+        } else {
+            Err(CargoWrapError::ToolFailed { tool: tool.to_string(), status, stderr: stderr() })
+        }
+    }
+
+    /// Private function that creates the error of a failed `cargo build`, keeping its output and
+    /// the error diagnostics of `output`
+    fn build_failed(status: ExitStatus, stdout: String, stderr: String, output: &BuildOutput) -> CargoWrapError {
+        let diagnostics = output.diagnostics_with_level(DiagnosticLevel::Error).cloned().collect();
+        CargoWrapError::CompilationFailed { status, stdout, stderr, diagnostics }
+    }
+
+    /// Executes the build process using `cargo build`.
+    ///
+    /// This function spawns a `cargo build` process with the specified settings,
+    /// such as release/debug mode, enabled features, and output directories.
+    /// Cargo is run with `--message-format=json-diagnostic-rendered-ansi`, so the paths of the
+    /// produced artifacts and every compiler diagnostic can be reported back.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BuildOutput)` - If the build succeeds, containing the produced artifacts and diagnostics.
+    /// * `Err(CargoWrapError)` - If the build process fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The `cargo` binary is missing from the system ([`CargoWrapError::Io`]).
+    /// - The build process fails, e.g. compilation errors ([`CargoWrapError::CompilationFailed`]).
+    ///   The error carries the exit status, the captured stdout and stderr, and the error diagnostics.
+    /// - The log file cannot be written to, if logging is enabled ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project");
+    ///     let builder = Builder::new(settings)?.jobs(4).log_path("build.log");
+    ///     let output = builder.build()?;
+    ///     for executable in output.executables() {
+    ///         println!("Built {}", executable.display());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build(&self) -> Result<BuildOutput> {
+        self.build_with(self.build_command()?)
+    }
+
+    /// Executes the build process for every member of the workspace using `cargo build --workspace`.
+    ///
+    /// This behaves like [`Builder::build`], regardless of whether [`ProjectSettings::set_workspace`]
+    /// was called. Packages excluded with [`ProjectSettings::exclude_packages`] are skipped.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BuildOutput)` - If the build succeeds, containing the produced artifacts and diagnostics.
+    /// * `Err(CargoWrapError)` - If the build process fails.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/workspace").exclude(["benchmarks"]);
+    ///     let builder = Builder::new(settings)?;
+    ///     builder.build_workspace()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_workspace(&self) -> Result<BuildOutput> {
+        let mut command = self.build_command()?;
+        if !self.project_settings.workspace {
+            command.arg("--workspace");
+            for package in &self.project_settings.excluded_packages {
+                command.arg("--exclude").arg(package);
+            }
+        }
+        self.build_with(command)
+    }
+
+    /// Builds the project for `aarch64-apple-darwin` and `x86_64-apple-darwin`, then combines
+    /// the binaries of both builds into macOS universal binaries with `lipo -create`.
+    ///
+    /// Both builds use the builder's configuration, backend and sinks, with the compilation
+    /// target replaced. Every executable, shared library and static library produced by both
+    /// builds (see [`BuildOutput::binaries`]) is combined into
+    /// `<target dir>/universal-apple-darwin/<profile dir>/`, e.g. `target/universal-apple-darwin/release/app`.
+    /// Hooks, the artifact pipeline and checksums are not applied to the per-architecture builds.
+    ///
+    /// `lipo` is searched in `PATH`, falling back to `llvm-lipo` on other hosts.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(UniversalOutput)` - The output of both builds and the paths of the universal binaries.
+    /// * `Err(CargoWrapError)` - If a build or `lipo` fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - One of the builds fails, e.g. because its target is not installed ([`CargoWrapError::CompilationFailed`]).
+    /// - Neither `lipo` nor `llvm-lipo` can be found ([`CargoWrapError::ToolNotFound`]).
+    /// - `lipo` fails ([`CargoWrapError::ToolFailed`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+    ///     let output = builder.build_universal()?;
+    ///     for binary in &output.binaries {
+    ///         println!("Built {}", binary.display());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_universal(&self) -> Result<UniversalOutput> {
+        let mut outputs = Vec::with_capacity(universal::UNIVERSAL_TARGETS.len());
+        for target in universal::UNIVERSAL_TARGETS {
+            let mut config = self.config();
+            config.project_settings = config.project_settings.compilation_target(target);
+            let mut builder = Builder::from_config(config)?;
+            builder.backend = self.backend.clone();
+            builder.sinks = self.sinks.clone();
+            outputs.push(builder.build()?);
+        }
+        let x86_64 = outputs.pop().unwrap_or_default();
+        let aarch64 = outputs.pop().unwrap_or_default();
+
+        let profile_dir = match self.get_active_profile() {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            profile => profile,
+        };
+        let directory = std::path::absolute(self.target_dir())?.join(universal::UNIVERSAL_DIR).join(profile_dir);
+        let lipo = Builder::find_binary("lipo").or_else(|| Builder::find_binary("llvm-lipo"));
+        let mut binaries = Vec::new();
+        let x86_64_binaries = x86_64.binaries();
+        for binary in aarch64.binaries() {
+            let Some(name) = binary.file_name() else { continue };
+            let Some(other) = x86_64_binaries.iter().find(|other| other.file_name() == Some(name)) else {
+                continue;
+            };
+            let lipo = lipo.as_ref().ok_or_else(|| CargoWrapError::ToolNotFound("lipo".to_string()))?;
+            fs::create_dir_all(&directory)?;
+            let universal = directory.join(name);
+            pipeline::run_tool("lipo", Command::new(lipo).arg("-create").arg("-output").arg(&universal).arg(&binary).arg(other))?;
+            binaries.push(universal);
+        }
+        Ok(UniversalOutput { aarch64, x86_64, binaries })
+    }
+
+    /// Executes the build process using `cargo build` and returns a structured report of it.
+    ///
+    /// Unlike [`Builder::build`], a failed build is not an error: the report carries the exit
+    /// status along with the diagnostics, the duration and the command line, so it can be
+    /// forwarded to CI dashboards as is.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BuildReport)` - The report of the finished build, successful or not.
+    /// * `Err(CargoWrapError)` - If the build could not be run.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The `cargo` process cannot be spawned ([`CargoWrapError::Io`]).
+    /// - The build exceeds the configured timeout ([`CargoWrapError::TimedOut`]).
+    /// - The log file cannot be written to, if logging is enabled ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     let report = builder.build_report()?;
+    ///     println!(
+    ///         "{} in {:?}: {} warnings, {} errors",
+    ///         if report.success() { "succeeded" } else { "failed" },
+    ///         report.duration,
+    ///         report.warning_count(),
+    ///         report.error_count()
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_report(&self) -> Result<BuildReport> {
+        self.run_build(self.build_command()?)
+    }
+
+    /// Builds the project once per profile, e.g. a debug and a release build of a plugin, with
+    /// the same settings otherwise, and reports every build (see [`Builder::build_report`]).
+    ///
+    /// Each build uses `--profile X`, which overrides the release setting and the profile of the
+    /// builder. The backend, output sinks, cache backend and artifact pipeline are kept; the
+    /// build hooks are not run.
+    ///
+    /// # Arguments
+    ///
+    /// * `profiles` - The profiles to build, e.g. `["dev", "release"]` or custom profiles.
+    /// * `parallel` - If `true`, the builds run at the same time, each in its own target directory
+    ///   (`<target dir>/profile-<name>`) so they do not wait for each other's lock. Otherwise they
+    ///   run in order and share the target directory.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BTreeMap<String, BuildReport>)` - The report of every build, by profile, successful or not.
+    /// * `Err(CargoWrapError)` - If a build could not be run.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a build cannot be run, see [`Builder::build_report`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/plugin"))?;
+    ///     for (profile, report) in builder.build_profiles(["dev", "release"], true)? {
+    ///         let binaries = report.output.binaries();
+    ///         println!("{}: {} ({:?})", profile, if report.success() { "ok" } else { "failed" }, binaries);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_profiles(&self, profiles: impl IntoIterator<Item = impl Into<String>>, parallel: bool) -> Result<BTreeMap<String, BuildReport>> {
+        let mut builders = Vec::new();
+        for profile in profiles.into_iter().map(Into::into) {
+            let mut config = self.config();
+            config.profile = Some(profile.clone());
+            if parallel {
+                let target_dir = std::path::absolute(self.target_dir())?.join(format!("profile-{}", profile));
+                config.project_settings.set_output_path(target_dir);
+            }
+            let mut builder = Builder::from_config(config)?;
+            builder.backend = self.backend.clone();
+            builder.sinks = self.sinks.clone();
+            builder.cache_backend = self.cache_backend.clone();
+            builder.artifact_pipeline = self.artifact_pipeline.clone();
+            builders.push((profile, builder));
+        }
+        if !parallel {
+            return builders.into_iter().map(|(profile, builder)| Ok((profile, builder.build_report()?))).collect();
+        }
+        let reports: Vec<(String, Result<BuildReport>)> = thread::scope(|scope| {
+            let handles: Vec<_> = builders
+                .iter()
+                .map(|(profile, builder)| (profile, scope.spawn(move || builder.build_report())))
+                .collect();
+            handles
+                .into_iter()
+                .map(|(profile, handle)| (profile.clone(), handle.join().unwrap_or_else(|panic| std::panic::resume_unwind(panic))))
+                .collect()
+        });
+        reports.into_iter().map(|(profile, report)| Ok((profile, report?))).collect()
+    }
+
+    /// Private function that runs a `cargo build` command along with the build hooks and reports it
+    fn run_build(&self, command: Command) -> Result<BuildReport> {
+        let mut command = self.finish_build_command(command);
+        let rendered = RenderedCommand::from(&command);
+        let cache_entry = match self.build_cache {
+            true => Some(self.target_dir().join(cache::CACHE_DIR).join(format!("{}.json", self.fingerprint_with(&rendered)?))),
+            false => None,
+        };
+        if let Some(entry) = cache_entry.as_deref().and_then(CacheEntry::load).filter(CacheEntry::is_valid) {
+            let mut output = BuildOutput::parse(&entry.stdout);
+            for artifact in &mut output.artifacts {
+                artifact.fresh = true;
+            }
+            let report = BuildReport {
+                command: rendered,
+                status: ExitStatus::default(),
+                duration: Duration::ZERO,
+                output,
+                stdout: entry.stdout.into_bytes(),
+                stderr: entry.stderr.into_bytes(),
+                cached: true,
+            };
+            self.hooks.run_after(&report);
+            return Ok(report);
+        }
+        self.check_prerequisites()?;
+        self.ensure_rust_src(false)?;
+        let duplicates = self.check_duplicates()?;
+        trace::instrument("build", &self.project_settings.project_path, || {
+            let (started, started_at) = (Instant::now(), SystemTime::now());
+            let output = self.execute_finished(&mut command)?;
+            let duration = started.elapsed();
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let mut build_output = BuildOutput::parse(&stdout);
+            trace::report(&build_output);
+            build_output.timings = self.read_timings(&stderr, started_at)?;
+            build_output.future_incompat = self.read_future_incompat(&stderr, started_at)?;
+            build_output.duplicates = duplicates;
+            if output.status.success() {
+                self.process_artifacts(&mut build_output)?;
+                if let Some(path) = &cache_entry {
+                    CacheEntry::new(stdout.into_owned(), stderr.into_owned(), &build_output).save(path)?;
+                }
+            }
+            let report = BuildReport {
+                command: rendered,
+                status: output.status,
+                duration,
+                output: build_output,
+                stdout: output.stdout,
+                stderr: output.stderr,
+                cached: false,
+            };
+            self.hooks.run_after(&report);
+            Ok(report)
+        })
+    }
+
+    /// Computes the fingerprint of the inputs of [`Builder::build`], used by the build cache (see
+    /// [`Builder::set_build_cache`]).
+    ///
+    /// The fingerprint is the SHA-256 hash of:
+    /// - The version, commit and host of the toolchain.
+    /// - The cargo invocation, with its arguments and environment overrides, which cover the
+    ///   project settings.
+    /// - The `Cargo.lock` of the project.
+    /// - The source tree: the directory of `Cargo.lock` (the workspace root), or the project
+    ///   directory. In a git repository, the files git tracks or would track (`.gitignore` is
+    ///   respected), otherwise every file outside of `.git` and `target` directories.
+    ///
+    /// Files outside the source tree, such as path dependencies elsewhere on disk, and environment
+    /// variables inherited by non-hermetic builds are not part of the fingerprint.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The fingerprint as a hex string.
+    /// * `Err(CargoWrapError)` - If the toolchain cannot be detected or a file cannot be read.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     println!("{}", builder.fingerprint()?);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn fingerprint(&self) -> Result<String> {
+        self.fingerprint_with(&self.render_command()?)
+    }
+
+    /// Restores the target directory from the cache backend, to warm it before building, e.g.
+    /// at the start of a CI job.
+    ///
+    /// The archive stored under the fingerprint of the build (see [`Builder::fingerprint`]) is
+    /// extracted into the target directory with `tar`, keeping modification times so cargo
+    /// considers the restored artifacts up to date. With [`Builder::set_build_cache`], the build
+    /// that produced the archive is recorded in it, and building skips cargo altogether.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - If an archive was restored.
+    /// * `Ok(false)` - If no archive is stored under the fingerprint.
+    /// * `Err(CargoWrapError)` - If the archive cannot be fetched or extracted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - No cache backend is set ([`CargoWrapError::InvalidSettings`]).
+    /// - The fingerprint cannot be computed, see [`Builder::fingerprint`].
+    /// - The backend fails, or `tar` cannot be found or fails ([`CargoWrapError::ToolNotFound`],
+    ///   [`CargoWrapError::ToolFailed`], [`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, HttpCache, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let cache = HttpCache::new("https://cache.example.com/my-project").header("Authorization", "Bearer secret");
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.build_cache(true).cache_backend(cache);
+    ///     let warm = builder.restore_target_dir()?;
+    ///     let report = builder.build_report()?;
+    ///     if !warm && report.success() {
+    ///         builder.save_target_dir()?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn restore_target_dir(&self) -> Result<bool> {
+        let cache_backend = self.get_cache_backend().ok_or_else(|| CargoWrapError::InvalidSettings("no cache backend is set".to_string()))?;
+        let key = self.fingerprint()?;
+        let archive = env::temp_dir().join(format!("cargo-wrap-{}-{}.tar.gz", key, std::process::id()));
+        if !cache_backend.restore(&key, &archive)? {
+            return Ok(false);
+        }
+        let result = remote_cache::unpack(&archive, &self.target_dir());
+        let _ = fs::remove_file(&archive);
+        result.map(|_| true)
+    }
+
+    /// Archives the target directory with `tar` and saves it to the cache backend under the
+    /// fingerprint of the build (see [`Builder::fingerprint`]), e.g. at the end of a CI job. See
+    /// [`Builder::restore_target_dir`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - No cache backend is set, or the target directory does not exist ([`CargoWrapError::InvalidSettings`]).
+    /// - The fingerprint cannot be computed, see [`Builder::fingerprint`].
+    /// - `tar` cannot be found or fails, or the backend fails ([`CargoWrapError::ToolNotFound`],
+    ///   [`CargoWrapError::ToolFailed`], [`CargoWrapError::Io`]).
+    pub fn save_target_dir(&self) -> Result<()> {
+        let cache_backend = self.get_cache_backend().ok_or_else(|| CargoWrapError::InvalidSettings("no cache backend is set".to_string()))?;
+        let target_dir = self.target_dir();
+        if !target_dir.is_dir() {
+            return Err(CargoWrapError::InvalidSettings(format!("the target directory {} does not exist", target_dir.display())));
+        }
+        let key = self.fingerprint()?;
+        let archive = env::temp_dir().join(format!("cargo-wrap-{}-{}.tar.gz", key, std::process::id()));
+        let result = remote_cache::pack(&target_dir, &archive).and_then(|_| cache_backend.save(&key, &archive));
+        let _ = fs::remove_file(&archive);
+        result
+    }
+
+    /// Private function that computes the fingerprint of the build run by `command`
+    fn fingerprint_with(&self, command: &RenderedCommand) -> Result<String> {
+        let lockfile = self.lockfile_path()?;
+        let root = match lockfile.as_deref().and_then(Path::parent) {
+            Some(root) => root.to_path_buf(),
+            None => std::path::absolute(&self.project_settings.project_path)?,
+        };
+        cache::fingerprint(&self.toolchain_info()?, command, lockfile.as_deref(), &root, &self.target_dir())
+    }
+
+    /// Private function that checks the dependency graph against the duplicate policy, if any
+    fn check_duplicates(&self) -> Result<Vec<DuplicatePackage>> {
+        match &self.duplicate_policy {
+            Some(policy) => policy.check(&self.dependency_graph()?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Renders the exact invocation [`Builder::build`] would execute, without running anything.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RenderedCommand)` - The program, arguments, working directory and environment
+    ///   overrides. Its `Display` implementation produces a shell-like command line.
+    /// * `Err(CargoWrapError)` - If the build needs the host of the toolchain as its target (see
+    ///   [`Builder::set_sanitizer`] and [`Builder::set_build_std`]) and it cannot be detected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").release(true);
+    ///     let builder = Builder::new(settings)?;
+    ///     let command = builder.render_command()?;
+    ///     assert!(command.args.iter().any(|arg| arg == "--release"));
+    ///     println!("{}", command);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn render_command(&self) -> Result<RenderedCommand> {
+        Ok(RenderedCommand::from(&self.finish_build_command(self.build_command()?)))
+    }
+
+    /// Starts the build process using `cargo build` without waiting for it to finish.
+    ///
+    /// The returned [`BuildHandle`] can be used to poll the build, wait for it with a timeout,
+    /// or cancel it. On Unix the build runs in its own process group so cancelling it also
+    /// kills every process cargo started (rustc, build scripts, linkers).
+    ///
+    /// Only the cargo invocation of [`Builder::build`] is run, with the before-build hooks: the
+    /// prerequisite and `rust-src` checks, the duplicate policy and the build cache are skipped,
+    /// and [`BuildHandle::wait`] does not post-process the build.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BuildHandle)` - A handle to the running build.
+    /// * `Err(CargoWrapError)` - If the `cargo` process cannot be spawned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    /// use std::time::Duration;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").compilation_target("aarch64-unknown-linux-gnu");
+    ///     let builder = Builder::new(settings)?;
+    ///     let mut handle = builder.spawn()?;
+    ///     if handle.wait_timeout(Duration::from_secs(60))?.is_none() {
+    ///         handle.cancel()?;
+    ///     } else {
+    ///         handle.wait()?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn spawn(&self) -> Result<BuildHandle> {
+        let log_writer = self.output_log().writer()?;
+        let child = spawn_in_group(&mut self.finish_build_command(self.build_command()?))?;
+        Ok(BuildHandle::new(child, log_writer))
+    }
+
+    /// Private function that assembles the `cargo build` invocation with JSON messages enabled, or
+    /// `cargo rustc` if the crate type is overridden
+    fn build_command(&self) -> Result<Command> {
+        let Some(crate_type) = self.crate_type else {
+            return Ok(self.add_build_flags(self.command("build")?));
+        };
+        let mut command = self.command("rustc")?;
+        let settings = &self.project_settings;
+        let selects_lib = settings.is_lib
+            || settings.build_targets.iter().any(|target| matches!(target, BuildTarget::Lib | BuildTarget::Example(_) | BuildTarget::Examples));
+        if !selects_lib {
+            command.arg("--lib");
+        }
+        command.arg("--crate-type").arg(crate_type.as_str());
+        Ok(self.add_build_flags(command))
+    }
+
+    /// Private function that adds the flags shared by every build invocation to `command`
+    fn add_build_flags(&self, mut command: Command) -> Command {
+        command.arg("--message-format=json-diagnostic-rendered-ansi");
+        if self.timings {
+            command.arg("--timings");
+        }
+        if self.keep_going {
+            command.arg("--keep-going");
+        }
+        if self.future_incompat_report {
+            command.arg("--future-incompat-report");
+        }
+        command
+    }
+
+    /// Private function that reads the timing report of a build started at `started`, if timings
+    /// are enabled and the report was written.
+    ///
+    /// Quiet builds do not print where the report is saved, so the latest report of the target
+    /// directory is used instead, as long as it was written after the build started.
+    fn read_timings(&self, stderr: &str, started: SystemTime) -> Result<Option<BuildTimings>> {
+        if !self.timings {
+            return Ok(None);
+        }
+        let report_path = BuildTimings::report_path(stderr).unwrap_or_else(|| self.target_dir().join("cargo-timings").join("cargo-timing.html"));
+        let written = fs::metadata(&report_path).and_then(|metadata| metadata.modified()).is_ok_and(|modified| modified >= started);
+        if !written {
+            return Ok(None);
+        }
+        BuildTimings::from_report(report_path).map(Some)
+    }
+
+    /// Private function that reads the future-incompatibility report of a build started at
+    /// `started` from the target directory, if reports are enabled and cargo recorded one.
+    ///
+    /// Cargo announces the report in `stderr`, except for quiet builds, where the latest report
+    /// is used if it was written during the build.
+    fn read_future_incompat(&self, stderr: &str, started: SystemTime) -> Result<Option<FutureIncompatReport>> {
+        if !self.future_incompat_report {
+            return Ok(None);
+        }
+        if let Some(id) = FutureIncompatReport::report_id(stderr) {
+            return Ok(self.future_incompat_reports()?.into_iter().find(|report| report.id == id));
+        }
+        let target_dir = self.metadata()?.target_directory;
+        let written = FutureIncompatReport::modified(&target_dir).is_some_and(|modified| modified >= started);
+        if !written {
+            return Ok(None);
+        }
+        Ok(FutureIncompatReport::read_all(target_dir)?.pop())
+    }
+
+    /// Private function that returns the target directory, as far as it can be known without
+    /// asking cargo
+    fn target_dir(&self) -> PathBuf {
+        let settings = &self.project_settings;
+        let target_dir = match settings.output_path.clone().or_else(|| self.env_var("CARGO_TARGET_DIR").map(PathBuf::from)) {
+            Some(dir) if settings.use_manifest_path => dir,
+            Some(dir) => settings.project_path.join(dir),
+            None => settings.project_path.join("target"),
+        };
+        match self.isolated_target_dir {
+            true => target_dir.join(format!("config-{}", self.configuration_hash())),
+            false => target_dir,
+        }
+    }
+
+    /// Private function that returns the target directory cargo is pointed at through
+    /// `CARGO_TARGET_DIR`, if it is not cargo's default
+    fn target_dir_env(&self) -> Option<PathBuf> {
+        if self.isolated_target_dir {
+            // Absolute, since cargo may run from another directory than this process
+            return Some(std::path::absolute(self.target_dir()).unwrap_or_else(|_| self.target_dir()));
+        }
+        self.project_settings.output_path.clone()
+    }
+
+    /// Private function that hashes the settings a build directory is isolated by, see
+    /// [`Builder::set_isolated_target_dir`]
+    fn configuration_hash(&self) -> String {
+        use sha2::Digest;
+        let settings = &self.project_settings;
+        let mut features: Vec<&str> = settings
+            .get_enabled_features()
+            .iter()
+            .flat_map(|features| features.split([',', ' ']))
+            .filter(|feature| !feature.is_empty())
+            .collect();
+        features.sort_unstable();
+        features.dedup();
+        let mut hasher = sha2::Sha256::new();
+        for part in [
+            settings.compilation_target.as_deref().unwrap_or_default(),
+            &features.join(","),
+            if settings.no_default_features { "no-default-features" } else { "" },
+            if settings.all_features { "all-features" } else { "" },
+            self.get_active_profile(),
+        ] {
+            hasher.update(part);
+            hasher.update([0]);
+        }
+        hasher.finalize().iter().take(8).map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Private function that runs a `cargo build` command and collects its JSON messages
+    fn build_with(&self, command: Command) -> Result<BuildOutput> {
+        self.run_build(command)?.into_result()
+    }
+
+    /// Private function that turns the output of a finished `cargo build` into a result
+    pub(crate) fn finish_build(output: &Output) -> Result<BuildOutput> {
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let build_output = BuildOutput::parse(&stdout);
+        trace::report(&build_output);
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            return Err(Builder::build_failed(output.status, stdout, stderr, &build_output));
+        }
+        Ok(build_output)
+    }
+
+    /// Type-checks the project using `cargo check`.
+    ///
+    /// Runs with the same settings as [`Builder::build`] (features, compilation target, release
+    /// mode, job count) but skips code generation, making it suitable for fast validation loops.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the project type-checks.
+    /// * `Err(CargoWrapError)` - If checking fails.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project");
+    ///     let builder = Builder::new(settings)?;
+    ///     builder.check()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn check(&self) -> Result<()> {
+        trace::instrument("check", &self.project_settings.project_path, || {
+            let output = self.execute(self.command("check")?)?;
+            Builder::check_status(output.status, || String::from_utf8_lossy(&output.stderr).into_owned())
+        })
+    }
+
+    /// Applies the fixes suggested by the compiler using `cargo fix`, optionally migrating the
+    /// project to the next edition.
+    ///
+    /// Runs with the same settings as [`Builder::build`] (features, compilation target, selected
+    /// targets and packages, release mode), so the code compiled for those settings is fixed.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The [`FixOptions`] of the run.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(FixOutput)` - The modified files, and the remaining warnings on stderr.
+    /// * `Err(CargoWrapError)` - If cargo refuses to fix the project or it does not compile.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - cargo exits unsuccessfully, e.g. because the working directory has uncommitted changes
+    ///   or the project does not compile ([`CargoWrapError::CompilationFailed`]).
+    /// - The `cargo` process cannot be run ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, FixOptions, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").all_features(true);
+    ///     let builder = Builder::new(settings)?;
+    ///     let output = builder.fix(&FixOptions { edition: true, allow_dirty: true, ..Default::default() })?;
+    ///     for file in &output.files {
+    ///         println!("{}: {} fixes", file.path.display(), file.fixes);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn fix(&self, options: &FixOptions) -> Result<FixOutput> {
+        let mut command = self.command("fix")?;
+        let flags = [
+            (options.edition, "--edition"),
+            (options.edition_idioms, "--edition-idioms"),
+            (options.broken_code, "--broken-code"),
+            (options.allow_dirty, "--allow-dirty"),
+            (options.allow_staged, "--allow-staged"),
+            (options.allow_no_vcs, "--allow-no-vcs"),
+        ];
+        command.args(flags.into_iter().filter(|(enabled, _)| *enabled).map(|(_, flag)| flag));
+        trace::instrument("fix", &self.project_settings.project_path, || {
+            let output = self.execute(command)?;
+            let stderr = String::from_utf8_lossy(&color::strip_ansi(&output.stderr)).into_owned();
+            Builder::check_status(output.status, || stderr.clone())?;
+            Ok(FixOutput { files: fix::parse_fixed(&stderr), stderr })
+        })
+    }
+
+    /// Formats the project using `cargo fmt`, or checks its formatting.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The [`FmtOptions`] of the run.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<PathBuf>)` - The files that are not formatted (check mode) or were reformatted,
+    ///   as absolute paths. Empty if every file is formatted.
+    /// * `Err(CargoWrapError)` - If rustfmt cannot be run or cannot parse a file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `cargo fmt` fails, e.g. because rustfmt is not installed or a file has syntax errors
+    ///   ([`CargoWrapError::ToolFailed`]).
+    /// - The `cargo` process cannot be run ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, FmtOptions, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/workspace"))?;
+    ///     let unformatted = builder.fmt(&FmtOptions { check: true, all: true, ..Default::default() })?;
+    ///     for file in &unformatted {
+    ///         eprintln!("not formatted: {}", file.display());
+    ///     }
+    ///     std::process::exit(if unformatted.is_empty() { 0 } else { 1 });
+    /// }
+    /// ```
+    pub fn fmt(&self, options: &FmtOptions) -> Result<Vec<PathBuf>> {
+        let settings = &self.project_settings;
+        // `cargo fmt` rejects the common flags such as `--color` and `--offline`
+        let mut command = self.env_command("fmt");
+        if options.check {
+            command.arg("--check");
+        }
+        if options.all || settings.workspace {
+            command.arg("--all");
+        } else {
+            for package in &settings.packages {
+                command.arg("-p").arg(package);
+            }
+        }
+        command.arg("--message-format").arg("short");
+        if settings.use_manifest_path {
+            command.arg("--manifest-path").arg(&settings.cargo_toml_path);
+        } else {
+            command.current_dir(&settings.project_path);
+        }
+        if !options.rustfmt_args.is_empty() {
+            command.arg("--").args(&options.rustfmt_args);
+        }
+        trace::instrument("fmt", &settings.project_path, || {
+            let output = self.execute(command)?;
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let files: Vec<PathBuf> = stdout.lines().map(str::trim).filter(|line| !line.is_empty()).map(PathBuf::from).collect();
+            // In check mode, cargo fmt also exits unsuccessfully if files are not formatted
+            if !options.check || files.is_empty() || rustfmt::has_errors(&stderr) {
+                Builder::check_tool_status("cargo fmt", output.status, || stderr.clone())?;
+            }
+            Ok(files)
+        })
+    }
+
+    /// Reads the packages, targets and resolved dependency graph of the project using
+    /// `cargo metadata --format-version 1`.
+    ///
+    /// The selected features and the output directory are taken into account. If a compilation
+    /// target is set, the graph only contains the dependencies used on that platform
+    /// (`--filter-platform`). The output is not written to the build log or the output sinks.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Metadata)` - The packages and the resolved dependency graph.
+    /// * `Err(CargoWrapError)` - If the dependency graph cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `cargo metadata` exits unsuccessfully, e.g. because a dependency cannot be fetched
+    ///   ([`CargoWrapError::ToolFailed`]).
+    /// - The metadata cannot be parsed ([`CargoWrapError::Io`]).
+    pub fn metadata(&self) -> Result<Metadata> {
+        let mut command = self.base_command("metadata");
+        command.arg("--format-version").arg("1");
+        if let Some(target_dir) = self.target_dir_env() {
+            command.env("CARGO_TARGET_DIR", target_dir);
+        }
+        if let Some(target) = &self.project_settings.compilation_target {
+            command.arg("--filter-platform").arg(backend::base_target_triple(target));
+        }
+        self.add_feature_args(&mut command);
+        self.add_project_args(&mut command);
+        // The metadata is read by other features on every build, so it is kept out of the build
+        // log and the output sinks, and is not subject to the build's retries and timeout
+        trace::instrument("metadata", &self.project_settings.project_path, || {
+            let stdout = pipeline::run_stdout("cargo metadata", &mut self.finish(command))?;
+            Ok(serde_json::from_str(&stdout).map_err(io::Error::from)?)
+        })
+    }
+
+    /// Reads the resolved dependency graph of the project, see [`Builder::metadata`] and
+    /// [`DependencyGraph`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Builder::metadata`].
+    pub fn dependency_graph(&self) -> Result<DependencyGraph> {
+        Ok(DependencyGraph::new(self.metadata()?))
+    }
+
+    /// Returns the units [`Builder::build`] would compile, without compiling anything, using
+    /// `cargo build --unit-graph -Z unstable-options`.
+    ///
+    /// The unit graph is a nightly-only cargo feature and its format may change, so this function
+    /// is only available with the `unstable` feature. Cargo's `--build-plan` was removed in favor
+    /// of the unit graph and is not supported.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(UnitGraph)` - Every unit of the build with its features and dependencies.
+    /// * `Err(CargoWrapError)` - If cargo fails or its output cannot be parsed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Cargo rejects the flags, e.g. because the toolchain is not nightly ([`CargoWrapError::CompilationFailed`]).
+    ///   Select a nightly toolchain with [`Builder::set_toolchain`].
+    /// - The unit graph cannot be parsed ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.toolchain("nightly");
+    ///     let graph = builder.unit_graph()?;
+    ///     for unit in graph.root_units() {
+    ///         println!("{} [{}]", unit.target.name, unit.features.join(", "));
+    ///         for dependency in graph.dependencies_of(unit) {
+    ///             println!("  -> {}", dependency.pkg_id);
+    ///         }
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "unstable")]
+    pub fn unit_graph(&self) -> Result<UnitGraph> {
+        let mut command = self.command("build")?;
+        command.args(["-Z", "unstable-options", "--unit-graph"]);
+        trace::instrument("unit-graph", &self.project_settings.project_path, || {
+            let output = self.execute(command)?;
+            Builder::check_status(output.status, || String::from_utf8_lossy(&output.stderr).into_owned())?;
+            Ok(serde_json::from_slice(&output.stdout).map_err(io::Error::from)?)
+        })
+    }
+
+    /// Executes the build process using `cargo build`, streaming its output line by line.
+    ///
+    /// Unlike [`Builder::build`], the output is not buffered until the process exits. Both
+    /// stdout and stderr are read as they are produced, and `on_line` is invoked for every line
+    /// on the calling thread. If a log path is configured, each line is also appended to it.
+    /// If a timeout is set, the build is killed once it elapses. Lines are not retained once
+    /// they were handed to `on_line` and the log, so memory use does not grow with the output:
+    /// errors only carry the last 64 KiB of stderr, and no stdout.
+    ///
+    /// The invocation is the one of [`Builder::build`], so stdout carries cargo's JSON messages
+    /// (`--message-format=json-diagnostic-rendered-ansi`), and a crate type set with
+    /// [`Builder::set_crate_type`] is built with `cargo rustc`.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_line` - Callback invoked with every line of output as a [`BuildLine`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the build succeeds.
+    /// * `Err(CargoWrapError)` - If the build process fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The `cargo` process cannot be spawned ([`CargoWrapError::Io`]).
+    /// - The build process fails, e.g. compilation errors ([`CargoWrapError::CompilationFailed`]).
+    /// - The log file cannot be written to, if logging is enabled ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{BuildLine, Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project");
+    ///     let builder = Builder::new(settings)?.jobs(4);
+    ///     builder.build_streaming(|line| match line {
+    ///         BuildLine::Stdout(line) => println!("{}", line),
+    ///         BuildLine::Stderr(line) => eprintln!("{}", line),
+    ///     })?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_streaming(&self, on_line: impl FnMut(BuildLine)) -> Result<()> {
+        trace::instrument("build", &self.project_settings.project_path, || {
+            let command = self.finish_build_command(self.build_command()?);
+            self.stream(command, on_line)
+        })
+    }
+
+    /// Executes the build process using `cargo build`, reporting its progress as it runs.
+    ///
+    /// Cargo is asked to draw its progress bar even though its output is not a terminal, and
+    /// `on_progress` is invoked on the calling thread whenever the bar is updated. Cargo only
+    /// updates the bar while units are compiled, so fully up-to-date builds report no progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `on_progress` - Callback invoked with every [`ProgressEvent`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BuildOutput)` - The produced artifacts and diagnostics if the build succeeds.
+    /// * `Err(CargoWrapError)` - If the build process fails.
+    ///
+    /// # Errors
+    ///
+    /// See [`Builder::build_streaming`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     builder.build_with_progress(|progress| {
+    ///         println!("{:.0}% ({}/{}) {}", progress.percent(), progress.finished, progress.total, progress.current.join(", "));
+    ///     })?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build_with_progress(&self, mut on_progress: impl FnMut(ProgressEvent)) -> Result<BuildOutput> {
+        let mut command = self.build_command()?;
+        command.env("CARGO_TERM_PROGRESS_WHEN", "always").env("CARGO_TERM_PROGRESS_WIDTH", "200");
+        self.check_prerequisites()?;
+        self.ensure_rust_src(false)?;
+        let duplicates = self.check_duplicates()?;
+        let command = self.finish_build_command(command);
+        trace::instrument("build", &self.project_settings.project_path, || {
+            let started = SystemTime::now();
+            let (mut stdout, mut stderr) = (String::new(), String::new());
+            let result = self.stream(command, |line| match line {
+                BuildLine::Stdout(line) => {
+                    stdout.push_str(&line);
+                    stdout.push('\n');
+                }
+                BuildLine::Stderr(line) => match ProgressEvent::parse(&line) {
+                    Some(progress) => on_progress(progress),
+                    None => {
+                        stderr.push_str(&line);
+                        stderr.push('\n');
+                    }
+                },
+            });
+            let mut build_output = BuildOutput::parse(&stdout);
+            trace::report(&build_output);
+            match result {
+                Err(CargoWrapError::CompilationFailed { status, stderr, .. }) => {
+                    return Err(Builder::build_failed(status, stdout, stderr, &build_output));
+                }
+                result => result?,
+            }
+            build_output.timings = self.read_timings(&stderr, started)?;
+            build_output.future_incompat = self.read_future_incompat(&stderr, started)?;
+            build_output.duplicates = duplicates;
+            self.process_artifacts(&mut build_output)?;
+            Ok(build_output)
+        })
+    }
+
+    /// Private function that runs `command`, already prepared by the backend, handing its output
+    /// to `on_line` line by line.
+    ///
+    /// Progress bar updates are passed to `on_line`, but are neither logged nor kept in the
+    /// captured stderr, of which only the last [`STDERR_TAIL_BYTES`] are kept.
+    fn stream(&self, mut command: Command, mut on_line: impl FnMut(BuildLine)) -> Result<()> {
+        let mut log_writer = self.output_log().writer()?;
+        let mut child = spawn_in_group(&mut command)?;
+
+        let (sender, receiver) = mpsc::channel();
+        let stdout = child.stdout.take().map(|stdout| {
+            let sender = sender.clone();
+            thread::spawn(move || forward_lines(stdout, sender, Stream::Stdout))
+        });
+        let stderr = child.stderr.take().map(|stderr| {
+            let sender = sender.clone();
+            thread::spawn(move || forward_lines(stderr, sender, Stream::Stderr))
+        });
+        drop(sender);
+
+        let deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+        let mut timed_out = false;
+        let mut log_result = Ok(());
+        let mut captured_stderr = OutputTail::new(STDERR_TAIL_BYTES);
+        loop {
+            let (stream, raw) = match deadline {
+                Some(deadline) if !timed_out => {
+                    match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Ok(line) => line,
+                        Err(RecvTimeoutError::Timeout) => {
+                            kill_process_group(&mut child)?;
+                            timed_out = true;
+                            continue;
+                        }
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                _ => match receiver.recv() {
+                    Ok(line) => line,
+                    Err(_) => break,
+                },
+            };
+            // The log keeps the raw bytes, the callback receives a lossy view
+            let text = String::from_utf8_lossy(&raw).into_owned();
+            let line = match stream {
+                Stream::Stdout => BuildLine::Stdout(text),
+                Stream::Stderr => BuildLine::Stderr(text),
+            };
+            if matches!(&line, BuildLine::Stderr(line) if ProgressEvent::parse(line).is_some()) {
+                on_line(line);
+                continue;
+            }
+            if log_result.is_ok() {
+                log_result = log_writer.write_line(stream, &raw);
+            }
+            if let BuildLine::Stderr(line) = &line {
+                captured_stderr.push(line);
+            }
+            on_line(line);
+        }
+        for reader in [stdout, stderr].into_iter().flatten() {
+            reader.join().map_err(|_| io::Error::other("Output reader thread panicked"))??;
+        }
+        let status = match deadline {
+            Some(deadline) if !timed_out => match wait_until(&mut child, deadline)? {
+                Some(status) => status,
+                None => {
+                    kill_process_group(&mut child)?;
+                    timed_out = true;
+                    child.wait()?
+                }
+            },
+            _ => child.wait()?,
+        };
+        log_result?;
+        if let (true, Some(timeout)) = (timed_out, self.timeout) {
+            return Err(CargoWrapError::TimedOut { timeout, stdout: String::new(), stderr: captured_stderr.into_string() });
+        }
+        Builder::check_status(status, || captured_stderr.into_string())
+    }
+
+    /// Runs the project's tests using `cargo test`.
+    ///
+    /// The same settings used by [`Builder::build`] (release mode, features, compilation target,
+    /// job count, etc.) are honored. Output from every test binary is parsed and the results are
+    /// summed into a single [`TestSummary`].
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A `TestOptions` instance with filters and harness settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TestSummary)` - If the tests were compiled. Failing tests are reported through
+    ///   `TestSummary::failed` and `TestSummary::success` rather than as an error.
+    /// * `Err(CargoWrapError)` - If the tests could not be compiled or `cargo` could not be run.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, TestOptions};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project");
+    ///     let builder = Builder::new(settings)?.jobs(4);
+    ///     let options = TestOptions { filters: vec!["parser".to_string()], ..Default::default() };
+    ///     let summary = builder.test(&options)?;
+    ///     println!("{} passed, {} failed", summary.passed, summary.failed);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn test(&self, options: &TestOptions) -> Result<TestSummary> {
+        let mut command = self.test_command(options)?;
+        let harness_args = options.harness_args();
+        if !harness_args.is_empty() {
+            command.arg("--").args(harness_args);
+        }
+        trace::instrument("test", &self.project_settings.project_path, || {
+            let output = self.execute(command)?;
+            match TestSummary::parse(&String::from_utf8_lossy(&output.stdout)) {
+                Some(summary) => Ok(TestSummary { success: output.status.success(), ..summary }),
+                None => {
+                    Builder::check_status(output.status, || String::from_utf8_lossy(&output.stderr).into_owned())?;
+                    Ok(TestSummary { success: true, ..Default::default() })
+                }
+            }
+        })
+    }
+
+    /// Runs the project's tests using `cargo test`, collecting the outcome, duration and output
+    /// of every test from libtest's JSON output (`--format json --report-time --show-output`).
+    ///
+    /// The JSON output of libtest is unstable, so a nightly toolchain is required. On other
+    /// toolchains, it can be enabled anyway with [`TestOptions::allow_bootstrap`], which sets
+    /// `RUSTC_BOOTSTRAP=1` for the run. This makes cargo rebuild the project and its
+    /// dependencies, and dependencies probing for nightly features in their build scripts may
+    /// enable unstable code, so the tested code can differ from that of a normal build.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A `TestOptions` instance with filters and harness settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TestReport)` - If the tests were compiled. Failing tests are reported in the report
+    ///   rather than as an error.
+    /// * `Err(CargoWrapError)` - If the tests could not be compiled or `cargo` could not be run.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The toolchain cannot be detected ([`CargoWrapError::ToolFailed`]).
+    /// - The toolchain is not a nightly one and `allow_bootstrap` is not set ([`CargoWrapError::InvalidSettings`]).
+    /// - The tests could not be compiled ([`CargoWrapError::CompilationFailed`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, TestOptions};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.toolchain("nightly");
+    ///     let report = builder.test_report(&TestOptions { no_fail_fast: true, ..Default::default() })?;
+    ///     for test in report.tests() {
+    ///         println!("{} {:?} {:?}", test.name, test.status, test.duration);
+    ///     }
+    ///     for failure in report.failures() {
+    ///         eprintln!("{}:\n{}", failure.name, failure.stdout.as_deref().unwrap_or_default());
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn test_report(&self, options: &TestOptions) -> Result<TestReport> {
+        let mut command = self.test_command(options)?;
+        let channel = self.toolchain_info()?.channel;
+        if channel != ReleaseChannel::Nightly {
+            if !options.allow_bootstrap {
+                return Err(CargoWrapError::InvalidSettings(format!(
+                    "libtest JSON output needs a nightly toolchain, found a {} one; set TestOptions::allow_bootstrap to use RUSTC_BOOTSTRAP=1",
+                    format!("{:?}", channel).to_lowercase()
+                )));
+            }
+            command.env("RUSTC_BOOTSTRAP", "1");
+        }
+        command.arg("--").args(options.harness_args());
+        command.args(["-Z", "unstable-options", "--format", "json", "--report-time", "--show-output"]);
+        trace::instrument("test", &self.project_settings.project_path, || {
+            let output = self.execute(command)?;
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            let report = TestReport::parse(&String::from_utf8_lossy(&output.stdout), &stderr);
+            if report.suites.is_empty() {
+                Builder::check_status(output.status, || stderr.clone())?;
+            }
+            Ok(TestReport { summary: TestSummary { success: output.status.success(), ..report.summary }, ..report })
+        })
+    }
+
+    /// Private function that assembles the `cargo test` invocation, without the harness arguments
+    fn test_command(&self, options: &TestOptions) -> Result<Command> {
+        let mut command = self.command("test")?;
+        if options.no_run {
+            command.arg("--no-run");
+        }
+        if options.no_fail_fast {
+            command.arg("--no-fail-fast");
+        }
+        Ok(command)
+    }
+
+    /// Updates the dependencies in `Cargo.lock` using `cargo update`, and reports what changed.
+    ///
+    /// The changes are computed by diffing the lockfile before and after the update. Dry runs
+    /// leave the lockfile untouched, so the changes are read from cargo's output instead and
+    /// carry no sources or checksums.
+    ///
+    /// The update only touches the lockfile, so it is run like a build: its output is logged,
+    /// the [`RetryPolicy`] retries it after transient registry errors, and the timeout applies.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - An `UpdateOptions` instance selecting the packages and versions.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(LockfileDiff)` - The packages that were (or, for dry runs, would be) added, removed and updated.
+    /// * `Err(CargoWrapError)` - If the update fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `precise` is set without exactly one package ([`CargoWrapError::InvalidSettings`]).
+    /// - `cargo update` exits unsuccessfully, e.g. because the version does not exist ([`CargoWrapError::ToolFailed`]).
+    /// - The lockfile cannot be read ([`CargoWrapError::Io`]) or parsed ([`CargoWrapError::LockfileParse`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, UpdateOptions};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     let options = UpdateOptions { packages: vec!["serde".to_string()], precise: Some("1.0.200".to_string()), ..Default::default() };
+    ///     let changes = builder.update(&options)?;
+    ///     for update in &changes.updated {
+    ///         println!("{} {} -> {}", update.name, update.from.version, update.to.version);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn update(&self, options: &UpdateOptions) -> Result<LockfileDiff> {
+        if options.precise.is_some() && options.packages.len() != 1 {
+            return Err(CargoWrapError::InvalidSettings("--precise requires exactly one package".to_string()));
+        }
+        let mut command = self.base_command("update");
+        for package in &options.packages {
+            command.arg("-p").arg(package);
+        }
+        if let Some(precise) = &options.precise {
+            command.arg("--precise").arg(precise);
+        }
+        if options.recursive {
+            command.arg("--recursive");
+        }
+        if options.workspace {
+            command.arg("--workspace");
+        }
+        if options.dry_run {
+            command.arg("--dry-run");
+        }
+        self.add_project_args(&mut command);
+        trace::instrument("update", &self.project_settings.project_path, || {
+            let before = self.read_lockfile()?;
+            let output = self.execute(command)?;
+            let stderr = String::from_utf8_lossy(&color::strip_ansi(&output.stderr)).into_owned();
+            Builder::check_tool_status("cargo update", output.status, || stderr.clone())?;
+            if options.dry_run {
+                return Ok(update::parse_changes(&stderr));
+            }
+            Ok(before.diff(&self.read_lockfile()?))
+        })
+    }
+
+    /// Copies the sources of every dependency into `dest_dir` using `cargo vendor`.
+    ///
+    /// Cargo prints the configuration that replaces the registry and git sources with the
+    /// vendored copies; it is returned, and merged into the project's `.cargo/config.toml` if
+    /// `options.write_config` is set. With that configuration in place, later builds work
+    /// without network access, e.g. with [`ProjectSettings::set_offline`].
+    ///
+    /// # Arguments
+    ///
+    /// * `dest_dir` - The directory the crates are vendored into, relative to the project directory.
+    /// * `options` - A `VendorOptions` instance with the vendoring settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(VendorOutput)` - The vendor directory and the printed configuration.
+    /// * `Err(CargoWrapError)` - If vendoring fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `cargo vendor` exits unsuccessfully, e.g. because a crate cannot be downloaded ([`CargoWrapError::ToolFailed`]).
+    /// - The configuration file cannot be read or written, or is not valid TOML ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, VendorOptions};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     let vendored = builder.vendor("vendor", &VendorOptions { write_config: true, ..Default::default() })?;
+    ///     println!("{}", vendored.config);
+    ///
+    ///     let mut settings = ProjectSettings::new("/path/to/project");
+    ///     settings.set_offline();
+    ///     Builder::new(settings)?.build()?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn vendor(&self, dest_dir: impl AsRef<Path>, options: &VendorOptions) -> Result<VendorOutput> {
+        let dest_dir = dest_dir.as_ref();
+        let mut command = self.base_command("vendor");
+        for manifest in &options.sync {
+            command.arg("--sync").arg(manifest);
+        }
+        if options.versioned_dirs {
+            command.arg("--versioned-dirs");
+        }
+        if options.respect_source_config {
+            command.arg("--respect-source-config");
+        }
+        self.add_project_args(&mut command);
+        // Made absolute since cargo may run from another directory than the current one
+        let directory = std::path::absolute(self.project_settings.project_path.join(dest_dir))?;
+        command.arg(&directory);
+        trace::instrument("vendor", &self.project_settings.project_path, || {
+            let output = self.execute(command)?;
+            Builder::check_tool_status("cargo vendor", output.status, || String::from_utf8_lossy(&output.stderr).into_owned())?;
+            let config = String::from_utf8_lossy(&output.stdout).into_owned();
+            let mut config_path = None;
+            if options.write_config {
+                let path = self.project_settings.project_path.join(".cargo").join("config.toml");
+                vendor::write_config(&path, &config, dest_dir)?;
+                config_path = Some(path);
+            }
+            Ok(VendorOutput { directory, config, config_path })
+        })
+    }
+
+    /// Generates a software bill of materials of the project, listing every package of its
+    /// lockfile with its version, declared license and checksum.
+    ///
+    /// Licenses are read with [`Builder::metadata`], which resolves (and, unless offline, fetches)
+    /// the dependency graph.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Sbom)` - The bill of materials, to be written with [`Sbom::write`].
+    /// * `Err(CargoWrapError)` - If the dependency graph cannot be resolved.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `cargo metadata` exits unsuccessfully ([`CargoWrapError::ToolFailed`]).
+    /// - The manifest or lockfile cannot be read or parsed ([`CargoWrapError::Io`],
+    ///   [`CargoWrapError::ManifestParse`], [`CargoWrapError::LockfileParse`]).
+    pub fn sbom(&self) -> Result<Sbom> {
+        let licenses = self
+            .metadata()?
+            .packages
+            .into_iter()
+            .filter_map(|package| Some(((package.name, package.version), package.license?)))
+            .collect();
+        // Read after `cargo metadata`, which creates the lockfile if it is missing
+        let lockfile = self.read_lockfile()?;
+        let root = self.project_settings.manifest()?.get_name().map(String::from);
+        Ok(Sbom::from_lockfile(&lockfile, &licenses, root.as_deref()))
+    }
+
+    /// Private function that reads the lockfile of the project, if any
+    fn read_lockfile(&self) -> Result<Lockfile> {
+        match self.lockfile_path()? {
+            Some(lockfile) => Lockfile::open(lockfile),
+            None => Ok(Lockfile::default()),
+        }
+    }
+
+    /// Private function that finds the lockfile of the project, the nearest `Cargo.lock` in the
+    /// directory of the manifest or one of its parents
+    fn lockfile_path(&self) -> Result<Option<PathBuf>> {
+        let directory = self.project_settings.cargo_toml_path.parent().filter(|directory| !directory.as_os_str().is_empty());
+        let absolute = std::path::absolute(directory.unwrap_or(Path::new(".")))?;
+        Ok(absolute.ancestors().map(|directory| directory.join("Cargo.lock")).find(|lockfile| lockfile.is_file()))
+    }
+
+    /// Private function that assembles the `cargo run` invocation for the configured binary
+    fn run_command(&self, args: &[String]) -> Result<Command> {
+        if self.project_settings.is_lib {
+            return Err(CargoWrapError::InvalidSettings("library targets cannot be run".to_string()));
+        }
+        if let Some(target) = self.project_settings.build_targets.iter().find(|t| !t.is_runnable()) {
+            return Err(CargoWrapError::InvalidSettings(format!("{:?} cannot be run", target)));
+        }
+        let mut command = self.command("run")?;
+        if !args.is_empty() {
+            command.arg("--").args(args);
+        }
+        let mut command = self.finish(command);
+        command
+            .stdin(self.run_stdio.stdin.to_stdio())
+            .stdout(self.run_stdio.stdout.to_stdio())
+            .stderr(self.run_stdio.stderr.to_stdio());
+        Ok(command)
+    }
+
+    /// Builds and executes the configured binary using `cargo run`, waiting for it to exit.
+    ///
+    /// Program arguments are forwarded after `--`. The standard streams are handled according
+    /// to [`Builder::run_stdio`]; streams set to [`StdioMode::Pipe`] are captured into the
+    /// returned [`RunOutput`].
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - Arguments passed to the program.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RunOutput)` - The exit status and captured output. A non-zero exit status of the
+    ///   program is not treated as an error.
+    /// * `Err(CargoWrapError)` - If `cargo run` could not be started.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The project is configured as a library, or a [`BuildTarget`] other than a single binary
+    ///   or example is selected ([`CargoWrapError::InvalidSettings`]).
+    /// - The `cargo` process cannot be spawned ([`CargoWrapError::Io`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, ProjectSettings, RunStdio, StdioMode};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let settings = ProjectSettings::new("/path/to/project").target("cli");
+    ///     let builder = Builder::new(settings)?
+    ///         .run_stdio(RunStdio { stdout: StdioMode::Pipe, ..Default::default() });
+    ///     let output = builder.run(&["--help".to_string()])?;
+    ///     println!("{}", String::from_utf8_lossy(&output.stdout));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn run(&self, args: &[String]) -> Result<RunOutput> {
+        let output = self.run_command(args)?.output()?;
+        Ok(RunOutput { status: output.status, stdout: output.stdout, stderr: output.stderr })
+    }
+
+    /// Starts the configured binary using `cargo run` without waiting for it to exit.
+    ///
+    /// This behaves like [`Builder::run`], but returns the `Child` process so the caller can
+    /// interact with streams set to [`StdioMode::Pipe`] (e.g. write to the program's stdin).
+    ///
+    /// # Errors
+    ///
+    /// See [`Builder::run`].
+    pub fn spawn_run(&self, args: &[String]) -> Result<Child> {
+        Ok(self.run_command(args)?.spawn()?)
+    }
+}
+
+/// A single line of output produced by a running `cargo` process.
+///
+/// Lines are delivered without their trailing newline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildLine {
+    /// A line written to the process' stdout.
+    Stdout(String),
+    /// A line written to the process' stderr (where cargo reports its progress).
+    Stderr(String),
+}
+
+impl BuildLine {
+    /// Returns the text of the line, regardless of which stream it came from
+    pub fn as_str(&self) -> &str {
+        match self {
+            BuildLine::Stdout(line) | BuildLine::Stderr(line) => line,
+        }
     }
 }
 
+
+/// Private function that reads `reader` in chunks and forwards the bytes of each line, without
+/// its terminator, through `sender` as soon as it is complete.
+///
+/// Cargo ends progress bar updates with a carriage return rather than a newline, so `'\r'`
+/// terminates a segment as well; the blank segments around progress updates are dropped.
+fn forward_lines(mut reader: impl io::Read, sender: mpsc::Sender<(Stream, Vec<u8>)>, stream: Stream) -> io::Result<()> {
+    let mut chunk = [0; 8192];
+    let mut pending = Vec::new();
+    // Whether the current line contained a carriage return, making its blank segments progress leftovers
+    let mut after_return = false;
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        for &byte in &chunk[..read] {
+            if byte != b'\r' && byte != b'\n' {
+                pending.push(byte);
+                continue;
+            }
+            let blank = pending.iter().all(u8::is_ascii_whitespace);
+            let segment = std::mem::take(&mut pending);
+            let keep = if byte == b'\r' || after_return { !blank } else { true };
+            after_return = byte == b'\r';
+            if keep && sender.send((stream, segment)).is_err() {
+                return Ok(());
+            }
+        }
+    }
+    if !pending.is_empty() {
+        let _ = sender.send((stream, pending));
+    }
+    Ok(())
+}
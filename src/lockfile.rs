@@ -0,0 +1,203 @@
+use crate::{CargoWrapError, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A package pinned in a `Cargo.lock`.
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `version` - The exact version of the package.
+/// * `source` - Optional source of the package, e.g. `registry+https://github.com/rust-lang/crates.io-index`
+///   or a git URL with the commit. `None` for path dependencies and workspace members.
+/// * `checksum` - Optional SHA-256 checksum of the package archive, for registry packages.
+/// * `dependencies` - The dependencies of the package, as `name`, or `name version` when several versions are locked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+    pub checksum: Option<String>,
+    pub dependencies: Vec<String>,
+}
+
+/// A package whose locked version, source or checksum changed, see [`LockfileDiff`].
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `from` - The package as locked before.
+/// * `to` - The package as locked after.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageUpdate {
+    pub name: String,
+    pub from: LockedPackage,
+    pub to: LockedPackage,
+}
+
+/// The changes between two lockfiles, computed by [`Lockfile::diff`].
+///
+/// A package whose checksum changed while its version and source did not is reported as
+/// updated, since the same version should never be published twice with different contents.
+///
+/// # Fields
+///
+/// * `added` - Packages that are only locked in the newer lockfile.
+/// * `removed` - Packages that are only locked in the older lockfile.
+/// * `updated` - Packages locked in both with a different version, source or checksum.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct LockfileDiff {
+    pub added: Vec<LockedPackage>,
+    pub removed: Vec<LockedPackage>,
+    pub updated: Vec<PackageUpdate>,
+}
+
+impl LockfileDiff {
+    /// Returns `true` if the lockfiles lock the same packages
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.updated.is_empty()
+    }
+}
+
+/// A parsed `Cargo.lock`.
+///
+/// # Fields
+///
+/// * `version` - Optional version of the lockfile format, missing for the oldest format.
+/// * `packages` - Every locked package, in the order of the lockfile (by name, then version).
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::Lockfile;
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let before = Lockfile::open("/path/to/previous/Cargo.lock")?;
+///     let after = Lockfile::open("/path/to/project/Cargo.lock")?;
+///     let diff = before.diff(&after);
+///     for update in &diff.updated {
+///         println!("{} {} -> {}", update.name, update.from.version, update.to.version);
+///     }
+///     for package in &diff.added {
+///         println!("new dependency {} {}", package.name, package.version);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Lockfile {
+    pub version: Option<u32>,
+    pub packages: Vec<LockedPackage>,
+}
+
+/// Private mirror of the lockfile format
+#[derive(Deserialize)]
+struct RawLockfile {
+    version: Option<u32>,
+    #[serde(default)]
+    package: Vec<RawPackage>,
+    /// Checksums of the oldest format, keyed as `checksum <name> <version> (<source>)`
+    #[serde(default)]
+    metadata: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct RawPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+    checksum: Option<String>,
+    #[serde(default)]
+    dependencies: Vec<String>,
+}
+
+impl Lockfile {
+    /// Reads and parses the lockfile at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The file cannot be read ([`CargoWrapError::Io`]).
+    /// - The file is not a valid lockfile ([`CargoWrapError::LockfileParse`]).
+    pub fn open(path: impl AsRef<Path>) -> Result<Lockfile> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        Lockfile::parse(&contents).map_err(|source| CargoWrapError::LockfileParse { path: path.to_path_buf(), source })
+    }
+
+    /// Parses the contents of a lockfile.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::Lockfile;
+    ///
+    /// let lockfile = Lockfile::parse("version = 4\n\n[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n").unwrap();
+    /// assert_eq!(lockfile.get("app").unwrap().version, "0.1.0");
+    /// ```
+    pub fn parse(contents: &str) -> std::result::Result<Lockfile, toml::de::Error> {
+        let raw: RawLockfile = toml::from_str(contents)?;
+        let packages = raw
+            .package
+            .into_iter()
+            .map(|package| {
+                let checksum = package.checksum.or_else(|| {
+                    let source = package.source.as_deref()?;
+                    raw.metadata.get(&format!("checksum {} {} ({})", package.name, package.version, source)).cloned()
+                });
+                LockedPackage {
+                    name: package.name,
+                    version: package.version,
+                    source: package.source,
+                    checksum: checksum.filter(|checksum| checksum != "<none>"),
+                    dependencies: package.dependencies,
+                }
+            })
+            .collect();
+        Ok(Lockfile { version: raw.version, packages })
+    }
+
+    /// Returns the first locked package named `name`, the lowest version if several are locked
+    pub fn get(&self, name: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|package| package.name == name)
+    }
+
+    /// Returns every locked version of the package named `name`
+    pub fn versions(&self, name: &str) -> impl Iterator<Item = &LockedPackage> {
+        self.packages.iter().filter(move |package| package.name == name)
+    }
+
+    /// Compares this lockfile with a newer one.
+    ///
+    /// When several versions of a package are locked, versions present in only one lockfile
+    /// are paired up in order as updates; the remaining ones are reported as added or removed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::Lockfile;
+    ///
+    /// let before = Lockfile::parse("[[package]]\nname = \"log\"\nversion = \"0.4.20\"\n").unwrap();
+    /// let after = Lockfile::parse("[[package]]\nname = \"log\"\nversion = \"0.4.22\"\n\n[[package]]\nname = \"memchr\"\nversion = \"2.7.4\"\n").unwrap();
+    /// let diff = before.diff(&after);
+    /// assert_eq!(diff.updated[0].to.version, "0.4.22");
+    /// assert_eq!(diff.added[0].name, "memchr");
+    /// assert!(diff.removed.is_empty());
+    /// ```
+    pub fn diff(&self, other: &Lockfile) -> LockfileDiff {
+        let mut names: Vec<&str> = self.packages.iter().chain(&other.packages).map(|package| package.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        let same = |a: &LockedPackage, b: &LockedPackage| a.version == b.version && a.source == b.source && a.checksum == b.checksum;
+        let mut diff = LockfileDiff::default();
+        for name in names {
+            let before: Vec<&LockedPackage> = self.versions(name).filter(|old| !other.versions(name).any(|new| same(old, new))).collect();
+            let after: Vec<&LockedPackage> = other.versions(name).filter(|new| !self.versions(name).any(|old| same(old, new))).collect();
+            for (from, to) in before.iter().zip(&after) {
+                diff.updated.push(PackageUpdate { name: name.to_string(), from: (*from).clone(), to: (*to).clone() });
+            }
+            diff.removed.extend(before.iter().skip(after.len()).map(|package| (*package).clone()));
+            diff.added.extend(after.iter().skip(before.len()).map(|package| (*package).clone()));
+        }
+        diff
+    }
+}
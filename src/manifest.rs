@@ -0,0 +1,1195 @@
+use crate::glob::glob_match;
+use crate::{BuildTarget, CargoWrapError, FeatureMap, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+/// The tables holding dependencies, at the top level of a manifest or under `[target.'cfg(..)']`.
+const DEPENDENCY_TABLES: [&str; 3] = ["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// The target sections besides `[lib]`, with the key disabling their discovery and the directory
+/// cargo discovers them in.
+const TARGET_KINDS: [(&str, &str, &str); 4] =
+    [("bin", "autobins", "src/bin"), ("example", "autoexamples", "examples"), ("test", "autotests", "tests"), ("bench", "autobenches", "benches")];
+
+/// Which part of a version [`Manifest::bump_version`] increments.
+///
+/// Lower parts are reset to zero, and pre-release and build metadata are dropped, so bumping
+/// `1.4.2-beta.1` by `Minor` gives `1.5.0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bump {
+    /// `1.4.2` becomes `2.0.0`.
+    Major,
+    /// `1.4.2` becomes `1.5.0`.
+    Minor,
+    /// `1.4.2` becomes `1.4.3`.
+    Patch,
+}
+
+impl Bump {
+    /// Private function that applies the bump to `version`, e.g. `1.4.2`
+    fn apply(self, version: &str) -> Result<String> {
+        let invalid = || CargoWrapError::InvalidSettings(format!("{} is not a valid version", version));
+        let core = version.split(['-', '+']).next().unwrap_or_default();
+        let parts: Vec<u64> = core.split('.').map(str::parse).collect::<std::result::Result<_, _>>().map_err(|_| invalid())?;
+        let [major, minor, patch] = parts[..] else { return Err(invalid()) };
+        Ok(match self {
+            Bump::Major => format!("{}.0.0", major + 1),
+            Bump::Minor => format!("{}.{}.0", major, minor + 1),
+            Bump::Patch => format!("{}.{}.{}", major, minor, patch + 1),
+        })
+    }
+}
+
+/// The table a dependency is declared in.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DependencyKind {
+    /// `[dependencies]`.
+    #[default]
+    Normal,
+    /// `[dev-dependencies]`.
+    Dev,
+    /// `[build-dependencies]`.
+    Build,
+}
+
+impl DependencyKind {
+    /// Private function that returns the name of the table
+    fn table_name(self) -> &'static str {
+        match self {
+            DependencyKind::Normal => "dependencies",
+            DependencyKind::Dev => "dev-dependencies",
+            DependencyKind::Build => "build-dependencies",
+        }
+    }
+}
+
+/// A dependency added with [`Manifest::add_dependency`].
+///
+/// A dependency with nothing but a version is written as `name = "1.0"`, any other as an
+/// inline table.
+///
+/// # Fields
+///
+/// * `version` - Optional version requirement, e.g. `1.0`.
+/// * `path` - Optional path of a local dependency, relative to the manifest.
+/// * `git` - Optional URL of a git dependency.
+/// * `branch` - Optional branch of a git dependency.
+/// * `tag` - Optional tag of a git dependency.
+/// * `rev` - Optional revision of a git dependency.
+/// * `package` - Optional name of the package, when the dependency is renamed.
+/// * `features` - Features enabled on the dependency.
+/// * `optional` - If `true`, the dependency is only used when a feature enables it.
+/// * `default_features` - If `false`, the default features of the dependency are disabled. Defaults to `true`.
+/// * `kind` - The table the dependency is added to.
+/// * `target` - Optional platform the dependency is limited to (`[target.X.dependencies]`), e.g. `cfg(unix)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DependencySpec {
+    pub version: Option<String>,
+    pub path: Option<PathBuf>,
+    pub git: Option<String>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub rev: Option<String>,
+    pub package: Option<String>,
+    pub features: Vec<String>,
+    pub optional: bool,
+    pub default_features: bool,
+    pub kind: DependencyKind,
+    pub target: Option<String>,
+}
+
+impl Default for DependencySpec {
+    fn default() -> Self {
+        DependencySpec {
+            version: None,
+            path: None,
+            git: None,
+            branch: None,
+            tag: None,
+            rev: None,
+            package: None,
+            features: Vec::new(),
+            optional: false,
+            default_features: true,
+            kind: DependencyKind::Normal,
+            target: None,
+        }
+    }
+}
+
+impl DependencySpec {
+    /// Private function that returns the value written to the manifest
+    fn to_value(&self) -> Value {
+        let strings = [
+            ("package", self.package.clone()),
+            ("version", self.version.clone()),
+            ("path", self.path.as_ref().map(|path| path.to_string_lossy().replace('\\', "/"))),
+            ("git", self.git.clone()),
+            ("branch", self.branch.clone()),
+            ("tag", self.tag.clone()),
+            ("rev", self.rev.clone()),
+        ];
+        let mut table = InlineTable::new();
+        for (key, value) in strings {
+            if let Some(value) = value {
+                table.insert(key, value.into());
+            }
+        }
+        if !self.features.is_empty() {
+            table.insert("features", Array::from_iter(&self.features).into());
+        }
+        if self.optional {
+            table.insert("optional", true.into());
+        }
+        if !self.default_features {
+            table.insert("default-features", false.into());
+        }
+        match (&self.version, table.len()) {
+            (Some(version), 1) => version.into(),
+            _ => {
+                table.fmt();
+                table.into()
+            }
+        }
+    }
+}
+
+/// A dependency declared in a manifest, see [`Manifest::get_dependencies`].
+///
+/// # Fields
+///
+/// * `name` - The name the dependency is declared under.
+/// * `spec` - The source, features and table of the dependency. For inherited dependencies,
+///   only the keys written in the manifest itself are set.
+/// * `inherited` - If `true`, the dependency is inherited from `[workspace.dependencies]` (`workspace = true`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    pub spec: DependencySpec,
+    pub inherited: bool,
+}
+
+/// The `[package]` section of a manifest.
+///
+/// String fields are `None` if they are missing or inherited from the workspace.
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `version` - The version of the package.
+/// * `edition` - The Rust edition, e.g. `2021`.
+/// * `rust_version` - The minimum supported Rust version (`rust-version`).
+/// * `description` - A short description of the package.
+/// * `license` - The SPDX license expression.
+/// * `repository` - The URL of the source repository.
+/// * `homepage` - The URL of the homepage.
+/// * `documentation` - The URL of the documentation.
+/// * `readme` - The path of the README file.
+/// * `authors` - The authors of the package.
+/// * `keywords` - The keywords of the package.
+/// * `categories` - The crates.io categories of the package.
+/// * `inherited` - The keys inherited from `[workspace.package]`, e.g. `version`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Package {
+    pub name: String,
+    pub version: Option<String>,
+    pub edition: Option<String>,
+    pub rust_version: Option<String>,
+    pub description: Option<String>,
+    pub license: Option<String>,
+    pub repository: Option<String>,
+    pub homepage: Option<String>,
+    pub documentation: Option<String>,
+    pub readme: Option<String>,
+    pub authors: Vec<String>,
+    pub keywords: Vec<String>,
+    pub categories: Vec<String>,
+    pub inherited: Vec<String>,
+}
+
+impl Package {
+    /// Private function that returns the string fields with their keys
+    fn strings(&self) -> [(&'static str, Option<&str>); 10] {
+        [
+            ("name", Some(self.name.as_str())),
+            ("version", self.version.as_deref()),
+            ("edition", self.edition.as_deref()),
+            ("rust-version", self.rust_version.as_deref()),
+            ("description", self.description.as_deref()),
+            ("license", self.license.as_deref()),
+            ("repository", self.repository.as_deref()),
+            ("homepage", self.homepage.as_deref()),
+            ("documentation", self.documentation.as_deref()),
+            ("readme", self.readme.as_deref()),
+        ]
+    }
+
+    /// Private function that returns the list fields with their keys
+    fn lists(&self) -> [(&'static str, &[String]); 3] {
+        [("authors", &self.authors), ("keywords", &self.keywords), ("categories", &self.categories)]
+    }
+}
+
+/// The `[lib]` section of a manifest.
+///
+/// # Fields
+///
+/// * `name` - Optional name of the library, defaulting to the package name.
+/// * `path` - Optional path of the crate root, defaulting to `src/lib.rs`.
+/// * `crate_type` - The crate types produced (`crate-type`), e.g. `cdylib`.
+/// * `proc_macro` - If `true`, the library is a procedural macro (`proc-macro`).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct LibTarget {
+    pub name: Option<String>,
+    pub path: Option<PathBuf>,
+    pub crate_type: Vec<String>,
+    pub proc_macro: bool,
+}
+
+/// A `[[bin]]` section of a manifest.
+///
+/// # Fields
+///
+/// * `name` - The name of the binary.
+/// * `path` - Optional path of the crate root, defaulting to `src/main.rs` or `src/bin/<name>.rs`.
+/// * `required_features` - The features that must be enabled to build the binary (`required-features`).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct BinTarget {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub required_features: Vec<String>,
+}
+
+/// The `[workspace]` section of a manifest.
+///
+/// # Fields
+///
+/// * `members` - The member patterns, see [`Manifest::get_workspace_members`] for the expanded directories.
+/// * `exclude` - The excluded patterns.
+/// * `default_members` - The members built when no package is selected (`default-members`).
+/// * `resolver` - Optional version of the feature resolver, e.g. `2`.
+/// * `version` - Optional version shared with the members (`workspace.package.version`).
+/// * `dependencies` - The dependencies members can inherit (`[workspace.dependencies]`).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub members: Vec<String>,
+    pub exclude: Vec<String>,
+    pub default_members: Vec<String>,
+    pub resolver: Option<String>,
+    pub version: Option<String>,
+    pub dependencies: Vec<Dependency>,
+}
+
+/// A `Cargo.toml` loaded for editing.
+///
+/// The manifest is edited in memory with its formatting, comments and key order preserved,
+/// then written back with [`Manifest::save`], which replaces the file atomically.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{Bump, Manifest};
+///
+/// let mut manifest = Manifest::parse("Cargo.toml", "[package]\nname = \"app\"\nversion = \"0.3.1\" # released\n").unwrap();
+/// assert_eq!(manifest.bump_version(Bump::Minor).unwrap(), "0.4.0");
+/// assert_eq!(manifest.to_string(), "[package]\nname = \"app\"\nversion = \"0.4.0\" # released\n");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Manifest {
+    path: PathBuf,
+    document: DocumentMut,
+}
+
+impl Manifest {
+    /// Reads and parses the manifest at `path`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The file cannot be read ([`CargoWrapError::Io`]).
+    /// - The file is not valid TOML ([`CargoWrapError::ManifestParse`]).
+    pub fn open(path: impl Into<PathBuf>) -> Result<Manifest> {
+        let path = path.into();
+        let contents = fs::read_to_string(&path)?;
+        Manifest::parse(path, &contents)
+    }
+
+    /// Parses `contents` as the manifest at `path`, without reading the file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::ManifestParse`] if `contents` is not valid TOML.
+    pub fn parse(path: impl Into<PathBuf>, contents: &str) -> Result<Manifest> {
+        let path = path.into();
+        // Parsed with `toml` first so errors are reported like every other manifest error
+        if let Err(source) = toml::from_str::<toml::Table>(contents) {
+            return Err(CargoWrapError::ManifestParse { path, source });
+        }
+        let document = contents.parse::<DocumentMut>().map_err(io::Error::other)?;
+        Ok(Manifest { path, document })
+    }
+
+    /// Creates the virtual manifest of a new workspace at `path`, with the version 2 resolver and
+    /// no members, in memory. Add members with [`Manifest::add_workspace_member`], then write it
+    /// with [`Manifest::save`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::Manifest;
+    ///
+    /// let mut manifest = Manifest::new_workspace("/path/to/monorepo/Cargo.toml");
+    /// manifest.add_workspace_member("crates/core").unwrap();
+    /// assert_eq!(manifest.to_string(), "[workspace]\nresolver = \"2\"\nmembers = [\"crates/core\"]\n");
+    /// ```
+    pub fn new_workspace(path: impl Into<PathBuf>) -> Manifest {
+        let document = "[workspace]\nresolver = \"2\"\nmembers = []\n".parse::<DocumentMut>().unwrap_or_default();
+        Manifest { path: path.into(), document }
+    }
+
+    /// Returns the path of the manifest
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the name of the package, `None` for virtual manifests
+    pub fn get_name(&self) -> Option<&str> {
+        self.document.get("package")?.get("name")?.as_str()
+    }
+
+    /// Returns the version of the package, `None` if it is missing or inherited from the workspace
+    pub fn get_version(&self) -> Option<&str> {
+        self.document.get("package")?.get("version")?.as_str()
+    }
+
+    /// Returns `true` if the version of the package is inherited from the workspace (`version.workspace = true`)
+    pub fn is_version_inherited(&self) -> bool {
+        self.document
+            .get("package")
+            .and_then(|package| package.get("version"))
+            .and_then(|version| version.get("workspace"))
+            .and_then(Item::as_bool)
+            .unwrap_or(false)
+    }
+
+    /// Returns the `[package]` section, `None` for virtual manifests
+    pub fn get_package(&self) -> Option<Package> {
+        let table = self.document.get("package")?.as_table_like()?;
+        let inherited = |key: &str| table.get(key).and_then(|item| item.get("workspace")).and_then(Item::as_bool).unwrap_or(false);
+        let mut package = Package {
+            name: string(table.get("name")).unwrap_or_default(),
+            version: string(table.get("version")),
+            edition: string(table.get("edition")),
+            rust_version: string(table.get("rust-version")),
+            description: string(table.get("description")),
+            license: string(table.get("license")),
+            repository: string(table.get("repository")),
+            homepage: string(table.get("homepage")),
+            documentation: string(table.get("documentation")),
+            readme: string(table.get("readme")),
+            authors: strings(table.get("authors")),
+            keywords: strings(table.get("keywords")),
+            categories: strings(table.get("categories")),
+            inherited: Vec::new(),
+        };
+        package.inherited = table.iter().map(|(key, _)| key).filter(|key| inherited(key)).map(String::from).collect();
+        Some(package)
+    }
+
+    /// Replaces the `[package]` section in memory with `package`.
+    ///
+    /// Only the keys modelled by [`Package`] are touched: keys whose value is unchanged keep
+    /// their formatting, keys listed in `inherited` are left alone, and keys without a value
+    /// are removed.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::InvalidSettings`] if `package` is not a table in the manifest.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::Manifest;
+    ///
+    /// let mut manifest = Manifest::parse("Cargo.toml", "[package]\nname = \"app\"\nedition = \"2021\"\n").unwrap();
+    /// let mut package = manifest.get_package().unwrap();
+    /// package.description = Some("An app".to_string());
+    /// manifest.set_package(&package).unwrap();
+    /// assert_eq!(manifest.to_string(), "[package]\nname = \"app\"\nedition = \"2021\"\ndescription = \"An app\"\n");
+    /// ```
+    pub fn set_package(&mut self, package: &Package) -> Result<()> {
+        let not_a_table = || CargoWrapError::InvalidSettings(format!("package is not a table in {}", self.path.display()));
+        let table = self.document.entry("package").or_insert_with(|| Item::Table(Table::new())).as_table_like_mut().ok_or_else(not_a_table)?;
+        for (key, value) in package.strings() {
+            if !package.inherited.iter().any(|inherited| inherited == key) {
+                set_field(table, key, value.map(Value::from));
+            }
+        }
+        for (key, values) in package.lists() {
+            if !package.inherited.iter().any(|inherited| inherited == key) {
+                set_field(table, key, Some(values).filter(|values| !values.is_empty()).map(|values| Array::from_iter(values).into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns every dependency of the package, including development, build and
+    /// target-specific dependencies
+    pub fn get_dependencies(&self) -> Vec<Dependency> {
+        let mut dependencies = Vec::new();
+        let root = self.document.as_table();
+        for kind in [DependencyKind::Normal, DependencyKind::Dev, DependencyKind::Build] {
+            dependencies.extend(read_dependencies(root.get(kind.table_name()), kind, None));
+        }
+        if let Some(targets) = root.get("target").and_then(Item::as_table_like) {
+            for (target, table) in targets.iter() {
+                for kind in [DependencyKind::Normal, DependencyKind::Dev, DependencyKind::Build] {
+                    dependencies.extend(read_dependencies(table.get(kind.table_name()), kind, Some(target)));
+                }
+            }
+        }
+        dependencies
+    }
+
+    /// Returns the features of the package, including the implicit features of optional dependencies
+    pub fn get_features(&self) -> FeatureMap {
+        let features = self.document.get("features").and_then(Item::as_table_like);
+        let features = features.map(|features| features.iter().map(|(name, values)| (name.to_string(), strings(Some(values)))).collect());
+        let optional = self.get_dependencies().into_iter().filter(|dependency| dependency.spec.optional).map(|dependency| dependency.name);
+        FeatureMap::from_parts(features.unwrap_or_default(), optional)
+    }
+
+    /// Sets the values the feature `name` enables in memory, adding the feature if it is missing
+    pub fn set_feature(&mut self, name: &str, values: &[String]) {
+        let features = self.document.entry("features").or_insert_with(|| Item::Table(Table::new()));
+        if let Some(features) = features.as_table_like_mut() {
+            set_field(features, name, Some(Array::from_iter(values).into()));
+        }
+    }
+
+    /// Removes the feature `name` in memory, returning `true` if it was declared
+    pub fn remove_feature(&mut self, name: &str) -> bool {
+        let features = self.document.get_mut("features").and_then(Item::as_table_like_mut);
+        features.is_some_and(|features| features.remove(name).is_some())
+    }
+
+    /// Returns the `[lib]` section, if declared
+    pub fn get_lib(&self) -> Option<LibTarget> {
+        let table = self.document.get("lib")?.as_table_like()?;
+        Some(LibTarget {
+            name: string(table.get("name")),
+            path: string(table.get("path")).map(PathBuf::from),
+            crate_type: strings(table.get("crate-type")),
+            proc_macro: table.get("proc-macro").and_then(Item::as_bool).unwrap_or(false),
+        })
+    }
+
+    /// Returns the `[[bin]]` sections. Binaries cargo discovers without a section are not included,
+    /// see [`Manifest::get_targets`].
+    pub fn get_bins(&self) -> Vec<BinTarget> {
+        let Some(bins) = self.document.get("bin").and_then(Item::as_array_of_tables) else { return Vec::new() };
+        bins.iter()
+            .map(|table| BinTarget {
+                name: string(table.get("name")).unwrap_or_default(),
+                path: string(table.get("path")).map(PathBuf::from),
+                required_features: strings(table.get("required-features")),
+            })
+            .collect()
+    }
+
+    /// Returns the name of the binary `cargo run` starts by default (`package.default-run`), if set
+    pub fn get_default_run(&self) -> Option<&str> {
+        self.document.get("package")?.get("default-run")?.as_str()
+    }
+
+    /// Returns every target of the package, as cargo sees them: those declared in `[lib]`,
+    /// `[[bin]]`, `[[example]]`, `[[test]]` and `[[bench]]`, and those cargo discovers from the
+    /// layout of the package unless disabled with `autobins`, `autoexamples`, `autotests` or
+    /// `autobenches`:
+    ///
+    /// * The library at `src/lib.rs`.
+    /// * A binary named after the package at `src/main.rs`.
+    /// * The files `src/bin/<name>.rs` and `src/bin/<name>/main.rs`, and likewise in `examples`,
+    ///   `tests` and `benches`.
+    ///
+    /// Targets are returned with the library first, then binaries, examples, tests and
+    /// benchmarks, each sorted by name. Virtual manifests have no targets.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{BuildTarget, Manifest};
+    ///
+    /// let manifest = Manifest::parse("/nonexistent/Cargo.toml", "[package]\nname = \"app\"\n\n[[bin]]\nname = \"cli\"\npath = \"cli.rs\"\n").unwrap();
+    /// assert_eq!(manifest.get_targets(), [BuildTarget::Bin("cli".to_string())]);
+    /// ```
+    pub fn get_targets(&self) -> Vec<BuildTarget> {
+        let Some(package) = self.document.get("package") else { return Vec::new() };
+        let root = self.path.parent().unwrap_or(Path::new(""));
+        let auto = |key: &str| package.get(key).and_then(Item::as_bool).unwrap_or(true);
+        let declared = |key: &str| -> Vec<String> {
+            let tables = self.document.get(key).and_then(Item::as_array_of_tables);
+            tables.map(|tables| tables.iter().filter_map(|table| string(table.get("name"))).collect()).unwrap_or_default()
+        };
+
+        let mut targets = Vec::new();
+        let lib = self.get_lib();
+        let lib_path = lib.as_ref().and_then(|lib| lib.path.clone()).unwrap_or_else(|| PathBuf::from("src/lib.rs"));
+        if root.join(lib_path).is_file() || lib.is_some_and(|lib| lib.path.is_some()) {
+            targets.push(BuildTarget::Lib);
+        }
+        for (key, auto_key, directory) in TARGET_KINDS {
+            let mut names = declared(key);
+            if auto(auto_key) {
+                if key == "bin"
+                    && let Some(name) = self.get_name()
+                    && root.join("src/main.rs").is_file()
+                {
+                    names.push(name.to_string());
+                }
+                names.extend(discover(&root.join(directory)));
+            }
+            names.sort();
+            names.dedup();
+            targets.extend(names.into_iter().map(|name| match key {
+                "bin" => BuildTarget::Bin(name),
+                "example" => BuildTarget::Example(name),
+                "test" => BuildTarget::Test(name),
+                _ => BuildTarget::Bench(name),
+            }));
+        }
+        targets
+    }
+
+    /// Returns the `[workspace]` section, if declared
+    pub fn get_workspace(&self) -> Option<Workspace> {
+        let table = self.document.get("workspace")?.as_table_like()?;
+        Some(Workspace {
+            members: strings(table.get("members")),
+            exclude: strings(table.get("exclude")),
+            default_members: strings(table.get("default-members")),
+            resolver: string(table.get("resolver")),
+            version: self.get_workspace_version().map(String::from),
+            dependencies: read_dependencies(table.get("dependencies"), DependencyKind::Normal, None),
+        })
+    }
+
+    /// Returns the version shared by the members of the workspace (`workspace.package.version`)
+    pub fn get_workspace_version(&self) -> Option<&str> {
+        self.document.get("workspace")?.get("package")?.get("version")?.as_str()
+    }
+
+    /// Increments the version of the package in memory, see [`Manifest::save`].
+    ///
+    /// Virtual manifests, and manifests whose version is inherited, increment the version of
+    /// `[workspace.package]` instead.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The new version.
+    /// * `Err(CargoWrapError)` - If there is no version to increment.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::InvalidSettings`] if the manifest has no
+    /// version it declares itself, or the version is not `major.minor.patch`.
+    pub fn bump_version(&mut self, bump: Bump) -> Result<String> {
+        if let Some(version) = self.get_version() {
+            let version = bump.apply(version)?;
+            set_string(&mut self.document["package"]["version"], &version);
+            return Ok(version);
+        }
+        if let Some(version) = self.get_workspace_version() {
+            let version = bump.apply(version)?;
+            set_string(&mut self.document["workspace"]["package"]["version"], &version);
+            return Ok(version);
+        }
+        Err(CargoWrapError::InvalidSettings(format!("{} declares no version", self.path.display())))
+    }
+
+    /// Returns the directories of the members of the workspace rooted at this manifest.
+    ///
+    /// Globs in `workspace.members` and `workspace.exclude` may use `*` and `?` within a path
+    /// component; only directories containing a `Cargo.toml` are returned. The root package
+    /// itself is not included.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`] if a member directory cannot be read.
+    pub fn get_workspace_members(&self) -> Result<Vec<PathBuf>> {
+        let root = self.path.parent().unwrap_or(Path::new(""));
+        let patterns = |key: &str| -> Vec<String> {
+            let values = self.document.get("workspace").and_then(|workspace| workspace.get(key)).and_then(Item::as_array);
+            values.map(|values| values.iter().filter_map(|value| value.as_str().map(String::from)).collect()).unwrap_or_default()
+        };
+        let excluded: Vec<PathBuf> = patterns("exclude").iter().map(|pattern| expand(root, pattern)).collect::<Result<Vec<_>>>()?.concat();
+        let mut members = Vec::new();
+        for pattern in patterns("members") {
+            for directory in expand(root, &pattern)? {
+                let manifest = directory.join("Cargo.toml");
+                if manifest.is_file() && !excluded.contains(&directory) && !members.contains(&directory) && manifest != self.path {
+                    members.push(directory);
+                }
+            }
+        }
+        Ok(members)
+    }
+
+    /// Adds `member`, a directory relative to this manifest, to `workspace.members` in memory.
+    ///
+    /// A `[workspace]` section is created if the manifest has none, which turns the package into
+    /// the root of a workspace. The member is inserted in order if the members are sorted, and
+    /// appended otherwise; multi-line arrays keep one member per line with their indentation.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - If the member was added.
+    /// * `Ok(false)` - If the member was already listed.
+    /// * `Err(CargoWrapError)` - If `workspace` or `workspace.members` is not a table or an array.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::InvalidSettings`] if `workspace` is not a table
+    /// or `workspace.members` is not an array.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::Manifest;
+    ///
+    /// let contents = "[workspace]\nmembers = [\n    # libraries\n    \"core\",\n    \"net\",\n]\n";
+    /// let mut manifest = Manifest::parse("Cargo.toml", contents).unwrap();
+    /// assert!(manifest.add_workspace_member("cli").unwrap());
+    /// assert!(!manifest.add_workspace_member("net/").unwrap());
+    /// assert_eq!(manifest.to_string(), "[workspace]\nmembers = [\n    \"cli\",\n    # libraries\n    \"core\",\n    \"net\",\n]\n");
+    /// ```
+    pub fn add_workspace_member(&mut self, member: &str) -> Result<bool> {
+        let member = normalize_member(member);
+        let not_an = |key: &str, kind: &str| CargoWrapError::InvalidSettings(format!("{} is not {} in {}", key, kind, self.path.display()));
+        let workspace = self.document.entry("workspace").or_insert_with(|| Item::Table(Table::new()));
+        let workspace = workspace.as_table_like_mut().ok_or_else(|| not_an("workspace", "a table"))?;
+        let members = workspace.entry("members").or_insert_with(|| Item::Value(Value::Array(Array::new())));
+        let members = members.as_array_mut().ok_or_else(|| not_an("workspace.members", "an array"))?;
+        if members.iter().any(|value| value.as_str().is_some_and(|value| normalize_member(value) == member)) {
+            return Ok(false);
+        }
+        let values: Vec<&str> = members.iter().filter_map(Value::as_str).collect();
+        let index = if values.is_sorted() {
+            values.iter().position(|value| *value > member.as_str()).unwrap_or(members.len())
+        } else {
+            members.len()
+        };
+        match multiline_indent(members) {
+            Some(indent) => {
+                let mut value = Value::from(member.as_str());
+                value.decor_mut().set_prefix(format!("\n{}", indent));
+                members.insert_formatted(index, value);
+            }
+            None => {
+                members.insert(index, member.as_str());
+                members.fmt();
+            }
+        }
+        Ok(true)
+    }
+
+    /// Removes `member` from `workspace.members` and `workspace.default-members` in memory. The
+    /// directory of the member is left untouched.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the member was listed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::Manifest;
+    ///
+    /// let mut manifest = Manifest::parse("Cargo.toml", "[workspace]\nmembers = [\"core\", \"cli\"] # all crates\n").unwrap();
+    /// assert!(manifest.remove_workspace_member("core"));
+    /// assert_eq!(manifest.to_string(), "[workspace]\nmembers = [\"cli\"] # all crates\n");
+    /// ```
+    pub fn remove_workspace_member(&mut self, member: &str) -> bool {
+        let member = normalize_member(member);
+        let Some(workspace) = self.document.get_mut("workspace").and_then(Item::as_table_like_mut) else { return false };
+        let mut removed = false;
+        for key in ["members", "default-members"] {
+            let Some(values) = workspace.get_mut(key).and_then(Item::as_array_mut) else { continue };
+            let len = values.len();
+            values.retain(|value| value.as_str().is_none_or(|value| normalize_member(value) != member));
+            if values.len() == len {
+                continue;
+            }
+            if key == "members" {
+                removed = true;
+            }
+            if multiline_indent(values).is_none() {
+                values.fmt();
+            }
+        }
+        removed
+    }
+
+    /// Turns the standalone package in `package_dir` into a member of the workspace rooted at
+    /// this manifest, then saves the changed manifests.
+    ///
+    /// The package must be below the workspace root. Its own `[workspace]` section, which makes
+    /// it the root of a workspace, is removed, and its `Cargo.lock` is moved to the workspace
+    /// root if the workspace has none yet, so the versions it locked are kept. The package is
+    /// then added to `workspace.members` with [`Manifest::add_workspace_member`].
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The member as listed in `workspace.members`, e.g. `crates/core`.
+    /// * `Err(CargoWrapError)` - If the package cannot be converted.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `package_dir` is not below the workspace root, or has no `[package]` ([`CargoWrapError::InvalidSettings`]).
+    /// - A manifest cannot be read or written, or the lockfile cannot be moved ([`CargoWrapError::Io`]).
+    /// - The manifest of the package is not valid TOML ([`CargoWrapError::ManifestParse`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::Manifest;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut workspace = Manifest::new_workspace("/path/to/monorepo/Cargo.toml");
+    ///     let member = workspace.adopt_package("/path/to/monorepo/tools/generator")?;
+    ///     assert_eq!(member, "tools/generator");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn adopt_package(&mut self, package_dir: impl AsRef<Path>) -> Result<String> {
+        let root = std::path::absolute(self.path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new(".")))?;
+        let package_dir = std::path::absolute(package_dir.as_ref())?;
+        let relative = match package_dir.strip_prefix(&root) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => {
+                return Err(CargoWrapError::InvalidSettings(format!(
+                    "{} is not below the workspace root {}",
+                    package_dir.display(),
+                    root.display()
+                )));
+            }
+        };
+        let member = relative.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+        let mut package = Manifest::open(package_dir.join("Cargo.toml"))?;
+        if package.get_package().is_none() {
+            return Err(CargoWrapError::InvalidSettings(format!("{} has no [package]", package.path.display())));
+        }
+        if package.document.remove("workspace").is_some() {
+            package.save()?;
+        }
+        let lockfile = package_dir.join("Cargo.lock");
+        if lockfile.is_file() && !root.join("Cargo.lock").exists() {
+            fs::rename(&lockfile, root.join("Cargo.lock"))?;
+        }
+        self.add_workspace_member(&member)?;
+        self.save()?;
+        Ok(member)
+    }
+
+    /// Increments the version of every package of the workspace rooted at this manifest, and the
+    /// requirements on them, then saves every changed manifest.
+    ///
+    /// Each package declaring its own version is bumped independently; packages inheriting
+    /// their version take the new `[workspace.package]` version. Path dependencies between the
+    /// packages (including `[workspace.dependencies]`) get their version requirement updated,
+    /// keeping the operator, e.g. `^0.3.1` becomes `^0.4.0`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BTreeMap<String, String>)` - The new version of every package, by name.
+    /// * `Err(CargoWrapError)` - If a manifest cannot be read, bumped or written.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - A member manifest cannot be read or written ([`CargoWrapError::Io`]).
+    /// - A member manifest is not valid TOML ([`CargoWrapError::ManifestParse`]).
+    /// - A version is not `major.minor.patch`, or a package inherits a version the workspace does
+    ///   not declare ([`CargoWrapError::InvalidSettings`]).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Bump, Manifest};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let mut manifest = Manifest::open("/path/to/workspace/Cargo.toml")?;
+    ///     for (package, version) in manifest.bump_workspace(Bump::Patch)? {
+    ///         println!("{} -> {}", package, version);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn bump_workspace(&mut self, bump: Bump) -> Result<BTreeMap<String, String>> {
+        let mut members = self.get_workspace_members()?.into_iter().map(|member| Manifest::open(member.join("Cargo.toml"))).collect::<Result<Vec<_>>>()?;
+        let workspace_version = match self.get_workspace_version() {
+            Some(version) => {
+                let version = bump.apply(version)?;
+                set_string(&mut self.document["workspace"]["package"]["version"], &version);
+                Some(version)
+            }
+            None => None,
+        };
+        let mut versions = BTreeMap::new();
+        for manifest in std::iter::once(&mut *self).chain(members.iter_mut()) {
+            let Some(name) = manifest.get_name().map(String::from) else { continue };
+            let version = if manifest.is_version_inherited() {
+                workspace_version.clone().ok_or_else(|| {
+                    CargoWrapError::InvalidSettings(format!("{} inherits a version the workspace does not declare", manifest.path.display()))
+                })?
+            } else if manifest.get_version().is_some() {
+                manifest.bump_version(bump)?
+            } else {
+                continue;
+            };
+            versions.insert(name, version);
+        }
+        for manifest in std::iter::once(&mut *self).chain(members.iter_mut()) {
+            manifest.update_requirements(&versions);
+        }
+        self.save()?;
+        for manifest in &members {
+            manifest.save()?;
+        }
+        Ok(versions)
+    }
+
+    /// Adds `name` to the dependencies in memory, replacing an existing declaration in the same table.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name the dependency is declared under.
+    /// * `spec` - The source, features and table of the dependency.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::InvalidSettings`] if:
+    /// - `spec` has neither a version, a path nor a git URL.
+    /// - The table the dependency belongs in is not a table in the manifest.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{DependencySpec, Manifest};
+    ///
+    /// let mut manifest = Manifest::parse("Cargo.toml", "[package]\nname = \"app\"\n\n[dependencies]\nlog = \"0.4\"\n").unwrap();
+    /// let serde = DependencySpec { version: Some("1.0".to_string()), features: vec!["derive".to_string()], ..Default::default() };
+    /// manifest.add_dependency("serde", serde).unwrap();
+    /// assert!(manifest.to_string().ends_with("log = \"0.4\"\nserde = { version = \"1.0\", features = [\"derive\"] }\n"));
+    /// ```
+    pub fn add_dependency(&mut self, name: &str, spec: DependencySpec) -> Result<()> {
+        if spec.version.is_none() && spec.path.is_none() && spec.git.is_none() {
+            return Err(CargoWrapError::InvalidSettings(format!("dependency {} has no version, path or git source", name)));
+        }
+        let not_a_table = |key: &str| CargoWrapError::InvalidSettings(format!("{} is not a table in {}", key, self.path.display()));
+        let root = self.document.as_table_mut();
+        let parent: &mut dyn TableLike = match &spec.target {
+            Some(target) => {
+                let targets = root.entry("target").or_insert_with(implicit_table).as_table_like_mut().ok_or_else(|| not_a_table("target"))?;
+                targets.entry(target).or_insert_with(implicit_table).as_table_like_mut().ok_or_else(|| not_a_table(target))?
+            }
+            None => root,
+        };
+        let key = spec.kind.table_name();
+        let table = parent.entry(key).or_insert_with(|| Item::Table(Table::new())).as_table_like_mut().ok_or_else(|| not_a_table(key))?;
+        match table.get_mut(name) {
+            Some(existing) => replace_value(existing, spec.to_value()),
+            None => {
+                let sorted = table.iter().map(|(key, _)| key).is_sorted();
+                table.insert(name, Item::Value(spec.to_value()));
+                if sorted {
+                    table.sort_values();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes `name` from every dependency table in memory, including target-specific tables.
+    ///
+    /// References to the dependency in `[features]` (`dep:name`, `name/feature`) are removed as well.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the dependency was declared.
+    pub fn remove_dependency(&mut self, name: &str) -> bool {
+        let mut removed = false;
+        for_each_dependency_table(&mut self.document, false, &mut |table| removed |= table.remove(name).is_some());
+        if !removed {
+            return removed;
+        }
+        let Some(features) = self.document.get_mut("features").and_then(Item::as_table_like_mut) else { return removed };
+        // A plain `name` value refers to the feature rather than the dependency if one exists
+        let is_feature = features.contains_key(name);
+        for (_, values) in features.iter_mut() {
+            if let Some(values) = values.as_array_mut() {
+                let len = values.len();
+                values.retain(|value| {
+                    let Some(value) = value.as_str() else { return true };
+                    let dependency = value.strip_prefix("dep:").unwrap_or(value).split(['/', '?']).next().unwrap_or_default();
+                    dependency != name || (value == name && is_feature)
+                });
+                if values.len() != len {
+                    values.fmt();
+                }
+            }
+        }
+        removed
+    }
+
+    /// Sets the version requirement of `name` in every dependency table in memory, including
+    /// `[workspace.dependencies]`. Declarations inherited from the workspace are left alone.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the dependency was declared.
+    pub fn set_dependency_version(&mut self, name: &str, version: &str) -> bool {
+        let mut found = false;
+        for_each_dependency_table(&mut self.document, true, &mut |table| {
+            let Some(dependency) = table.get_mut(name) else { return };
+            found = true;
+            if dependency.is_str() {
+                set_string(dependency, version);
+            } else if let Some(dependency) = dependency.as_table_like_mut().filter(|dependency| !dependency.contains_key("workspace")) {
+                match dependency.get_mut("version") {
+                    Some(requirement) => set_string(requirement, version),
+                    None => {
+                        dependency.insert("version", toml_edit::value(version));
+                    }
+                }
+            }
+        });
+        found
+    }
+
+    /// Private function that sets the version requirement of every path dependency on one of
+    /// `versions` to its new version
+    fn update_requirements(&mut self, versions: &BTreeMap<String, String>) {
+        for_each_dependency_table(&mut self.document, true, &mut |table| {
+            for (key, dependency) in table.iter_mut() {
+                let Some(dependency) = dependency.as_table_like_mut().filter(|dependency| dependency.contains_key("path")) else {
+                    continue;
+                };
+                let package = dependency.get("package").and_then(Item::as_str).unwrap_or(key.get());
+                let Some(version) = versions.get(package) else { continue };
+                let Some(requirement) = dependency.get_mut("version") else { continue };
+                if let Some(updated) = requirement.as_str().and_then(|requirement| bump_requirement(requirement, version)) {
+                    set_string(requirement, &updated);
+                }
+            }
+        });
+    }
+
+    /// Writes the manifest back to its path.
+    ///
+    /// The manifest is written to a temporary file next to it first, which then replaces the
+    /// manifest, so readers never observe a partially written file.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`] if the file cannot be written.
+    pub fn save(&self) -> Result<()> {
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        fs::write(&temporary, self.document.to_string())?;
+        if let Err(e) = fs::rename(&temporary, &self.path) {
+            let _ = fs::remove_file(&temporary);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.document)
+    }
+}
+
+/// Private function that returns the string at `item`
+fn string(item: Option<&Item>) -> Option<String> {
+    item?.as_str().map(String::from)
+}
+
+/// Private function that returns the names of the targets cargo discovers in `directory`:
+/// its `<name>.rs` files and `<name>/main.rs` directories
+fn discover(directory: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(directory) else { return Vec::new() };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rs") && path.is_file() || path.join("main.rs").is_file())
+        .filter_map(|path| path.file_stem().map(|stem| stem.to_string_lossy().into_owned()))
+        .collect()
+}
+
+/// Private function that returns a workspace member without a leading `./` and trailing `/`,
+/// with `/` separators
+fn normalize_member(member: &str) -> String {
+    let member = member.replace('\\', "/");
+    member.trim_start_matches("./").trim_end_matches('/').to_string()
+}
+
+/// Private function that returns the indentation of the values of `array` if they are written
+/// one per line
+fn multiline_indent(array: &Array) -> Option<String> {
+    array.iter().find_map(|value| {
+        let prefix = value.decor().prefix()?.as_str()?;
+        prefix.rsplit_once('\n').map(|(_, indent)| indent.to_string())
+    })
+}
+
+/// Private function that returns the strings of the array at `item`
+fn strings(item: Option<&Item>) -> Vec<String> {
+    let values = item.and_then(Item::as_array);
+    values.map(|values| values.iter().filter_map(|value| value.as_str().map(String::from)).collect()).unwrap_or_default()
+}
+
+/// Private function that reads the dependencies of the table at `item`
+fn read_dependencies(item: Option<&Item>, kind: DependencyKind, target: Option<&str>) -> Vec<Dependency> {
+    let Some(table) = item.and_then(Item::as_table_like) else { return Vec::new() };
+    let mut dependencies = Vec::new();
+    for (name, item) in table.iter() {
+        let mut spec = DependencySpec { kind, target: target.map(String::from), ..Default::default() };
+        let mut inherited = false;
+        if let Some(version) = item.as_str() {
+            spec.version = Some(version.to_string());
+        } else if let Some(fields) = item.as_table_like() {
+            spec.version = string(fields.get("version"));
+            spec.path = string(fields.get("path")).map(PathBuf::from);
+            spec.git = string(fields.get("git"));
+            spec.branch = string(fields.get("branch"));
+            spec.tag = string(fields.get("tag"));
+            spec.rev = string(fields.get("rev"));
+            spec.package = string(fields.get("package"));
+            spec.features = strings(fields.get("features"));
+            spec.optional = fields.get("optional").and_then(Item::as_bool).unwrap_or(false);
+            spec.default_features = fields.get("default-features").and_then(Item::as_bool).unwrap_or(true);
+            inherited = fields.get("workspace").and_then(Item::as_bool).unwrap_or(false);
+        }
+        dependencies.push(Dependency { name: name.to_string(), spec, inherited });
+    }
+    dependencies
+}
+
+/// Private function that sets `key` of `table` to `value`, removing it if `value` is `None`.
+///
+/// Values that are unchanged keep their formatting.
+fn set_field(table: &mut dyn TableLike, key: &str, value: Option<Value>) {
+    match (table.get_mut(key), value) {
+        (Some(existing), Some(value)) => {
+            if !existing.as_value().is_some_and(|existing| same_value(existing, &value)) {
+                replace_value(existing, value);
+            }
+        }
+        (None, Some(value)) => {
+            table.insert(key, Item::Value(value));
+        }
+        (Some(_), None) => {
+            table.remove(key);
+        }
+        (None, None) => {}
+    }
+}
+
+/// Private function that compares two values, ignoring their formatting
+fn same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_value(a, b)),
+        _ => false,
+    }
+}
+
+/// Private function that replaces the string at `item`, keeping its surrounding whitespace and comments
+fn set_string(item: &mut Item, value: &str) {
+    replace_value(item, Value::from(value));
+}
+
+/// Private function that replaces the value at `item`, keeping its surrounding whitespace and comments
+fn replace_value(item: &mut Item, mut value: Value) {
+    match item.as_value_mut() {
+        Some(old) => {
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+        }
+        None => *item = Item::Value(value),
+    }
+}
+
+/// Private function that creates a table only shown through its subtables, e.g. `[target]`
+fn implicit_table() -> Item {
+    let mut table = Table::new();
+    table.set_implicit(true);
+    Item::Table(table)
+}
+
+/// Private function that points a version requirement at `version`, keeping its operator.
+///
+/// Returns `None` for requirements with several comparators or wildcards, which are left alone.
+fn bump_requirement(requirement: &str, version: &str) -> Option<String> {
+    let requirement = requirement.trim();
+    if requirement.contains([',', '*']) {
+        return None;
+    }
+    let operator = &requirement[..requirement.find(|c: char| c.is_ascii_digit())?];
+    Some(format!("{}{}", operator.trim(), version))
+}
+
+/// Private function that calls `f` with every dependency table of `document`, including
+/// target-specific tables, and `[workspace.dependencies]` if `workspace` is set
+fn for_each_dependency_table(document: &mut DocumentMut, workspace: bool, f: &mut dyn FnMut(&mut dyn TableLike)) {
+    let root = document.as_table_mut();
+    for key in DEPENDENCY_TABLES {
+        if let Some(table) = root.get_mut(key).and_then(Item::as_table_like_mut) {
+            f(table);
+        }
+    }
+    if let Some(targets) = root.get_mut("target").and_then(Item::as_table_like_mut) {
+        for (_, target) in targets.iter_mut() {
+            for key in DEPENDENCY_TABLES {
+                if let Some(table) = target.get_mut(key).and_then(Item::as_table_like_mut) {
+                    f(table);
+                }
+            }
+        }
+    }
+    if !workspace {
+        return;
+    }
+    if let Some(table) = root.get_mut("workspace").and_then(|workspace| workspace.get_mut("dependencies")).and_then(Item::as_table_like_mut) {
+        f(table);
+    }
+}
+
+/// Private function that returns the directories below `root` matching the glob `pattern`
+fn expand(root: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let mut directories = vec![root.to_path_buf()];
+    for component in pattern.split('/').filter(|component| !component.is_empty() && *component != ".") {
+        if !component.contains(['*', '?']) {
+            directories = directories.into_iter().map(|directory| directory.join(component)).filter(|directory| directory.is_dir()).collect();
+            continue;
+        }
+        let mut matches = Vec::new();
+        for directory in directories {
+            for entry in fs::read_dir(&directory)? {
+                let entry = entry?;
+                if entry.file_type()?.is_dir() && glob_match(component.as_bytes(), entry.file_name().as_encoded_bytes()) {
+                    matches.push(entry.path());
+                }
+            }
+        }
+        matches.sort();
+        directories = matches;
+    }
+    Ok(directories)
+}
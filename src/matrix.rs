@@ -0,0 +1,204 @@
+use crate::{BuildOutput, Builder, BuilderConfig, ProjectSettings, Result};
+use std::fmt;
+use std::path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+/// Builds a project for every combination of compilation targets, feature sets and profiles.
+///
+/// Every axis is optional: an empty axis keeps the corresponding setting of the base
+/// [`ProjectSettings`]. Cells are built in order, or concurrently with
+/// [`BuildMatrix::parallelism`]. Cells sharing a target directory wait for each other's lock, so
+/// parallel matrices usually want [`BuildMatrix::isolate_target_dirs`].
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{BuildMatrix, ProjectSettings};
+///
+/// let matrix = BuildMatrix::new(ProjectSettings::new("/path/to/project"))
+///     .targets(["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"])
+///     .feature_set(Vec::<String>::new())
+///     .feature_set(["tls"])
+///     .profiles(["dev", "release"])
+///     .parallelism(2);
+/// for result in matrix.run() {
+///     match result.result {
+///         Ok(output) => println!("{}: {} warnings", result.cell, output.warning_count()),
+///         Err(error) => println!("{}: {}", result.cell, error),
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BuildMatrix {
+    settings: ProjectSettings,
+    config: BuilderConfig,
+    targets: Vec<String>,
+    feature_sets: Vec<Vec<String>>,
+    profiles: Vec<String>,
+    parallelism: usize,
+    isolate_target_dirs: bool,
+}
+
+/// One combination of a [`BuildMatrix`].
+///
+/// # Fields
+///
+/// * `target` - The compilation target triple, `None` meaning the base settings' target.
+/// * `features` - The features enabled on top of the base settings' features.
+/// * `profile` - The profile, `None` meaning the base configuration's profile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixCell {
+    pub target: Option<String>,
+    pub features: Vec<String>,
+    pub profile: Option<String>,
+}
+
+/// The result of building one cell of a [`BuildMatrix`].
+///
+/// # Fields
+///
+/// * `cell` - The combination that was built.
+/// * `result` - The output of the build, or the error it failed with.
+#[derive(Debug)]
+pub struct MatrixResult {
+    pub cell: MatrixCell,
+    pub result: Result<BuildOutput>,
+}
+
+impl fmt::Display for MatrixCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.target.as_deref().unwrap_or("host"))?;
+        write!(f, " [{}]", self.features.join(","))?;
+        if let Some(profile) = &self.profile {
+            write!(f, " {}", profile)?;
+        }
+        Ok(())
+    }
+}
+
+impl BuildMatrix {
+    /// Creates a new `BuildMatrix` from the base settings shared by every cell
+    pub fn new(settings: ProjectSettings) -> Self {
+        BuildMatrix {
+            settings,
+            config: BuilderConfig::default(),
+            targets: Vec::new(),
+            feature_sets: Vec::new(),
+            profiles: Vec::new(),
+            parallelism: 1,
+            isolate_target_dirs: false,
+        }
+    }
+
+    /// Sets the builder configuration shared by every cell (jobs, rustc flags, toolchain, ...).
+    /// Its project settings are replaced by those of the cells.
+    pub fn builder_config(mut self, config: BuilderConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Adds compilation target triples to the target axis
+    pub fn targets(mut self, targets: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.targets.extend(targets.into_iter().map(Into::into));
+        self
+    }
+
+    /// Adds a set of features to the feature axis. An empty set builds the base features only.
+    pub fn feature_set(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.feature_sets.push(features.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Adds profiles (e.g. `dev`, `release` or custom profiles) to the profile axis
+    pub fn profiles(mut self, profiles: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.profiles.extend(profiles.into_iter().map(Into::into));
+        self
+    }
+
+    /// Sets how many cells are built at the same time. Defaults to 1.
+    pub fn parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Sets whether every cell builds into its own target directory (`<target dir>/matrix-<index>`),
+    /// so parallel cells do not wait for each other
+    pub fn isolate_target_dirs(mut self, isolate_target_dirs: bool) -> Self {
+        self.isolate_target_dirs = isolate_target_dirs;
+        self
+    }
+
+    /// Returns every combination of the axes, in the order they are built
+    pub fn cells(&self) -> Vec<MatrixCell> {
+        let targets: Vec<Option<String>> = axis(&self.targets);
+        let feature_sets: Vec<Vec<String>> = if self.feature_sets.is_empty() { vec![Vec::new()] } else { self.feature_sets.clone() };
+        let profiles: Vec<Option<String>> = axis(&self.profiles);
+        let mut cells = Vec::with_capacity(targets.len() * feature_sets.len() * profiles.len());
+        for target in &targets {
+            for features in &feature_sets {
+                for profile in &profiles {
+                    cells.push(MatrixCell { target: target.clone(), features: features.clone(), profile: profile.clone() });
+                }
+            }
+        }
+        cells
+    }
+
+    /// Builds every cell with [`Builder::build`].
+    ///
+    /// # Returns
+    ///
+    /// The result of every cell, in the order of [`BuildMatrix::cells`]. A failing cell does not
+    /// stop the others.
+    pub fn run(&self) -> Vec<MatrixResult> {
+        let cells = self.cells();
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..self.parallelism.min(cells.len()) {
+                let sender = sender.clone();
+                let (cells, next) = (&cells, &next);
+                scope.spawn(move || {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(cell) = cells.get(index) else { break };
+                        let _ = sender.send((index, self.build_cell(index, cell)));
+                    }
+                });
+            }
+        });
+        drop(sender);
+        let mut results: Vec<Option<Result<BuildOutput>>> = cells.iter().map(|_| None).collect();
+        for (index, result) in receiver {
+            results[index] = Some(result);
+        }
+        cells
+            .into_iter()
+            .zip(results)
+            .map(|(cell, result)| MatrixResult { cell, result: result.expect("every cell is built") })
+            .collect()
+    }
+
+    /// Private function that builds a single cell
+    fn build_cell(&self, index: usize, cell: &MatrixCell) -> Result<BuildOutput> {
+        let mut settings = self.settings.clone().features(cell.features.clone());
+        if let Some(target) = &cell.target {
+            settings = settings.compilation_target(target);
+        }
+        let mut config = self.config.clone();
+        config.profile = cell.profile.clone().or(config.profile);
+        config.project_settings = settings;
+        let mut builder = Builder::from_config(config)?;
+        if self.isolate_target_dirs {
+            let target_dir = path::absolute(builder.target_dir())?.join(format!("matrix-{}", index));
+            builder.project_settings.set_output_path(target_dir);
+        }
+        builder.build()
+    }
+}
+
+/// Private function that turns an axis into its values, an empty axis keeping the base setting
+fn axis(values: &[String]) -> Vec<Option<String>> {
+    if values.is_empty() { vec![None] } else { values.iter().cloned().map(Some).collect() }
+}
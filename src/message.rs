@@ -0,0 +1,122 @@
+use crate::{Artifact, BuildScriptOutput, Diagnostic, DiagnosticLevel, DiagnosticSpan};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// A JSON message emitted by cargo with `--message-format=json*`.
+///
+/// Only the messages `cargo_wrap` uses are modelled, every other message is ignored.
+#[derive(Deserialize)]
+#[serde(tag = "reason", rename_all = "kebab-case")]
+pub(crate) enum Message {
+    CompilerArtifact(ArtifactMessage),
+    CompilerMessage(CompilerMessage),
+    BuildScriptExecuted(BuildScriptMessage),
+    #[serde(other)]
+    Other,
+}
+
+impl Message {
+    /// Private function that parses one line of cargo's stdout, returning `None` for non-JSON lines
+    pub(crate) fn parse(line: &str) -> Option<Message> {
+        if !line.starts_with('{') {
+            return None;
+        }
+        serde_json::from_str(line).ok()
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ArtifactMessage {
+    package_id: String,
+    target: ArtifactTarget,
+    filenames: Vec<PathBuf>,
+    executable: Option<PathBuf>,
+    fresh: bool,
+    #[serde(default)]
+    manifest_path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct ArtifactTarget {
+    name: String,
+    kind: Vec<String>,
+}
+
+impl From<ArtifactMessage> for Artifact {
+    fn from(message: ArtifactMessage) -> Self {
+        Artifact {
+            package_id: message.package_id,
+            target_name: message.target.name,
+            kind: message.target.kind,
+            filenames: message.filenames,
+            executable: message.executable,
+            fresh: message.fresh,
+            manifest_path: message.manifest_path,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CompilerMessage {
+    package_id: String,
+    message: RustcDiagnostic,
+}
+
+#[derive(Deserialize)]
+struct RustcDiagnostic {
+    message: String,
+    code: Option<RustcCode>,
+    level: DiagnosticLevel,
+    spans: Vec<DiagnosticSpan>,
+    children: Vec<RustcDiagnostic>,
+    rendered: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RustcCode {
+    code: String,
+}
+
+impl RustcDiagnostic {
+    fn into_diagnostic(self, package_id: &str) -> Diagnostic {
+        Diagnostic {
+            package_id: package_id.to_string(),
+            level: self.level,
+            message: self.message,
+            code: self.code.map(|c| c.code),
+            spans: self.spans,
+            children: self.children.into_iter().map(|c| c.into_diagnostic(package_id)).collect(),
+            rendered: self.rendered,
+        }
+    }
+}
+
+impl From<CompilerMessage> for Diagnostic {
+    fn from(message: CompilerMessage) -> Self {
+        message.message.into_diagnostic(&message.package_id)
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct BuildScriptMessage {
+    package_id: String,
+    linked_libs: Vec<String>,
+    linked_paths: Vec<String>,
+    cfgs: Vec<String>,
+    env: Vec<(String, String)>,
+    out_dir: PathBuf,
+}
+
+impl From<BuildScriptMessage> for BuildScriptOutput {
+    fn from(message: BuildScriptMessage) -> Self {
+        BuildScriptOutput {
+            package_id: message.package_id,
+            out_dir: message.out_dir,
+            linked_libs: message.linked_libs,
+            linked_paths: message.linked_paths,
+            cfgs: message.cfgs,
+            env: message.env,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::DependencyKind;
+use serde::{Deserialize, Deserializer};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// The packages and resolved dependency graph of a project, as reported by
+/// `cargo metadata --format-version 1`, see [`Builder::metadata`](crate::Builder::metadata).
+///
+/// # Fields
+///
+/// * `version` - The version of the metadata format.
+/// * `packages` - Every package of the dependency graph, including the workspace members.
+/// * `workspace_members` - The package IDs of the workspace members.
+/// * `workspace_default_members` - The package IDs of the default workspace members, empty with older cargo versions.
+/// * `resolve` - Optional resolved dependency graph, `None` if it was not requested.
+/// * `workspace_root` - The root directory of the workspace.
+/// * `target_directory` - The directory build outputs are written to.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     let metadata = builder.metadata()?;
+///     for package in metadata.workspace_packages() {
+///         let bins = package.targets.iter().filter(|target| target.is_bin()).map(|target| target.name.as_str());
+///         println!("{} {}: {}", package.name, package.version, bins.collect::<Vec<_>>().join(", "));
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Metadata {
+    pub version: u32,
+    pub packages: Vec<MetadataPackage>,
+    pub workspace_members: Vec<String>,
+    #[serde(default)]
+    pub workspace_default_members: Vec<String>,
+    pub resolve: Option<Resolve>,
+    pub workspace_root: PathBuf,
+    pub target_directory: PathBuf,
+}
+
+/// A package of the dependency graph.
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `version` - The exact version of the package.
+/// * `id` - The cargo package ID, used to refer to the package in the resolve graph.
+/// * `source` - Optional source of the package, `None` for path dependencies and workspace members.
+/// * `license` - Optional SPDX license expression of the package.
+/// * `license_file` - Optional path of the license file of the package.
+/// * `description` - Optional description of the package.
+/// * `repository` - Optional repository URL of the package.
+/// * `edition` - The Rust edition of the package.
+/// * `rust_version` - Optional minimum supported Rust version of the package.
+/// * `authors` - The authors of the package.
+/// * `dependencies` - The dependencies declared in the manifest, before resolution.
+/// * `targets` - The targets of the package.
+/// * `features` - The features of the package and what they enable.
+/// * `manifest_path` - Path of the package's `Cargo.toml`.
+/// * `links` - Optional name of the native library the package links, see the `links` manifest key.
+/// * `publish` - Optional registries the package may be published to; an empty list means it is never published.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MetadataPackage {
+    pub name: String,
+    pub version: String,
+    pub id: String,
+    pub source: Option<String>,
+    pub license: Option<String>,
+    pub license_file: Option<PathBuf>,
+    pub description: Option<String>,
+    pub repository: Option<String>,
+    pub edition: String,
+    pub rust_version: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    pub dependencies: Vec<MetadataDependency>,
+    pub targets: Vec<MetadataTarget>,
+    pub features: BTreeMap<String, Vec<String>>,
+    pub manifest_path: PathBuf,
+    pub links: Option<String>,
+    pub publish: Option<Vec<String>>,
+}
+
+/// A dependency as declared in the manifest of a [`MetadataPackage`].
+///
+/// # Fields
+///
+/// * `name` - The name of the depended-on package.
+/// * `source` - Optional source of the dependency, `None` for path dependencies.
+/// * `req` - The version requirement, e.g. `^1.0`.
+/// * `kind` - The table the dependency is declared in.
+/// * `rename` - Optional name the dependency is renamed to (`package = "..."`).
+/// * `optional` - `true` if the dependency is optional.
+/// * `uses_default_features` - `true` unless the default features are disabled.
+/// * `features` - The features enabled on the dependency.
+/// * `target` - Optional platform the dependency is restricted to, e.g. `cfg(windows)`.
+/// * `path` - Optional path of a path dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MetadataDependency {
+    pub name: String,
+    pub source: Option<String>,
+    pub req: String,
+    #[serde(deserialize_with = "dependency_kind")]
+    pub kind: DependencyKind,
+    pub rename: Option<String>,
+    pub optional: bool,
+    pub uses_default_features: bool,
+    pub features: Vec<String>,
+    pub target: Option<String>,
+    pub path: Option<PathBuf>,
+}
+
+/// A target of a [`MetadataPackage`].
+///
+/// # Fields
+///
+/// * `name` - The name of the target.
+/// * `kind` - The target kinds, e.g. `["bin"]`, `["lib"]`, `["example"]` or `["custom-build"]`.
+/// * `crate_types` - The crate types produced for the target.
+/// * `src_path` - Path of the target's root source file.
+/// * `edition` - The Rust edition of the target.
+/// * `required_features` - The features required to build the target.
+/// * `doctest` - `true` if the documentation examples of the target are tested.
+/// * `test` - `true` if the target is tested by `cargo test`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MetadataTarget {
+    pub name: String,
+    pub kind: Vec<String>,
+    pub crate_types: Vec<String>,
+    pub src_path: PathBuf,
+    pub edition: String,
+    #[serde(default, rename = "required-features")]
+    pub required_features: Vec<String>,
+    #[serde(default)]
+    pub doctest: bool,
+    #[serde(default)]
+    pub test: bool,
+}
+
+/// The resolved dependency graph of a [`Metadata`].
+///
+/// # Fields
+///
+/// * `nodes` - One node per package of the graph.
+/// * `root` - Optional package ID of the root package, `None` for virtual workspaces.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Resolve {
+    pub nodes: Vec<ResolveNode>,
+    pub root: Option<String>,
+}
+
+/// A package of the resolved dependency graph.
+///
+/// # Fields
+///
+/// * `id` - The package ID of the package.
+/// * `deps` - The resolved dependencies of the package.
+/// * `features` - The features enabled on the package.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ResolveNode {
+    pub id: String,
+    #[serde(default)]
+    pub deps: Vec<NodeDependency>,
+    #[serde(default)]
+    pub features: Vec<String>,
+}
+
+/// An edge of the resolved dependency graph.
+///
+/// # Fields
+///
+/// * `name` - The name the dependency is available as in the dependent package.
+/// * `pkg` - The package ID of the dependency.
+/// * `dep_kinds` - The tables, and platforms, the dependency is declared in.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NodeDependency {
+    pub name: String,
+    pub pkg: String,
+    #[serde(default)]
+    pub dep_kinds: Vec<DependencyKindInfo>,
+}
+
+/// How a [`NodeDependency`] is declared.
+///
+/// # Fields
+///
+/// * `kind` - The table the dependency is declared in.
+/// * `target` - Optional platform the dependency is restricted to.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DependencyKindInfo {
+    #[serde(deserialize_with = "dependency_kind")]
+    pub kind: DependencyKind,
+    pub target: Option<String>,
+}
+
+impl Metadata {
+    /// Returns the package with the package ID `id`
+    pub fn package(&self, id: &str) -> Option<&MetadataPackage> {
+        self.packages.iter().find(|package| package.id == id)
+    }
+
+    /// Returns every package named `name`, one per version in the graph
+    pub fn packages_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a MetadataPackage> {
+        self.packages.iter().filter(move |package| package.name == name)
+    }
+
+    /// Returns the packages of the workspace members
+    pub fn workspace_packages(&self) -> impl Iterator<Item = &MetadataPackage> {
+        self.packages.iter().filter(|package| self.workspace_members.contains(&package.id))
+    }
+
+    /// Returns the root package, `None` for virtual workspaces or if the graph was not resolved
+    pub fn root_package(&self) -> Option<&MetadataPackage> {
+        self.package(self.resolve.as_ref()?.root.as_deref()?)
+    }
+
+    /// Returns the resolve node of the package with the package ID `id`
+    pub fn node(&self, id: &str) -> Option<&ResolveNode> {
+        self.resolve.as_ref()?.nodes.iter().find(|node| node.id == id)
+    }
+}
+
+impl MetadataTarget {
+    /// Returns `true` if the target is a binary
+    pub fn is_bin(&self) -> bool {
+        self.kind.iter().any(|kind| kind == "bin")
+    }
+
+    /// Returns `true` if the target is a library of any crate type, including proc-macros
+    pub fn is_lib(&self) -> bool {
+        self.kind.iter().any(|kind| matches!(kind.as_str(), "lib" | "rlib" | "dylib" | "cdylib" | "staticlib" | "proc-macro"))
+    }
+}
+
+/// Private function that deserializes the `kind` of a dependency, `null` meaning `[dependencies]`
+fn dependency_kind<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<DependencyKind, D::Error> {
+    match Option::<String>::deserialize(deserializer)?.as_deref() {
+        None | Some("normal") => Ok(DependencyKind::Normal),
+        Some("dev") => Ok(DependencyKind::Dev),
+        Some("build") => Ok(DependencyKind::Build),
+        Some(other) => Err(serde::de::Error::unknown_variant(other, &["normal", "dev", "build"])),
+    }
+}
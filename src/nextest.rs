@@ -0,0 +1,160 @@
+use crate::color::strip_ansi;
+use crate::{Builder, Result, TestOptions, TestReport, TestSummary, Verbosity, trace};
+use std::process::Stdio;
+
+/// Runs the tests of a project using [cargo-nextest](https://nexte.st) (`cargo nextest run`),
+/// which runs every test in its own process and is much faster on large test suites.
+///
+/// The builder provides the project location, environment, profile, features, compilation
+/// target, job count and the selected packages, like for `cargo test`. The per-test results are
+/// read from nextest's libtest-compatible JSON output (`--message-format libtest-json`), which
+/// is experimental and enabled through `NEXTEST_EXPERIMENTAL_LIBTEST_JSON=1`.
+///
+/// If `cargo-nextest` is not installed, [`Nextest::run`] falls back to
+/// [`Builder::test_report`], ignoring the nextest-only settings.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, Nextest, ProjectSettings, TestOptions};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     let nextest = Nextest::new(builder).partition("count:1/4").retries(2);
+///     let report = nextest.run(&TestOptions { no_fail_fast: true, ..Default::default() })?;
+///     for test in report.flaky() {
+///         println!("{} passed after {} attempts", test.name, test.attempts);
+///     }
+///     println!("{} passed, {} failed", report.summary.passed, report.summary.failed);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Nextest {
+    builder: Builder,
+    partition: Option<String>,
+    retries: Option<usize>,
+    profile: Option<String>,
+}
+
+impl Nextest {
+    /// Creates a new `Nextest` for the project of `builder`
+    pub fn new(builder: Builder) -> Self {
+        Nextest { builder, partition: None, retries: None, profile: None }
+    }
+
+    /// Sets the share of the tests to run (`--partition X`), e.g. `count:1/4` for the first of
+    /// four partitions, or `hash:2/3` to partition by test name hashes. Used to split a test
+    /// suite across CI jobs.
+    pub fn partition(mut self, partition: impl Into<String>) -> Self {
+        self.partition = Some(partition.into());
+        self
+    }
+
+    /// Sets how many times failing tests are retried (`--retries N`). Tests passing on a retry
+    /// are reported by [`TestReport::flaky`].
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Sets the nextest profile (`--profile X`), as configured in `.config/nextest.toml`.
+    /// The cargo profile is taken from the builder.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Returns the builder providing the project settings
+    pub fn get_builder(&self) -> &Builder {
+        &self.builder
+    }
+
+    /// Returns the selected partition
+    pub fn get_partition(&self) -> Option<&str> {
+        self.partition.as_deref()
+    }
+
+    /// Returns how many times failing tests are retried, `None` meaning the nextest profile's setting
+    pub fn get_retries(&self) -> Option<usize> {
+        self.retries
+    }
+
+    /// Returns the selected nextest profile
+    pub fn get_profile(&self) -> Option<&str> {
+        self.profile.as_deref()
+    }
+
+    /// Checks whether `cargo nextest` can be run with the builder's cargo and toolchain
+    pub fn is_available(&self) -> bool {
+        let mut command = self.builder.env_command("nextest");
+        command.arg("--version").stdout(Stdio::null()).stderr(Stdio::null());
+        self.builder.finish(command).status().is_ok_and(|status| status.success())
+    }
+
+    /// Runs the project's tests using `cargo nextest run`, collecting the outcome, duration,
+    /// output and attempts of every test.
+    ///
+    /// nextest does not run documentation tests.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A `TestOptions` instance with filters and harness settings.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(TestReport)` - If the tests were compiled. Failing tests are reported in the report
+    ///   rather than as an error. Suites are named after nextest's binary IDs.
+    /// * `Err(CargoWrapError)` - If the tests could not be compiled, the partition is invalid or
+    ///   `cargo` could not be run.
+    pub fn run(&self, options: &TestOptions) -> Result<TestReport> {
+        if !self.is_available() {
+            return self.builder.test_report(options);
+        }
+        let builder = &self.builder;
+        let mut command = builder.env_command("nextest");
+        command.arg("run");
+        // nextest's own `--verbose` changes its output, the build output is controlled separately
+        match builder.verbosity {
+            Verbosity::Quiet => command.arg("--cargo-quiet"),
+            Verbosity::Normal => &mut command,
+            Verbosity::Verbose => command.arg("--cargo-verbose"),
+            Verbosity::VeryVerbose => command.args(["--cargo-verbose", "--cargo-verbose"]),
+        };
+        if let Some(color) = builder.color.as_arg() {
+            command.arg("--color").arg(color);
+        }
+        builder.add_build_args(&mut command, "--cargo-profile", "--build-jobs")?;
+        command.args(["--message-format", "libtest-json"]).env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
+        if let Some(partition) = &self.partition {
+            command.arg("--partition").arg(partition);
+        }
+        if let Some(retries) = self.retries {
+            command.arg("--retries").arg(retries.to_string());
+        }
+        if let Some(profile) = &self.profile {
+            command.arg("--profile").arg(profile);
+        }
+        if options.no_run {
+            command.arg("--no-run");
+        }
+        if options.no_fail_fast {
+            command.arg("--no-fail-fast");
+        }
+        if options.nocapture {
+            command.arg("--no-capture");
+        }
+        if let Some(threads) = options.test_threads {
+            command.arg("--test-threads").arg(threads.to_string());
+        }
+        command.args(&options.filters);
+        trace::instrument("nextest", &builder.project_settings.project_path, || {
+            let output = builder.execute(command)?;
+            let stderr = String::from_utf8_lossy(&strip_ansi(&output.stderr)).into_owned();
+            let report = TestReport::parse_nextest(&String::from_utf8_lossy(&output.stdout), &stderr);
+            if report.suites.is_empty() {
+                Builder::check_status(output.status, || stderr.clone())?;
+            }
+            Ok(TestReport { summary: TestSummary { success: output.status.success(), ..report.summary }, ..report })
+        })
+    }
+}
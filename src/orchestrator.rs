@@ -0,0 +1,256 @@
+use crate::{BuildOutput, Builder, CargoWrapError, Result};
+use std::collections::VecDeque;
+use std::sync::mpsc;
+use std::thread;
+
+/// Builds several projects, each after the projects it depends on.
+///
+/// Builds are registered under a name with [`Orchestrator::add`] and run with [`Builder::build`]
+/// in dependency order. Independent builds run concurrently up to [`Orchestrator::parallelism`].
+/// If a build fails, every build depending on it, directly or not, is skipped while unrelated
+/// builds continue.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, Orchestrator, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let tool = Builder::new(ProjectSettings::new("/path/to/codegen-tool"))?;
+///     let plugin = Builder::new(ProjectSettings::new("/path/to/plugin"))?;
+///     let report = Orchestrator::new()
+///         .add("codegen-tool", tool, [])
+///         .add("plugin", plugin, ["codegen-tool"])
+///         .parallelism(2)
+///         .run()?;
+///     for job in &report.jobs {
+///         println!("{}: {}", job.name, if job.status.is_success() { "ok" } else { "not built" });
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Orchestrator {
+    jobs: Vec<Job>,
+    parallelism: usize,
+}
+
+/// Private description of a registered build
+#[derive(Debug)]
+struct Job {
+    name: String,
+    builder: Builder,
+    dependencies: Vec<String>,
+}
+
+/// The outcome of one build run by an [`Orchestrator`].
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum JobStatus {
+    /// The build succeeded.
+    Succeeded(BuildOutput),
+    /// The build failed.
+    Failed(CargoWrapError),
+    /// The build was not run because one of its dependencies did not succeed.
+    ///
+    /// * `dependency` - The name of the dependency that failed or was skipped.
+    Skipped { dependency: String },
+}
+
+/// The result of one build run by an [`Orchestrator`].
+///
+/// # Fields
+///
+/// * `name` - The name the build was registered under.
+/// * `status` - The outcome of the build.
+#[derive(Debug)]
+pub struct JobResult {
+    pub name: String,
+    pub status: JobStatus,
+}
+
+/// The aggregated results of an [`Orchestrator`] run.
+///
+/// # Fields
+///
+/// * `jobs` - The result of every build, in registration order.
+#[derive(Debug)]
+pub struct OrchestratorReport {
+    pub jobs: Vec<JobResult>,
+}
+
+impl JobStatus {
+    /// Returns `true` if the build succeeded
+    pub fn is_success(&self) -> bool {
+        matches!(self, JobStatus::Succeeded(_))
+    }
+
+    /// Returns the output of the build, if it succeeded
+    pub fn output(&self) -> Option<&BuildOutput> {
+        match self {
+            JobStatus::Succeeded(output) => Some(output),
+            _ => None,
+        }
+    }
+}
+
+impl OrchestratorReport {
+    /// Returns `true` if every build succeeded
+    pub fn success(&self) -> bool {
+        self.jobs.iter().all(|job| job.status.is_success())
+    }
+
+    /// Returns the result of the build registered as `name`
+    pub fn get(&self, name: &str) -> Option<&JobResult> {
+        self.jobs.iter().find(|job| job.name == name)
+    }
+
+    /// Returns the builds that failed
+    pub fn failed(&self) -> impl Iterator<Item = &JobResult> {
+        self.jobs.iter().filter(|job| matches!(job.status, JobStatus::Failed(_)))
+    }
+}
+
+impl Default for Orchestrator {
+    fn default() -> Self {
+        Orchestrator { jobs: Vec::new(), parallelism: 1 }
+    }
+}
+
+impl Orchestrator {
+    /// Creates a new, empty `Orchestrator` that runs one build at a time
+    pub fn new() -> Self {
+        Orchestrator::default()
+    }
+
+    /// Registers a build under `name`, run once every build named in `dependencies` succeeded
+    pub fn add<'a>(mut self, name: impl Into<String>, builder: Builder, dependencies: impl IntoIterator<Item = &'a str>) -> Self {
+        self.jobs.push(Job {
+            name: name.into(),
+            builder,
+            dependencies: dependencies.into_iter().map(String::from).collect(),
+        });
+        self
+    }
+
+    /// Sets how many builds run at the same time. Defaults to 1.
+    pub fn parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Returns the names of the registered builds, in registration order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.jobs.iter().map(|job| job.name.as_str())
+    }
+
+    /// Runs every registered build in dependency order.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OrchestratorReport)` - The result of every build, whether it succeeded or not.
+    /// * `Err(CargoWrapError)` - If the dependencies cannot be ordered.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::InvalidSettings`] if two builds share a name,
+    /// a dependency is not registered, or the dependencies form a cycle.
+    pub fn run(&self) -> Result<OrchestratorReport> {
+        let dependents = self.dependents()?;
+        let mut remaining: Vec<usize> = self.jobs.iter().map(|job| job.dependencies.len()).collect();
+        let mut statuses: Vec<Option<JobStatus>> = self.jobs.iter().map(|_| None).collect();
+        let mut ready: VecDeque<usize> = (0..self.jobs.len()).filter(|&index| remaining[index] == 0).collect();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| {
+            let mut running = 0;
+            loop {
+                while running < self.parallelism {
+                    let Some(index) = ready.pop_front() else { break };
+                    let sender = sender.clone();
+                    let builder = &self.jobs[index].builder;
+                    scope.spawn(move || {
+                        let _ = sender.send((index, builder.build()));
+                    });
+                    running += 1;
+                }
+                if running == 0 {
+                    break;
+                }
+                let Ok((index, result)) = receiver.recv() else { break };
+                running -= 1;
+                let succeeded = result.is_ok();
+                statuses[index] = Some(match result {
+                    Ok(output) => JobStatus::Succeeded(output),
+                    Err(error) => JobStatus::Failed(error),
+                });
+                for &dependent in &dependents[index] {
+                    if succeeded {
+                        remaining[dependent] -= 1;
+                        if remaining[dependent] == 0 && statuses[dependent].is_none() {
+                            ready.push_back(dependent);
+                        }
+                    } else {
+                        self.skip(dependent, index, &dependents, &mut statuses);
+                    }
+                }
+            }
+        });
+
+        let jobs = self
+            .jobs
+            .iter()
+            .zip(statuses)
+            .map(|(job, status)| JobResult { name: job.name.clone(), status: status.expect("every build is run or skipped") })
+            .collect();
+        Ok(OrchestratorReport { jobs })
+    }
+
+    /// Private function that marks `index` and everything depending on it as skipped because
+    /// `dependency` did not succeed
+    fn skip(&self, index: usize, dependency: usize, dependents: &[Vec<usize>], statuses: &mut [Option<JobStatus>]) {
+        if statuses[index].is_some() {
+            return;
+        }
+        statuses[index] = Some(JobStatus::Skipped { dependency: self.jobs[dependency].name.clone() });
+        for &dependent in &dependents[index] {
+            self.skip(dependent, index, dependents, statuses);
+        }
+    }
+
+    /// Private function that returns the builds depending on every build, checking that the
+    /// dependencies can be ordered
+    fn dependents(&self) -> Result<Vec<Vec<usize>>> {
+        let index_of = |name: &str| self.jobs.iter().position(|job| job.name == name);
+        let mut dependents = vec![Vec::new(); self.jobs.len()];
+        for (index, job) in self.jobs.iter().enumerate() {
+            if index_of(&job.name) != Some(index) {
+                return Err(CargoWrapError::InvalidSettings(format!("build {} is registered twice", job.name)));
+            }
+            for dependency in &job.dependencies {
+                let dependency = index_of(dependency).ok_or_else(|| {
+                    CargoWrapError::InvalidSettings(format!("build {} depends on unknown build {}", job.name, dependency))
+                })?;
+                dependents[dependency].push(index);
+            }
+        }
+
+        // Kahn's algorithm: every build must be reachable from the builds without dependencies
+        let mut remaining: Vec<usize> = self.jobs.iter().map(|job| job.dependencies.len()).collect();
+        let mut ready: Vec<usize> = (0..self.jobs.len()).filter(|&index| remaining[index] == 0).collect();
+        let mut ordered = 0;
+        while let Some(index) = ready.pop() {
+            ordered += 1;
+            for &dependent in &dependents[index] {
+                remaining[dependent] -= 1;
+                if remaining[dependent] == 0 {
+                    ready.push(dependent);
+                }
+            }
+        }
+        if ordered < self.jobs.len() {
+            let cycle: Vec<&str> = self.names().zip(&remaining).filter(|(_, count)| **count > 0).map(|(name, _)| name).collect();
+            return Err(CargoWrapError::InvalidSettings(format!("dependency cycle between builds {}", cycle.join(", "))));
+        }
+        Ok(dependents)
+    }
+}
@@ -0,0 +1,208 @@
+use crate::pipeline::{resolve_llvm_tool, run_tool};
+use crate::{BuildOutput, Builder, CargoWrapError, Result};
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A training run of a [`PgoPipeline`].
+enum TrainingRun {
+    /// Runs the instrumented executable with these arguments.
+    Args(Vec<String>),
+    /// Runs the command created from the instrumented build.
+    Command(Box<dyn Fn(&BuildOutput) -> Command + Send + Sync>),
+}
+
+impl fmt::Debug for TrainingRun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrainingRun::Args(args) => f.debug_tuple("Args").field(args).finish(),
+            TrainingRun::Command(_) => f.write_str("Command(..)"),
+        }
+    }
+}
+
+/// The output of [`PgoPipeline::run`].
+///
+/// # Fields
+///
+/// * `instrumented` - The output of the instrumented build the training ran with.
+/// * `optimized` - The output of the final build, optimized with the collected profile.
+/// * `profdata` - The merged profile (`merged.profdata`) the final build used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgoOutput {
+    pub instrumented: BuildOutput,
+    pub optimized: BuildOutput,
+    pub profdata: PathBuf,
+}
+
+/// Builds a project with profile-guided optimization (PGO).
+///
+/// The pipeline runs four phases:
+/// 1. Builds the project with `-C profile-generate` into a separate target directory.
+/// 2. Runs the training runs, which execute the instrumented binaries on representative workloads.
+///    Every instrumented process writes a profile to the output directory.
+/// 3. Merges the profiles with `llvm-profdata`.
+/// 4. Rebuilds the project with `-C profile-use` into the regular target directory, applying the
+///    builder's [`ArtifactPipeline`](crate::ArtifactPipeline).
+///
+/// PGO only pays off for optimized builds, so the builder should build in release mode.
+/// `llvm-profdata` must match the LLVM version of rustc; it is taken from the `llvm-tools`
+/// rustup component (`rustup component add llvm-tools`) when it is installed, otherwise from
+/// `PATH`.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, PgoPipeline, ProjectSettings};
+/// use std::process::Command;
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?;
+///     let output = PgoPipeline::new(builder)
+///         .training_run(["compress", "benches/corpus.txt"])
+///         .training_command(|build| {
+///             let mut command = Command::new("benches/run.sh");
+///             command.args(build.executables());
+///             command
+///         })
+///         .run()?;
+///     println!("{:?}", output.optimized.binaries());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PgoPipeline {
+    builder: Builder,
+    training: Vec<TrainingRun>,
+    out_dir: Option<PathBuf>,
+    llvm_tools_dir: Option<PathBuf>,
+}
+
+impl PgoPipeline {
+    /// Creates a new `PgoPipeline` that builds with `builder`
+    pub fn new(builder: Builder) -> Self {
+        PgoPipeline { builder, training: Vec::new(), out_dir: None, llvm_tools_dir: None }
+    }
+
+    /// Adds a training run executing the instrumented binary with `args`. The binary is the
+    /// first executable of the instrumented build, so the builder should select one with
+    /// [`ProjectSettings::target`](crate::ProjectSettings::target) if the project has several.
+    pub fn training_run<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.training.push(TrainingRun::Args(args.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// Adds a training run executing the command created by `command` from the output of the
+    /// instrumented build, e.g. a benchmark script given the paths of the instrumented binaries
+    pub fn training_command(mut self, command: impl Fn(&BuildOutput) -> Command + Send + Sync + 'static) -> Self {
+        self.training.push(TrainingRun::Command(Box::new(command)));
+        self
+    }
+
+    /// Sets the directory the profiles and instrumented build are written to. Defaults to `pgo/`
+    /// in the target directory.
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Sets the directory `llvm-profdata` is taken from
+    pub fn llvm_tools_dir(mut self, llvm_tools_dir: impl Into<PathBuf>) -> Self {
+        self.llvm_tools_dir = Some(llvm_tools_dir.into());
+        self
+    }
+
+    /// Returns the builder used for the builds
+    pub fn get_builder(&self) -> &Builder {
+        &self.builder
+    }
+
+    /// Returns the directory the profiles and instrumented build are written to
+    pub fn get_out_dir(&self) -> PathBuf {
+        self.out_dir.clone().unwrap_or_else(|| self.builder.target_dir().join("pgo"))
+    }
+
+    /// Runs the instrumented build, the training runs, the profile merge and the optimized build.
+    ///
+    /// Profiles of earlier runs in the output directory are removed first.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PgoOutput)` - The outputs of both builds, and the path of the merged profile.
+    /// * `Err(CargoWrapError)` - If a build, a training run or the profile merge fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - No training run is configured, or a training run needs an executable and the
+    ///   instrumented build produced none ([`CargoWrapError::InvalidSettings`]).
+    /// - A build fails ([`CargoWrapError::CompilationFailed`]).
+    /// - A training run exits unsuccessfully, or wrote no profile ([`CargoWrapError::ToolFailed`],
+    ///   [`CargoWrapError::InvalidSettings`]).
+    /// - `llvm-profdata` cannot be found or fails, e.g. because its LLVM version does not match
+    ///   rustc's ([`CargoWrapError::ToolNotFound`], [`CargoWrapError::ToolFailed`]).
+    pub fn run(&self) -> Result<PgoOutput> {
+        if self.training.is_empty() {
+            return Err(CargoWrapError::InvalidSettings("PGO needs at least one training run".to_string()));
+        }
+        let out_dir = std::path::absolute(self.get_out_dir())?;
+        let profile_dir = out_dir.join("profiles");
+        if profile_dir.exists() {
+            fs::remove_dir_all(&profile_dir)?;
+        }
+        fs::create_dir_all(&profile_dir)?;
+
+        let mut config = self.builder.config();
+        config.project_settings.output_path = Some(out_dir.join("target"));
+        config.rustc_flags.push(format!("-Cprofile-generate={}", profile_dir.display()));
+        let mut builder = Builder::from_config(config)?;
+        builder.backend = self.builder.backend.clone();
+        builder.sinks = self.builder.sinks.clone();
+        let instrumented = builder.build()?;
+
+        for training in &self.training {
+            let mut command = match training {
+                TrainingRun::Args(args) => {
+                    let executable = instrumented
+                        .artifacts
+                        .iter()
+                        .filter(|artifact| !artifact.kind.iter().any(|kind| kind == "custom-build"))
+                        .find_map(|artifact| artifact.executable.clone())
+                        .ok_or_else(|| CargoWrapError::InvalidSettings("the instrumented build produced no executable".to_string()))?;
+                    let mut command = Command::new(executable);
+                    command.args(args);
+                    command
+                }
+                TrainingRun::Command(create) => create(&instrumented),
+            };
+            let program = command.get_program().to_string_lossy().into_owned();
+            run_tool(&program, &mut command)?;
+        }
+
+        let profiles: Vec<PathBuf> = fs::read_dir(&profile_dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension() == Some(OsStr::new("profraw")))
+            .collect();
+        if profiles.is_empty() {
+            return Err(CargoWrapError::InvalidSettings("no training run wrote a profile".to_string()));
+        }
+        let profdata = out_dir.join("merged.profdata");
+        let mut merge = Command::new(resolve_llvm_tool(&self.builder, self.llvm_tools_dir.as_deref(), "llvm-profdata")?);
+        merge.arg("merge").arg("-o").arg(&profdata).args(&profiles);
+        run_tool("llvm-profdata", &mut merge)?;
+
+        let mut config = self.builder.config();
+        config.rustc_flags.push(format!("-Cprofile-use={}", profdata.display()));
+        let mut builder = Builder::from_config(config)?;
+        builder.backend = self.builder.backend.clone();
+        builder.sinks = self.builder.sinks.clone();
+        builder.artifact_pipeline = self.builder.artifact_pipeline.clone();
+        let optimized = builder.build()?;
+        Ok(PgoOutput { instrumented, optimized, profdata })
+    }
+}
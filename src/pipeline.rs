@@ -0,0 +1,423 @@
+use crate::sign::SignStep;
+use crate::toolchain::rustc_path;
+use crate::{BuildOutput, Builder, CargoWrapError, Result, Rustup, Signer};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::{env, fmt};
+
+/// A post-processing step applied to the artifacts of a build by an [`ArtifactPipeline`].
+pub trait ArtifactStep: fmt::Debug + Send + Sync {
+    /// Returns the name of the step used in reports, e.g. `strip`
+    fn name(&self) -> &str;
+
+    /// Processes the artifact at `path`, usually in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The binary or shared library to process.
+    /// * `context` - The target and profile the artifact was built for.
+    fn process(&self, path: &Path, context: &StepContext) -> Result<StepOutcome>;
+}
+
+/// What an [`ArtifactStep`] knows about the build that produced an artifact.
+///
+/// # Fields
+///
+/// * `target` - The compilation target triple, `None` meaning the host.
+/// * `profile` - The name of the profile the artifact was built with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepContext {
+    pub target: Option<String>,
+    pub profile: String,
+}
+
+impl StepContext {
+    /// Returns `true` if the artifacts are built for an Apple platform (macOS, iOS, ...)
+    pub fn is_apple(&self) -> bool {
+        self.target.as_deref().map_or(cfg!(target_vendor = "apple"), |target| target.contains("-apple-"))
+    }
+
+    /// Returns `true` if the artifacts are built for Windows
+    pub fn is_windows(&self) -> bool {
+        self.target.as_deref().map_or(cfg!(windows), |target| target.contains("-windows"))
+    }
+}
+
+/// The outcome of an [`ArtifactStep`] applied to a single artifact.
+///
+/// # Fields
+///
+/// * `output` - Everything the tools run by the step printed.
+/// * `warnings` - Why the step did not fully apply, if it did not.
+/// * `produced` - Files created next to the artifact.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepOutcome {
+    pub output: String,
+    pub warnings: Vec<String>,
+    pub produced: Vec<PathBuf>,
+}
+
+/// The report of an [`ArtifactStep`] applied to a single artifact, see [`BuildOutput::pipeline`].
+///
+/// # Fields
+///
+/// * `step` - The name of the step.
+/// * `artifact` - The path of the processed artifact.
+/// * `outcome` - What the step did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepReport {
+    pub step: String,
+    pub artifact: PathBuf,
+    pub outcome: StepOutcome,
+}
+
+/// Steps applied to the binaries and shared libraries of every successful build, see
+/// [`Builder::set_artifact_pipeline`].
+///
+/// Every step is applied to every artifact in order. Artifacts that were up to date are skipped:
+/// they were processed by the build that produced them.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{ArtifactPipeline, Builder, ProjectSettings, Strip};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let settings = ProjectSettings::new("/path/to/project").release(true);
+///     let builder = Builder::new(settings)?.artifact_pipeline(ArtifactPipeline::new().step(Strip::new()));
+///     let output = builder.build()?;
+///     for report in &output.pipeline {
+///         println!("{}: {}", report.step, report.artifact.display());
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactPipeline {
+    steps: Vec<Arc<dyn ArtifactStep>>,
+}
+
+impl ArtifactPipeline {
+    /// Creates a new, empty `ArtifactPipeline`
+    pub fn new() -> Self {
+        ArtifactPipeline::default()
+    }
+
+    /// Appends a step to the pipeline
+    pub fn step(mut self, step: impl ArtifactStep + 'static) -> Self {
+        self.steps.push(Arc::new(step));
+        self
+    }
+
+    /// Appends a step signing every artifact with `signer`, see [`Signer`]
+    pub fn signer(self, signer: impl Signer + 'static) -> Self {
+        self.step(SignStep(signer))
+    }
+
+    /// Returns the steps of the pipeline, in order
+    pub fn get_steps(&self) -> impl Iterator<Item = &dyn ArtifactStep> {
+        self.steps.iter().map(|step| step.as_ref())
+    }
+
+    /// Returns the files of `output` the pipeline processes: the executables and shared libraries
+    /// that were rebuilt, excluding build scripts
+    pub fn files(output: &BuildOutput) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for artifact in output.artifacts.iter().filter(|a| !a.fresh && !a.kind.iter().any(|kind| kind == "custom-build")) {
+            if let Some(executable) = &artifact.executable {
+                files.push(executable.clone());
+            }
+            if artifact.kind.iter().any(|kind| kind == "cdylib" || kind == "dylib") {
+                files.extend(artifact.filenames.iter().filter(|path| is_shared_library(path)).cloned());
+            }
+        }
+        files
+    }
+
+    /// Applies every step to the files of `output`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<StepReport>)` - A report for every step applied to every file.
+    /// * `Err(CargoWrapError)` - If a step failed.
+    ///
+    /// # Errors
+    ///
+    /// This function returns the error of the first step that failed, e.g.
+    /// [`CargoWrapError::ToolNotFound`] or [`CargoWrapError::ToolFailed`].
+    pub fn run(&self, output: &BuildOutput, context: &StepContext) -> Result<Vec<StepReport>> {
+        let mut reports = Vec::new();
+        for file in ArtifactPipeline::files(output) {
+            for step in &self.steps {
+                let outcome = step.process(&file, context)?;
+                for warning in &outcome.warnings {
+                    crate::trace::step_warning(step.name(), &file, warning);
+                }
+                reports.push(StepReport { step: step.name().to_string(), artifact: file.clone(), outcome });
+            }
+        }
+        Ok(reports)
+    }
+}
+
+/// Private function that checks whether `path` is a shared library rather than, e.g., an import library
+pub(crate) fn is_shared_library(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "so" || extension == "dylib" || extension == "dll")
+}
+
+/// Private function that checks whether `target` is the triple of the host, `None` meaning the host
+pub(crate) fn is_host_target(target: Option<&str>) -> bool {
+    let Some(target) = target else {
+        return true;
+    };
+    let os = match env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    target.split('-').next() == Some(env::consts::ARCH) && target.contains(os)
+}
+
+/// Private function that returns the binutils tool (`strip`, `objcopy`) handling artifacts of
+/// `target`, unless `tool` is set.
+///
+/// When cross-compiling, `<target>-<name>` is searched first, also without the `unknown` or `pc`
+/// vendor, then `llvm-<name>` and `rust-objcopy`, which handle every target.
+pub(crate) fn resolve_binutil(tool: Option<&Path>, name: &str, target: Option<&str>) -> Result<PathBuf> {
+    if let Some(tool) = tool {
+        return Ok(tool.to_path_buf());
+    }
+    let mut candidates = Vec::new();
+    match target {
+        Some(target) if !is_host_target(Some(target)) => {
+            candidates.push(format!("{}-{}", target, name));
+            let parts: Vec<&str> = target.split('-').collect();
+            if parts.len() > 2 && (parts[1] == "unknown" || parts[1] == "pc") {
+                candidates.push(format!("{}-{}-{}", parts[0], parts[2..].join("-"), name));
+            }
+        }
+        _ => candidates.push(name.to_string()),
+    }
+    candidates.push(format!("llvm-{}", name));
+    candidates.push("rust-objcopy".to_string());
+    candidates
+        .iter()
+        .find_map(|candidate| Builder::find_binary(candidate))
+        .ok_or_else(|| CargoWrapError::ToolNotFound(name.to_string()))
+}
+
+/// Private function that returns `tool`, either a path or a name searched in `PATH`, defaulting to `name`
+pub(crate) fn resolve_tool(tool: Option<&Path>, name: &str) -> Result<PathBuf> {
+    let tool = match tool {
+        Some(tool) if tool.is_file() => Some(tool.to_path_buf()),
+        Some(tool) => tool.to_str().and_then(Builder::find_binary),
+        None => Builder::find_binary(name),
+    };
+    tool.ok_or_else(|| CargoWrapError::ToolNotFound(name.to_string()))
+}
+
+/// Private function that returns the LLVM tool `name` (e.g. `llvm-profdata`) matching the
+/// toolchain of `builder`: from `dir` if set, otherwise from the `llvm-tools` rustup component,
+/// falling back to `PATH`
+pub(crate) fn resolve_llvm_tool(builder: &Builder, dir: Option<&Path>, name: &str) -> Result<PathBuf> {
+    let file_name = format!("{}{}", name, env::consts::EXE_SUFFIX);
+    if let Some(dir) = dir {
+        return resolve_tool(Some(&dir.join(file_name)), name);
+    }
+    let mut rustc = match &builder.toolchain {
+        Some(toolchain) => {
+            let mut rustc = Rustup::new().command();
+            rustc.args(["run", toolchain, "rustc"]);
+            rustc
+        }
+        None => Command::new(rustc_path(&builder.cargo_path)?),
+    };
+    let sysroot = run_stdout("rustc", rustc.args(["--print", "sysroot"]))?;
+    let host = builder.toolchain_info()?.host;
+    let component = Path::new(sysroot.trim()).join("lib").join("rustlib").join(host).join("bin").join(file_name);
+    resolve_tool(component.is_file().then_some(component.as_path()), name)
+}
+
+/// Private function that runs `command` to completion, returning its stdout
+pub(crate) fn run_stdout(tool: &str, command: &mut Command) -> Result<String> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(CargoWrapError::ToolFailed {
+            tool: tool.to_string(),
+            status: output.status,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Private function that runs `command` to completion, returning everything it printed
+pub(crate) fn run_tool(tool: &str, command: &mut Command) -> Result<String> {
+    let output = command.output()?;
+    let mut printed = String::from_utf8_lossy(&output.stdout).into_owned();
+    printed.push_str(&String::from_utf8_lossy(&output.stderr));
+    if !output.status.success() {
+        return Err(CargoWrapError::ToolFailed { tool: tool.to_string(), status: output.status, stderr: printed });
+    }
+    Ok(printed)
+}
+
+/// A pipeline step that removes symbols from binaries with `strip`, `llvm-strip` or
+/// `rust-objcopy` (from cargo-binutils).
+///
+/// Unless a tool is set, the first one found is used: when cross-compiling, `<target>-strip`
+/// (also without the `unknown` or `pc` vendor, e.g. `aarch64-linux-gnu-strip`), then
+/// `llvm-strip` and `rust-objcopy`, which handle every target; otherwise the host `strip` first.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::Strip;
+///
+/// let strip = Strip::new().debug_only(true).tool("/opt/cross/bin/aarch64-linux-gnu-strip");
+/// assert!(strip.is_debug_only());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Strip {
+    tool: Option<PathBuf>,
+    debug_only: bool,
+}
+
+impl Strip {
+    /// Creates a new `Strip` step removing every symbol with the first tool found
+    pub fn new() -> Self {
+        Strip::default()
+    }
+
+    /// Sets the tool to strip with
+    pub fn tool(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    /// Sets whether only debug info is removed, keeping the symbol table
+    pub fn debug_only(mut self, debug_only: bool) -> Self {
+        self.debug_only = debug_only;
+        self
+    }
+
+    /// Returns the tool to strip with, if set
+    pub fn get_tool(&self) -> Option<&Path> {
+        self.tool.as_deref()
+    }
+
+    /// Returns `true` if only debug info is removed
+    pub fn is_debug_only(&self) -> bool {
+        self.debug_only
+    }
+
+}
+
+impl ArtifactStep for Strip {
+    fn name(&self) -> &str {
+        "strip"
+    }
+
+    fn process(&self, path: &Path, context: &StepContext) -> Result<StepOutcome> {
+        let tool = resolve_binutil(self.tool.as_deref(), "strip", context.target.as_deref())?;
+        let name = tool.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut command = Command::new(&tool);
+        if context.is_apple() && name == "strip" {
+            // The Xcode strip removes every symbol it can without flags, and has no long options
+            if self.debug_only {
+                command.arg("-S");
+            }
+        } else {
+            command.arg(if self.debug_only { "--strip-debug" } else { "--strip-all" });
+        }
+        let output = run_tool(&name, command.arg(path))?;
+        Ok(StepOutcome { output, ..Default::default() })
+    }
+}
+
+/// A pipeline step that moves the debug info of ELF binaries into a separate `<artifact>.debug`
+/// file, with `objcopy`, `llvm-objcopy` or `rust-objcopy` (from cargo-binutils).
+///
+/// The debug info is copied with `--only-keep-debug`, removed from the artifact with
+/// `--strip-debug`, and the artifact is linked to the new file with `--add-gnu-debuglink`, so
+/// debuggers find the symbols next to it. Tools are resolved like for [`Strip`]. Artifacts that
+/// are not ELF files (Mach-O, PE, wasm) are left untouched, with a warning in the
+/// [`StepOutcome`].
+///
+/// Add this step before a [`Strip`] step, which would otherwise remove the debug info first.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{ArtifactPipeline, Builder, ProjectSettings, SplitDebugInfo, Strip};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let pipeline = ArtifactPipeline::new().step(SplitDebugInfo::new()).step(Strip::new());
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?.artifact_pipeline(pipeline);
+///     for report in builder.build()?.pipeline {
+///         println!("{}: {:?}", report.artifact.display(), report.outcome.produced);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SplitDebugInfo {
+    tool: Option<PathBuf>,
+}
+
+impl SplitDebugInfo {
+    /// Creates a new `SplitDebugInfo` step with the first tool found
+    pub fn new() -> Self {
+        SplitDebugInfo::default()
+    }
+
+    /// Sets the `objcopy` tool to use
+    pub fn tool(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    /// Returns the `objcopy` tool to use, if set
+    pub fn get_tool(&self) -> Option<&Path> {
+        self.tool.as_deref()
+    }
+
+    /// Returns the path the debug info of `artifact` is written to
+    pub fn debug_path(artifact: &Path) -> PathBuf {
+        let mut path = OsString::from(artifact.as_os_str());
+        path.push(".debug");
+        PathBuf::from(path)
+    }
+}
+
+/// Private function that checks whether the file at `path` starts with the ELF magic number
+pub(crate) fn is_elf(path: &Path) -> Result<bool> {
+    let mut magic = [0; 4];
+    match File::open(path)?.read_exact(&mut magic) {
+        Ok(()) => Ok(&magic == b"\x7fELF"),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl ArtifactStep for SplitDebugInfo {
+    fn name(&self) -> &str {
+        "split-debuginfo"
+    }
+
+    fn process(&self, path: &Path, context: &StepContext) -> Result<StepOutcome> {
+        if !is_elf(path)? {
+            let warning = format!("{} is not an ELF file, its debug info is not split", path.display());
+            return Ok(StepOutcome { warnings: vec![warning], ..Default::default() });
+        }
+        let tool = resolve_binutil(self.tool.as_deref(), "objcopy", context.target.as_deref())?;
+        let name = tool.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let debug_path = SplitDebugInfo::debug_path(path);
+        let mut output = run_tool(&name, Command::new(&tool).arg("--only-keep-debug").arg(path).arg(&debug_path))?;
+        output.push_str(&run_tool(&name, Command::new(&tool).arg("--strip-debug").arg(path))?);
+        let mut debuglink = OsString::from("--add-gnu-debuglink=");
+        debuglink.push(&debug_path);
+        output.push_str(&run_tool(&name, Command::new(&tool).arg(debuglink).arg(path))?);
+        Ok(StepOutcome { output, warnings: Vec::new(), produced: vec![debug_path] })
+    }
+}
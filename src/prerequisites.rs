@@ -0,0 +1,191 @@
+use crate::toolchain::compare_versions;
+use crate::{Builder, CargoWrapError, Result};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::process::{Command, Stdio};
+
+/// A tool or library a build needs, optionally at a minimum version, see [`Prerequisites`].
+///
+/// # Fields
+///
+/// * `name` - The name of the binary (e.g. `cmake`) or pkg-config package (e.g. `openssl`).
+/// * `min_version` - Optional minimum version, e.g. `3.20`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Requirement {
+    pub name: String,
+    pub min_version: Option<String>,
+}
+
+/// The system prerequisites of a build, probed before cargo runs, see
+/// [`Builder::set_prerequisites`](crate::Builder::set_prerequisites).
+///
+/// Build scripts of `-sys` crates usually fail deep into a build, with hundreds of lines of
+/// compiler output, when a native tool or library is missing. Declaring them up front turns
+/// that into a [`CargoWrapError::MissingPrerequisites`] listing everything that is missing.
+///
+/// # Fields
+///
+/// * `binaries` - Programs that must be found in `PATH`. Minimum versions are compared with
+///   the first version number printed by `<binary> --version`.
+/// * `pkg_config` - Packages that must be known to `pkg-config`, checked with
+///   `pkg-config --exists` or `pkg-config --atleast-version`.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::Prerequisites;
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     Prerequisites::new()
+///         .binary("nasm")
+///         .binary_version("cmake", "3.20")
+///         .binary_version("ld", "2.38")
+///         .pkg_config_version("openssl", "3.0")
+///         .check()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Prerequisites {
+    pub binaries: Vec<Requirement>,
+    pub pkg_config: Vec<Requirement>,
+}
+
+impl Prerequisites {
+    /// Creates a new `Prerequisites` requiring nothing
+    pub fn new() -> Self {
+        Prerequisites::default()
+    }
+
+    /// Requires the program `name` to be found in `PATH`
+    pub fn binary(mut self, name: impl Into<String>) -> Self {
+        self.binaries.push(Requirement { name: name.into(), min_version: None });
+        self
+    }
+
+    /// Requires the program `name` to be found in `PATH`, at `min_version` or newer
+    pub fn binary_version(mut self, name: impl Into<String>, min_version: impl Into<String>) -> Self {
+        self.binaries.push(Requirement { name: name.into(), min_version: Some(min_version.into()) });
+        self
+    }
+
+    /// Requires the pkg-config package `name`
+    pub fn pkg_config(mut self, name: impl Into<String>) -> Self {
+        self.pkg_config.push(Requirement { name: name.into(), min_version: None });
+        self
+    }
+
+    /// Requires the pkg-config package `name` at `min_version` or newer
+    pub fn pkg_config_version(mut self, name: impl Into<String>, min_version: impl Into<String>) -> Self {
+        self.pkg_config.push(Requirement { name: name.into(), min_version: Some(min_version.into()) });
+        self
+    }
+
+    /// Probes every prerequisite with the environment of the current process.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If every prerequisite is available.
+    /// * `Err(CargoWrapError::MissingPrerequisites)` - Listing every missing prerequisite.
+    pub fn check(&self) -> Result<()> {
+        let missing = self.missing(&BTreeMap::new());
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(CargoWrapError::MissingPrerequisites(missing))
+    }
+
+    /// Private function that probes every prerequisite, with `envs` set for the probes (e.g.
+    /// `PKG_CONFIG_PATH`), and returns the missing ones
+    pub(crate) fn missing(&self, envs: &BTreeMap<String, String>) -> Vec<MissingPrerequisite> {
+        let mut missing = Vec::new();
+        for requirement in &self.binaries {
+            let Some(path) = Builder::find_binary(&requirement.name) else {
+                missing.push(MissingPrerequisite::Binary(requirement.name.clone()));
+                continue;
+            };
+            let Some(required) = &requirement.min_version else {
+                continue;
+            };
+            let mut command = Command::new(path);
+            command.arg("--version").envs(envs);
+            let found = probe(&mut command).and_then(|output| find_version(&output).map(String::from));
+            if found.as_deref().is_none_or(|found| compare_versions(found, required) == Ordering::Less) {
+                missing.push(MissingPrerequisite::BinaryVersion { name: requirement.name.clone(), required: required.clone(), found });
+            }
+        }
+        if self.pkg_config.is_empty() {
+            return missing;
+        }
+        if Builder::find_binary("pkg-config").is_none() {
+            missing.push(MissingPrerequisite::Binary("pkg-config".to_string()));
+            return missing;
+        }
+        for requirement in &self.pkg_config {
+            let mut command = Command::new("pkg-config");
+            match &requirement.min_version {
+                Some(version) => command.arg(format!("--atleast-version={}", version)),
+                None => command.arg("--exists"),
+            };
+            command.arg(&requirement.name).envs(envs);
+            if probe(&mut command).is_none() {
+                missing.push(MissingPrerequisite::PkgConfig { name: requirement.name.clone(), min_version: requirement.min_version.clone() });
+            }
+        }
+        missing
+    }
+}
+
+/// A prerequisite that was not found, see [`CargoWrapError::MissingPrerequisites`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MissingPrerequisite {
+    /// The program is not in `PATH`.
+    Binary(String),
+    /// The program is older than required, or its version could not be determined.
+    ///
+    /// * `name` - The name of the program.
+    /// * `required` - The minimum version.
+    /// * `found` - The version of the program, if `--version` printed one.
+    BinaryVersion { name: String, required: String, found: Option<String> },
+    /// The pkg-config package is not installed, or older than required.
+    ///
+    /// * `name` - The name of the package.
+    /// * `min_version` - The minimum version, if any.
+    PkgConfig { name: String, min_version: Option<String> },
+}
+
+impl fmt::Display for MissingPrerequisite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MissingPrerequisite::Binary(name) => write!(f, "{} is not installed", name),
+            MissingPrerequisite::BinaryVersion { name, required, found: Some(found) } => {
+                write!(f, "{} {} or newer is required, found {}", name, required, found)
+            }
+            MissingPrerequisite::BinaryVersion { name, required, found: None } => {
+                write!(f, "{} {} or newer is required, its version is unknown", name, required)
+            }
+            MissingPrerequisite::PkgConfig { name, min_version: Some(version) } => {
+                write!(f, "pkg-config package {} {} or newer is not installed", name, version)
+            }
+            MissingPrerequisite::PkgConfig { name, min_version: None } => write!(f, "pkg-config package {} is not installed", name),
+        }
+    }
+}
+
+/// Private function that runs `command`, returning its stdout and stderr if it succeeds
+fn probe(command: &mut Command) -> Option<String> {
+    let output = command.stdin(Stdio::null()).output().ok().filter(|output| output.status.success())?;
+    Some(format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)))
+}
+
+/// Private function that finds the first version number (digits separated by dots) in the
+/// output of `--version` on its first line, e.g. `3.28.3` in `cmake version 3.28.3`
+fn find_version(output: &str) -> Option<&str> {
+    output.lines().next()?.split([' ', '(', ')', ',']).find(|word| {
+        let mut parts = word.split('.');
+        word.contains('.') && parts.all(|part| !part.is_empty() && part.bytes().all(|byte| byte.is_ascii_digit()))
+    })
+}
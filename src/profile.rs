@@ -0,0 +1,48 @@
+/// Overrides for the settings of the profile a build uses, without editing the project's `Cargo.toml`.
+///
+/// Each set value is passed to cargo as a `CARGO_PROFILE_<NAME>_<KEY>` environment variable,
+/// where `<NAME>` is the active profile (the custom profile if one is set, otherwise `release`
+/// or `dev`). Values use the same syntax as the corresponding `Cargo.toml` keys.
+///
+/// # Fields
+///
+/// * `opt_level` - Optimization level, e.g. `"3"` or `"z"` (`opt-level`).
+/// * `lto` - Link-time optimization, e.g. `"thin"`, `"fat"` or `"off"` (`lto`).
+/// * `codegen_units` - Number of codegen units (`codegen-units`).
+/// * `panic` - Panic strategy, `"unwind"` or `"abort"` (`panic`).
+/// * `debug` - Debug info level, e.g. `"0"`, `"line-tables-only"` or `"full"` (`debug`).
+/// * `strip` - What to strip from binaries, e.g. `"symbols"` or `"debuginfo"` (`strip`).
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ProfileOverrides {
+    pub opt_level: Option<String>,
+    pub lto: Option<String>,
+    pub codegen_units: Option<u32>,
+    pub panic: Option<String>,
+    pub debug: Option<String>,
+    pub strip: Option<String>,
+}
+
+impl ProfileOverrides {
+    /// Returns `true` if no setting is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == ProfileOverrides::default()
+    }
+
+    /// Private function that returns the environment variables applying the overrides to `profile`
+    pub(crate) fn envs(&self, profile: &str) -> Vec<(String, String)> {
+        let prefix = format!("CARGO_PROFILE_{}", profile.to_uppercase().replace('-', "_"));
+        [
+            ("OPT_LEVEL", self.opt_level.clone()),
+            ("LTO", self.lto.clone()),
+            ("CODEGEN_UNITS", self.codegen_units.map(|units| units.to_string())),
+            ("PANIC", self.panic.clone()),
+            ("DEBUG", self.debug.clone()),
+            ("STRIP", self.strip.clone()),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.map(|value| (format!("{}_{}", prefix, key), value)))
+        .collect()
+    }
+}
@@ -0,0 +1,37 @@
+/// A progress update of a running build, as reported by cargo's progress bar.
+///
+/// # Fields
+///
+/// * `finished` - Number of compilation units finished so far.
+/// * `total` - Total number of compilation units in the build.
+/// * `current` - Names of the crates being compiled, e.g. `serde` or `serde(build.rs)`.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ProgressEvent {
+    pub finished: usize,
+    pub total: usize,
+    pub current: Vec<String>,
+}
+
+impl ProgressEvent {
+    /// Returns the completed fraction of the build as a percentage between `0.0` and `100.0`
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        (self.finished as f64 / self.total as f64 * 100.0).min(100.0)
+    }
+
+    /// Private function that parses a progress bar line such as
+    /// `Building [=====>    ] 8/37: quote, syn`
+    pub(crate) fn parse(line: &str) -> Option<ProgressEvent> {
+        let bar = line.trim().strip_prefix("Building [")?;
+        let (_, counts) = bar.split_once("] ")?;
+        let (counts, current) = counts.split_once(':').unwrap_or((counts, ""));
+        let (finished, total) = counts.split_once('/')?;
+        Some(ProgressEvent {
+            finished: finished.trim().parse().ok()?,
+            total: total.trim().parse().ok()?,
+            current: current.split(',').map(str::trim).filter(|c| !c.is_empty()).map(String::from).collect(),
+        })
+    }
+}
@@ -0,0 +1,240 @@
+use crate::color::strip_ansi;
+use crate::{Builder, Result, trace};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// A package handled by a [`Publisher`].
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `version` - The version of the package.
+/// * `crate_file` - The `.crate` archive cargo assembled for the package, if it could be found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishedPackage {
+    pub name: String,
+    pub version: String,
+    pub crate_file: Option<PathBuf>,
+}
+
+/// The output of [`Publisher::publish`] or [`Publisher::package`].
+///
+/// # Fields
+///
+/// * `packages` - Every package cargo packaged, in order.
+/// * `uploaded` - Whether the packages were uploaded to the registry, `false` for dry runs and `cargo package`.
+/// * `stderr` - Everything cargo wrote to stderr.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishOutput {
+    pub packages: Vec<PublishedPackage>,
+    pub uploaded: bool,
+    pub stderr: String,
+}
+
+/// Publishes a project to a registry using `cargo publish`, or assembles its `.crate` archives
+/// using `cargo package`.
+///
+/// The builder provides the project location, environment, features, compilation target, job
+/// count and the selected packages (`-p`); flags that only apply to builds, such as `--release`,
+/// are not passed. Failures are reported through the same errors as builds, e.g.
+/// [`CargoWrapError::CompilationFailed`](crate::CargoWrapError::CompilationFailed) when cargo
+/// rejects the package.
+///
+/// The registry token is passed to cargo through `CARGO_REGISTRY_TOKEN`, or
+/// `CARGO_REGISTRIES_<NAME>_TOKEN` when a registry is selected, so it never appears in the
+/// arguments of the process.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectSettings, Publisher};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     let token = std::env::var("RELEASE_TOKEN").unwrap_or_default();
+///     let output = Publisher::new(builder).registry("internal").token(token).dry_run(true).publish()?;
+///     for package in &output.packages {
+///         println!("{} {} -> {:?}", package.name, package.version, package.crate_file);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct Publisher {
+    builder: Builder,
+    dry_run: bool,
+    allow_dirty: bool,
+    no_verify: bool,
+    registry: Option<String>,
+    token: Option<String>,
+}
+
+impl fmt::Debug for Publisher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Publisher")
+            .field("builder", &self.builder)
+            .field("dry_run", &self.dry_run)
+            .field("allow_dirty", &self.allow_dirty)
+            .field("no_verify", &self.no_verify)
+            .field("registry", &self.registry)
+            .field("token", &self.token.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl Publisher {
+    /// Creates a new `Publisher` for the project of `builder`
+    pub fn new(builder: Builder) -> Self {
+        Publisher { builder, dry_run: false, allow_dirty: false, no_verify: false, registry: None, token: None }
+    }
+
+    /// Sets whether every check is performed without uploading (`--dry-run`)
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Sets whether uncommitted changes in the working directory are allowed (`--allow-dirty`)
+    pub fn allow_dirty(mut self, allow_dirty: bool) -> Self {
+        self.allow_dirty = allow_dirty;
+        self
+    }
+
+    /// Sets whether building the packaged sources is skipped (`--no-verify`)
+    pub fn no_verify(mut self, no_verify: bool) -> Self {
+        self.no_verify = no_verify;
+        self
+    }
+
+    /// Sets the registry to publish to (`--registry X`), as named in the cargo configuration.
+    /// Defaults to crates.io.
+    pub fn registry(mut self, registry: impl Into<String>) -> Self {
+        self.registry = Some(registry.into());
+        self
+    }
+
+    /// Sets the token used to authenticate with the registry. It is passed through the environment.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Returns the builder providing the project settings
+    pub fn get_builder(&self) -> &Builder {
+        &self.builder
+    }
+
+    /// Returns whether the publisher performs dry runs
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Returns the selected registry, `None` meaning crates.io
+    pub fn get_registry(&self) -> Option<&str> {
+        self.registry.as_deref()
+    }
+
+    /// Returns the environment variable the token is passed through, e.g. `CARGO_REGISTRIES_INTERNAL_TOKEN`
+    pub fn token_env(&self) -> String {
+        match &self.registry {
+            Some(registry) => format!("CARGO_REGISTRIES_{}_TOKEN", registry.to_uppercase().replace('-', "_")),
+            None => "CARGO_REGISTRY_TOKEN".to_string(),
+        }
+    }
+
+    /// Publishes the project using `cargo publish`.
+    ///
+    /// Retrying a partially completed upload is not safe, so unless it is a dry run, the command
+    /// runs once regardless of the builder's [`RetryPolicy`](crate::RetryPolicy).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PublishOutput)` - The packaged crates, and whether they were uploaded.
+    /// * `Err(CargoWrapError)` - If packaging, verification or the upload fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - cargo exits unsuccessfully, e.g. because the working directory is dirty, the version
+    ///   already exists or the token is rejected ([`CargoWrapError::ToolFailed`](crate::CargoWrapError::ToolFailed)).
+    /// - The `cargo` process cannot be run ([`CargoWrapError::Io`](crate::CargoWrapError::Io)).
+    pub fn publish(&self) -> Result<PublishOutput> {
+        let mut command = self.command("publish");
+        if self.dry_run {
+            command.arg("--dry-run");
+        }
+        if let Some(token) = &self.token {
+            command.env(self.token_env(), token);
+        }
+        trace::instrument("publish", &self.builder.project_settings.project_path, || self.execute("cargo publish", command, !self.dry_run))
+    }
+
+    /// Assembles the `.crate` archives of the project using `cargo package`, without uploading them.
+    ///
+    /// # Errors
+    ///
+    /// See [`Publisher::publish`].
+    pub fn package(&self) -> Result<PublishOutput> {
+        let command = self.command("package");
+        trace::instrument("package", &self.builder.project_settings.project_path, || self.execute("cargo package", command, false))
+    }
+
+    /// Private function that assembles the `cargo publish`/`cargo package` invocation
+    fn command(&self, subcommand: &str) -> Command {
+        let builder = &self.builder;
+        let settings = &builder.project_settings;
+        let mut command = builder.base_command(subcommand);
+        if builder.thread_count > 0 {
+            command.arg("--jobs").arg(builder.thread_count.to_string());
+        }
+        if let Some(target_dir) = builder.target_dir_env() {
+            command.env("CARGO_TARGET_DIR", target_dir);
+        }
+        if let Some(target) = &settings.compilation_target {
+            command.arg("--target").arg(target);
+        }
+        builder.add_feature_args(&mut command);
+        for package in &settings.packages {
+            command.arg("-p").arg(package);
+        }
+        if let Some(registry) = &self.registry {
+            command.arg("--registry").arg(registry);
+        }
+        if self.allow_dirty {
+            command.arg("--allow-dirty");
+        }
+        if self.no_verify {
+            command.arg("--no-verify");
+        }
+        builder.add_project_args(&mut command);
+        command
+    }
+
+    /// Private function that runs `command`, only retrying it if nothing is uploaded, and collects
+    /// the packages from cargo's status lines
+    fn execute(&self, tool: &str, command: Command, upload: bool) -> Result<PublishOutput> {
+        let mut command = self.builder.finish(command);
+        let output = match upload {
+            true => self.builder.execute_once(&mut command)?,
+            false => self.builder.execute_finished(&mut command)?,
+        };
+        let stderr = String::from_utf8_lossy(&strip_ansi(&output.stderr)).into_owned();
+        Builder::check_tool_status(tool, output.status, || stderr.clone())?;
+        let package_dir = self.builder.target_dir().join("package");
+        let mut packages = Vec::new();
+        for line in stderr.lines() {
+            // e.g. "   Packaging my-crate v0.1.0 (/path/to/my-crate)"
+            let mut words = line.split_whitespace();
+            let (Some("Packaging"), Some(name), Some(version)) = (words.next(), words.next(), words.next()) else {
+                continue;
+            };
+            let Some(version) = version.strip_prefix('v') else { continue };
+            let crate_file = package_dir.join(format!("{}-{}.crate", name, version));
+            packages.push(PublishedPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                crate_file: crate_file.is_file().then_some(crate_file),
+            });
+        }
+        Ok(PublishOutput { packages, uploaded: upload, stderr })
+    }
+}
@@ -0,0 +1,230 @@
+use crate::pipeline::run_tool;
+use crate::{Builder, CargoWrapError, Result};
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// A store for archived target directories, see
+/// [`Builder::set_cache_backend`](crate::Builder::set_cache_backend).
+///
+/// Archives are gzipped tarballs of the target directory, keyed by the fingerprint of the build
+/// inputs (see [`Builder::fingerprint`](crate::Builder::fingerprint)).
+pub trait CacheBackend: fmt::Debug + Send + Sync {
+    /// Fetches the archive stored under `key` into the file `archive`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - If the archive was fetched.
+    /// * `Ok(false)` - If nothing is stored under `key`.
+    /// * `Err(CargoWrapError)` - If the store cannot be reached.
+    fn restore(&self, key: &str, archive: &Path) -> Result<bool>;
+
+    /// Stores the file `archive` under `key`, replacing any archive stored under it.
+    fn save(&self, key: &str, archive: &Path) -> Result<()>;
+}
+
+/// Private function that returns the file name of the archive stored under `key`
+fn archive_name(key: &str) -> String {
+    format!("{}.tar.gz", key)
+}
+
+/// A cache backend that keeps archives in a directory, e.g. a network share or a directory
+/// persisted between CI jobs.
+///
+/// # Fields
+///
+/// * `dir` - The directory the archives are stored in, as `<key>.tar.gz`. Created when the first
+///   archive is saved.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, FilesystemCache, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.cache_backend(FilesystemCache::new("/mnt/ci-cache"));
+///     builder.restore_target_dir()?;
+///     builder.build()?;
+///     builder.save_target_dir()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilesystemCache {
+    pub dir: PathBuf,
+}
+
+impl FilesystemCache {
+    /// Creates a new `FilesystemCache` storing archives in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        FilesystemCache { dir: dir.into() }
+    }
+}
+
+impl CacheBackend for FilesystemCache {
+    fn restore(&self, key: &str, archive: &Path) -> Result<bool> {
+        let stored = self.dir.join(archive_name(key));
+        if !stored.is_file() {
+            return Ok(false);
+        }
+        fs::copy(stored, archive)?;
+        Ok(true)
+    }
+
+    fn save(&self, key: &str, archive: &Path) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        // Copied next to its final name first, so jobs restoring concurrently never see a partial archive
+        let partial = self.dir.join(format!("{}.{}.partial", archive_name(key), std::process::id()));
+        fs::copy(archive, &partial)?;
+        fs::rename(partial, self.dir.join(archive_name(key)))?;
+        Ok(())
+    }
+}
+
+/// A cache backend that downloads and uploads archives over HTTP with `curl`, e.g. from an S3
+/// bucket, a CI cache service or any server accepting `PUT` requests.
+///
+/// Archives are fetched with `GET <url>/<key>.tar.gz` and stored with `PUT <url>/<key>.tar.gz`;
+/// a `404` response means nothing is stored under the key. Headers and credentials are passed to
+/// `curl` through its standard input, so they do not show up in the process list.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, HttpCache, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let cache = HttpCache::new("https://my-bucket.s3.eu-west-1.amazonaws.com/target-cache")
+///         .credentials(std::env::var("AWS_ACCESS_KEY_ID").unwrap(), std::env::var("AWS_SECRET_ACCESS_KEY").unwrap())
+///         .aws_sigv4("aws:amz:eu-west-1:s3");
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.cache_backend(cache);
+///     if !builder.restore_target_dir()? {
+///         println!("Cold cache");
+///     }
+///     builder.build()?;
+///     builder.save_target_dir()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct HttpCache {
+    url: String,
+    headers: Vec<(String, String)>,
+    credentials: Option<(String, String)>,
+    aws_sigv4: Option<String>,
+}
+
+impl fmt::Debug for HttpCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<&str> = self.headers.iter().map(|(name, _)| name.as_str()).collect();
+        f.debug_struct("HttpCache")
+            .field("url", &self.url)
+            .field("headers", &headers)
+            .field("credentials", &self.credentials.as_ref().map(|(user, _)| user))
+            .field("aws_sigv4", &self.aws_sigv4)
+            .finish()
+    }
+}
+
+impl HttpCache {
+    /// Creates a new `HttpCache` storing archives under `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        HttpCache { url: url.into(), headers: Vec::new(), credentials: None, aws_sigv4: None }
+    }
+
+    /// Adds a header sent with every request, e.g. `Authorization: Bearer <token>`
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the user and password requests are authenticated with (`curl --user`). With
+    /// [`HttpCache::aws_sigv4`], these are the access key ID and the secret access key.
+    pub fn credentials(mut self, user: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials = Some((user.into(), password.into()));
+        self
+    }
+
+    /// Signs requests with AWS Signature Version 4 (`curl --aws-sigv4`), for S3 and compatible
+    /// stores, e.g. `aws:amz:us-east-1:s3`. Requires curl 7.75 or newer.
+    pub fn aws_sigv4(mut self, provider: impl Into<String>) -> Self {
+        self.aws_sigv4 = Some(provider.into());
+        self
+    }
+
+    /// Returns the URL archives are stored under
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    /// Private function that runs `curl` on the archive stored under `key`, returning the HTTP
+    /// status code along with the output
+    fn curl(&self, key: &str, args: &[&std::ffi::OsStr]) -> Result<(String, std::process::Output)> {
+        let curl = Builder::find_binary("curl").ok_or_else(|| CargoWrapError::ToolNotFound("curl".to_string()))?;
+        let quote = |value: &str| format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut config = String::new();
+        for (name, value) in &self.headers {
+            config.push_str(&format!("header = {}\n", quote(&format!("{}: {}", name, value))));
+        }
+        if let Some((user, password)) = &self.credentials {
+            config.push_str(&format!("user = {}\n", quote(&format!("{}:{}", user, password))));
+        }
+        if let Some(provider) = &self.aws_sigv4 {
+            config.push_str(&format!("aws-sigv4 = {}\n", quote(provider)));
+        }
+        let url = format!("{}/{}", self.url.trim_end_matches('/'), archive_name(key));
+        let mut child = Command::new(curl)
+            .args(["--silent", "--show-error", "--fail", "--location", "--config", "-", "--write-out", "%{http_code}"])
+            .args(args)
+            .arg(url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(config.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        Ok((String::from_utf8_lossy(&output.stdout).trim().to_string(), output))
+    }
+}
+
+impl CacheBackend for HttpCache {
+    fn restore(&self, key: &str, archive: &Path) -> Result<bool> {
+        let (status, output) = self.curl(key, &["--output".as_ref(), archive.as_os_str()])?;
+        if status == "404" {
+            let _ = fs::remove_file(archive);
+            return Ok(false);
+        }
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            return Err(CargoWrapError::ToolFailed { tool: "curl".to_string(), status: output.status, stderr });
+        }
+        Ok(true)
+    }
+
+    fn save(&self, key: &str, archive: &Path) -> Result<()> {
+        let (_, output) = self.curl(key, &["--upload-file".as_ref(), archive.as_os_str()])?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+            return Err(CargoWrapError::ToolFailed { tool: "curl".to_string(), status: output.status, stderr });
+        }
+        Ok(())
+    }
+}
+
+/// Archives the contents of `directory` into the gzipped tarball `archive` with `tar`. The pax
+/// format keeps sub-second modification times, which cargo and the build cache compare.
+pub(crate) fn pack(directory: &Path, archive: &Path) -> Result<()> {
+    let tar = Builder::find_binary("tar").ok_or_else(|| CargoWrapError::ToolNotFound("tar".to_string()))?;
+    run_tool("tar", Command::new(tar).arg("--format=pax").arg("-czf").arg(archive).arg("-C").arg(directory).arg("."))?;
+    Ok(())
+}
+
+/// Extracts the gzipped tarball `archive` into `directory` with `tar`, keeping modification times
+pub(crate) fn unpack(archive: &Path, directory: &Path) -> Result<()> {
+    let tar = Builder::find_binary("tar").ok_or_else(|| CargoWrapError::ToolNotFound("tar".to_string()))?;
+    fs::create_dir_all(directory)?;
+    run_tool("tar", Command::new(tar).arg("-xzf").arg(archive).arg("-C").arg(directory))?;
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use crate::artifact::split_package_id;
+use crate::color::strip_ansi;
+use crate::{Artifact, BuildOutput, CargoWrapError, Diagnostic, DiagnosticLevel, RenderedCommand, Result};
+use std::borrow::Cow;
+use std::process::ExitStatus;
+use std::time::Duration;
+
+/// A structured summary of a finished `cargo build`, returned by
+/// [`Builder::build_report`](crate::Builder::build_report) whether the build succeeded or not.
+///
+/// # Fields
+///
+/// * `command` - The cargo invocation that was executed.
+/// * `status` - The exit status of the `cargo` process.
+/// * `duration` - The wall-clock duration of the build.
+/// * `output` - The artifacts and diagnostics reported by cargo.
+/// * `stdout` - The raw bytes the process wrote to stdout: the JSON messages cargo emitted.
+/// * `stderr` - The raw bytes the process wrote to stderr, which may not be valid UTF-8 when
+///   build scripts of C dependencies print compiler output. See [`BuildReport::stderr_lossy`].
+/// * `cached` - `true` if cargo was not invoked because the inputs matched an earlier successful
+///   build, see [`Builder::set_build_cache`](crate::Builder::set_build_cache). The output and
+///   stderr are those of that build.
+#[derive(Debug, Clone)]
+pub struct BuildReport {
+    pub command: RenderedCommand,
+    pub status: ExitStatus,
+    pub duration: Duration,
+    pub output: BuildOutput,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub cached: bool,
+}
+
+impl BuildReport {
+    /// Returns `true` if the build succeeded
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    /// Returns the stdout of the process as a string, invalid UTF-8 sequences replaced with `�`
+    pub fn stdout_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.stdout)
+    }
+
+    /// Returns the stderr of the process as a string, invalid UTF-8 sequences replaced with `�`
+    pub fn stderr_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.stderr)
+    }
+
+    /// Returns every artifact reported by cargo
+    pub fn artifacts(&self) -> &[Artifact] {
+        &self.output.artifacts
+    }
+
+    /// Returns the number of warnings emitted during the build
+    pub fn warning_count(&self) -> usize {
+        self.output.warning_count()
+    }
+
+    /// Returns the number of errors emitted during the build
+    pub fn error_count(&self) -> usize {
+        self.output.error_count()
+    }
+
+    /// Returns the error diagnostics emitted during the build
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.output.diagnostics_with_level(DiagnosticLevel::Error)
+    }
+
+    /// Converts the report into the result [`Builder::build`](crate::Builder::build) returns.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(BuildOutput)` - The output of the build, if it succeeded.
+    /// * `Err(CargoWrapError)` - A [`CargoWrapError::CompilationFailed`] carrying the exit status,
+    ///   the captured stdout and stderr, and the error diagnostics, if it failed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::{Builder, CargoWrapError, ProjectSettings};
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+    ///     match builder.build_report()?.into_result() {
+    ///         Ok(output) => println!("Built {:?}", output.binaries()),
+    ///         Err(CargoWrapError::CompilationFailed { status, diagnostics, .. }) => {
+    ///             eprintln!("cargo exited with {:?}", status.code());
+    ///             for diagnostic in diagnostics {
+    ///                 eprintln!("{}", diagnostic.message);
+    ///             }
+    ///         }
+    ///         Err(e) => return Err(e),
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn into_result(self) -> Result<BuildOutput> {
+        if self.success() {
+            return Ok(self.output);
+        }
+        let diagnostics = self.errors().cloned().collect();
+        let (stdout, stderr) = (self.stdout_lossy().into_owned(), self.stderr_lossy().into_owned());
+        Err(CargoWrapError::CompilationFailed { status: self.status, stdout, stderr, diagnostics })
+    }
+
+    /// Returns the names of the packages that failed to build, sorted: those with compiler errors,
+    /// those cargo could not compile and those whose build script failed. Without
+    /// [`Builder::set_keep_going`](crate::Builder::set_keep_going), cargo stops at the first
+    /// failure and other broken packages are not reported.
+    pub fn failed_packages(&self) -> Vec<String> {
+        let errors = self.output.diagnostics_with_level(DiagnosticLevel::Error).map(|diagnostic| split_package_id(&diagnostic.package_id).0);
+        // Cargo names the packages it gave up on in its own error messages, e.g. for build scripts
+        let stderr = String::from_utf8_lossy(&strip_ansi(&self.stderr)).into_owned();
+        let reported = stderr.lines().filter_map(|line| {
+            let line = line.strip_prefix("error: ")?;
+            let name = line.strip_prefix("could not compile `").or_else(|| line.strip_prefix("failed to run custom build command for `"))?;
+            name.split(['`', ' ']).next()
+        });
+        let mut packages: Vec<String> = errors.chain(reported).map(String::from).collect();
+        packages.sort();
+        packages.dedup();
+        packages
+    }
+
+    /// Returns the names of the packages that produced artifacts and did not fail, sorted
+    pub fn succeeded_packages(&self) -> Vec<String> {
+        let failed = self.failed_packages();
+        let mut packages: Vec<String> = self
+            .output
+            .artifacts
+            .iter()
+            .map(Artifact::package_name)
+            .filter(|name| !failed.iter().any(|failed| failed == name))
+            .map(String::from)
+            .collect();
+        packages.sort();
+        packages.dedup();
+        packages
+    }
+}
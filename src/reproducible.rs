@@ -0,0 +1,57 @@
+use crate::ChecksumManifest;
+
+/// The result of building a project twice and comparing the artifacts, see
+/// [`Builder::verify_reproducible`](crate::Builder::verify_reproducible).
+///
+/// Artifacts are matched by file name, since both builds write to different directories.
+///
+/// # Fields
+///
+/// * `first` - The checksums of the artifacts of the first build.
+/// * `second` - The checksums of the artifacts of the second build.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{ArtifactChecksum, ChecksumManifest, ReproducibilityReport};
+///
+/// let checksum = |sha256: &str| ArtifactChecksum {
+///     name: "app".to_string(),
+///     path: "target/app".into(),
+///     size: 4,
+///     sha256: sha256.to_string(),
+///     blake3: None,
+/// };
+/// let manifest = |sha256: &str| ChecksumManifest { target: None, profile: "release".to_string(), artifacts: vec![checksum(sha256)] };
+/// let report = ReproducibilityReport { first: manifest("9f86d0"), second: manifest("60303a") };
+/// assert!(!report.is_reproducible());
+/// assert_eq!(report.mismatches(), vec!["app"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReproducibilityReport {
+    pub first: ChecksumManifest,
+    pub second: ChecksumManifest,
+}
+
+impl ReproducibilityReport {
+    /// Returns the names of the artifacts that differ between the builds, or were only produced
+    /// by one of them, in order
+    pub fn mismatches(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = Vec::new();
+        for artifact in &self.first.artifacts {
+            if self.second.get(&artifact.name).is_none_or(|other| other.sha256 != artifact.sha256) {
+                names.push(&artifact.name);
+            }
+        }
+        for artifact in &self.second.artifacts {
+            if self.first.get(&artifact.name).is_none() {
+                names.push(&artifact.name);
+            }
+        }
+        names
+    }
+
+    /// Returns `true` if both builds produced the same artifacts, bit for bit
+    pub fn is_reproducible(&self) -> bool {
+        self.mismatches().is_empty()
+    }
+}
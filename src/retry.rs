@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+/// Messages cargo prints when a build fails because of the network or the registry.
+const NETWORK_ERROR_PATTERNS: [&str; 12] = [
+    "failed to fetch",
+    "failed to download",
+    "failed to update registry",
+    "failed to load source for dependency",
+    "failed to get `",
+    "spurious network error",
+    "error sending request",
+    "couldn't resolve host",
+    "connection reset",
+    "connection refused",
+    "timed out",
+    "http/2 stream",
+];
+
+/// When and how often failed builds are retried, see [`Builder::set_retry_policy`](crate::Builder::set_retry_policy).
+///
+/// A failed build is retried if its stderr contains one of `patterns` (compared ignoring case)
+/// and fewer than `max_attempts` attempts were made. Before attempt `n + 1`, the policy waits
+/// `backoff * backoff_multiplier^(n - 1)`.
+///
+/// # Fields
+///
+/// * `max_attempts` - The maximum number of attempts, including the first one.
+/// * `backoff` - The delay before the first retry.
+/// * `backoff_multiplier` - The factor the delay is multiplied by after every retry.
+/// * `patterns` - Messages identifying retryable failures. Defaults to network and registry errors.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(4).backoff(Duration::from_secs(2)).pattern("503 Service Unavailable");
+/// assert!(policy.is_retryable("error: failed to download from `https://index.crates.io/...`"));
+/// assert!(!policy.is_retryable("error: could not compile `app`"));
+/// assert_eq!(policy.delay(2), Duration::from_secs(4));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub backoff: Duration,
+    pub backoff_multiplier: u32,
+    pub patterns: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            backoff: Duration::from_secs(1),
+            backoff_multiplier: 2,
+            patterns: NETWORK_ERROR_PATTERNS.iter().map(|pattern| pattern.to_string()).collect(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new `RetryPolicy` making up to `max_attempts` attempts on network failures
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy { max_attempts, ..Default::default() }
+    }
+
+    /// Sets the delay before the first retry
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the factor the delay is multiplied by after every retry
+    pub fn backoff_multiplier(mut self, backoff_multiplier: u32) -> Self {
+        self.backoff_multiplier = backoff_multiplier;
+        self
+    }
+
+    /// Adds a message identifying retryable failures
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Returns `true` if a build that failed with `stderr` may be retried
+    pub fn is_retryable(&self, stderr: &str) -> bool {
+        let stderr = stderr.to_lowercase();
+        self.patterns.iter().any(|pattern| stderr.contains(&pattern.to_lowercase()))
+    }
+
+    /// Returns the delay after the failed attempt number `attempt`, starting at 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.backoff_multiplier.saturating_pow(attempt.saturating_sub(1));
+        self.backoff.saturating_mul(factor)
+    }
+}
@@ -0,0 +1,56 @@
+use std::process::{ExitStatus, Stdio};
+
+/// How one of the standard streams of a program started with `cargo run` is handled.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StdioMode {
+    /// The stream is inherited from the current process.
+    #[default]
+    Inherit,
+    /// The stream is connected to the null device.
+    Null,
+    /// The stream is piped. Piped stdout/stderr are captured into [`RunOutput`] by
+    /// `Builder::run`, or left for the caller to read when using `Builder::spawn_run`.
+    Pipe,
+}
+
+impl StdioMode {
+    /// Private function that converts the mode into a `Stdio` handle
+    pub(crate) fn to_stdio(self) -> Stdio {
+        match self {
+            StdioMode::Inherit => Stdio::inherit(),
+            StdioMode::Null => Stdio::null(),
+            StdioMode::Pipe => Stdio::piped(),
+        }
+    }
+}
+
+/// Standard stream configuration for programs started with `cargo run`.
+///
+/// # Fields
+///
+/// * `stdin` - How the program's stdin is handled.
+/// * `stdout` - How the program's stdout is handled.
+/// * `stderr` - How the program's stderr is handled. Note that cargo's own output is written here.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RunStdio {
+    pub stdin: StdioMode,
+    pub stdout: StdioMode,
+    pub stderr: StdioMode,
+}
+
+/// The result of a finished `cargo run` invocation.
+///
+/// # Fields
+///
+/// * `status` - The exit status of the `cargo run` process, which mirrors the program's exit status.
+/// * `stdout` - Captured stdout, empty unless stdout was set to [`StdioMode::Pipe`].
+/// * `stderr` - Captured stderr, empty unless stderr was set to [`StdioMode::Pipe`].
+#[derive(Debug, Clone)]
+pub struct RunOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
@@ -0,0 +1,22 @@
+/// Options controlling a `cargo fmt` run, see [`Builder::fmt`](crate::Builder::fmt).
+///
+/// # Fields
+///
+/// * `check` - If `true`, the files are only checked and left untouched (`--check`).
+/// * `all` - If `true`, every package of the workspace and its path dependencies is formatted (`--all`).
+///   The selected packages of the project settings (`-p`) are formatted otherwise, or the current package.
+/// * `rustfmt_args` - Additional arguments passed to `rustfmt`, e.g. `["--edition", "2021"]`.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FmtOptions {
+    pub check: bool,
+    pub all: bool,
+    pub rustfmt_args: Vec<String>,
+}
+
+/// Private function that checks whether rustfmt reported an error, e.g. a file it cannot parse,
+/// rather than only differences
+pub(crate) fn has_errors(stderr: &str) -> bool {
+    stderr.lines().any(|line| line.starts_with("error") || line.starts_with("Error"))
+}
@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// A runtime sanitizer the project is instrumented with, see [`Builder::set_sanitizer`](crate::Builder::set_sanitizer).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Sanitizer {
+    /// AddressSanitizer: out-of-bounds accesses, use-after-free, double frees and leaks.
+    Address,
+    /// ThreadSanitizer: data races.
+    Thread,
+    /// LeakSanitizer: memory leaks, with less overhead than AddressSanitizer.
+    Leak,
+    /// MemorySanitizer: reads of uninitialized memory.
+    Memory,
+}
+
+impl Sanitizer {
+    /// Returns the name of the sanitizer as understood by `rustc -Z sanitizer=X`, e.g. `address`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Sanitizer::Address => "address",
+            Sanitizer::Thread => "thread",
+            Sanitizer::Leak => "leak",
+            Sanitizer::Memory => "memory",
+        }
+    }
+
+    /// Returns `true` if the standard library has to be rebuilt with the sanitizer (`-Z build-std`)
+    /// to avoid false positives, which is the case for ThreadSanitizer and MemorySanitizer
+    pub fn requires_build_std(self) -> bool {
+        matches!(self, Sanitizer::Thread | Sanitizer::Memory)
+    }
+}
+
+impl fmt::Display for Sanitizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
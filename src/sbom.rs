@@ -0,0 +1,250 @@
+use crate::timestamp::rfc3339;
+use crate::{Lockfile, Result};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The source of packages downloaded from crates.io, as written in `Cargo.lock`.
+const CRATES_IO: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+/// The format a [`Sbom`] is written in.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SbomFormat {
+    /// [CycloneDX](https://cyclonedx.org) 1.5 JSON.
+    #[default]
+    CycloneDx,
+    /// [SPDX](https://spdx.dev) 2.3 JSON.
+    Spdx,
+}
+
+/// A package listed in a [`Sbom`].
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `version` - The exact version of the package.
+/// * `license` - Optional SPDX license expression of the package, as declared in its manifest.
+/// * `source` - Optional source of the package, as written in `Cargo.lock`. `None` for path dependencies.
+/// * `checksum` - Optional SHA-256 checksum of the package archive, for registry packages.
+/// * `dependencies` - The package URLs of the dependencies of the package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SbomComponent {
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+    pub source: Option<String>,
+    pub checksum: Option<String>,
+    pub dependencies: Vec<String>,
+}
+
+impl SbomComponent {
+    /// Returns the [package URL](https://github.com/package-url/purl-spec) of the package,
+    /// e.g. `pkg:cargo/serde@1.0.200`
+    pub fn purl(&self) -> String {
+        format!("pkg:cargo/{}@{}", self.name, self.version)
+    }
+
+    /// Private function that returns the URL the package archive is downloaded from, if known
+    fn download_location(&self) -> Option<String> {
+        match self.source.as_deref() {
+            Some(CRATES_IO) => Some(format!("https://crates.io/api/v1/crates/{}/{}/download", self.name, self.version)),
+            Some(source) => source.strip_prefix("git+").map(|url| format!("git+{}", url)),
+            None => None,
+        }
+    }
+
+    /// Private function that returns a valid SPDX identifier of the package
+    fn spdx_id(&self) -> String {
+        let id: String = format!("{}-{}", self.name, self.version)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '-' })
+            .collect();
+        format!("SPDXRef-Package-{}", id)
+    }
+}
+
+/// A software bill of materials of a project: every package in its lockfile, with versions,
+/// licenses and checksums, see [`Builder::sbom`](crate::Builder::sbom).
+///
+/// # Fields
+///
+/// * `root` - Optional package the bill describes, `None` for virtual workspaces.
+/// * `components` - Every other package of the lockfile, including workspace members.
+/// * `created` - When the bill was created.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectSettings, SbomFormat};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     let sbom = builder.sbom()?;
+///     sbom.write("dist/sbom.cdx.json", SbomFormat::CycloneDx)?;
+///     sbom.write("dist/sbom.spdx.json", SbomFormat::Spdx)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sbom {
+    pub root: Option<SbomComponent>,
+    pub components: Vec<SbomComponent>,
+    pub created: SystemTime,
+}
+
+impl Sbom {
+    /// Private function that builds the bill from a lockfile and the licenses of its packages,
+    /// keyed by name and version
+    pub(crate) fn from_lockfile(lockfile: &Lockfile, licenses: &BTreeMap<(String, String), String>, root: Option<&str>) -> Sbom {
+        let purl = |name: &str, version: &str| format!("pkg:cargo/{}@{}", name, version);
+        let mut components: Vec<SbomComponent> = lockfile
+            .packages
+            .iter()
+            .map(|package| {
+                // Dependencies are written as `name`, or `name version (source)` when several versions are locked
+                let dependencies = package
+                    .dependencies
+                    .iter()
+                    .filter_map(|dependency| {
+                        let mut parts = dependency.split_whitespace();
+                        let name = parts.next()?;
+                        let version = match parts.next() {
+                            Some(version) => version.to_string(),
+                            None => lockfile.get(name)?.version.clone(),
+                        };
+                        Some(purl(name, &version))
+                    })
+                    .collect();
+                SbomComponent {
+                    name: package.name.clone(),
+                    version: package.version.clone(),
+                    license: licenses.get(&(package.name.clone(), package.version.clone())).cloned(),
+                    source: package.source.clone(),
+                    checksum: package.checksum.clone(),
+                    dependencies,
+                }
+            })
+            .collect();
+        let root = root
+            .and_then(|root| components.iter().position(|component| component.name == root && component.source.is_none()))
+            .map(|index| components.remove(index));
+        Sbom { root, components, created: SystemTime::now() }
+    }
+
+    /// Returns the bill as pretty-printed CycloneDX 1.5 JSON
+    pub fn to_cyclonedx(&self) -> String {
+        let component = |component: &SbomComponent, kind: &str| {
+            let mut entry = json!({
+                "type": kind,
+                "bom-ref": component.purl(),
+                "name": component.name,
+                "version": component.version,
+                "purl": component.purl(),
+            });
+            if let Some(license) = &component.license {
+                entry["licenses"] = json!([{ "expression": license }]);
+            }
+            if let Some(checksum) = &component.checksum {
+                entry["hashes"] = json!([{ "alg": "SHA-256", "content": checksum }]);
+            }
+            entry
+        };
+        let mut metadata = json!({
+            "timestamp": rfc3339(self.created),
+            "tools": { "components": [{ "type": "application", "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") }] },
+        });
+        if let Some(root) = &self.root {
+            metadata["component"] = component(root, "application");
+        }
+        let dependencies: Vec<Value> =
+            self.all().map(|component| json!({ "ref": component.purl(), "dependsOn": component.dependencies })).collect();
+        let bom = json!({
+            "bomFormat": "CycloneDX",
+            "specVersion": "1.5",
+            "version": 1,
+            "metadata": metadata,
+            "components": self.components.iter().map(|c| component(c, "library")).collect::<Vec<_>>(),
+            "dependencies": dependencies,
+        });
+        serde_json::to_string_pretty(&bom).unwrap_or_default()
+    }
+
+    /// Returns the bill as pretty-printed SPDX 2.3 JSON
+    pub fn to_spdx(&self) -> String {
+        let ids: BTreeMap<String, String> = self.all().map(|component| (component.purl(), component.spdx_id())).collect();
+        let packages: Vec<Value> = self
+            .all()
+            .map(|component| {
+                let mut package = json!({
+                    "name": component.name,
+                    "SPDXID": component.spdx_id(),
+                    "versionInfo": component.version,
+                    "downloadLocation": component.download_location().unwrap_or_else(|| "NOASSERTION".to_string()),
+                    "filesAnalyzed": false,
+                    "licenseConcluded": "NOASSERTION",
+                    "licenseDeclared": component.license.as_deref().unwrap_or("NOASSERTION"),
+                    "copyrightText": "NOASSERTION",
+                    "externalRefs": [{ "referenceCategory": "PACKAGE-MANAGER", "referenceType": "purl", "referenceLocator": component.purl() }],
+                });
+                if let Some(checksum) = &component.checksum {
+                    package["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": checksum }]);
+                }
+                package
+            })
+            .collect();
+        let mut relationships = Vec::new();
+        if let Some(root) = &self.root {
+            relationships.push(json!({ "spdxElementId": "SPDXRef-DOCUMENT", "relationshipType": "DESCRIBES", "relatedSpdxElement": root.spdx_id() }));
+        }
+        for component in self.all() {
+            for dependency in component.dependencies.iter().filter_map(|dependency| ids.get(dependency)) {
+                relationships.push(json!({ "spdxElementId": component.spdx_id(), "relationshipType": "DEPENDS_ON", "relatedSpdxElement": dependency }));
+            }
+        }
+        let name = self.root.as_ref().map(|root| format!("{}-{}", root.name, root.version)).unwrap_or_else(|| "workspace".to_string());
+        let created = self.created.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+        let document = json!({
+            "spdxVersion": "SPDX-2.3",
+            "dataLicense": "CC0-1.0",
+            "SPDXID": "SPDXRef-DOCUMENT",
+            "name": name,
+            "documentNamespace": format!("https://spdx.org/spdxdocs/{}-{}", name, created),
+            "creationInfo": {
+                "created": rfc3339(self.created),
+                "creators": [format!("Tool: {}-{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))],
+            },
+            "packages": packages,
+            "relationships": relationships,
+        });
+        serde_json::to_string_pretty(&document).unwrap_or_default()
+    }
+
+    /// Returns the bill in `format`
+    pub fn render(&self, format: SbomFormat) -> String {
+        match format {
+            SbomFormat::CycloneDx => self.to_cyclonedx(),
+            SbomFormat::Spdx => self.to_spdx(),
+        }
+    }
+
+    /// Writes the bill in `format` to `path`, creating its parent directories.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the file cannot be written.
+    pub fn write(&self, path: impl AsRef<Path>, format: SbomFormat) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.render(format) + "\n")?;
+        Ok(())
+    }
+
+    /// Private function that iterates over the root and every component
+    fn all(&self) -> impl Iterator<Item = &SbomComponent> {
+        self.root.iter().chain(&self.components)
+    }
+}
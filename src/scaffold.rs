@@ -0,0 +1,311 @@
+use crate::pipeline::run_tool;
+use crate::{Builder, CargoWrapError, ProjectSettings, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// The kind of package created by a [`ProjectScaffold`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum ProjectKind {
+    /// A binary package with `src/main.rs` (`--bin`).
+    #[default]
+    Bin,
+    /// A library package with `src/lib.rs` (`--lib`).
+    Lib,
+}
+
+impl ProjectKind {
+    /// Returns the flag passed to `cargo new`, e.g. `--lib`
+    pub fn as_flag(self) -> &'static str {
+        match self {
+            ProjectKind::Bin => "--bin",
+            ProjectKind::Lib => "--lib",
+        }
+    }
+}
+
+/// The version control system a new package is initialized with (`--vcs X`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum Vcs {
+    /// A git repository, with a `.gitignore` ignoring `target/`.
+    Git,
+    /// A Mercurial repository.
+    Hg,
+    /// A Pijul repository.
+    Pijul,
+    /// A Fossil repository.
+    Fossil,
+    /// No repository is created.
+    None,
+}
+
+impl Vcs {
+    /// Returns the name of the system as understood by `cargo new --vcs X`, e.g. `git`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Vcs::Git => "git",
+            Vcs::Hg => "hg",
+            Vcs::Pijul => "pijul",
+            Vcs::Fossil => "fossil",
+            Vcs::None => "none",
+        }
+    }
+}
+
+impl fmt::Display for Vcs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How a [`ProjectScaffold`] creates a package.
+///
+/// Templates are written after cargo created the package, overwriting the files it generated
+/// (e.g. `src/main.rs`). Placeholders written `{{key}}` are replaced in their contents and
+/// paths: `{{name}}` is the package name, `{{crate_name}}` the name with `-` replaced by `_`,
+/// `{{edition}}` the edition if set, and every variable set with [`ScaffoldOptions::variable`].
+/// Other placeholders are left as is.
+///
+/// # Fields
+///
+/// * `name` - Optional package name (`--name X`). Defaults to the name of the directory.
+/// * `edition` - Optional Rust edition (`--edition X`), e.g. `2021`. Defaults to cargo's latest.
+/// * `vcs` - Optional version control system (`--vcs X`). Defaults to git, unless the directory is already in a repository.
+/// * `files` - Template files written into the package, by path relative to the package root.
+/// * `template_dir` - Optional directory whose files are all written into the package as templates.
+/// * `variables` - Additional values of the `{{key}}` placeholders.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{ScaffoldOptions, Vcs};
+///
+/// let options = ScaffoldOptions::new()
+///     .name("hello-cli")
+///     .vcs(Vcs::None)
+///     .variable("greeting", "Hello")
+///     .file("src/main.rs", "fn main() {\n    println!(\"{{greeting}} from {{crate_name}}\");\n}\n");
+/// assert_eq!(options.render("{{greeting}} from {{crate_name}}", "hello-cli"), "Hello from hello_cli");
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct ScaffoldOptions {
+    pub name: Option<String>,
+    pub edition: Option<String>,
+    pub vcs: Option<Vcs>,
+    pub files: BTreeMap<PathBuf, String>,
+    pub template_dir: Option<PathBuf>,
+    pub variables: BTreeMap<String, String>,
+}
+
+impl ScaffoldOptions {
+    /// Creates new `ScaffoldOptions` with cargo's defaults and no templates
+    pub fn new() -> Self {
+        ScaffoldOptions::default()
+    }
+
+    /// Sets the package name
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the Rust edition, e.g. `2021`
+    pub fn edition(mut self, edition: impl Into<String>) -> Self {
+        self.edition = Some(edition.into());
+        self
+    }
+
+    /// Sets the version control system
+    pub fn vcs(mut self, vcs: Vcs) -> Self {
+        self.vcs = Some(vcs);
+        self
+    }
+
+    /// Adds a template file at `path`, relative to the package root
+    pub fn file(mut self, path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
+        self.files.insert(path.into(), contents.into());
+        self
+    }
+
+    /// Sets the directory whose files are written into the package as templates, keeping their
+    /// relative paths
+    pub fn template_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.template_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets the value of the `{{key}}` placeholder
+    pub fn variable(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.variables.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns `template` with its placeholders replaced, for the package named `name`
+    pub fn render(&self, template: &str, name: &str) -> String {
+        let mut values: BTreeMap<&str, &str> = self.variables.iter().map(|(key, value)| (key.as_str(), value.as_str())).collect();
+        let crate_name = name.replace('-', "_");
+        values.insert("name", name);
+        values.insert("crate_name", &crate_name);
+        if let Some(edition) = &self.edition {
+            values.insert("edition", edition);
+        }
+        let mut rendered = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            match after.find("}}").and_then(|end| Some((values.get(after[..end].trim())?, end))) {
+                Some((value, end)) => {
+                    rendered.push_str(value);
+                    rest = &after[end + 2..];
+                }
+                None => {
+                    rendered.push_str("{{");
+                    rest = after;
+                }
+            }
+        }
+        rendered.push_str(rest);
+        rendered
+    }
+}
+
+/// Creates packages with `cargo new` or `cargo init`, then writes template files into them.
+///
+/// Both functions return the [`ProjectSettings`] of the new package, so it can be built right
+/// away with a [`Builder`].
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectKind, ProjectScaffold, ScaffoldOptions};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let options = ScaffoldOptions::new().edition("2021").template_dir("/path/to/templates/cli");
+///     let settings = ProjectScaffold::new()?.new_project("/path/to/hello-cli", ProjectKind::Bin, &options)?;
+///     let output = Builder::new(settings)?.build()?;
+///     println!("{:?}", output.executables().collect::<Vec<_>>());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProjectScaffold {
+    cargo_path: PathBuf,
+}
+
+impl ProjectScaffold {
+    /// Creates a new `ProjectScaffold` with the `cargo` binary found like [`Builder::new`] does.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::CargoNotFound`] if `cargo` cannot be found.
+    pub fn new() -> Result<Self> {
+        Ok(ProjectScaffold::with_cargo_path(Builder::find_cargo_path()?))
+    }
+
+    /// Creates a new `ProjectScaffold` using the given `cargo` binary
+    pub fn with_cargo_path(cargo_path: impl Into<PathBuf>) -> Self {
+        ProjectScaffold { cargo_path: cargo_path.into() }
+    }
+
+    /// Returns the path to the `cargo` binary
+    pub fn get_cargo_path(&self) -> &Path {
+        &self.cargo_path
+    }
+
+    /// Creates a package in the new directory `path` with `cargo new`, then writes the templates
+    /// of `options` into it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory to create.
+    /// * `kind` - Whether a binary or a library package is created.
+    /// * `options` - The name, edition, version control system and templates of the package.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ProjectSettings)` - The settings of the new package.
+    /// * `Err(CargoWrapError)` - If the package could not be created.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `cargo new` fails, e.g. because `path` already exists ([`CargoWrapError::ToolFailed`]).
+    /// - A template path is absolute or leaves the package ([`CargoWrapError::InvalidSettings`]).
+    /// - A template cannot be read or written ([`CargoWrapError::Io`]).
+    pub fn new_project(&self, path: impl AsRef<Path>, kind: ProjectKind, options: &ScaffoldOptions) -> Result<ProjectSettings> {
+        self.scaffold("new", path.as_ref(), kind, options)
+    }
+
+    /// Creates a package in the existing directory `path` with `cargo init`, then writes the
+    /// templates of `options` into it.
+    ///
+    /// # Errors
+    ///
+    /// See [`ProjectScaffold::new_project`]. `cargo init` fails if the directory already
+    /// contains a `Cargo.toml`.
+    pub fn init_project(&self, path: impl AsRef<Path>, kind: ProjectKind, options: &ScaffoldOptions) -> Result<ProjectSettings> {
+        self.scaffold("init", path.as_ref(), kind, options)
+    }
+
+    /// Private function that renders the templates, runs `cargo <subcommand>` for `path` and
+    /// writes the templates
+    fn scaffold(&self, subcommand: &str, path: &Path, kind: ProjectKind, options: &ScaffoldOptions) -> Result<ProjectSettings> {
+        // cargo names the package after the directory unless a name is given
+        let name = match &options.name {
+            Some(name) => name.clone(),
+            None => path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+        };
+        let mut templates: Vec<(PathBuf, String)> = Vec::new();
+        if let Some(dir) = &options.template_dir {
+            collect_templates(dir, Path::new(""), &mut templates)?;
+        }
+        templates.extend(options.files.iter().map(|(path, contents)| (path.clone(), contents.clone())));
+        let mut files = Vec::with_capacity(templates.len());
+        for (relative, contents) in templates {
+            let relative = PathBuf::from(options.render(&relative.to_string_lossy(), &name));
+            if relative.components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir)) {
+                return Err(CargoWrapError::InvalidSettings(format!("Template path {} must stay inside the package", relative.display())));
+            }
+            files.push((path.join(relative), options.render(&contents, &name)));
+        }
+        let mut command = Command::new(&self.cargo_path);
+        command.arg(subcommand).arg(kind.as_flag());
+        if let Some(name) = &options.name {
+            command.arg("--name").arg(name);
+        }
+        if let Some(edition) = &options.edition {
+            command.arg("--edition").arg(edition);
+        }
+        if let Some(vcs) = options.vcs {
+            command.arg("--vcs").arg(vcs.as_str());
+        }
+        run_tool(&format!("cargo {}", subcommand), command.arg(path))?;
+        for (destination, contents) in files {
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(destination, contents)?;
+        }
+        Ok(ProjectSettings::new(path))
+    }
+}
+
+/// Private function that reads every file below `dir` into `files`, by path relative to the
+/// template root
+fn collect_templates(dir: &Path, relative: &Path, files: &mut Vec<(PathBuf, String)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let relative = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_templates(&entry.path(), &relative, files)?;
+        } else {
+            files.push((relative, fs::read_to_string(entry.path())?));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use crate::pipeline::{resolve_tool, run_tool};
+use crate::{ArtifactStep, Result, StepContext, StepOutcome};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Signs the artifacts of a build, added to an [`ArtifactPipeline`](crate::ArtifactPipeline) with
+/// [`ArtifactPipeline::signer`](crate::ArtifactPipeline::signer).
+///
+/// Built-in signers shell out to `codesign` ([`Codesign`]), `osslsigncode` ([`Osslsigncode`])
+/// and `gpg` ([`GpgSigner`]). Signers for other tools or services implement this trait:
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{ArtifactPipeline, Builder, ProjectSettings, Signer, StepContext, StepOutcome};
+/// use std::path::Path;
+///
+/// #[derive(Debug)]
+/// struct SigningService;
+///
+/// impl Signer for SigningService {
+///     fn name(&self) -> &str {
+///         "signing-service"
+///     }
+///
+///     fn sign(&self, path: &Path, context: &StepContext) -> cargo_wrap::Result<StepOutcome> {
+///         println!("uploading {} ({}) for signing", path.display(), context.profile);
+///         Ok(StepOutcome::default())
+///     }
+/// }
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let pipeline = ArtifactPipeline::new().signer(SigningService);
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?.artifact_pipeline(pipeline);
+///     builder.build()?;
+///     Ok(())
+/// }
+/// ```
+pub trait Signer: fmt::Debug + Send + Sync {
+    /// Returns the name of the signer used in reports, e.g. `codesign`
+    fn name(&self) -> &str;
+
+    /// Signs the artifact at `path`, in place or by producing a signature file next to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The binary or shared library to sign.
+    /// * `context` - The target and profile the artifact was built for.
+    fn sign(&self, path: &Path, context: &StepContext) -> Result<StepOutcome>;
+}
+
+/// Private pipeline step running a [`Signer`]
+#[derive(Debug)]
+pub(crate) struct SignStep<S>(pub(crate) S);
+
+impl<S: Signer> ArtifactStep for SignStep<S> {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn process(&self, path: &Path, context: &StepContext) -> Result<StepOutcome> {
+        self.0.sign(path, context)
+    }
+}
+
+/// Private function that returns the outcome of a signer skipping an artifact it cannot sign
+fn skipped(path: &Path, platform: &str) -> StepOutcome {
+    let warning = format!("{} is not built for {}, it is not signed", path.display(), platform);
+    StepOutcome { warnings: vec![warning], ..Default::default() }
+}
+
+/// A signer running Apple's `codesign`, for artifacts built for Apple platforms.
+///
+/// Runs `codesign --force --sign <identity>`, with the hardened runtime, a secure timestamp and
+/// entitlements if requested. Artifacts built for other platforms are skipped with a warning.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{ArtifactPipeline, Codesign};
+///
+/// let signer = Codesign::new("Developer ID Application: Example (TEAMID)").hardened_runtime(true).timestamp(true);
+/// let pipeline = ArtifactPipeline::new().signer(signer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Codesign {
+    tool: Option<PathBuf>,
+    identity: String,
+    entitlements: Option<PathBuf>,
+    hardened_runtime: bool,
+    timestamp: bool,
+}
+
+impl Codesign {
+    /// Creates a new `Codesign` signer with the signing identity `identity`, `-` meaning ad-hoc signing
+    pub fn new(identity: impl Into<String>) -> Self {
+        Codesign { tool: None, identity: identity.into(), entitlements: None, hardened_runtime: false, timestamp: false }
+    }
+
+    /// Sets the path or name of the `codesign` binary. Defaults to `codesign`, searched in `PATH`.
+    pub fn tool(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    /// Sets the entitlements file embedded in the signature (`--entitlements X`)
+    pub fn entitlements(mut self, entitlements: impl Into<PathBuf>) -> Self {
+        self.entitlements = Some(entitlements.into());
+        self
+    }
+
+    /// Sets whether the hardened runtime is enabled (`--options runtime`), as required for notarization
+    pub fn hardened_runtime(mut self, hardened_runtime: bool) -> Self {
+        self.hardened_runtime = hardened_runtime;
+        self
+    }
+
+    /// Sets whether a secure timestamp is included (`--timestamp`)
+    pub fn timestamp(mut self, timestamp: bool) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Returns the signing identity
+    pub fn get_identity(&self) -> &str {
+        &self.identity
+    }
+}
+
+impl Signer for Codesign {
+    fn name(&self) -> &str {
+        "codesign"
+    }
+
+    fn sign(&self, path: &Path, context: &StepContext) -> Result<StepOutcome> {
+        if !context.is_apple() {
+            return Ok(skipped(path, "an Apple platform"));
+        }
+        let mut command = Command::new(resolve_tool(self.tool.as_deref(), "codesign")?);
+        command.arg("--force").arg("--sign").arg(&self.identity);
+        if self.hardened_runtime {
+            command.arg("--options").arg("runtime");
+        }
+        if self.timestamp {
+            command.arg("--timestamp");
+        }
+        if let Some(entitlements) = &self.entitlements {
+            command.arg("--entitlements").arg(entitlements);
+        }
+        let output = run_tool("codesign", command.arg(path))?;
+        Ok(StepOutcome { output, ..Default::default() })
+    }
+}
+
+/// A signer running [`osslsigncode`](https://github.com/mtrojnar/osslsigncode), which applies
+/// Authenticode signatures to artifacts built for Windows from any host.
+///
+/// The signed file is written next to the artifact, then replaces it. Artifacts built for other
+/// platforms are skipped with a warning.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{ArtifactPipeline, Osslsigncode};
+///
+/// let signer = Osslsigncode::new("certs/code-signing.pem", "certs/code-signing.key")
+///     .description("My App")
+///     .timestamp_url("http://timestamp.digicert.com");
+/// let pipeline = ArtifactPipeline::new().signer(signer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Osslsigncode {
+    tool: Option<PathBuf>,
+    certificate: PathBuf,
+    key: PathBuf,
+    password_file: Option<PathBuf>,
+    description: Option<String>,
+    timestamp_url: Option<String>,
+}
+
+impl Osslsigncode {
+    /// Creates a new `Osslsigncode` signer with a certificate (chain) and its private key
+    pub fn new(certificate: impl Into<PathBuf>, key: impl Into<PathBuf>) -> Self {
+        Osslsigncode {
+            tool: None,
+            certificate: certificate.into(),
+            key: key.into(),
+            password_file: None,
+            description: None,
+            timestamp_url: None,
+        }
+    }
+
+    /// Sets the path or name of the `osslsigncode` binary. Defaults to `osslsigncode`, searched in `PATH`.
+    pub fn tool(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    /// Sets the file containing the password of the private key (`-readpass X`)
+    pub fn password_file(mut self, password_file: impl Into<PathBuf>) -> Self {
+        self.password_file = Some(password_file.into());
+        self
+    }
+
+    /// Sets the description shown by Windows when the signed program asks for elevation (`-n X`)
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the URL of the timestamp server (`-t X`)
+    pub fn timestamp_url(mut self, timestamp_url: impl Into<String>) -> Self {
+        self.timestamp_url = Some(timestamp_url.into());
+        self
+    }
+}
+
+impl Signer for Osslsigncode {
+    fn name(&self) -> &str {
+        "osslsigncode"
+    }
+
+    fn sign(&self, path: &Path, context: &StepContext) -> Result<StepOutcome> {
+        if !context.is_windows() {
+            return Ok(skipped(path, "Windows"));
+        }
+        let mut signed = OsString::from(path.as_os_str());
+        signed.push(".signed");
+        let signed = PathBuf::from(signed);
+        let mut command = Command::new(resolve_tool(self.tool.as_deref(), "osslsigncode")?);
+        command.arg("sign").arg("-certs").arg(&self.certificate).arg("-key").arg(&self.key);
+        if let Some(password_file) = &self.password_file {
+            command.arg("-readpass").arg(password_file);
+        }
+        if let Some(description) = &self.description {
+            command.arg("-n").arg(description);
+        }
+        if let Some(timestamp_url) = &self.timestamp_url {
+            command.arg("-t").arg(timestamp_url);
+        }
+        let output = run_tool("osslsigncode", command.arg("-in").arg(path).arg("-out").arg(&signed))?;
+        fs::rename(&signed, path)?;
+        Ok(StepOutcome { output, ..Default::default() })
+    }
+}
+
+/// A signer creating detached GnuPG signatures (`gpg --detach-sign`) next to the artifacts,
+/// as `<artifact>.sig`, or `<artifact>.asc` when ASCII-armored.
+///
+/// `gpg` runs in batch mode, so the key must be usable without a passphrase prompt, e.g.
+/// through `gpg-agent`.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{ArtifactPipeline, GpgSigner};
+///
+/// let pipeline = ArtifactPipeline::new().signer(GpgSigner::new().key("release@example.com").armor(true));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GpgSigner {
+    tool: Option<PathBuf>,
+    key: Option<String>,
+    armor: bool,
+}
+
+impl GpgSigner {
+    /// Creates a new `GpgSigner` signing with the default key
+    pub fn new() -> Self {
+        GpgSigner::default()
+    }
+
+    /// Sets the path or name of the `gpg` binary. Defaults to `gpg`, searched in `PATH`.
+    pub fn tool(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    /// Sets the key to sign with (`--local-user X`), e.g. a key ID or an email address
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    /// Sets whether the signatures are ASCII-armored (`--armor`)
+    pub fn armor(mut self, armor: bool) -> Self {
+        self.armor = armor;
+        self
+    }
+
+    /// Returns the path of the signature of `artifact`
+    pub fn signature_path(&self, artifact: &Path) -> PathBuf {
+        let mut path = OsString::from(artifact.as_os_str());
+        path.push(if self.armor { ".asc" } else { ".sig" });
+        PathBuf::from(path)
+    }
+}
+
+impl Signer for GpgSigner {
+    fn name(&self) -> &str {
+        "gpg"
+    }
+
+    fn sign(&self, path: &Path, _context: &StepContext) -> Result<StepOutcome> {
+        let signature = self.signature_path(path);
+        let mut command = Command::new(resolve_tool(self.tool.as_deref(), "gpg")?);
+        command.arg("--batch").arg("--yes");
+        if let Some(key) = &self.key {
+            command.arg("--local-user").arg(key);
+        }
+        if self.armor {
+            command.arg("--armor");
+        }
+        command.arg("--output").arg(&signature).arg("--detach-sign").arg(path);
+        let output = run_tool("gpg", &mut command)?;
+        Ok(StepOutcome { output, warnings: Vec::new(), produced: vec![signature] })
+    }
+}
@@ -0,0 +1,259 @@
+use crate::color::strip_ansi;
+use crate::timestamp::rfc3339_millis;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::collections::VecDeque;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::process::Child;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::SystemTime;
+
+/// A destination for the output of cargo processes started by a [`Builder`](crate::Builder).
+///
+/// Every type implementing [`Write`] (files, sockets, `Vec<u8>`, `Box<dyn Write + Send>`, ...)
+/// is an `OutputSink`. Output is delivered exactly as it would be written to the log file: line
+/// by line as it is read, stdout and stderr interleaved in the order the lines arrive.
+pub trait OutputSink: Send {
+    /// Receives a chunk of output
+    fn write_output(&mut self, output: &[u8]) -> io::Result<()>;
+}
+
+impl<W: Write + Send> OutputSink for W {
+    fn write_output(&mut self, output: &[u8]) -> io::Result<()> {
+        self.write_all(output)?;
+        self.flush()
+    }
+}
+
+/// An in-memory [`OutputSink`] whose contents can be read while the builder still holds a clone.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, MemorySink, ProjectSettings};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let buffer = MemorySink::new();
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?.sink(buffer.clone());
+///     builder.build()?;
+///     println!("{}", String::from_utf8_lossy(&buffer.contents()));
+///     Ok(())
+/// }
+/// ```
+#[derive(Default, Debug, Clone)]
+pub struct MemorySink {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl MemorySink {
+    /// Creates a new, empty `MemorySink`
+    pub fn new() -> Self {
+        MemorySink::default()
+    }
+
+    /// Returns a copy of everything written to the sink so far
+    pub fn contents(&self) -> Vec<u8> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl Write for MemorySink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.lock().unwrap_or_else(PoisonError::into_inner).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Private collection of the sinks attached to a builder, shared with the builds it starts
+#[derive(Default)]
+pub(crate) struct OutputSinks(Mutex<Vec<Box<dyn OutputSink>>>);
+
+impl OutputSinks {
+    /// Private function that attaches another sink
+    pub(crate) fn push(&self, sink: Box<dyn OutputSink>) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).push(sink);
+    }
+
+    /// Private function that returns the number of attached sinks
+    pub(crate) fn len(&self) -> usize {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Private function that writes `output` to every sink, returning the first error after all
+    /// sinks were written to
+    pub(crate) fn write(&self, output: &[u8]) -> io::Result<()> {
+        let mut sinks = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        sinks.iter_mut().map(|sink| sink.write_output(output)).fold(Ok(()), Result::and)
+    }
+}
+
+impl fmt::Debug for OutputSinks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "OutputSinks({})", self.len())
+    }
+}
+
+/// Private buffer keeping the last lines of a stream, up to `limit` bytes, so errors can carry
+/// the end of the output without holding all of it in memory
+#[derive(Debug)]
+pub(crate) struct OutputTail {
+    lines: VecDeque<String>,
+    bytes: usize,
+    limit: usize,
+    truncated: bool,
+}
+
+impl OutputTail {
+    /// Private function that creates an empty tail keeping at most `limit` bytes
+    pub(crate) fn new(limit: usize) -> Self {
+        OutputTail { lines: VecDeque::new(), bytes: 0, limit, truncated: false }
+    }
+
+    /// Private function that appends `line`, dropping the oldest lines once over the limit
+    pub(crate) fn push(&mut self, line: &str) {
+        self.bytes += line.len() + 1;
+        self.lines.push_back(line.to_string());
+        while self.bytes > self.limit && self.lines.len() > 1 {
+            if let Some(dropped) = self.lines.pop_front() {
+                self.bytes -= dropped.len() + 1;
+                self.truncated = true;
+            }
+        }
+    }
+
+    /// Private function that returns the kept lines, preceded by a marker if earlier lines were dropped
+    pub(crate) fn into_string(self) -> String {
+        let mut output = String::with_capacity(self.bytes + 32);
+        if self.truncated {
+            output.push_str("[earlier output truncated]\n");
+        }
+        for line in self.lines {
+            output.push_str(&line);
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Private identifier of the stream a line of output was read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    /// Private function that returns the tag of the stream in timestamped logs
+    fn name(self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+}
+
+/// Private description of where the output of a cargo process is written: the log file, if
+/// any, and every attached sink
+#[derive(Debug, Clone)]
+pub(crate) struct OutputLog {
+    pub(crate) path: Option<PathBuf>,
+    pub(crate) sinks: Arc<OutputSinks>,
+    pub(crate) strip_ansi: bool,
+    pub(crate) timestamps: bool,
+}
+
+impl OutputLog {
+    /// Private function that opens the log file for appending
+    pub(crate) fn writer(&self) -> io::Result<OutputLogWriter> {
+        let file = match &self.path {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+        Ok(OutputLogWriter { log: self.clone(), file })
+    }
+}
+
+/// Private writer returned by [`OutputLog::writer`], which keeps the log file open
+pub(crate) struct OutputLogWriter {
+    log: OutputLog,
+    file: Option<File>,
+}
+
+impl OutputLogWriter {
+    /// Private function that writes the line `line` (without its newline) read from `stream` to
+    /// the log file and every sink, prefixed with the time and stream if timestamps are enabled
+    pub(crate) fn write_line(&mut self, stream: Stream, line: &[u8]) -> io::Result<()> {
+        let mut output = Vec::with_capacity(line.len() + 40);
+        if self.log.timestamps {
+            output.extend_from_slice(format!("[{} {}] ", rfc3339_millis(SystemTime::now()), stream.name()).as_bytes());
+        }
+        output.extend_from_slice(line);
+        output.push(b'\n');
+        let output = if self.log.strip_ansi { strip_ansi(&output).into_owned() } else { output };
+        if let Some(file) = &mut self.file {
+            file.write_all(&output)?;
+        }
+        self.log.sinks.write(&output)
+    }
+
+    /// Private function that reads the stdout and stderr of `child` on background threads,
+    /// writing every line to the log as soon as it is read, so both streams are interleaved in
+    /// the order the process wrote them
+    pub(crate) fn capture(self, child: &mut Child) -> CapturedOutput {
+        let writer = Arc::new(Mutex::new(self));
+        let stdout = child.stdout.take().map(|stdout| {
+            let writer = writer.clone();
+            thread::spawn(move || tee(stdout, Stream::Stdout, &writer))
+        });
+        let stderr = child.stderr.take().map(|stderr| thread::spawn(move || tee(stderr, Stream::Stderr, &writer)));
+        CapturedOutput { stdout, stderr }
+    }
+}
+
+/// Private handle to the threads started by [`OutputLogWriter::capture`]
+#[derive(Debug)]
+pub(crate) struct CapturedOutput {
+    stdout: Option<JoinHandle<io::Result<Vec<u8>>>>,
+    stderr: Option<JoinHandle<io::Result<Vec<u8>>>>,
+}
+
+impl CapturedOutput {
+    /// Private function that waits for both streams to be closed and returns everything read
+    /// from stdout and stderr
+    pub(crate) fn join(self) -> io::Result<(Vec<u8>, Vec<u8>)> {
+        Ok((join(self.stdout)?, join(self.stderr)?))
+    }
+}
+
+/// Private function that reads `reader` to its end, writing every line to `writer`. Reading
+/// continues if the log cannot be written, so the process never blocks on a full pipe; the
+/// first write error is returned once the stream is closed.
+fn tee(reader: impl Read, stream: Stream, writer: &Mutex<OutputLogWriter>) -> io::Result<Vec<u8>> {
+    let mut reader = BufReader::new(reader);
+    let (mut output, mut result) = (Vec::new(), Ok(()));
+    loop {
+        let start = output.len();
+        if reader.read_until(b'\n', &mut output)? == 0 {
+            break;
+        }
+        if result.is_ok() {
+            let line = &output[start..];
+            let line = line.strip_suffix(b"\n").unwrap_or(line);
+            result = writer.lock().unwrap_or_else(PoisonError::into_inner).write_line(stream, line);
+        }
+    }
+    result.map(|_| output)
+}
+
+/// Private function that joins an output reader thread
+fn join(reader: Option<JoinHandle<io::Result<Vec<u8>>>>) -> io::Result<Vec<u8>> {
+    match reader {
+        Some(reader) => reader.join().map_err(|_| io::Error::other("Output reader thread panicked"))?,
+        None => Ok(Vec::new()),
+    }
+}
@@ -0,0 +1,422 @@
+use crate::{BuildOutput, Builder, Result};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The size of a section of a binary, e.g. `.text`, see [`ArtifactSize`].
+///
+/// # Fields
+///
+/// * `name` - The name of the section.
+/// * `size` - The size of the section, in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionSize {
+    pub name: String,
+    pub size: u64,
+}
+
+/// The size of a binary or library produced by a build, see [`SizeSnapshot`].
+///
+/// # Fields
+///
+/// * `name` - The file name of the artifact.
+/// * `path` - The path of the artifact.
+/// * `size` - The size of the file, in bytes.
+/// * `sections` - The sizes of its non-empty sections, as reported by `size -A`, `llvm-size -A` or
+///   `objdump -h`. Empty if none of them is installed or understands the file format. Sections of
+///   the members of static libraries are summed up by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactSize {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub sections: Vec<SectionSize>,
+}
+
+impl ArtifactSize {
+    /// Measures the file at `path` and its sections.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the file cannot be read.
+    pub fn measure(path: impl AsRef<Path>) -> Result<ArtifactSize> {
+        let path = path.as_ref();
+        Ok(ArtifactSize {
+            name: path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default(),
+            path: path.to_path_buf(),
+            size: fs::metadata(path)?.len(),
+            sections: section_sizes(path),
+        })
+    }
+
+    /// Returns the size of the section named `name`, e.g. `.text`
+    pub fn section(&self, name: &str) -> Option<u64> {
+        self.sections.iter().find(|section| section.name == name).map(|section| section.size)
+    }
+}
+
+/// Private function that reads the section sizes of the binary at `path`, with the first tool
+/// that is installed and understands it
+fn section_sizes(path: &Path) -> Vec<SectionSize> {
+    for tool in ["size", "llvm-size"] {
+        if let Some(sections) = run_size_tool(tool, &["-A"], path, parse_sysv) {
+            return sections;
+        }
+    }
+    run_size_tool("objdump", &["-h"], path, parse_objdump).unwrap_or_default()
+}
+
+/// Private function that runs `tool` on `path` and parses its output with `parse`
+fn run_size_tool(tool: &str, args: &[&str], path: &Path, parse: fn(&str) -> Vec<(String, u64)>) -> Option<Vec<SectionSize>> {
+    let output = Command::new(Builder::find_binary(tool)?).args(args).arg(path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut sections: Vec<SectionSize> = Vec::new();
+    for (name, size) in parse(&String::from_utf8_lossy(&output.stdout)) {
+        match sections.iter_mut().find(|section| section.name == name) {
+            Some(section) => section.size += size,
+            None if size > 0 => sections.push(SectionSize { name, size }),
+            None => {}
+        }
+    }
+    Some(sections)
+}
+
+/// Private function that parses the SysV format of `size -A`: a `section size addr` row per section
+fn parse_sysv(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let (name, size) = (columns.next()?, columns.next()?.parse().ok()?);
+            (name != "Total").then(|| (name.to_string(), size))
+        })
+        .collect()
+}
+
+/// Private function that parses the section headers printed by `objdump -h`: an
+/// `index name size vma ...` row per section, sizes being hexadecimal
+fn parse_objdump(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            columns.next()?.parse::<usize>().ok()?;
+            let name = columns.next()?;
+            Some((name.to_string(), u64::from_str_radix(columns.next()?, 16).ok()?))
+        })
+        .collect()
+}
+
+/// The sizes of the binaries and libraries produced by a build, recorded in a [`SizeHistory`]
+/// by [`Builder::set_size_tracking`](crate::Builder::set_size_tracking).
+///
+/// # Fields
+///
+/// * `timestamp` - When the build finished, in seconds since the Unix epoch.
+/// * `target` - The compilation target triple, `None` meaning the host.
+/// * `profile` - The name of the profile the artifacts were built with.
+/// * `artifacts` - The sizes of every executable, shared library and static library of the build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeSnapshot {
+    pub timestamp: u64,
+    pub target: Option<String>,
+    pub profile: String,
+    pub artifacts: Vec<ArtifactSize>,
+}
+
+impl SizeSnapshot {
+    /// Measures the executables, shared libraries and static libraries of `output`, see
+    /// [`BuildOutput::binaries`].
+    ///
+    /// # Arguments
+    ///
+    /// * `output` - The output of the build.
+    /// * `target` - The compilation target triple of the build, `None` meaning the host.
+    /// * `profile` - The name of the profile of the build.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if an artifact cannot be read.
+    pub fn measure(output: &BuildOutput, target: Option<&str>, profile: &str) -> Result<SizeSnapshot> {
+        let artifacts = output.binaries().iter().map(ArtifactSize::measure).collect::<Result<_>>()?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+        Ok(SizeSnapshot { timestamp, target: target.map(String::from), profile: profile.to_string(), artifacts })
+    }
+
+    /// Returns the size of the artifact named `name`
+    pub fn get(&self, name: &str) -> Option<&ArtifactSize> {
+        self.artifacts.iter().find(|artifact| artifact.name == name)
+    }
+
+    /// Returns the total size of the artifacts, in bytes
+    pub fn total(&self) -> u64 {
+        self.artifacts.iter().map(|artifact| artifact.size).sum()
+    }
+
+    /// Private function that converts the snapshot to the JSON stored in the history file
+    fn to_value(&self) -> Value {
+        let artifacts: Vec<Value> = self
+            .artifacts
+            .iter()
+            .map(|artifact| {
+                let sections: Vec<Value> =
+                    artifact.sections.iter().map(|section| serde_json::json!({ "name": section.name, "size": section.size })).collect();
+                serde_json::json!({ "name": artifact.name, "path": artifact.path, "size": artifact.size, "sections": sections })
+            })
+            .collect();
+        serde_json::json!({ "timestamp": self.timestamp, "target": self.target, "profile": self.profile, "artifacts": artifacts })
+    }
+
+    /// Private function that reads a snapshot from the JSON stored in the history file
+    fn from_value(value: &Value) -> Option<SizeSnapshot> {
+        let artifacts = value["artifacts"].as_array()?.iter().map(|artifact| {
+            let sections = artifact["sections"].as_array().into_iter().flatten().filter_map(|section| {
+                Some(SectionSize { name: section["name"].as_str()?.to_string(), size: section["size"].as_u64()? })
+            });
+            Some(ArtifactSize {
+                name: artifact["name"].as_str()?.to_string(),
+                path: PathBuf::from(artifact["path"].as_str().unwrap_or_default()),
+                size: artifact["size"].as_u64()?,
+                sections: sections.collect(),
+            })
+        });
+        Some(SizeSnapshot {
+            timestamp: value["timestamp"].as_u64().unwrap_or_default(),
+            target: value["target"].as_str().map(String::from),
+            profile: value["profile"].as_str()?.to_string(),
+            artifacts: artifacts.collect::<Option<_>>()?,
+        })
+    }
+}
+
+/// The sizes of successive builds, stored as JSON by
+/// [`Builder::set_size_tracking`](crate::Builder::set_size_tracking):
+///
+/// ```json
+/// {
+///   "snapshots": [
+///     {
+///       "timestamp": 1718000000,
+///       "target": null,
+///       "profile": "release",
+///       "artifacts": [
+///         { "name": "app", "path": "target/release/app", "size": 4435400, "sections": [{ "name": ".text", "size": 3190784 }] }
+///       ]
+///     }
+///   ]
+/// }
+/// ```
+///
+/// # Fields
+///
+/// * `snapshots` - The sizes of every recorded build, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeHistory {
+    pub snapshots: Vec<SizeSnapshot>,
+}
+
+impl SizeHistory {
+    /// Reads the history stored at `path`, or returns an empty history if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be read ([`CargoWrapError::Io`](crate::CargoWrapError::Io)),
+    /// or is not a size history ([`CargoWrapError::InvalidSettings`](crate::CargoWrapError::InvalidSettings)).
+    pub fn open(path: impl AsRef<Path>) -> Result<SizeHistory> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(SizeHistory::default());
+        }
+        SizeHistory::from_json(&fs::read_to_string(path)?).ok_or_else(|| {
+            crate::CargoWrapError::InvalidSettings(format!("{} is not a size history", path.display()))
+        })
+    }
+
+    /// Parses a history from its JSON representation, returning `None` if it is malformed
+    pub fn from_json(json: &str) -> Option<SizeHistory> {
+        let value: Value = serde_json::from_str(json).ok()?;
+        let snapshots = value["snapshots"].as_array()?.iter().map(SizeSnapshot::from_value).collect::<Option<_>>()?;
+        Some(SizeHistory { snapshots })
+    }
+
+    /// Returns the history as pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        let snapshots: Vec<Value> = self.snapshots.iter().map(SizeSnapshot::to_value).collect();
+        serde_json::to_string_pretty(&serde_json::json!({ "snapshots": snapshots })).unwrap_or_default()
+    }
+
+    /// Writes the history as JSON to `path`, creating its parent directories.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the file cannot be written.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_json() + "\n")?;
+        Ok(())
+    }
+
+    /// Returns the snapshots of builds for `target` with `profile`, oldest first
+    pub fn snapshots_for<'a>(&'a self, target: Option<&'a str>, profile: &'a str) -> impl DoubleEndedIterator<Item = &'a SizeSnapshot> {
+        self.snapshots.iter().filter(move |snapshot| snapshot.target.as_deref() == target && snapshot.profile == profile)
+    }
+
+    /// Compares the last two builds for `target` with `profile`.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The compilation target triple of the builds, `None` meaning the host.
+    /// * `profile` - The name of the profile of the builds.
+    /// * `threshold` - The growth, in percent, beyond which an artifact is a regression.
+    ///
+    /// # Returns
+    ///
+    /// The comparison, or `None` if no build for `target` with `profile` was recorded. If only one
+    /// was, it is compared with nothing.
+    pub fn compare_latest(&self, target: Option<&str>, profile: &str, threshold: f64) -> Option<SizeComparison> {
+        let mut snapshots = self.snapshots_for(target, profile).rev();
+        let current = snapshots.next()?;
+        Some(SizeComparison::new(snapshots.next(), current, threshold))
+    }
+}
+
+/// The change in size of an artifact or section between two builds, see [`SizeComparison`].
+///
+/// # Fields
+///
+/// * `name` - The name of the artifact or section.
+/// * `previous` - The size in the previous build, `None` if it did not exist.
+/// * `current` - The size in the current build, `None` if it no longer exists.
+/// * `sections` - The changes of the sections of an artifact, empty for sections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeDelta {
+    pub name: String,
+    pub previous: Option<u64>,
+    pub current: Option<u64>,
+    pub sections: Vec<SizeDelta>,
+}
+
+impl SizeDelta {
+    /// Returns the change in bytes, positive if the artifact grew
+    pub fn delta(&self) -> i64 {
+        self.current.unwrap_or_default() as i64 - self.previous.unwrap_or_default() as i64
+    }
+
+    /// Returns the change in percent of the previous size, or `None` if there is no previous size
+    pub fn percent(&self) -> Option<f64> {
+        let previous = self.previous.filter(|previous| *previous > 0)?;
+        Some(self.delta() as f64 * 100.0 / previous as f64)
+    }
+}
+
+/// Private function that pairs the sizes of `previous` and `current` by name, in the order of
+/// `current` followed by the names only in `previous`
+fn pair_sizes(previous: &[(&str, u64)], current: &[(&str, u64)]) -> Vec<SizeDelta> {
+    let size_in = |sizes: &[(&str, u64)], name: &str| sizes.iter().find(|(other, _)| *other == name).map(|(_, size)| *size);
+    let delta = |name: &str, previous, current| SizeDelta { name: name.to_string(), previous, current, sections: Vec::new() };
+    let mut deltas: Vec<SizeDelta> = current.iter().map(|(name, size)| delta(name, size_in(previous, name), Some(*size))).collect();
+    for (name, size) in previous {
+        if size_in(current, name).is_none() {
+            deltas.push(delta(name, Some(*size), None));
+        }
+    }
+    deltas
+}
+
+/// Private function that lists the sizes of the artifacts of `snapshot`, if any
+fn artifact_sizes(snapshot: Option<&SizeSnapshot>) -> Vec<(&str, u64)> {
+    snapshot.into_iter().flat_map(|snapshot| &snapshot.artifacts).map(|artifact| (artifact.name.as_str(), artifact.size)).collect()
+}
+
+/// Private function that lists the sizes of the sections of the artifact `name` of `snapshot`, if any
+fn artifact_section_sizes<'a>(snapshot: Option<&'a SizeSnapshot>, name: &str) -> Vec<(&'a str, u64)> {
+    let artifact = snapshot.and_then(|snapshot| snapshot.get(name));
+    artifact.into_iter().flat_map(|artifact| &artifact.sections).map(|section| (section.name.as_str(), section.size)).collect()
+}
+
+/// The changes in size between two builds, returned by
+/// [`Builder::compare_with_previous`](crate::Builder::compare_with_previous).
+///
+/// An artifact is a regression if it grew by more than the threshold, in percent of its previous
+/// size. Artifacts that are new in the current build are not regressions.
+///
+/// # Fields
+///
+/// * `previous` - The sizes of the previous build, `None` if the current build is the first.
+/// * `current` - The sizes of the current build.
+/// * `deltas` - The change of every artifact of both builds.
+/// * `threshold` - The growth, in percent, beyond which an artifact is a regression.
+///
+/// # Example
+/// ```rust
+/// use cargo_wrap::{ArtifactSize, SizeComparison, SizeSnapshot};
+///
+/// let snapshot = |size: u64| SizeSnapshot {
+///     timestamp: 0,
+///     target: None,
+///     profile: "release".to_string(),
+///     artifacts: vec![ArtifactSize { name: "app".to_string(), path: "target/release/app".into(), size, sections: Vec::new() }],
+/// };
+/// let (previous, current) = (snapshot(1000), snapshot(1100));
+/// let comparison = SizeComparison::new(Some(&previous), &current, 5.0);
+/// assert_eq!(comparison.deltas[0].delta(), 100);
+/// assert_eq!(comparison.regressions().len(), 1);
+/// assert!(SizeComparison::new(Some(&previous), &current, 20.0).regressions().is_empty());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeComparison {
+    pub previous: Option<SizeSnapshot>,
+    pub current: SizeSnapshot,
+    pub deltas: Vec<SizeDelta>,
+    pub threshold: f64,
+}
+
+impl SizeComparison {
+    /// Compares the sizes of `current` with those of `previous`
+    pub fn new(previous: Option<&SizeSnapshot>, current: &SizeSnapshot, threshold: f64) -> Self {
+        let mut deltas = pair_sizes(&artifact_sizes(previous), &artifact_sizes(Some(current)));
+        for delta in &mut deltas {
+            delta.sections =
+                pair_sizes(&artifact_section_sizes(previous, &delta.name), &artifact_section_sizes(Some(current), &delta.name));
+        }
+        SizeComparison { previous: previous.cloned(), current: current.clone(), deltas, threshold }
+    }
+
+    /// Returns the artifacts that grew by more than the threshold
+    pub fn regressions(&self) -> Vec<&SizeDelta> {
+        self.deltas.iter().filter(|delta| delta.percent().is_some_and(|percent| percent > self.threshold)).collect()
+    }
+
+    /// Returns `true` if an artifact grew by more than the threshold
+    pub fn has_regressions(&self) -> bool {
+        !self.regressions().is_empty()
+    }
+}
+
+impl fmt::Display for SizeComparison {
+    /// Formats the comparison as one line per artifact, e.g.
+    /// `app: 1000 -> 1100 bytes (+100, +10.00%) regression`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for delta in &self.deltas {
+            let size = |size: Option<u64>| size.map_or_else(|| "-".to_string(), |size| size.to_string());
+            write!(f, "{}: {} -> {} bytes ({:+}", delta.name, size(delta.previous), size(delta.current), delta.delta())?;
+            if let Some(percent) = delta.percent() {
+                write!(f, ", {:+.2}%", percent)?;
+            }
+            f.write_str(")")?;
+            if delta.percent().is_some_and(|percent| percent > self.threshold) {
+                f.write_str(" regression")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
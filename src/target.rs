@@ -0,0 +1,53 @@
+use std::process::Command;
+
+/// A selection of package targets to build, in addition to (or instead of) the named
+/// binary/library configured with [`ProjectSettings::target`](crate::ProjectSettings::target).
+///
+/// Several selections can be combined, e.g. building a binary together with an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BuildTarget {
+    /// The package's library (`--lib`).
+    Lib,
+    /// A single binary (`--bin <name>`).
+    Bin(String),
+    /// Every binary (`--bins`).
+    Bins,
+    /// A single example (`--example <name>`).
+    Example(String),
+    /// Every example (`--examples`).
+    Examples,
+    /// A single integration test (`--test <name>`).
+    Test(String),
+    /// Every integration test (`--tests`).
+    Tests,
+    /// A single benchmark (`--bench <name>`).
+    Bench(String),
+    /// Every benchmark (`--benches`).
+    Benches,
+    /// Every target of the package (`--all-targets`).
+    AllTargets,
+}
+
+impl BuildTarget {
+    /// Private function that appends the flags selecting this target to `command`
+    pub(crate) fn apply(&self, command: &mut Command) {
+        match self {
+            BuildTarget::Lib => command.arg("--lib"),
+            BuildTarget::Bin(name) => command.arg("--bin").arg(name),
+            BuildTarget::Bins => command.arg("--bins"),
+            BuildTarget::Example(name) => command.arg("--example").arg(name),
+            BuildTarget::Examples => command.arg("--examples"),
+            BuildTarget::Test(name) => command.arg("--test").arg(name),
+            BuildTarget::Tests => command.arg("--tests"),
+            BuildTarget::Bench(name) => command.arg("--bench").arg(name),
+            BuildTarget::Benches => command.arg("--benches"),
+            BuildTarget::AllTargets => command.arg("--all-targets"),
+        };
+    }
+
+    /// Returns `true` if this selection can be started with `cargo run`
+    pub fn is_runnable(&self) -> bool {
+        matches!(self, BuildTarget::Bin(_) | BuildTarget::Example(_))
+    }
+}
@@ -0,0 +1,345 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Options controlling a `cargo test` run.
+///
+/// # Fields
+///
+/// * `filters` - Test name filters; only tests whose names contain one of the filters are run.
+/// * `no_run` - If `true`, compiles the tests without running them (`--no-run`).
+/// * `nocapture` - If `true`, test output is not captured by the harness (`--nocapture`).
+/// * `test_threads` - Optional number of threads the harness uses to run tests (`--test-threads N`).
+/// * `no_fail_fast` - If `true`, every test binary is run even if one of them fails (`--no-fail-fast`).
+/// * `allow_bootstrap` - If `true`, [`Builder::test_report`](crate::Builder::test_report) enables
+///   unstable options on stable and beta toolchains with `RUSTC_BOOTSTRAP=1`.
+#[derive(Default, Debug, Clone)]
+pub struct TestOptions {
+    pub filters: Vec<String>,
+    pub no_run: bool,
+    pub nocapture: bool,
+    pub test_threads: Option<usize>,
+    pub no_fail_fast: bool,
+    pub allow_bootstrap: bool,
+}
+
+impl TestOptions {
+    /// Private function that returns the arguments passed to the test harness after `--`
+    pub(crate) fn harness_args(&self) -> Vec<String> {
+        let mut args = self.filters.clone();
+        if self.nocapture {
+            args.push("--nocapture".to_string());
+        }
+        if let Some(threads) = self.test_threads {
+            args.push("--test-threads".to_string());
+            args.push(threads.to_string());
+        }
+        args
+    }
+}
+
+/// Aggregated results of a `cargo test` run, summed over every test binary that was executed.
+///
+/// # Fields
+///
+/// * `passed` - Number of tests that passed.
+/// * `failed` - Number of tests that failed.
+/// * `ignored` - Number of tests that were ignored.
+/// * `measured` - Number of benchmarks that were measured.
+/// * `filtered_out` - Number of tests excluded by the filters.
+/// * `success` - Whether `cargo test` exited successfully.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TestSummary {
+    pub passed: usize,
+    pub failed: usize,
+    pub ignored: usize,
+    pub measured: usize,
+    pub filtered_out: usize,
+    pub success: bool,
+}
+
+impl TestSummary {
+    /// Private function that sums up every `test result:` line found in the harness output.
+    ///
+    /// Returns `None` if no test binary reported results.
+    pub(crate) fn parse(output: &str) -> Option<TestSummary> {
+        let mut summary = TestSummary::default();
+        let mut found = false;
+        for line in output.lines() {
+            let Some(counts) = line.trim().strip_prefix("test result: ") else {
+                continue;
+            };
+            found = true;
+            for count in counts.split(';') {
+                let mut parts = count.split_whitespace();
+                let (Some(value), Some(name)) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+                // The first entry looks like "ok. 5 passed"
+                let (value, name) = match value.strip_suffix('.') {
+                    Some(_) => (name, parts.next().unwrap_or_default()),
+                    None => (value, name),
+                };
+                let Ok(value) = value.parse::<usize>() else {
+                    continue;
+                };
+                match name {
+                    "passed" => summary.passed += value,
+                    "failed" => summary.failed += value,
+                    "ignored" => summary.ignored += value,
+                    "measured" => summary.measured += value,
+                    "filtered" => summary.filtered_out += value,
+                    _ => {}
+                }
+            }
+        }
+        found.then_some(summary)
+    }
+}
+
+/// The outcome of a single test, see [`TestCase`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestStatus {
+    /// The test passed.
+    Passed,
+    /// The test failed.
+    Failed,
+    /// The test was ignored (`#[ignore]`).
+    Ignored,
+    /// The test exceeded the harness's time limit.
+    TimedOut,
+}
+
+/// A single test of a [`TestSuite`].
+///
+/// # Fields
+///
+/// * `name` - The path of the test, e.g. `parser::tests::empty_input`, or the location of a documentation test.
+/// * `status` - The outcome of the test.
+/// * `duration` - Optional time the test took, missing for ignored tests.
+/// * `stdout` - Optional output captured from the test, including the panic message of failed tests.
+/// * `message` - Optional failure message reported by the harness, e.g. for `#[should_panic]` tests.
+/// * `attempts` - How many times the test was run, more than 1 if it was retried by nextest.
+///   The other fields describe the last attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    pub name: String,
+    pub status: TestStatus,
+    pub duration: Option<Duration>,
+    pub stdout: Option<String>,
+    pub message: Option<String>,
+    pub attempts: usize,
+}
+
+/// The tests run by one test binary, see [`TestReport`].
+///
+/// # Fields
+///
+/// * `name` - Optional description of the binary as printed by cargo, e.g.
+///   `unittests src/lib.rs (target/debug/deps/app-1f2e3d)` or `Doc-tests app`, or its nextest
+///   binary ID, e.g. `app::integration`.
+/// * `tests` - Every test that finished, in the order they finished.
+/// * `duration` - Optional time the whole binary took.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestSuite {
+    pub name: Option<String>,
+    pub tests: Vec<TestCase>,
+    pub duration: Option<Duration>,
+}
+
+/// The per-test results of a `cargo test` run, see [`Builder::test_report`](crate::Builder::test_report),
+/// or of a `cargo nextest run`, see [`Nextest::run`](crate::Nextest::run).
+///
+/// # Fields
+///
+/// * `suites` - One suite per test binary that was run, in order.
+/// * `summary` - The results summed over every suite.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct TestReport {
+    pub suites: Vec<TestSuite>,
+    pub summary: TestSummary,
+}
+
+/// Private mirror of the events of libtest's JSON format
+#[derive(Deserialize)]
+struct TestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    exec_time: Option<f64>,
+    stdout: Option<String>,
+    message: Option<String>,
+    #[serde(default)]
+    passed: usize,
+    #[serde(default)]
+    failed: usize,
+    #[serde(default)]
+    ignored: usize,
+    #[serde(default)]
+    measured: usize,
+    #[serde(default)]
+    filtered_out: usize,
+}
+
+impl TestReport {
+    /// Returns every test of every suite
+    pub fn tests(&self) -> impl Iterator<Item = &TestCase> {
+        self.suites.iter().flat_map(|suite| &suite.tests)
+    }
+
+    /// Returns the tests that failed or timed out
+    pub fn failures(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests().filter(|test| matches!(test.status, TestStatus::Failed | TestStatus::TimedOut))
+    }
+
+    /// Returns the tests that passed after failing at least once
+    pub fn flaky(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests().filter(|test| test.status == TestStatus::Passed && test.attempts > 1)
+    }
+
+    /// Parses the libtest JSON events on `stdout`, naming the suites after cargo's
+    /// `Running ...` and `Doc-tests ...` lines on `stderr`.
+    ///
+    /// Lines that are not JSON events, e.g. output of tests run with `--nocapture`, are skipped.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{TestReport, TestStatus};
+    ///
+    /// let stdout = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+    /// { "type": "test", "event": "started", "name": "tests::adds" }
+    /// { "type": "test", "name": "tests::adds", "event": "ok", "exec_time": 0.001 }
+    /// { "type": "test", "name": "tests::parses", "event": "failed", "exec_time": 0.002, "stdout": "assertion failed\n" }
+    /// { "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.003 }"#;
+    /// let stderr = "     Running unittests src/lib.rs (target/debug/deps/app-1f2e3d)\n";
+    /// let report = TestReport::parse(stdout, stderr);
+    /// assert_eq!(report.suites[0].name.as_deref(), Some("unittests src/lib.rs (target/debug/deps/app-1f2e3d)"));
+    /// assert_eq!(report.summary.failed, 1);
+    /// let failure = report.failures().next().unwrap();
+    /// assert_eq!((failure.name.as_str(), failure.status), ("tests::parses", TestStatus::Failed));
+    /// ```
+    pub fn parse(stdout: &str, stderr: &str) -> TestReport {
+        let mut names = stderr.lines().map(str::trim).filter_map(|line| match line.strip_prefix("Running ") {
+            Some(name) => Some(name.to_string()),
+            None => line.starts_with("Doc-tests ").then(|| line.to_string()),
+        });
+        let duration = |seconds: Option<f64>| seconds.filter(|seconds| seconds.is_finite() && *seconds >= 0.0).map(Duration::from_secs_f64);
+        let mut report = TestReport::default();
+        for line in stdout.lines() {
+            let Ok(event) = serde_json::from_str::<TestEvent>(line.trim()) else { continue };
+            match (event.kind.as_str(), event.event.as_str()) {
+                ("suite", "started") => report.suites.push(TestSuite { name: names.next(), tests: Vec::new(), duration: None }),
+                ("suite", _) => {
+                    let summary = &mut report.summary;
+                    summary.passed += event.passed;
+                    summary.failed += event.failed;
+                    summary.ignored += event.ignored;
+                    summary.measured += event.measured;
+                    summary.filtered_out += event.filtered_out;
+                    if let Some(suite) = report.suites.last_mut() {
+                        suite.duration = duration(event.exec_time);
+                    }
+                }
+                ("test", outcome) => {
+                    let status = match outcome {
+                        "ok" => TestStatus::Passed,
+                        "failed" => TestStatus::Failed,
+                        "ignored" => TestStatus::Ignored,
+                        "timeout" => TestStatus::TimedOut,
+                        _ => continue,
+                    };
+                    let test = TestCase {
+                        name: event.name.unwrap_or_default(),
+                        status,
+                        duration: duration(event.exec_time),
+                        stdout: event.stdout.filter(|stdout| !stdout.is_empty()),
+                        message: event.message,
+                        attempts: 1,
+                    };
+                    if let Some(suite) = report.suites.last_mut() {
+                        suite.tests.push(test);
+                    }
+                }
+                _ => {}
+            }
+        }
+        report
+    }
+
+    /// Private function that parses the libtest JSON events written by
+    /// `cargo nextest run --message-format libtest-json`, and the summary line on `stderr`.
+    ///
+    /// nextest names tests `<binary id>$<test name>`, adding `#<attempts>` when a test was
+    /// retried, and only reports the last attempt. Its suite events do not match the test
+    /// binaries, so the suites are grouped by binary ID instead, and the tests skipped by filters
+    /// or partitioning are taken from the `Summary` line.
+    pub(crate) fn parse_nextest(stdout: &str, stderr: &str) -> TestReport {
+        let duration = |seconds: Option<f64>| seconds.filter(|seconds| seconds.is_finite() && *seconds >= 0.0).map(Duration::from_secs_f64);
+        let mut report = TestReport::default();
+        // Every binary is run once per test, so its ignored tests are reported several times
+        let mut ignored: BTreeMap<String, usize> = BTreeMap::new();
+        let mut binary = String::new();
+        for line in stdout.lines() {
+            let Ok(event) = serde_json::from_str::<TestEvent>(line.trim()) else { continue };
+            match (event.kind.as_str(), event.event.as_str()) {
+                ("suite", "started") => {}
+                ("suite", _) => {
+                    let count = ignored.entry(binary.clone()).or_default();
+                    *count = (*count).max(event.ignored);
+                    if let Some(suite) = report.suites.iter_mut().find(|suite| suite.name.as_deref() == Some(&binary))
+                        && let Some(elapsed) = duration(event.exec_time)
+                    {
+                        suite.duration = Some(suite.duration.unwrap_or_default() + elapsed);
+                    }
+                }
+                ("test", outcome) => {
+                    let full_name = event.name.unwrap_or_default();
+                    let (binary_id, name) = full_name.split_once('$').unwrap_or(("", &full_name));
+                    binary = binary_id.to_string();
+                    let status = match outcome {
+                        "ok" => TestStatus::Passed,
+                        "failed" => TestStatus::Failed,
+                        "ignored" => TestStatus::Ignored,
+                        "timeout" => TestStatus::TimedOut,
+                        _ => continue,
+                    };
+                    let (name, attempts) = match name.rsplit_once('#').map(|(name, attempts)| (name, attempts.parse())) {
+                        Some((name, Ok(attempts))) => (name, attempts),
+                        _ => (name, 1),
+                    };
+                    let test = TestCase {
+                        name: name.to_string(),
+                        status,
+                        duration: duration(event.exec_time),
+                        stdout: event.stdout.filter(|stdout| !stdout.is_empty()),
+                        message: event.message,
+                        attempts,
+                    };
+                    match report.suites.iter_mut().find(|suite| suite.name.as_deref() == Some(binary_id)) {
+                        Some(suite) => suite.tests.push(test),
+                        None => report.suites.push(TestSuite { name: Some(binary.clone()), tests: vec![test], duration: None }),
+                    }
+                }
+                _ => {}
+            }
+        }
+        let summary = &mut report.summary;
+        for test in report.suites.iter().flat_map(|suite| &suite.tests) {
+            match test.status {
+                TestStatus::Passed => summary.passed += 1,
+                TestStatus::Failed | TestStatus::TimedOut => summary.failed += 1,
+                TestStatus::Ignored => summary.ignored += 1,
+            }
+        }
+        summary.ignored += ignored.values().sum::<usize>();
+        // e.g. "     Summary [   0.050s] 2 tests run: 1 passed, 1 failed, 3 skipped"
+        let skipped = stderr
+            .lines()
+            .filter(|line| line.trim_start().starts_with("Summary "))
+            .flat_map(|line| line.split(&[':', ',']))
+            .find_map(|count| count.trim().strip_suffix(" skipped")?.parse::<usize>().ok());
+        summary.filtered_out = skipped.unwrap_or_default().saturating_sub(summary.ignored);
+        report
+    }
+}
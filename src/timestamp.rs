@@ -0,0 +1,36 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Private function that formats `time` as an RFC 3339 UTC timestamp, e.g. `2024-05-01T12:00:00Z`
+pub(crate) fn rfc3339(time: SystemTime) -> String {
+    let (date, time) = civil(time);
+    format!("{}T{}Z", date, time)
+}
+
+/// Private function that formats `time` as an RFC 3339 UTC timestamp with milliseconds, e.g.
+/// `2024-05-01T12:00:00.250Z`
+pub(crate) fn rfc3339_millis(time: SystemTime) -> String {
+    let millis = time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.subsec_millis()).unwrap_or_default();
+    let (date, time) = civil(time);
+    format!("{}T{}.{:03}Z", date, time, millis)
+}
+
+/// Private function that splits `time` into its UTC date (`2024-05-01`) and time of day
+/// (`12:00:00`), truncated to the second
+fn civil(time: SystemTime) -> (String, String) {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or_default();
+    let (days, seconds) = ((seconds / 86_400) as i64, seconds % 86_400);
+    // Converts days since 1970-01-01 to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (
+        format!("{:04}-{:02}-{:02}", year, month, day),
+        format!("{:02}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60),
+    )
+}
@@ -0,0 +1,151 @@
+use crate::Result;
+use crate::color::strip_ansi;
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// The line cargo prints once the timing report is written.
+const REPORT_SAVED: &str = "Timing report saved to ";
+
+/// The JavaScript constant holding the per-unit data in cargo's HTML timing report.
+const UNIT_DATA: &str = "const UNIT_DATA = ";
+
+/// The compile time of a single unit, as recorded in cargo's timing report.
+///
+/// A package can be compiled as several units, e.g. its build script, the execution of the
+/// build script and its library.
+///
+/// # Fields
+///
+/// * `name` - The name of the package.
+/// * `version` - The version of the package.
+/// * `target` - A description of the compiled target, empty for the library (e.g. `build-script`,
+///   `build-script (run)` or `bin "cli"`).
+/// * `mode` - How the unit was compiled (e.g. `todo` for builds, `run-custom-build` for running build scripts).
+/// * `start` - The time elapsed since the start of the build when the unit started compiling.
+/// * `duration` - The time spent compiling the unit.
+/// * `rmeta_time` - The time after which the unit's metadata was available to dependent units, if reported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitTiming {
+    pub name: String,
+    pub version: String,
+    pub target: String,
+    pub mode: String,
+    pub start: Duration,
+    pub duration: Duration,
+    pub rmeta_time: Option<Duration>,
+}
+
+/// The compile times of a build, read from the report cargo writes with `--timings`.
+///
+/// # Fields
+///
+/// * `report_path` - Path of the HTML timing report.
+/// * `units` - Every unit of the build. Units that were up to date have a zero duration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildTimings {
+    pub report_path: PathBuf,
+    pub units: Vec<UnitTiming>,
+}
+
+#[derive(Deserialize)]
+struct RawUnit {
+    name: String,
+    version: String,
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    mode: String,
+    start: f64,
+    duration: f64,
+    #[serde(default)]
+    rmeta_time: Option<f64>,
+}
+
+impl From<RawUnit> for UnitTiming {
+    fn from(unit: RawUnit) -> Self {
+        UnitTiming {
+            name: unit.name,
+            version: unit.version,
+            target: unit.target.trim().to_string(),
+            mode: unit.mode,
+            start: seconds(unit.start),
+            duration: seconds(unit.duration),
+            rmeta_time: unit.rmeta_time.map(seconds),
+        }
+    }
+}
+
+/// Private function that converts a number of seconds reported by cargo into a `Duration`
+fn seconds(seconds: f64) -> Duration {
+    Duration::try_from_secs_f64(seconds).unwrap_or_default()
+}
+
+impl BuildTimings {
+    /// Reads the per-unit compile times from an HTML timing report written by `cargo build --timings`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the report, e.g. `target/cargo-timings/cargo-timing.html`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return [`CargoWrapError::Io`](crate::CargoWrapError::Io) if the report
+    /// cannot be read or does not contain the unit data.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::BuildTimings;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     let timings = BuildTimings::from_report("target/cargo-timings/cargo-timing.html")?;
+    ///     for (name, duration) in timings.crate_durations().iter().take(5) {
+    ///         println!("{:>8.2}s {}", duration.as_secs_f64(), name);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_report(path: impl AsRef<Path>) -> Result<BuildTimings> {
+        let path = path.as_ref();
+        let report = fs::read_to_string(path)?;
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a cargo timing report", path.display()));
+        let (_, data) = report.split_once(UNIT_DATA).ok_or_else(invalid)?;
+        let units: Vec<RawUnit> = serde_json::Deserializer::from_str(data)
+            .into_iter()
+            .next()
+            .ok_or_else(invalid)?
+            .map_err(io::Error::from)?;
+        Ok(BuildTimings { report_path: path.to_path_buf(), units: units.into_iter().map(Into::into).collect() })
+    }
+
+    /// Returns the total compile time of every package, summed over its units, slowest first
+    pub fn crate_durations(&self) -> Vec<(String, Duration)> {
+        let mut durations: BTreeMap<&str, Duration> = BTreeMap::new();
+        for unit in &self.units {
+            *durations.entry(&unit.name).or_default() += unit.duration;
+        }
+        let mut durations: Vec<(String, Duration)> = durations.into_iter().map(|(name, duration)| (name.to_string(), duration)).collect();
+        durations.sort_by_key(|(_, duration)| Reverse(*duration));
+        durations
+    }
+
+    /// Returns the total compile time of the package named `name`, if it was compiled
+    pub fn duration_of(&self, name: &str) -> Option<Duration> {
+        let mut units = self.units.iter().filter(|unit| unit.name == name).peekable();
+        units.peek()?;
+        Some(units.map(|unit| unit.duration).sum())
+    }
+
+    /// Private function that finds the path of the timing report in cargo's stderr
+    pub(crate) fn report_path(stderr: &str) -> Option<PathBuf> {
+        let stderr = strip_ansi(stderr.as_bytes());
+        String::from_utf8_lossy(&stderr)
+            .lines()
+            .find_map(|line| line.trim_start().strip_prefix(REPORT_SAVED))
+            .map(|path| PathBuf::from(path.trim_end()))
+    }
+}
@@ -0,0 +1,425 @@
+use crate::pipeline::run_tool;
+use crate::{Builder, CargoWrapError, Result};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::{env, fmt, io};
+
+/// A thin wrapper around the `rustup` binary, used to inspect and manage toolchains.
+///
+/// # Fields
+///
+/// * `rustup_path` - Path to the `rustup` binary. Defaults to `rustup`, resolved through `PATH`.
+#[derive(Debug, Clone)]
+pub struct Rustup {
+    rustup_path: PathBuf,
+}
+
+impl Default for Rustup {
+    fn default() -> Self {
+        Rustup { rustup_path: PathBuf::from("rustup") }
+    }
+}
+
+impl Rustup {
+    /// Creates a new `Rustup` instance that resolves `rustup` through `PATH`
+    pub fn new() -> Self {
+        Rustup::default()
+    }
+
+    /// Creates a new `Rustup` instance using the given `rustup` binary
+    pub fn with_path(rustup_path: impl Into<PathBuf>) -> Self {
+        Rustup { rustup_path: rustup_path.into() }
+    }
+
+    /// Returns the path to the `rustup` binary
+    pub fn get_path(&self) -> &Path {
+        &self.rustup_path
+    }
+
+    /// Private function that creates a `rustup` command
+    pub(crate) fn command(&self) -> Command {
+        Command::new(&self.rustup_path)
+    }
+
+    /// Private function that runs a `rustup` command and returns its stdout on success
+    pub(crate) fn output(&self, args: &[&str]) -> Result<String> {
+        let output = self.command().args(args).output()?;
+        if !output.status.success() {
+            return Err(CargoWrapError::ToolFailed {
+                tool: "rustup".to_string(),
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Retrieves the names of every installed toolchain (e.g. `stable-x86_64-unknown-linux-gnu`).
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rustup` cannot be run or fails.
+    pub fn installed_toolchains(&self) -> Result<Vec<String>> {
+        let listing = self.output(&["toolchain", "list"])?;
+        Ok(listing
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .filter(|name| *name != "no")
+            .map(String::from)
+            .collect())
+    }
+
+    /// Checks whether `toolchain` is installed.
+    ///
+    /// The toolchain can be given with or without the host triple, e.g. both `nightly-2024-05-01`
+    /// and `nightly-2024-05-01-x86_64-unknown-linux-gnu` are accepted. `nightly` does not match
+    /// a dated nightly such as `nightly-2024-05-01`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rustup` cannot be run or fails.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use cargo_wrap::Rustup;
+    ///
+    /// fn main() -> cargo_wrap::Result<()> {
+    ///     if !Rustup::new().is_installed("nightly")? {
+    ///         eprintln!("nightly is not installed");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn is_installed(&self, toolchain: &str) -> Result<bool> {
+        Ok(self.installed_toolchains()?.iter().any(|name| toolchain_matches(name, toolchain)))
+    }
+
+    /// Installs `toolchain` with the minimal profile using `rustup toolchain install`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rustup` cannot be run or fails, e.g. because the
+    /// toolchain does not exist ([`CargoWrapError::ToolFailed`]).
+    pub fn install_toolchain(&self, toolchain: &str) -> Result<()> {
+        self.output(&["toolchain", "install", toolchain, "--profile", "minimal"]).map(|_| ())
+    }
+
+    /// Retrieves the compilation targets installed for a toolchain.
+    ///
+    /// # Arguments
+    ///
+    /// * `toolchain` - The toolchain to inspect, or `None` for the active toolchain.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rustup` cannot be run or fails.
+    pub fn installed_targets(&self, toolchain: Option<&str>) -> Result<Vec<String>> {
+        let mut args = vec!["target", "list", "--installed"];
+        if let Some(toolchain) = toolchain {
+            args.extend(["--toolchain", toolchain]);
+        }
+        Ok(self.output(&args)?.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+    }
+
+    /// Installs a compilation target using `rustup target add`.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target triple to install, e.g. `aarch64-unknown-linux-musl`.
+    /// * `toolchain` - The toolchain to install the target for, or `None` for the active toolchain.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rustup` cannot be run or fails, e.g. because the
+    /// target is not available for the toolchain ([`CargoWrapError::ToolFailed`]).
+    pub fn add_target(&self, target: &str, toolchain: Option<&str>) -> Result<()> {
+        let mut args = vec!["target", "add", target];
+        if let Some(toolchain) = toolchain {
+            args.extend(["--toolchain", toolchain]);
+        }
+        self.output(&args).map(|_| ())
+    }
+
+    /// Retrieves the components installed for a toolchain, e.g. `rust-src` or
+    /// `clippy-x86_64-unknown-linux-gnu`.
+    ///
+    /// # Arguments
+    ///
+    /// * `toolchain` - The toolchain to inspect, or `None` for the active toolchain.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rustup` cannot be run or fails.
+    pub fn installed_components(&self, toolchain: Option<&str>) -> Result<Vec<String>> {
+        let mut args = vec!["component", "list", "--installed"];
+        if let Some(toolchain) = toolchain {
+            args.extend(["--toolchain", toolchain]);
+        }
+        Ok(self.output(&args)?.lines().map(str::trim).filter(|l| !l.is_empty()).map(String::from).collect())
+    }
+
+    /// Installs a component using `rustup component add`.
+    ///
+    /// # Arguments
+    ///
+    /// * `component` - The component to install, e.g. `rust-src`.
+    /// * `toolchain` - The toolchain to install the component for, or `None` for the active toolchain.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if `rustup` cannot be run or fails, e.g. because the
+    /// component is not available for the toolchain ([`CargoWrapError::ToolFailed`]).
+    pub fn add_component(&self, component: &str, toolchain: Option<&str>) -> Result<()> {
+        let mut args = vec!["component", "add", component];
+        if let Some(toolchain) = toolchain {
+            args.extend(["--toolchain", toolchain]);
+        }
+        self.output(&args).map(|_| ())
+    }
+}
+
+/// Private function that checks whether an installed toolchain `name` is the requested `toolchain`
+fn toolchain_matches(name: &str, toolchain: &str) -> bool {
+    match name.strip_prefix(toolchain) {
+        Some("") => true,
+        // What's left must be the host triple, not a date or a more specific version
+        Some(rest) => rest.strip_prefix('-').is_some_and(|host| !host.starts_with(|c: char| c.is_ascii_digit())),
+        None => false,
+    }
+}
+
+/// The release channel of a Rust toolchain, see [`ToolchainInfo`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReleaseChannel {
+    /// Stable releases, e.g. `1.80.0`.
+    #[default]
+    Stable,
+    /// Beta releases, e.g. `1.81.0-beta.3`.
+    Beta,
+    /// Nightly releases, e.g. `1.82.0-nightly`.
+    Nightly,
+    /// A locally built toolchain (`-dev` releases).
+    Dev,
+}
+
+/// The numeric part of a Rust release, e.g. `1.80.0` for `1.80.0-nightly`.
+///
+/// Versions are ordered by their parts, so they can be compared to gate features on the
+/// toolchain, see [`ToolchainInfo::is_at_least`].
+///
+/// # Fields
+///
+/// * `major` - The major version.
+/// * `minor` - The minor version.
+/// * `patch` - The patch version.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RustVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl RustVersion {
+    /// Creates a new `RustVersion`
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        RustVersion { major, minor, patch }
+    }
+
+    /// Parses a release such as `1.80.0`, `1.80` or `1.81.0-nightly`. A missing patch version is `0`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::RustVersion;
+    ///
+    /// assert_eq!(RustVersion::parse("1.81.0-nightly"), Some(RustVersion::new(1, 81, 0)));
+    /// assert!(RustVersion::parse("1.70").unwrap() < RustVersion::new(1, 70, 1));
+    /// assert_eq!(RustVersion::parse("stable"), None);
+    /// ```
+    pub fn parse(release: &str) -> Option<RustVersion> {
+        let numbers = release.split(['-', '+']).next()?;
+        let mut parts = numbers.split('.').map(str::parse::<u64>);
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = match parts.next() {
+            Some(patch) => patch.ok()?,
+            None => 0,
+        };
+        Some(RustVersion { major, minor, patch })
+    }
+}
+
+impl fmt::Display for RustVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// The versions of `cargo` and `rustc` of a toolchain, as reported by `cargo -vV` and `rustc -vV`.
+///
+/// # Fields
+///
+/// * `cargo_version` - The version of cargo.
+/// * `rustc_version` - The version of rustc.
+/// * `release` - The full rustc release, e.g. `1.81.0-nightly`.
+/// * `channel` - The release channel of the toolchain.
+/// * `commit_hash` - Optional commit rustc was built from, missing for some distributions.
+/// * `commit_date` - Optional date of that commit, e.g. `2024-07-21`.
+/// * `host` - The host triple of the toolchain, e.g. `x86_64-unknown-linux-gnu`.
+/// * `llvm_version` - Optional version of the LLVM backend of rustc.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{RustVersion, ToolchainInfo};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let info = ToolchainInfo::detect()?;
+///     println!("rustc {} ({:?}) on {}", info.rustc_version, info.channel, info.host);
+///     let timings = info.is_at_least(RustVersion::new(1, 60, 0));
+///     println!("--timings supported: {}", timings);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ToolchainInfo {
+    pub cargo_version: RustVersion,
+    pub rustc_version: RustVersion,
+    pub release: String,
+    pub channel: ReleaseChannel,
+    pub commit_hash: Option<String>,
+    pub commit_date: Option<String>,
+    pub host: String,
+    pub llvm_version: Option<String>,
+}
+
+impl ToolchainInfo {
+    /// Detects the versions of the `cargo` and `rustc` used by default: `$CARGO` and `$RUSTC`
+    /// if set, otherwise the binaries found in `PATH` or `~/.cargo/bin`.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - `cargo` or `rustc` cannot be found ([`CargoWrapError::CargoNotFound`], [`CargoWrapError::ToolNotFound`]).
+    /// - Either exits unsuccessfully ([`CargoWrapError::ToolFailed`]).
+    /// - Their output cannot be parsed ([`CargoWrapError::Io`]).
+    pub fn detect() -> Result<ToolchainInfo> {
+        let cargo = env::var_os("CARGO").map(PathBuf::from).or_else(|| Builder::find_binary("cargo")).ok_or(CargoWrapError::CargoNotFound)?;
+        ToolchainInfo::detect_with(&cargo)
+    }
+
+    /// Detects the versions of the rustup toolchain `toolchain`, using `rustup run <toolchain>`.
+    ///
+    /// # Errors
+    ///
+    /// See [`ToolchainInfo::detect`]. `rustup` fails if the toolchain is not installed.
+    pub fn detect_toolchain(toolchain: &str) -> Result<ToolchainInfo> {
+        let rustup = Rustup::new();
+        let mut cargo = rustup.command();
+        cargo.args(["run", toolchain, "cargo"]);
+        let mut rustc = rustup.command();
+        rustc.args(["run", toolchain, "rustc"]);
+        ToolchainInfo::from_commands(cargo, rustc)
+    }
+
+    /// Private function that detects the versions of the `cargo` binary at `cargo`, and of the
+    /// `rustc` next to it, if any
+    pub(crate) fn detect_with(cargo: &Path) -> Result<ToolchainInfo> {
+        ToolchainInfo::from_commands(Command::new(cargo), Command::new(rustc_path(cargo)?))
+    }
+
+    /// Private function that runs `cargo -vV` and `rustc -vV` and parses their output
+    fn from_commands(mut cargo: Command, mut rustc: Command) -> Result<ToolchainInfo> {
+        let cargo = run_tool("cargo", cargo.arg("-vV"))?;
+        let rustc = run_tool("rustc", rustc.arg("-vV"))?;
+        ToolchainInfo::parse(&cargo, &rustc).ok_or_else(|| io::Error::other("unrecognized output of `rustc -vV`").into())
+    }
+
+    /// Parses the output of `cargo -vV` and `rustc -vV`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the release or host of `rustc` is missing, or a version cannot be parsed.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cargo_wrap::{ReleaseChannel, RustVersion, ToolchainInfo};
+    ///
+    /// let cargo = "cargo 1.81.0-nightly (bc89bffa5 2024-06-22)\nrelease: 1.81.0-nightly\nhost: x86_64-unknown-linux-gnu\n";
+    /// let rustc = "rustc 1.81.0-nightly (6b0f4b5ec 2024-06-24)\nbinary: rustc\ncommit-hash: 6b0f4b5ec3aa707ecaa78230722117324a4ce23c\n\
+    ///              commit-date: 2024-06-24\nhost: x86_64-unknown-linux-gnu\nrelease: 1.81.0-nightly\nLLVM version: 18.1.7\n";
+    /// let info = ToolchainInfo::parse(cargo, rustc).unwrap();
+    /// assert_eq!(info.rustc_version, RustVersion::new(1, 81, 0));
+    /// assert_eq!(info.channel, ReleaseChannel::Nightly);
+    /// assert_eq!(info.commit_date.as_deref(), Some("2024-06-24"));
+    /// assert!(info.is_at_least(RustVersion::new(1, 60, 0)));
+    /// ```
+    pub fn parse(cargo: &str, rustc: &str) -> Option<ToolchainInfo> {
+        let fields = |output: &str| -> BTreeMap<String, String> {
+            output
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect()
+        };
+        let (cargo, mut rustc) = (fields(cargo), fields(rustc));
+        let release = rustc.remove("release")?;
+        let channel = match release.split_once('-').map(|(_, channel)| channel) {
+            Some(channel) if channel.starts_with("nightly") => ReleaseChannel::Nightly,
+            Some(channel) if channel.starts_with("beta") => ReleaseChannel::Beta,
+            Some(channel) if channel.starts_with("dev") => ReleaseChannel::Dev,
+            _ => ReleaseChannel::Stable,
+        };
+        // Distributions sometimes report `unknown` for the commit
+        let known = |value: Option<String>| value.filter(|value| value != "unknown");
+        Some(ToolchainInfo {
+            cargo_version: RustVersion::parse(cargo.get("release")?)?,
+            rustc_version: RustVersion::parse(&release)?,
+            channel,
+            commit_hash: known(rustc.remove("commit-hash")),
+            commit_date: known(rustc.remove("commit-date")),
+            host: rustc.remove("host")?,
+            llvm_version: rustc.remove("LLVM version"),
+            release,
+        })
+    }
+
+    /// Returns `true` if rustc is at least `version`, e.g. to check whether a cargo flag is supported
+    pub fn is_at_least(&self, version: RustVersion) -> bool {
+        self.rustc_version >= version
+    }
+}
+
+/// Private function that returns the `rustc` used along the `cargo` binary at `cargo`: `$RUSTC`
+/// if set, otherwise the `rustc` next to it or the one found in `PATH`
+pub(crate) fn rustc_path(cargo: &Path) -> Result<PathBuf> {
+    let sibling = cargo.with_file_name(format!("rustc{}", env::consts::EXE_SUFFIX));
+    env::var_os("RUSTC")
+        .map(PathBuf::from)
+        .or_else(|| sibling.is_file().then_some(sibling))
+        .or_else(|| Builder::find_binary("rustc"))
+        .ok_or_else(|| CargoWrapError::ToolNotFound("rustc".to_string()))
+}
+
+/// Private function that orders versions such as `3.28.3` or `1.0.0-rc.1` by their numeric parts,
+/// missing parts counting as `0` and a release coming after its pre-releases. Shared by the
+/// minimum versions of [`Prerequisites`](crate::Prerequisites) and the dependency graph.
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn key(version: &str) -> (Vec<u64>, Option<&str>) {
+        let version = version.split_once('+').map_or(version, |(version, _)| version);
+        let (release, pre) = version.split_once('-').map_or((version, None), |(release, pre)| (release, Some(pre)));
+        let numbers = release
+            .split('.')
+            .map(|part| part.chars().take_while(char::is_ascii_digit).collect::<String>().parse().unwrap_or(0))
+            .collect();
+        (numbers, pre)
+    }
+    let ((a, a_pre), (b, b_pre)) = (key(a), key(b));
+    (0..a.len().max(b.len()))
+        .map(|i| a.get(i).copied().unwrap_or(0).cmp(&b.get(i).copied().unwrap_or(0)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| (a_pre.is_none(), a_pre).cmp(&(b_pre.is_none(), b_pre)))
+}
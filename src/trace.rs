@@ -0,0 +1,61 @@
+use crate::{BuildOutput, Result};
+use std::path::Path;
+use std::time::Duration;
+
+/// Private function that runs `f` inside a `cargo` span, emitting events when it starts and ends
+pub(crate) fn instrument<T>(subcommand: &str, project: &Path, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::info_span!("cargo", subcommand, project = %project.display());
+        let _guard = span.enter();
+        tracing::info!("{} started", subcommand);
+        let result = f();
+        match &result {
+            Ok(_) => tracing::info!("{} finished", subcommand),
+            Err(error) => tracing::error!(%error, "{} failed", subcommand),
+        }
+        result
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (subcommand, project);
+        f()
+    }
+}
+
+/// Private function that emits an event for every compiled crate and every warning or error
+pub(crate) fn report(output: &BuildOutput) {
+    #[cfg(feature = "tracing")]
+    {
+        use crate::DiagnosticLevel;
+        for artifact in &output.artifacts {
+            tracing::debug!(target_name = %artifact.target_name, kind = ?artifact.kind, fresh = artifact.fresh, "compiled crate");
+        }
+        for diagnostic in &output.diagnostics {
+            let code = diagnostic.code.as_deref().unwrap_or_default();
+            match diagnostic.level {
+                DiagnosticLevel::Error => tracing::error!(package_id = %diagnostic.package_id, code, "{}", diagnostic.message),
+                DiagnosticLevel::Warning => tracing::warn!(package_id = %diagnostic.package_id, code, "{}", diagnostic.message),
+                _ => {}
+            }
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = output;
+}
+
+/// Private function that emits an event when a failed build is retried
+pub(crate) fn retry(attempt: u32, delay: Duration) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(attempt, ?delay, "build failed because of the network, retrying");
+    #[cfg(not(feature = "tracing"))]
+    let _ = (attempt, delay);
+}
+
+/// Private function that emits an event when a pipeline step did not fully apply to an artifact
+pub(crate) fn step_warning(step: &str, artifact: &Path, warning: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(step, artifact = %artifact.display(), "{}", warning);
+    #[cfg(not(feature = "tracing"))]
+    let _ = (step, artifact, warning);
+}
@@ -0,0 +1,111 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// The units cargo would compile for a build, as reported by the nightly
+/// `cargo build --unit-graph -Z unstable-options`.
+///
+/// # Fields
+///
+/// * `version` - The version of the unit graph format.
+/// * `units` - Every unit of the build. Dependencies refer to units by their index in this list.
+/// * `roots` - Indices of the units that were requested, e.g. the package's library and binaries.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UnitGraph {
+    pub version: u32,
+    pub units: Vec<Unit>,
+    pub roots: Vec<usize>,
+}
+
+/// A single unit of compilation: one target of one package, built with one profile in one mode.
+///
+/// # Fields
+///
+/// * `pkg_id` - The cargo package ID of the package the unit belongs to.
+/// * `target` - The target that is compiled.
+/// * `profile` - The profile settings the unit is compiled with.
+/// * `platform` - The target triple the unit is compiled for, `None` meaning the host.
+/// * `mode` - What is done with the target (e.g. `build`, `check`, `test` or `run-custom-build`).
+/// * `features` - The features enabled for the unit.
+/// * `dependencies` - The units this unit depends on.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Unit {
+    pub pkg_id: String,
+    pub target: UnitTarget,
+    pub profile: UnitProfile,
+    pub platform: Option<String>,
+    pub mode: String,
+    pub features: Vec<String>,
+    pub dependencies: Vec<UnitDependency>,
+}
+
+/// The target a [`Unit`] compiles.
+///
+/// # Fields
+///
+/// * `name` - The name of the target.
+/// * `kind` - The target kinds, e.g. `["bin"]`, `["lib"]` or `["custom-build"]`.
+/// * `crate_types` - The crate types produced for the target.
+/// * `src_path` - Path of the target's root source file.
+/// * `edition` - The Rust edition of the target.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UnitTarget {
+    pub name: String,
+    pub kind: Vec<String>,
+    pub crate_types: Vec<String>,
+    pub src_path: PathBuf,
+    pub edition: String,
+}
+
+/// The profile settings a [`Unit`] is compiled with.
+///
+/// # Fields
+///
+/// * `name` - The name of the profile (e.g. `dev` or `release`).
+/// * `opt_level` - The optimization level.
+/// * `lto` - The LTO setting.
+/// * `panic` - The panic strategy.
+/// * `debug_assertions` - `true` if debug assertions are enabled.
+/// * `overflow_checks` - `true` if integer overflow checks are enabled.
+/// * `incremental` - `true` if the unit is compiled incrementally.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UnitProfile {
+    pub name: String,
+    pub opt_level: String,
+    pub lto: String,
+    pub panic: String,
+    pub debug_assertions: bool,
+    pub overflow_checks: bool,
+    pub incremental: bool,
+}
+
+/// An edge of the unit graph.
+///
+/// # Fields
+///
+/// * `index` - Index of the dependency in [`UnitGraph::units`].
+/// * `extern_crate_name` - The name the dependency is available as in the dependent unit.
+/// * `public` - `true` if the dependency is public.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UnitDependency {
+    pub index: usize,
+    pub extern_crate_name: String,
+    #[serde(default)]
+    pub public: bool,
+}
+
+impl UnitGraph {
+    /// Returns the units that were requested
+    pub fn root_units(&self) -> impl Iterator<Item = &Unit> {
+        self.roots.iter().filter_map(|&index| self.units.get(index))
+    }
+
+    /// Returns the units `unit` directly depends on
+    pub fn dependencies_of<'a>(&'a self, unit: &'a Unit) -> impl Iterator<Item = &'a Unit> {
+        unit.dependencies.iter().filter_map(|dependency| self.units.get(dependency.index))
+    }
+
+    /// Returns the units of the target named `target_name`
+    pub fn units_for<'a>(&'a self, target_name: &'a str) -> impl Iterator<Item = &'a Unit> {
+        self.units.iter().filter(move |unit| unit.target.name == target_name)
+    }
+}
@@ -0,0 +1,22 @@
+use crate::BuildOutput;
+use std::path::PathBuf;
+
+/// The Apple targets combined into universal binaries by [`Builder::build_universal`](crate::Builder::build_universal).
+pub(crate) const UNIVERSAL_TARGETS: [&str; 2] = ["aarch64-apple-darwin", "x86_64-apple-darwin"];
+
+/// The directory, inside the target directory, universal binaries are written to.
+pub(crate) const UNIVERSAL_DIR: &str = "universal-apple-darwin";
+
+/// The output of a macOS universal build, see [`Builder::build_universal`](crate::Builder::build_universal).
+///
+/// # Fields
+///
+/// * `aarch64` - The output of the `aarch64-apple-darwin` build.
+/// * `x86_64` - The output of the `x86_64-apple-darwin` build.
+/// * `binaries` - The universal binaries and libraries created by `lipo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniversalOutput {
+    pub aarch64: BuildOutput,
+    pub x86_64: BuildOutput,
+    pub binaries: Vec<PathBuf>,
+}
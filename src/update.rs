@@ -0,0 +1,49 @@
+use crate::{LockedPackage, LockfileDiff, PackageUpdate};
+
+/// Options controlling a `cargo update` run, see [`Builder::update`](crate::Builder::update).
+///
+/// # Fields
+///
+/// * `packages` - The packages to update (`-p X`). Every dependency is updated if empty.
+/// * `precise` - Optional exact version to update the single selected package to (`--precise X`).
+/// * `recursive` - If `true`, the dependencies of the selected packages are updated as well (`--recursive`).
+/// * `workspace` - If `true`, only the workspace members are updated in the lockfile (`--workspace`).
+/// * `dry_run` - If `true`, the changes are reported without writing the lockfile (`--dry-run`).
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct UpdateOptions {
+    pub packages: Vec<String>,
+    pub precise: Option<String>,
+    pub recursive: bool,
+    pub workspace: bool,
+    pub dry_run: bool,
+}
+
+/// Private function that collects the changes cargo reports on stderr, for dry runs where the
+/// lockfile is left untouched.
+///
+/// Only names and versions are reported, so the sources and checksums of the packages are unknown.
+pub(crate) fn parse_changes(stderr: &str) -> LockfileDiff {
+    let package = |name: &str, version: &str| LockedPackage {
+        name: name.to_string(),
+        version: version.strip_prefix('v').unwrap_or(version).to_string(),
+        source: None,
+        checksum: None,
+        dependencies: Vec::new(),
+    };
+    let mut diff = LockfileDiff::default();
+    for line in stderr.lines() {
+        // e.g. "    Updating log v0.4.20 -> v0.4.22 (available: v0.5.0)"
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words[..] {
+            ["Updating" | "Downgrading", name, from, "->", to, ..] => {
+                diff.updated.push(PackageUpdate { name: name.to_string(), from: package(name, from), to: package(name, to) })
+            }
+            ["Adding", name, version, ..] => diff.added.push(package(name, version)),
+            ["Removing", name, version, ..] => diff.removed.push(package(name, version)),
+            _ => {}
+        }
+    }
+    diff
+}
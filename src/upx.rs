@@ -0,0 +1,76 @@
+use crate::pipeline::{resolve_tool, run_tool};
+use crate::{ArtifactStep, Result, StepContext, StepOutcome};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A pipeline step that compresses binaries with [UPX](https://upx.github.io).
+///
+/// The binary is compressed in place; UPX prints a summary of the sizes before and after, which
+/// is kept in the [`StepOutcome`] of every artifact. The step fails with
+/// [`CargoWrapError::ToolNotFound`](crate::CargoWrapError::ToolNotFound) if `upx` cannot be found.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{ArtifactPipeline, Builder, ProjectSettings, Strip, Upx};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let pipeline = ArtifactPipeline::new().step(Strip::new()).step(Upx::new().level(9));
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project").release(true))?.artifact_pipeline(pipeline);
+///     for report in builder.build()?.pipeline {
+///         println!("{}", report.outcome.output);
+///     }
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Upx {
+    tool: Option<PathBuf>,
+    level: u8,
+}
+
+impl Default for Upx {
+    fn default() -> Self {
+        Upx { tool: None, level: 7 }
+    }
+}
+
+impl Upx {
+    /// Creates a new `Upx` step with UPX's default compression level (7)
+    pub fn new() -> Self {
+        Upx::default()
+    }
+
+    /// Sets the path or name of the `upx` binary. Defaults to `upx`, searched in `PATH`.
+    pub fn tool(mut self, tool: impl Into<PathBuf>) -> Self {
+        self.tool = Some(tool.into());
+        self
+    }
+
+    /// Sets the compression level, from 1 (fastest) to 9 (smallest)
+    pub fn level(mut self, level: u8) -> Self {
+        self.level = level.clamp(1, 9);
+        self
+    }
+
+    /// Returns the path of the `upx` binary, if set
+    pub fn get_tool(&self) -> Option<&Path> {
+        self.tool.as_deref()
+    }
+
+    /// Returns the compression level
+    pub fn get_level(&self) -> u8 {
+        self.level
+    }
+}
+
+impl ArtifactStep for Upx {
+    fn name(&self) -> &str {
+        "upx"
+    }
+
+    fn process(&self, path: &Path, _context: &StepContext) -> Result<StepOutcome> {
+        let tool = resolve_tool(self.tool.as_deref(), "upx")?;
+        let output = run_tool("upx", Command::new(tool).arg(format!("-{}", self.level)).arg(path))?;
+        Ok(StepOutcome { output, ..Default::default() })
+    }
+}
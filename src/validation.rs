@@ -0,0 +1,140 @@
+use crate::backend::base_target_triple;
+use crate::{BuildTarget, Manifest, ProjectSettings};
+use std::fmt;
+use std::path::PathBuf;
+
+/// A problem found in [`ProjectSettings`] before building, see
+/// [`ProjectSettings::validate`](crate::ProjectSettings::validate).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The project directory does not exist.
+    ProjectPathMissing(PathBuf),
+    /// The project has no `Cargo.toml`.
+    ManifestMissing(PathBuf),
+    /// `Cargo.toml` is not valid TOML.
+    ///
+    /// * `path` - The path to the manifest.
+    /// * `reason` - The parse error.
+    ManifestInvalid { path: PathBuf, reason: String },
+    /// An enabled feature is not declared by the package.
+    ///
+    /// * `feature` - The unknown feature.
+    /// * `available` - The features of the package.
+    UnknownFeature { feature: String, available: Vec<String> },
+    /// An enabled `dependency/feature` names a dependency the package does not have.
+    UnknownDependency(String),
+    /// The compilation target is not a target triple, e.g. `x86_64-unknown-linux-gnu`.
+    InvalidTargetTriple(String),
+    /// The package has no binary with the requested name.
+    ///
+    /// * `name` - The requested binary.
+    /// * `available` - The binaries of the package, declared or discovered by cargo.
+    UnknownBin { name: String, available: Vec<String> },
+    /// The library is built but the package has none.
+    MissingLib,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::ProjectPathMissing(path) => write!(f, "the project directory {} does not exist", path.display()),
+            ValidationError::ManifestMissing(path) => write!(f, "{} does not exist", path.display()),
+            ValidationError::ManifestInvalid { path, reason } => write!(f, "{} is invalid: {}", path.display(), reason),
+            ValidationError::UnknownFeature { feature, available } => {
+                write!(f, "the package has no feature `{}` (available: {})", feature, available.join(", "))
+            }
+            ValidationError::UnknownDependency(feature) => write!(f, "the package has no dependency for the feature `{}`", feature),
+            ValidationError::InvalidTargetTriple(target) => write!(f, "`{}` is not a valid target triple", target),
+            ValidationError::UnknownBin { name, available } => {
+                write!(f, "the package has no binary `{}` (available: {})", name, available.join(", "))
+            }
+            ValidationError::MissingLib => write!(f, "the package has no library"),
+        }
+    }
+}
+
+/// Private function that checks `settings` against the project on disk, see
+/// [`ProjectSettings::validate`](crate::ProjectSettings::validate)
+pub(crate) fn validate(settings: &ProjectSettings) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    if let Some(target) = settings.get_compilation_target() {
+        errors.extend(validate_target(target));
+    }
+    if !settings.get_project_path().is_dir() {
+        errors.insert(0, ValidationError::ProjectPathMissing(settings.get_project_path().to_path_buf()));
+        return errors;
+    }
+    let path = settings.get_cargo_toml_path();
+    if !path.is_file() {
+        errors.insert(0, ValidationError::ManifestMissing(path.to_path_buf()));
+        return errors;
+    }
+    let manifest = match Manifest::open(path) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            errors.insert(0, ValidationError::ManifestInvalid { path: path.to_path_buf(), reason: e.to_string() });
+            return errors;
+        }
+    };
+    // Features and targets of other packages are resolved by cargo against the selected packages
+    let other_packages = settings.is_workspace() || settings.get_packages().iter().any(|package| Some(package.as_str()) != manifest.get_name());
+    if manifest.get_name().is_none() || other_packages {
+        return errors;
+    }
+    errors.extend(validate_features(settings, &manifest));
+    let targets = manifest.get_targets();
+    if settings.is_lib() {
+        if !targets.contains(&BuildTarget::Lib) {
+            errors.push(ValidationError::MissingLib);
+        }
+    } else if let Some(name) = settings.get_target() {
+        let available: Vec<String> = targets
+            .into_iter()
+            .filter_map(|target| match target {
+                BuildTarget::Bin(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        if !available.iter().any(|bin| bin == name) {
+            errors.push(ValidationError::UnknownBin { name: name.to_string(), available });
+        }
+    }
+    errors
+}
+
+/// Private function that checks the syntax of the target triple `target`. Custom target
+/// specifications (`.json`) are not checked.
+fn validate_target(target: &str) -> Option<ValidationError> {
+    if target.ends_with(".json") {
+        return None;
+    }
+    let triple = base_target_triple(target);
+    let components: Vec<&str> = triple.split('-').collect();
+    let valid = (2..=5).contains(&components.len())
+        && components.iter().all(|component| !component.is_empty() && component.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'));
+    (!valid).then(|| ValidationError::InvalidTargetTriple(target.to_string()))
+}
+
+/// Private function that checks that the enabled features exist in `manifest`
+fn validate_features(settings: &ProjectSettings, manifest: &Manifest) -> Vec<ValidationError> {
+    let features = manifest.get_features();
+    let dependencies = manifest.get_dependencies();
+    settings
+        .get_enabled_features()
+        .iter()
+        .flat_map(|features| features.split([',', ' ']))
+        .filter(|feature| !feature.is_empty())
+        .filter_map(|feature| match feature.split_once('/') {
+            Some((dependency, _)) => {
+                let dependency = dependency.trim_end_matches('?');
+                (!dependencies.iter().any(|declared| declared.name == dependency))
+                    .then(|| ValidationError::UnknownDependency(feature.to_string()))
+            }
+            None => (!features.contains(feature)).then(|| ValidationError::UnknownFeature {
+                feature: feature.to_string(),
+                available: features.names().map(String::from).collect(),
+            }),
+        })
+        .collect()
+}
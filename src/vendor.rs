@@ -0,0 +1,78 @@
+use crate::Result;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Options controlling a `cargo vendor` run, see [`Builder::vendor`](crate::Builder::vendor).
+///
+/// # Fields
+///
+/// * `sync` - Additional manifests whose dependencies are vendored as well (`--sync X`).
+/// * `versioned_dirs` - If `true`, every vendored crate gets a directory with its version in the name (`--versioned-dirs`).
+/// * `respect_source_config` - If `true`, the sources configured in `[source]` are used to fetch the crates (`--respect-source-config`).
+/// * `write_config` - If `true`, the source replacement cargo prints is merged into the project's
+///   `.cargo/config.toml`, so later builds (including `--offline` ones) use the vendored crates.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct VendorOptions {
+    pub sync: Vec<PathBuf>,
+    pub versioned_dirs: bool,
+    pub respect_source_config: bool,
+    pub write_config: bool,
+}
+
+/// The output of [`Builder::vendor`](crate::Builder::vendor).
+///
+/// # Fields
+///
+/// * `directory` - The directory the crates were vendored into.
+/// * `config` - The cargo configuration printed by `cargo vendor`, replacing the sources with the vendored crates.
+/// * `config_path` - The configuration file `config` was merged into, if `write_config` was set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VendorOutput {
+    pub directory: PathBuf,
+    pub config: String,
+    pub config_path: Option<PathBuf>,
+}
+
+/// Private function that merges the `[source]` tables of `snippet` into the configuration file at
+/// `config_path`, keeping every other setting.
+///
+/// `directory` replaces the directories of the snippet, since cargo prints them relative to where
+/// it ran rather than to the configuration file.
+pub(crate) fn write_config(config_path: &Path, snippet: &str, directory: &Path) -> Result<()> {
+    let snippet: DocumentMut = snippet.parse().map_err(io::Error::other)?;
+    let mut config: DocumentMut = match fs::read_to_string(config_path) {
+        Ok(contents) => contents.parse().map_err(io::Error::other)?,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => DocumentMut::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let Some(sources) = snippet.get("source").and_then(Item::as_table_like) else { return Ok(()) };
+    let separate = !config.is_empty();
+    let target = config.entry("source").or_insert_with(|| {
+        let mut table = Table::new();
+        table.set_implicit(true);
+        Item::Table(table)
+    });
+    let target = target.as_table_like_mut().ok_or_else(|| io::Error::other(format!("source is not a table in {}", config_path.display())))?;
+    for (name, source) in sources.iter() {
+        let mut source = source.clone();
+        if source.get("directory").is_some() {
+            source["directory"] = toml_edit::value(directory.to_string_lossy().replace('\\', "/"));
+        }
+        if let (true, Some(table)) = (separate, source.as_table_mut()) {
+            table.decor_mut().set_prefix("\n");
+        }
+        target.insert(name, source);
+    }
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temporary = config_path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    fs::write(&temporary, config.to_string())?;
+    fs::rename(&temporary, config_path)?;
+    Ok(())
+}
@@ -0,0 +1,26 @@
+/// How much cargo reports about a build.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Verbosity {
+    /// Cargo's own messages are suppressed (`--quiet`). Compiler warnings and errors are still reported.
+    Quiet,
+    /// Cargo's default output. No flag is passed.
+    #[default]
+    Normal,
+    /// Verbose output, including the commands cargo runs (`--verbose`).
+    Verbose,
+    /// Very verbose output, including build script output (`-vv`).
+    VeryVerbose,
+}
+
+impl Verbosity {
+    /// Private function that returns the flag selecting this verbosity, or `None` for cargo's default
+    pub(crate) fn as_arg(self) -> Option<&'static str> {
+        match self {
+            Verbosity::Quiet => Some("--quiet"),
+            Verbosity::Normal => None,
+            Verbosity::Verbose => Some("--verbose"),
+            Verbosity::VeryVerbose => Some("-vv"),
+        }
+    }
+}
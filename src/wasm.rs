@@ -0,0 +1,208 @@
+use crate::pipeline::{resolve_tool, run_tool};
+use crate::{BuildOutput, Builder, Result};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The target [`WasmBuild`] compiles for.
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+/// How [`WasmBuild`] runs [`wasm-bindgen`](https://github.com/rustwasm/wasm-bindgen) on the
+/// compiled modules.
+///
+/// # Fields
+///
+/// * `target` - The kind of JS output (`--target X`), e.g. `web`, `bundler`, `nodejs` or `no-modules`.
+/// * `typescript` - If `true`, TypeScript declarations (`.d.ts`) are generated as well.
+/// * `args` - Additional arguments passed to `wasm-bindgen`, e.g. `--weak-refs`.
+/// * `tool` - Optional path or name of the `wasm-bindgen` binary. Defaults to `wasm-bindgen`, searched in `PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WasmBindgen {
+    pub target: String,
+    pub typescript: bool,
+    pub args: Vec<String>,
+    pub tool: Option<PathBuf>,
+}
+
+impl Default for WasmBindgen {
+    fn default() -> Self {
+        WasmBindgen { target: "web".to_string(), typescript: true, args: Vec::new(), tool: None }
+    }
+}
+
+/// How [`WasmBuild`] optimizes the final modules with `wasm-opt` from
+/// [Binaryen](https://github.com/WebAssembly/binaryen).
+///
+/// # Fields
+///
+/// * `level` - The optimization flag, e.g. `-O3` or `-Oz`.
+/// * `args` - Additional arguments passed to `wasm-opt`, e.g. `--enable-simd`.
+/// * `tool` - Optional path or name of the `wasm-opt` binary. Defaults to `wasm-opt`, searched in `PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WasmOpt {
+    pub level: String,
+    pub args: Vec<String>,
+    pub tool: Option<PathBuf>,
+}
+
+impl Default for WasmOpt {
+    fn default() -> Self {
+        WasmOpt { level: "-O".to_string(), args: Vec::new(), tool: None }
+    }
+}
+
+/// The output of a [`WasmBuild`].
+///
+/// # Fields
+///
+/// * `build` - The output of the cargo build.
+/// * `wasm` - The final WebAssembly modules.
+/// * `js` - The JS glue generated by `wasm-bindgen`, if it ran.
+/// * `typescript` - The TypeScript declarations generated by `wasm-bindgen`, if it ran.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WasmOutput {
+    pub build: BuildOutput,
+    pub wasm: Vec<PathBuf>,
+    pub js: Vec<PathBuf>,
+    pub typescript: Vec<PathBuf>,
+}
+
+/// Builds a project for `wasm32-unknown-unknown`, then optionally runs `wasm-bindgen` and
+/// `wasm-opt` on the compiled modules.
+///
+/// The compilation target of the builder is replaced by `wasm32-unknown-unknown`. When a
+/// post-step is configured, the results are written to the output directory (`pkg/` in the
+/// project directory by default) and the modules produced by cargo are left untouched; otherwise
+/// the modules of the cargo build are returned as is.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectSettings, WasmBindgen, WasmBuild, WasmOpt};
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/web-app").release(true))?;
+///     let output = WasmBuild::new(builder)
+///         .bindgen(WasmBindgen { target: "bundler".to_string(), ..Default::default() })
+///         .wasm_opt(WasmOpt { level: "-Oz".to_string(), ..Default::default() })
+///         .out_dir("/path/to/web-app/dist")
+///         .run()?;
+///     println!("{:?} {:?}", output.js, output.wasm);
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct WasmBuild {
+    builder: Builder,
+    bindgen: Option<WasmBindgen>,
+    wasm_opt: Option<WasmOpt>,
+    out_dir: Option<PathBuf>,
+}
+
+impl WasmBuild {
+    /// Creates a new `WasmBuild` that builds with `builder`, targeting `wasm32-unknown-unknown`
+    pub fn new(mut builder: Builder) -> Self {
+        builder.project_settings.compilation_target = Some(WASM_TARGET.to_string());
+        WasmBuild { builder, bindgen: None, wasm_opt: None, out_dir: None }
+    }
+
+    /// Runs `wasm-bindgen` on the compiled modules
+    pub fn bindgen(mut self, bindgen: WasmBindgen) -> Self {
+        self.bindgen = Some(bindgen);
+        self
+    }
+
+    /// Optimizes the final modules with `wasm-opt`
+    pub fn wasm_opt(mut self, wasm_opt: WasmOpt) -> Self {
+        self.wasm_opt = Some(wasm_opt);
+        self
+    }
+
+    /// Sets the directory the post-steps write to. Defaults to `pkg/` in the project directory.
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Returns the builder used for the cargo build
+    pub fn get_builder(&self) -> &Builder {
+        &self.builder
+    }
+
+    /// Returns the directory the post-steps write to
+    pub fn get_out_dir(&self) -> PathBuf {
+        self.out_dir.clone().unwrap_or_else(|| self.builder.project_settings.project_path.join("pkg"))
+    }
+
+    /// Builds the project and runs the configured post-steps.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(WasmOutput)` - The output of the build and the paths of the final files.
+    /// * `Err(CargoWrapError)` - If the build or a post-step fails.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The build fails, e.g. because the `wasm32-unknown-unknown` target is not installed
+    ///   ([`CargoWrapError::CompilationFailed`](crate::CargoWrapError::CompilationFailed)).
+    /// - `wasm-bindgen` or `wasm-opt` cannot be found ([`CargoWrapError::ToolNotFound`](crate::CargoWrapError::ToolNotFound)).
+    /// - `wasm-bindgen` or `wasm-opt` fails ([`CargoWrapError::ToolFailed`](crate::CargoWrapError::ToolFailed)).
+    pub fn run(&self) -> Result<WasmOutput> {
+        let build = self.builder.build()?;
+        let mut modules = Vec::new();
+        for artifact in build.artifacts.iter().filter(|artifact| !artifact.kind.iter().any(|kind| kind == "custom-build")) {
+            for path in artifact.executable.iter().chain(&artifact.filenames) {
+                if path.extension() == Some(OsStr::new("wasm")) && !modules.contains(path) {
+                    modules.push(path.clone());
+                }
+            }
+        }
+        let mut output = WasmOutput { build, wasm: Vec::new(), js: Vec::new(), typescript: Vec::new() };
+        if self.bindgen.is_none() && self.wasm_opt.is_none() {
+            output.wasm = modules;
+            return Ok(output);
+        }
+        let out_dir = std::path::absolute(self.get_out_dir())?;
+        fs::create_dir_all(&out_dir)?;
+        for module in modules {
+            let Some(name) = module.file_stem().map(|stem| stem.to_string_lossy().into_owned()) else { continue };
+            let wasm = match &self.bindgen {
+                Some(bindgen) => {
+                    self.run_bindgen(bindgen, &module, &out_dir)?;
+                    output.js.push(out_dir.join(format!("{}.js", name)));
+                    if bindgen.typescript {
+                        output.typescript.push(out_dir.join(format!("{}.d.ts", name)));
+                    }
+                    out_dir.join(format!("{}_bg.wasm", name))
+                }
+                None => out_dir.join(format!("{}.wasm", name)),
+            };
+            if let Some(wasm_opt) = &self.wasm_opt {
+                // Without wasm-bindgen, the module produced by cargo is optimized into the output directory
+                let input = if self.bindgen.is_some() { wasm.as_path() } else { module.as_path() };
+                let mut command = Command::new(resolve_tool(wasm_opt.tool.as_deref(), "wasm-opt")?);
+                command.arg(&wasm_opt.level).args(&wasm_opt.args).arg(input).arg("-o").arg(&wasm);
+                run_tool("wasm-opt", &mut command)?;
+            }
+            output.wasm.push(wasm);
+        }
+        Ok(output)
+    }
+
+    /// Private function that runs `wasm-bindgen` on `module`, writing to `out_dir`
+    fn run_bindgen(&self, bindgen: &WasmBindgen, module: &Path, out_dir: &Path) -> Result<()> {
+        let mut command = Command::new(resolve_tool(bindgen.tool.as_deref(), "wasm-bindgen")?);
+        command.arg("--target").arg(&bindgen.target).arg("--out-dir").arg(out_dir);
+        if !bindgen.typescript {
+            command.arg("--no-typescript");
+        }
+        command.args(&bindgen.args).arg(module);
+        run_tool("wasm-bindgen", &mut command)?;
+        Ok(())
+    }
+}
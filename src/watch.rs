@@ -0,0 +1,178 @@
+use crate::glob::path_matches;
+use crate::{BuildReport, Builder, CargoWrapError, Result};
+use notify::event::{AccessKind, AccessMode};
+use notify::{Event, EventKind, RecursiveMode, Watcher as _};
+use std::fs;
+use std::io;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// Paths ignored by every new [`Watcher`].
+const DEFAULT_IGNORED: [&str; 2] = ["target/", ".git/"];
+
+/// Rebuilds a project whenever its sources change.
+///
+/// The project directory is watched recursively. Changes are debounced, so saving several files
+/// at once triggers a single build, and changes to ignored paths are skipped. The target
+/// directory and the log file of the builder are always ignored.
+///
+/// # Ignore patterns
+///
+/// Patterns are matched against paths relative to the project directory, using `/` as the
+/// separator. `*` matches any characters except `/`, `**` matches any characters and `?` matches
+/// a single character. A pattern containing no `/` (other than a trailing one) matches any path
+/// component, e.g. `*.swp` or `node_modules/`. A matching directory ignores everything inside it.
+///
+/// # Example
+/// ```no_run
+/// use cargo_wrap::{Builder, ProjectSettings, Watcher};
+/// use std::ops::ControlFlow;
+///
+/// fn main() -> cargo_wrap::Result<()> {
+///     let builder = Builder::new(ProjectSettings::new("/path/to/project"))?;
+///     Watcher::new(builder).ignore("*.swp").watch(|report| {
+///         println!("{} warnings, {} errors", report.warning_count(), report.error_count());
+///         ControlFlow::Continue(())
+///     })
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Watcher {
+    builder: Builder,
+    ignored: Vec<String>,
+    debounce: Duration,
+}
+
+impl Watcher {
+    /// Creates a new `Watcher` that rebuilds with `builder`, ignoring `target/` and `.git/` and
+    /// debouncing changes for 200 milliseconds
+    pub fn new(builder: Builder) -> Watcher {
+        Watcher {
+            builder,
+            ignored: DEFAULT_IGNORED.iter().map(|pattern| pattern.to_string()).collect(),
+            debounce: Duration::from_millis(200),
+        }
+    }
+
+    /// Ignores changes to paths matching `pattern`
+    pub fn ignore(mut self, pattern: impl Into<String>) -> Self {
+        self.ignored.push(pattern.into());
+        self
+    }
+
+    /// Sets how long to wait for further changes before building
+    pub fn debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    /// Returns the builder used to rebuild the project
+    pub fn get_builder(&self) -> &Builder {
+        &self.builder
+    }
+
+    /// Returns the patterns of ignored paths
+    pub fn get_ignored(&self) -> &[String] {
+        &self.ignored
+    }
+
+    /// Returns how long to wait for further changes before building
+    pub fn get_debounce(&self) -> Duration {
+        self.debounce
+    }
+
+    /// Builds the project, then rebuilds it every time its sources change.
+    ///
+    /// Every build is run with [`Builder::build_report`], and `on_build` is invoked with its
+    /// report, whether the build succeeded or not. Watching stops once `on_build` returns
+    /// [`ControlFlow::Break`].
+    ///
+    /// # Arguments
+    ///
+    /// * `on_build` - Callback invoked with the report of every build.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - The project directory cannot be watched ([`CargoWrapError::Io`]).
+    /// - A build cannot be run, see [`Builder::build_report`].
+    pub fn watch(&self, mut on_build: impl FnMut(&BuildReport) -> ControlFlow<()>) -> Result<()> {
+        let root = fs::canonicalize(&self.builder.project_settings.project_path)?;
+        // Cargo creates a missing target directory under a temporary name, which would not be ignored
+        let target_dir = self.builder.target_dir();
+        fs::create_dir_all(&target_dir)?;
+        let ignored_paths: Vec<PathBuf> = [Some(target_dir), self.builder.log_path.clone()]
+            .into_iter()
+            .flatten()
+            .map(|path| resolve(&path))
+            .collect();
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+        watcher.watch(&root, RecursiveMode::Recursive).map_err(watch_error)?;
+
+        loop {
+            if on_build(&self.builder.build_report()?).is_break() {
+                return Ok(());
+            }
+            // Wait for a relevant change, then until no change happened for `debounce`
+            let mut changed = false;
+            loop {
+                let event = if changed {
+                    match receiver.recv_timeout(self.debounce) {
+                        Ok(event) => event,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => return Ok(()),
+                    }
+                } else {
+                    match receiver.recv() {
+                        Ok(event) => event,
+                        Err(_) => return Ok(()),
+                    }
+                };
+                let event = event.map_err(watch_error)?;
+                changed |= self.is_relevant(&event, &root, &ignored_paths);
+            }
+        }
+    }
+
+    /// Private function that checks whether `event` modified a path that is not ignored
+    fn is_relevant(&self, event: &Event, root: &Path, ignored_paths: &[PathBuf]) -> bool {
+        let modifies = match event.kind {
+            EventKind::Access(AccessKind::Close(AccessMode::Write)) => true,
+            EventKind::Access(_) => false,
+            _ => true,
+        };
+        modifies
+            && event.paths.iter().any(|path| {
+                !ignored_paths.iter().any(|ignored| path.starts_with(ignored))
+                    && path.strip_prefix(root).is_ok_and(|relative| !self.is_ignored(relative))
+            })
+    }
+
+    /// Private function that checks whether `relative` matches one of the ignore patterns
+    fn is_ignored(&self, relative: &Path) -> bool {
+        self.ignored.iter().any(|pattern| path_matches(pattern, relative))
+    }
+}
+
+/// Private function that makes `path` absolute and resolves symbolic links, as far as it exists
+fn resolve(path: &Path) -> PathBuf {
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+    match (path.parent().and_then(|parent| fs::canonicalize(parent).ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+    }
+}
+
+/// Private function that maps an error of the file watcher to a `CargoWrapError`
+fn watch_error(error: notify::Error) -> CargoWrapError {
+    match error.kind {
+        notify::ErrorKind::Io(error) => CargoWrapError::Io(error),
+        _ => CargoWrapError::Io(io::Error::other(error)),
+    }
+}